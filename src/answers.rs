@@ -0,0 +1,160 @@
+//! Known answers and the attempts before them, from the submission history, for `aoc answers`.
+use crate::error::AocError;
+use crate::submissions::Submission;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Verdicts of wrong answers.
+const WRONG: [&str; 3] = ["incorrect", "too high", "too low"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PartAnswers {
+    pub day: u8,
+    pub part: u8,
+    /// The accepted answer, from the submissions or `answers.toml`.
+    pub answer: Option<String>,
+    pub wrong_attempts: usize,
+    /// Wrong answers AoC said were too high or too low, with the verdict.
+    pub hints: Vec<(String, String)>,
+    /// Every wrong submission, only collected when asked for.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub wrong: Vec<Submission>,
+}
+
+/// Answers of `part` of `day`, from its `submissions` and the answer in `answers.toml`.
+pub fn part_answers(
+    day: u8,
+    part: u8,
+    known: Option<&String>,
+    submissions: &[Submission],
+    include_wrong: bool,
+) -> PartAnswers {
+    let submissions: Vec<&Submission> = submissions.iter().filter(|submission| submission.part == part).collect();
+    let correct = submissions.iter().find(|submission| submission.verdict == "correct");
+    let wrong: Vec<&Submission> = submissions
+        .iter()
+        .copied()
+        .filter(|submission| WRONG.contains(&submission.verdict.as_str()))
+        .collect();
+    PartAnswers {
+        day,
+        part,
+        answer: correct.map(|submission| submission.answer.clone()).or_else(|| known.cloned()),
+        wrong_attempts: wrong.len(),
+        hints: wrong
+            .iter()
+            .filter(|submission| submission.verdict != "incorrect")
+            .map(|submission| (submission.answer.clone(), submission.verdict.clone()))
+            .collect(),
+        wrong: if include_wrong {
+            wrong.into_iter().cloned().collect()
+        } else {
+            Vec::new()
+        },
+    }
+}
+
+/// Answers of every part of the days in `year_dir`, or only of `day`, which have an answer or a
+/// submission.
+pub fn collect(
+    year_dir: &Path,
+    day_format: &str,
+    day: Option<u8>,
+    include_wrong: bool,
+) -> Result<Vec<PartAnswers>, AocError> {
+    let mut answers = Vec::new();
+    for (day_number, day_dir) in crate::scaffold::day_dirs(year_dir, day_format)? {
+        if day.is_some_and(|day| day != day_number) {
+            continue;
+        }
+        let known: BTreeMap<String, String> = crate::report::read_answers(&day_dir)?;
+        let submissions = crate::submissions::read(&day_dir)?;
+        for part in 1..=2 {
+            let known = known.get(&format!("part_{}", part));
+            if known.is_none() && !submissions.iter().any(|submission| submission.part == part) {
+                continue;
+            }
+            answers.push(part_answers(day_number, part, known, &submissions, include_wrong));
+        }
+    }
+    Ok(answers)
+}
+
+/// The first part in `year_dir` with a known answer, by day, part and answer, to check answers
+/// can be submitted.
+pub fn first_known(year_dir: &Path, day_format: &str) -> Result<Option<(u8, u8, String)>, AocError> {
+    Ok(collect(year_dir, day_format, None, false)?
+        .into_iter()
+        .find_map(|answers| Some((answers.day, answers.part, answers.answer?))))
+}
+
+/// A line per part, like `Day 1 part 1: 24000 (2 wrong; 99999 too high)`, with the wrong
+/// attempts below it when collected.
+pub fn render(answers: &[PartAnswers]) -> String {
+    let mut text = String::new();
+    for part in answers {
+        let answer = part.answer.as_deref().unwrap_or("-");
+        text.push_str(&format!("Day {} part {}: {}", part.day, part.part, answer));
+        if part.wrong_attempts > 0 {
+            text.push_str(&format!(" ({} wrong", part.wrong_attempts));
+            if !part.hints.is_empty() {
+                let hints: Vec<String> = part.hints.iter().map(|(answer, hint)| format!("{} {}", answer, hint)).collect();
+                text.push_str(&format!("; {}", hints.join(", ")));
+            }
+            text.push(')');
+        }
+        text.push('\n');
+        for submission in &part.wrong {
+            text.push_str(&format!("    {} {}\n", submission.answer, submission.verdict));
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = tmp_dir.path().join("advent-of-code-2022");
+        let day_dir = year_dir.join("day-01");
+        std::fs::create_dir_all(&day_dir).unwrap();
+        std::fs::create_dir_all(year_dir.join("day-02")).unwrap();
+        std::fs::write(day_dir.join("answers.toml"), "part_2 = \"45000\"\n").unwrap();
+        std::fs::write(
+            day_dir.join("submissions.jsonl"),
+            r#"{"timestamp":1,"part":1,"answer":"99999","verdict":"too high"}
+{"timestamp":2,"part":1,"answer":"1234","verdict":"incorrect"}
+{"timestamp":3,"part":1,"answer":"100","verdict":"rate limited"}
+{"timestamp":4,"part":1,"answer":"10","verdict":"too low"}
+{"timestamp":5,"part":1,"answer":"24000","verdict":"correct"}
+"#,
+        )
+        .unwrap();
+
+        let answers = collect(&year_dir, "day-", None, false).unwrap();
+        assert_eq!(answers.len(), 2);
+        assert_eq!(answers[0].answer.as_deref(), Some("24000"));
+        assert_eq!(answers[0].wrong_attempts, 3);
+        assert_eq!(
+            render(&answers),
+            "Day 1 part 1: 24000 (3 wrong; 99999 too high, 10 too low)\n\
+             Day 1 part 2: 45000\n"
+        );
+
+        let answers = collect(&year_dir, "day-", Some(1), true).unwrap();
+        assert_eq!(
+            render(&answers[..1]),
+            "Day 1 part 1: 24000 (3 wrong; 99999 too high, 10 too low)\n    \
+                 99999 too high\n    \
+                 1234 incorrect\n    \
+                 10 too low\n"
+        );
+        assert!(collect(&year_dir, "day-", Some(2), true).unwrap().is_empty());
+        assert_eq!(first_known(&year_dir, "day-").unwrap(), Some((1, 1, "24000".to_owned())));
+        assert!(first_known(&tmp_dir.path().join("missing"), "day-").is_err());
+    }
+}
@@ -0,0 +1,219 @@
+//! Moving puzzle data between machines as a `.tar.gz`, for `aoc archive`.
+//!
+//! Only the data which isn't code is archived: inputs, examples, answers, submissions and
+//! runtimes. The session cookie in `.env` is left out unless asked for.
+use crate::error::AocError;
+use crate::clean::Formats;
+use clap::ValueEnum;
+use colored::*;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+/// Puzzle data in each day directory.
+pub const DAY_FILES: [&str; 6] =
+    ["input.txt", "input.txt.enc", "example.txt", "answers.toml", "submissions.jsonl", "bench.toml"];
+
+/// Puzzle data in each year directory.
+pub const YEAR_FILES: [&str; 1] = ["perf.jsonl"];
+
+/// File with the session cookie, next to the year directories.
+const AUTH_FILE: &str = ".env";
+
+/// What to do with a file being imported which already exists with other contents.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Conflict {
+    /// Ask for each file.
+    #[default]
+    Ask,
+    Skip,
+    Overwrite,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Created,
+    Overwritten,
+    Unchanged,
+    Skipped,
+}
+
+impl Outcome {
+    /// What happened to the file at `path`, for the user.
+    pub fn render(self, path: &Path) -> String {
+        match self {
+            Outcome::Created => format!("New {}: {}", path.display(), "Success".green()),
+            Outcome::Overwritten => format!("Update {}: {}", path.display(), "Success".green()),
+            Outcome::Unchanged => format!("{}: {}", path.display(), "Up to date".green()),
+            Outcome::Skipped => format!("{}: {}", path.display(), "Skipped, changed locally".yellow()),
+        }
+    }
+}
+
+/// Paths, relative to `root`, of the puzzle data of the year directories in `root`, or only of
+/// `year`, and of `.env` with `include_auth`.
+pub fn files(root: &Path, formats: &Formats, year: Option<u16>, include_auth: bool) -> Result<Vec<PathBuf>, AocError> {
+    let entries = std::fs::read_dir(root).map_err(|e| AocError::io(format!("Failed to read {}", root.display()), e))?;
+    let mut year_dirs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .filter(|path| {
+            crate::env::parse_year(path, formats.year).is_ok_and(|parsed| year.is_none_or(|year| year == parsed))
+        })
+        .collect();
+    year_dirs.sort();
+
+    let mut files = Vec::new();
+    for year_dir in year_dirs {
+        let mut candidates: Vec<PathBuf> = YEAR_FILES.iter().map(|name| year_dir.join(name)).collect();
+        for (_, day_dir) in crate::scaffold::day_dirs(&year_dir, formats.day)? {
+            candidates.extend(DAY_FILES.iter().map(|name| day_dir.join(name)));
+        }
+        files.extend(
+            candidates
+                .into_iter()
+                .filter(|path| path.is_file())
+                .filter_map(|path| path.strip_prefix(root).ok().map(Path::to_owned)),
+        );
+    }
+    if include_auth && root.join(AUTH_FILE).is_file() {
+        files.push(PathBuf::from(AUTH_FILE));
+    }
+    Ok(files)
+}
+
+/// Write `files`, relative to `root`, to the `.tar.gz` at `archive_path`.
+pub fn export(root: &Path, files: &[PathBuf], archive_path: &Path) -> Result<(), AocError> {
+    let file = std::fs::File::create(archive_path)
+        .map_err(|e| AocError::io(format!("Failed to create {}", archive_path.display()), e))?;
+    let mut builder = tar::Builder::new(GzEncoder::new(file, flate2::Compression::default()));
+    for path in files {
+        builder
+            .append_path_with_name(root.join(path), path)
+            .map_err(|e| AocError::io(format!("Failed to archive {}", path.display()), e))?;
+    }
+    builder
+        .into_inner()
+        .and_then(|encoder| encoder.finish())
+        .map_err(|e| AocError::io(format!("Failed to write {}", archive_path.display()), e))?;
+    Ok(())
+}
+
+/// Restore the files of the `.tar.gz` at `archive_path` into `root`, leaving out `.env` unless
+/// `include_auth`. `overwrite` decides whether to replace a file which exists with other contents.
+pub fn import(
+    archive_path: &Path,
+    root: &Path,
+    include_auth: bool,
+    mut overwrite: impl FnMut(&Path) -> bool,
+) -> Result<Vec<(PathBuf, Outcome)>, AocError> {
+    let file = std::fs::File::open(archive_path)
+        .map_err(|e| AocError::io(format!("Failed to open {}", archive_path.display()), e))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+    let invalid = |e: std::io::Error| AocError::io(format!("Invalid archive {}", archive_path.display()), e);
+    let mut outcomes = Vec::new();
+    for entry in archive.entries().map_err(invalid)? {
+        let mut entry = entry.map_err(invalid)?;
+        let path = entry.path().map_err(invalid)?.into_owned();
+        if !path.components().all(|component| matches!(component, Component::Normal(_))) {
+            return Err(AocError::Parse(format!("Refusing to import {}, outside of the directory", path.display())));
+        }
+        if path == Path::new(AUTH_FILE) && !include_auth {
+            continue;
+        }
+        let mut contents = Vec::new();
+        entry.read_to_end(&mut contents).map_err(invalid)?;
+
+        let target = root.join(&path);
+        let outcome = match std::fs::read(&target) {
+            Ok(existing) if existing == contents => Outcome::Unchanged,
+            Ok(_) if !overwrite(&path) => Outcome::Skipped,
+            Ok(_) => Outcome::Overwritten,
+            Err(_) => Outcome::Created,
+        };
+        if matches!(outcome, Outcome::Created | Outcome::Overwritten) {
+            if let Some(dir) = target.parent() {
+                std::fs::create_dir_all(dir)
+                    .map_err(|e| AocError::io(format!("Failed to create {}", dir.display()), e))?;
+            }
+            std::fs::write(&target, &contents)
+                .map_err(|e| AocError::io(format!("Failed to write {}", target.display()), e))?;
+        }
+        outcomes.push((path, outcome));
+    }
+    Ok(outcomes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FORMATS: Formats = Formats { year: "advent-of-code-", day: "day-" };
+
+    fn write(root: &Path, path: &str, contents: &str) {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    fn fixture(root: &Path) {
+        write(root, ".env", "session=abc123\n");
+        write(root, "advent-of-code-2021/perf.jsonl", "{}\n");
+        write(root, "advent-of-code-2021/day-01/input.txt", "1\n2\n");
+        write(root, "advent-of-code-2022/day-01/input.txt", "3\n");
+        write(root, "advent-of-code-2022/day-01/answers.toml", "part_1 = \"3\"\n");
+        write(root, "advent-of-code-2022/day-01/src/bin/part_1.rs", "fn main() {}\n");
+    }
+
+    #[test]
+    fn test_files() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        fixture(tmp_dir.path());
+
+        assert_eq!(
+            files(tmp_dir.path(), &FORMATS, Some(2022), false).unwrap(),
+            [
+                PathBuf::from("advent-of-code-2022/day-01/input.txt"),
+                PathBuf::from("advent-of-code-2022/day-01/answers.toml"),
+            ]
+        );
+        let all = files(tmp_dir.path(), &FORMATS, None, true).unwrap();
+        assert_eq!(all.len(), 5);
+        assert!(all.contains(&PathBuf::from(".env")));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let (from, to) = (tmp_dir.path().join("from"), tmp_dir.path().join("to"));
+        fixture(&from);
+        std::fs::create_dir(&to).unwrap();
+        let archive_path = tmp_dir.path().join("aoc.tar.gz");
+
+        let exported = files(&from, &FORMATS, None, true).unwrap();
+        export(&from, &exported, &archive_path).unwrap();
+
+        let outcomes = import(&archive_path, &to, false, |_| unreachable!()).unwrap();
+        assert_eq!(outcomes.len(), 4);
+        assert!(outcomes.iter().all(|(_, outcome)| *outcome == Outcome::Created));
+        assert_eq!(files(&to, &FORMATS, None, true).unwrap(), &exported[..4]);
+        for path in &exported[..4] {
+            assert_eq!(std::fs::read(from.join(path)).unwrap(), std::fs::read(to.join(path)).unwrap());
+        }
+        assert!(!to.join(".env").exists());
+
+        // Conflicts
+        write(&to, "advent-of-code-2022/day-01/input.txt", "changed\n");
+        let outcomes = import(&archive_path, &to, true, |_| false).unwrap();
+        assert!(outcomes.contains(&(PathBuf::from("advent-of-code-2022/day-01/input.txt"), Outcome::Skipped)));
+        assert!(outcomes.contains(&(PathBuf::from("advent-of-code-2021/day-01/input.txt"), Outcome::Unchanged)));
+        assert!(outcomes.contains(&(PathBuf::from(".env"), Outcome::Created)));
+        let env_path = to.join(".env");
+        assert!(Outcome::Created.render(&env_path).starts_with(&format!("New {}: ", env_path.display())));
+        let outcomes = import(&archive_path, &to, true, |_| true).unwrap();
+        assert!(outcomes.contains(&(PathBuf::from("advent-of-code-2022/day-01/input.txt"), Outcome::Overwritten)));
+        assert_eq!(std::fs::read_to_string(to.join("advent-of-code-2022/day-01/input.txt")).unwrap(), "3\n");
+    }
+}
@@ -0,0 +1,173 @@
+//! Shields-style badges of the stars of a year, like `AoC 2023: 38★`, for `aoc badge`.
+use crate::error::AocError;
+use clap::ValueEnum;
+
+/// Default colors: AoC's gold stars on its dark blue background.
+pub const COLOR: &str = "ffff66";
+pub const LABEL_COLOR: &str = "0f0f23";
+
+/// How `aoc badge` prints a badge.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// A Markdown image of the shields.io badge.
+    #[default]
+    Markdown,
+    /// The shields.io URL of the badge.
+    Url,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Badge {
+    pub label: String,
+    pub message: String,
+    /// Colors as hex, like `ffff66`, or names, like `yellow`.
+    pub color: String,
+    pub label_color: String,
+}
+
+impl Badge {
+    pub fn new(year: u16, stars: u32, color: &str, label_color: &str) -> Self {
+        Self {
+            label: format!("AoC {}", year),
+            message: format!("{}★", stars),
+            color: color.to_owned(),
+            label_color: label_color.to_owned(),
+        }
+    }
+
+    /// The badge on shields.io.
+    pub fn url(&self) -> String {
+        format!(
+            "https://img.shields.io/badge/{}-{}-{}?labelColor={}",
+            shields_escape(&self.label),
+            shields_escape(&self.message),
+            percent_encode(self.color.trim_start_matches('#')),
+            percent_encode(self.label_color.trim_start_matches('#'))
+        )
+    }
+
+    pub fn markdown(&self) -> String {
+        format!("![{}: {}]({})", self.label, self.message, self.url())
+    }
+
+    /// Write the badge as an SVG to `path`, rendered with the user's or the embedded template.
+    pub fn write_svg(&self, path: &std::path::Path) -> Result<(), AocError> {
+        let svg = self.svg(&crate::template::load("badge.svg")?)?;
+        std::fs::write(path, svg).map_err(|e| AocError::io(format!("Failed to write {}", path.display()), e))
+    }
+
+    /// The badge as a self-contained SVG, rendered with the `badge.svg` template.
+    pub fn svg(&self, template: &str) -> Result<String, AocError> {
+        let label_width = text_width(&self.label);
+        let message_width = text_width(&self.message);
+        let (color, label_color) = (svg_color(&self.color), svg_color(&self.label_color));
+        let mut environment = minijinja::Environment::new();
+        environment.set_auto_escape_callback(|_| minijinja::AutoEscape::Html);
+        environment
+            .add_template("badge.svg", template)
+            .map_err(|e| AocError::Parse(format!("Invalid badge template: {}", e)))?;
+        environment
+            .get_template("badge.svg")
+            .and_then(|template| {
+                template.render(minijinja::context! {
+                    label => self.label,
+                    message => self.message,
+                    width => label_width + message_width,
+                    label_width,
+                    message_width,
+                    label_x => f64::from(label_width) / 2.0,
+                    message_x => f64::from(label_width) + f64::from(message_width) / 2.0,
+                    text_color => text_color(&color),
+                    label_text_color => text_color(&label_color),
+                    color,
+                    label_color,
+                })
+            })
+            .map_err(|e| AocError::Parse(format!("Failed to render badge: {}", e)))
+    }
+}
+
+/// Approximate width in pixels of `text` in 11px Verdana, with padding.
+fn text_width(text: &str) -> u32 {
+    text.chars().map(|c| if c.is_ascii() { 7 } else { 12 }).sum::<u32>() + 10
+}
+
+/// `color` for SVG, with a `#` before hex colors.
+fn svg_color(color: &str) -> String {
+    let hex = color.trim_start_matches('#');
+    if matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        format!("#{}", hex)
+    } else {
+        color.to_owned()
+    }
+}
+
+/// Black or white, whichever reads better on the hex `background`, or white for named colors.
+fn text_color(background: &str) -> &'static str {
+    let hex = background.trim_start_matches('#');
+    let hex = if hex.len() == 3 { hex.chars().flat_map(|c| [c, c]).collect() } else { hex.to_owned() };
+    let channel = |index: usize| hex.get(index..index + 2).and_then(|channel| u8::from_str_radix(channel, 16).ok());
+    match (channel(0), channel(2), channel(4)) {
+        (Some(red), Some(green), Some(blue)) if hex.len() == 6 => {
+            let luminance = 0.299 * f64::from(red) + 0.587 * f64::from(green) + 0.114 * f64::from(blue);
+            if luminance > 150.0 {
+                "#000000"
+            } else {
+                "#ffffff"
+            }
+        }
+        _ => "#ffffff",
+    }
+}
+
+/// `text` for a part of a shields.io badge path, where `-` and `_` are doubled.
+fn shields_escape(text: &str) -> String {
+    percent_encode(&text.replace('-', "--").replace('_', "__"))
+}
+
+fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => char::from(byte).to_string(),
+            byte => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url() {
+        let badge = Badge::new(2023, 38, COLOR, LABEL_COLOR);
+        assert_eq!(
+            badge.url(),
+            "https://img.shields.io/badge/AoC%202023-38%E2%98%85-ffff66?labelColor=0f0f23"
+        );
+        assert_eq!(
+            badge.markdown(),
+            "![AoC 2023: 38★](https://img.shields.io/badge/AoC%202023-38%E2%98%85-ffff66?labelColor=0f0f23)"
+        );
+        let badge = Badge { label: "my-aoc_2023".to_owned(), ..Badge::new(2023, 0, "#ff0000", "blue") };
+        assert_eq!(badge.url(), "https://img.shields.io/badge/my--aoc__2023-0%E2%98%85-ff0000?labelColor=blue");
+    }
+
+    #[test]
+    fn test_svg() {
+        let template = include_str!("../templates/badge.svg");
+        let svg = Badge::new(2023, 38, COLOR, LABEL_COLOR).svg(template).unwrap();
+        assert_eq!(svg, include_str!("../tests/fixtures/badge.svg"));
+
+        let svg = Badge::new(2023, 38, "green", "#333").svg(template).unwrap();
+        assert!(svg.contains(r##"fill="green""##) && svg.contains(r##"fill="#333""##));
+    }
+
+    #[test]
+    fn test_text_color() {
+        assert_eq!(text_color("#ffff66"), "#000000");
+        assert_eq!(text_color("#0f0f23"), "#ffffff");
+        assert_eq!(text_color("#fff"), "#000000");
+        assert_eq!(text_color("yellow"), "#ffffff");
+    }
+}
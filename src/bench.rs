@@ -0,0 +1,234 @@
+//! Benchmarking a day's parts, for `aoc bench`, with an optional history in `bench.toml` in the
+//! day directory.
+use crate::error::AocError;
+use serde::Serialize;
+use std::path::Path;
+use std::time::Duration;
+
+const BENCH_FILE: &str = "bench.toml";
+
+/// Runtime statistics of a part over several runs.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BenchResult {
+    pub part: u8,
+    pub runs: usize,
+    pub min_ms: f64,
+    pub median_ms: f64,
+    pub mean_ms: f64,
+    pub stddev_ms: f64,
+}
+
+/// Benchmark each of `parts` of `day_dir`, built in release mode with `cargo_args`, over `runs`
+/// runs after `warmup` ones.
+pub fn run(
+    day_dir: &Path,
+    parts: &[u8],
+    runs: usize,
+    warmup: usize,
+    cargo_args: &[String],
+) -> Result<Vec<BenchResult>, AocError> {
+    if runs == 0 {
+        return Err(AocError::Config("--runs must be at least 1".to_owned()));
+    }
+    let cargo_args: Vec<String> = std::iter::once("--release".to_owned()).chain(cargo_args.iter().cloned()).collect();
+    let mut results = Vec::new();
+    for &part in parts {
+        let executable = crate::runner::build_part(day_dir, part, &cargo_args)?;
+        let samples = measure(runs, warmup, || {
+            let output = crate::runner::execute_part(&executable, day_dir, &[])?;
+            if !output.status.success() {
+                return Err(AocError::PartFailed { part, status: output.status });
+            }
+            Ok(output.elapsed)
+        })?;
+        results.push(statistics(part, &samples));
+    }
+    Ok(results)
+}
+
+/// Call `run` `warmup` times, ignoring the timings, and then `runs` times, returning the
+/// runtimes it reports in milliseconds.
+pub fn measure(
+    runs: usize,
+    warmup: usize,
+    mut run: impl FnMut() -> Result<Duration, AocError>,
+) -> Result<Vec<f64>, AocError> {
+    for _ in 0..warmup {
+        run()?;
+    }
+    (0..runs)
+        .map(|_| run().map(|elapsed| elapsed.as_secs_f64() * 1_000.0))
+        .collect()
+}
+
+/// Statistics of the runtimes `samples`, which can't be empty. The standard deviation is the
+/// population's.
+pub fn statistics(part: u8, samples: &[f64]) -> BenchResult {
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let runs = sorted.len();
+    let median_ms = if runs.is_multiple_of(2) {
+        (sorted[runs / 2 - 1] + sorted[runs / 2]) / 2.0
+    } else {
+        sorted[runs / 2]
+    };
+    let mean_ms = sorted.iter().sum::<f64>() / runs as f64;
+    let variance = sorted.iter().map(|sample| (sample - mean_ms).powi(2)).sum::<f64>() / runs as f64;
+    BenchResult {
+        part,
+        runs,
+        min_ms: sorted[0],
+        median_ms,
+        mean_ms,
+        stddev_ms: variance.sqrt(),
+    }
+}
+
+pub fn render_table(results: &[BenchResult]) -> String {
+    let mut table = format!(
+        "{:>4} {:>4} {:>12} {:>12} {:>12} {:>12}\n",
+        "Part", "Runs", "Min (ms)", "Median (ms)", "Mean (ms)", "Stddev (ms)"
+    );
+    for result in results {
+        table.push_str(&format!(
+            "{:>4} {:>4} {:>12.3} {:>12.3} {:>12.3} {:>12.3}\n",
+            result.part, result.runs, result.min_ms, result.median_ms, result.mean_ms, result.stddev_ms
+        ));
+    }
+    table
+}
+
+/// Append `results` to the `[[bench]]` history in `bench.toml` in `day_dir`.
+pub fn record(day_dir: &Path, results: &[BenchResult]) -> Result<(), AocError> {
+    let path = day_dir.join(BENCH_FILE);
+    let mut history = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents
+            .parse::<toml_edit::Document>()
+            .map_err(|e| AocError::Parse(format!("Failed to parse {}: {}", path.display(), e)))?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => toml_edit::Document::new(),
+        Err(e) => return Err(AocError::io(format!("Failed to read {}", path.display()), e)),
+    };
+    let entries = history
+        .as_table_mut()
+        .entry("bench")
+        .or_insert(toml_edit::Item::ArrayOfTables(Default::default()))
+        .as_array_of_tables_mut()
+        .ok_or_else(|| AocError::Parse(format!("`bench` in {} isn't an array of tables", path.display())))?;
+
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    for result in results {
+        let mut entry = toml_edit::Table::new();
+        entry["timestamp"] = toml_edit::value(timestamp as i64);
+        entry["part"] = toml_edit::value(result.part as i64);
+        entry["runs"] = toml_edit::value(result.runs as i64);
+        entry["min_ms"] = toml_edit::value(result.min_ms);
+        entry["median_ms"] = toml_edit::value(result.median_ms);
+        entry["mean_ms"] = toml_edit::value(result.mean_ms);
+        entry["stddev_ms"] = toml_edit::value(result.stddev_ms);
+        entries.push(entry);
+    }
+
+    std::fs::write(&path, history.to_string())
+        .map_err(|e| AocError::io(format!("Failed to write {}", path.display()), e))?;
+    crate::logging::wrote(&path);
+    Ok(())
+}
+
+/// The `[[bench]]` history in `bench.toml` in `day_dir`, with the timestamp of each result,
+/// skipping entries which can't be read.
+pub fn read(day_dir: &Path) -> Result<Vec<(u64, BenchResult)>, AocError> {
+    let path = day_dir.join(BENCH_FILE);
+    let history: toml::Value = match std::fs::read_to_string(&path) {
+        Ok(contents) => toml::from_str(&contents)
+            .map_err(|e| AocError::Parse(format!("Failed to parse {}: {}", path.display(), e)))?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(AocError::io(format!("Failed to read {}", path.display()), e)),
+    };
+    let entries = history.get("bench").and_then(toml::Value::as_array).map_or(&[][..], Vec::as_slice);
+    Ok(entries
+        .iter()
+        .filter_map(|entry| {
+            let integer = |key: &str| entry.get(key)?.as_integer();
+            let float = |key: &str| entry.get(key)?.as_float();
+            let result = BenchResult {
+                part: u8::try_from(integer("part")?).ok()?,
+                runs: usize::try_from(integer("runs")?).ok()?,
+                min_ms: float("min_ms")?,
+                median_ms: float("median_ms")?,
+                mean_ms: float("mean_ms")?,
+                stddev_ms: float("stddev_ms")?,
+            };
+            Some((u64::try_from(integer("timestamp")?).ok()?, result))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure() {
+        let mut timings = [5, 1, 4, 2, 3].into_iter();
+        let samples = measure(3, 2, || Ok(Duration::from_millis(timings.next().unwrap()))).unwrap();
+        // The warmup runs are dropped
+        assert_eq!(samples, vec![4.0, 2.0, 3.0]);
+        assert!(timings.next().is_none());
+
+        let mut calls = 0;
+        let result = measure(3, 1, || {
+            calls += 1;
+            Err(AocError::Build("failed".to_owned()))
+        });
+        assert_eq!(result.unwrap_err().to_string(), "failed");
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_run_needs_runs() {
+        let err = run(Path::new("."), &[1], 0, 3, &[]).unwrap_err();
+        assert!(matches!(err, AocError::Config(_)));
+    }
+
+    #[test]
+    fn test_statistics() {
+        let result = statistics(1, &[4.0, 2.0, 8.0, 6.0]);
+        assert_eq!(
+            result,
+            BenchResult {
+                part: 1,
+                runs: 4,
+                min_ms: 2.0,
+                median_ms: 5.0,
+                mean_ms: 5.0,
+                stddev_ms: 5.0_f64.sqrt(),
+            }
+        );
+        let result = statistics(2, &[3.0, 1.0, 2.0]);
+        assert_eq!((result.median_ms, result.mean_ms), (2.0, 2.0));
+    }
+
+    #[test]
+    fn test_record() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let result = statistics(1, &[1.0, 3.0]);
+        record(tmp_dir.path(), std::slice::from_ref(&result)).unwrap();
+        record(tmp_dir.path(), &[result, statistics(2, &[1.0])]).unwrap();
+
+        let history: toml::Value = std::fs::read_to_string(tmp_dir.path().join(BENCH_FILE))
+            .unwrap()
+            .parse()
+            .unwrap();
+        let entries = history["bench"].as_array().unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0]["median_ms"].as_float(), Some(2.0));
+        assert_eq!(entries[2]["part"].as_integer(), Some(2));
+
+        let results = read(tmp_dir.path()).unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[2].1, statistics(2, &[1.0]));
+        assert!(read(&tmp_dir.path().join("missing")).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,109 @@
+//! A year's solutions as a `.zip`, for sharing them, for `aoc zip`.
+//!
+//! Build output in `target/`, hidden files such as `.git/` and the session cookie's `.env`, and
+//! backups are always left out, and so is the puzzle data, inputs included, which AoC asks not to
+//! share, unless asked for.
+use crate::error::AocError;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Paths, relative to `year_dir`, of the files in it, leaving out `target/` directories, hidden
+/// files and directories, backups (`*.bak*`), and the puzzle data unless `include_inputs`.
+pub fn files(year_dir: &Path, include_inputs: bool) -> Result<Vec<PathBuf>, AocError> {
+    use crate::archive::{DAY_FILES, YEAR_FILES};
+
+    let puzzle_data = |name: &str| DAY_FILES.contains(&name) || YEAR_FILES.contains(&name);
+    let mut files = Vec::new();
+    let mut dirs = vec![year_dir.to_owned()];
+    while let Some(dir) = dirs.pop() {
+        let entries = std::fs::read_dir(&dir)
+            .map_err(|e| AocError::io(format!("Failed to read {}", dir.display()), e))?;
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.starts_with('.') {
+                continue;
+            }
+            if path.is_dir() {
+                if name != "target" {
+                    dirs.push(path);
+                }
+            } else if !name.contains(".bak") && (include_inputs || !puzzle_data(&name)) {
+                files.extend(path.strip_prefix(year_dir).ok().map(Path::to_owned));
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Write `files`, relative to `year_dir`, to the `.zip` at `archive_path`, returning its size.
+pub fn write(year_dir: &Path, files: &[PathBuf], archive_path: &Path) -> Result<u64, AocError> {
+    let file = std::fs::File::create(archive_path)
+        .map_err(|e| AocError::io(format!("Failed to create {}", archive_path.display()), e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for path in files {
+        let contents =
+            std::fs::read(year_dir.join(path))
+                .map_err(|e| AocError::io(format!("Failed to read {}", path.display()), e))?;
+        // Forward slashes, whatever the platform
+        let name = path.components().map(|component| component.as_os_str().to_string_lossy()).collect::<Vec<_>>();
+        zip.start_file(name.join("/"), options)
+            .and_then(|()| zip.write_all(&contents).map_err(Into::into))
+            .map_err(|e| AocError::io(format!("Failed to archive {}", path.display()), e.into()))?;
+    }
+    let file = zip
+        .finish()
+        .map_err(|e| AocError::io(format!("Failed to write {}", archive_path.display()), e.into()))?;
+    file.metadata()
+        .map(|metadata| metadata.len())
+        .map_err(|e| AocError::io(format!("Failed to read {}", archive_path.display()), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zip() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = tmp_dir.path().join("advent-of-code-2022");
+        for file in [
+            "Cargo.toml",
+            ".env",
+            ".git/HEAD",
+            "day-01/.git/config",
+            "day-01/Cargo.toml",
+            "day-01/Cargo.toml.bak",
+            "day-01/example.txt",
+            "day-01/input.txt",
+            "day-01/src/bin/part_1.rs",
+            "day-01/src/bin/part_2.rs.bak-1670000000",
+            "day-01/target/debug/part_1",
+            "target/debug/part_1",
+        ] {
+            let path = year_dir.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, file).unwrap();
+        }
+
+        let files = files(&year_dir, false).unwrap();
+        let expected = ["Cargo.toml", "day-01/Cargo.toml", "day-01/src/bin/part_1.rs"];
+        assert_eq!(files, expected.map(PathBuf::from));
+        let with_inputs = super::files(&year_dir, true).unwrap();
+        assert!(with_inputs.contains(&PathBuf::from("day-01/input.txt")));
+        assert!(with_inputs.contains(&PathBuf::from("day-01/example.txt")));
+        assert!(!with_inputs.contains(&PathBuf::from(".env")));
+
+        let archive_path = tmp_dir.path().join("advent-of-code-2022.zip");
+        let size = write(&year_dir, &files, &archive_path).unwrap();
+        assert_eq!(size, std::fs::metadata(&archive_path).unwrap().len());
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(&archive_path).unwrap()).unwrap();
+        assert_eq!(archive.file_names().collect::<std::collections::BTreeSet<_>>(), expected.into());
+        let mut contents = String::new();
+        std::io::Read::read_to_string(&mut archive.by_name("day-01/src/bin/part_1.rs").unwrap(), &mut contents)
+            .unwrap();
+        assert_eq!(contents, "day-01/src/bin/part_1.rs");
+    }
+}
@@ -0,0 +1,212 @@
+//! On-disk cache of what's fetched from adventofcode.com, in `~/.cache/aoc/http`.
+//!
+//! Each entry is a JSON file holding the payload, when it was fetched and its ETag, under a
+//! directory per profile (the account logged in), so accounts don't share their inputs. How long
+//! an entry is good for is up to the caller: inputs never change, puzzle pages last until a part is
+//! completed, and leaderboards for a while.
+use crate::error::AocError;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Input,
+    Puzzle,
+    /// A private leaderboard, by its id.
+    Leaderboard(u64),
+}
+
+/// What an entry is of, and for whom.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Key {
+    pub kind: Kind,
+    pub year: u16,
+    /// 0 for what's of a whole year, like leaderboards.
+    pub day: u8,
+    pub profile: String,
+}
+
+impl Key {
+    pub fn new(kind: Kind, year: u16, day: u8, profile: &str) -> Self {
+        Key { kind, year, day, profile: profile.to_owned() }
+    }
+
+    /// Path of the entry, relative to the cache directory.
+    fn path(&self) -> PathBuf {
+        let file = match self.kind {
+            Kind::Input => format!("input/{}-{:02}.json", self.year, self.day),
+            Kind::Puzzle => format!("puzzle/{}-{:02}.json", self.year, self.day),
+            Kind::Leaderboard(id) => format!("leaderboard/{}-{}.json", self.year, id),
+        };
+        Path::new(&self.profile).join(file)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    pub payload: String,
+    /// Seconds since the Unix epoch.
+    pub fetched_at: u64,
+    pub etag: Option<String>,
+}
+
+impl Entry {
+    /// `payload`, fetched now.
+    pub fn new(payload: String) -> Self {
+        let fetched_at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        Entry { payload, fetched_at, etag: None }
+    }
+
+    pub fn with_etag(mut self, etag: Option<String>) -> Self {
+        self.etag = etag;
+        self
+    }
+
+    /// How long ago the entry was fetched.
+    pub fn age(&self) -> Duration {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        Duration::from_secs(now.saturating_sub(self.fetched_at))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Cache { dir: dir.into() }
+    }
+
+    /// `aoc/http` in the user's cache directory.
+    pub fn default_dir() -> Option<PathBuf> {
+        crate::template::cache_dir().map(|dir| dir.join("aoc").join("http"))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The entry of `key`, unless it's missing, unreadable, or older than `ttl` (if any).
+    pub fn get(&self, key: &Key, ttl: Option<Duration>) -> Option<Entry> {
+        let contents = std::fs::read_to_string(self.dir.join(key.path())).ok()?;
+        let entry: Entry = serde_json::from_str(&contents).ok()?;
+        match ttl {
+            Some(ttl) if entry.age() >= ttl => None,
+            _ => Some(entry),
+        }
+    }
+
+    pub fn put(&self, key: &Key, entry: &Entry) -> Result<(), AocError> {
+        let path = self.dir.join(key.path());
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| AocError::io(format!("Failed to create {}", dir.display()), e))?;
+        }
+        let contents = serde_json::to_string(entry)
+            .map_err(|e| AocError::Parse(format!("Failed to serialize the entry: {}", e)))?;
+        std::fs::write(&path, contents).map_err(|e| AocError::io(format!("Failed to write {}", path.display()), e))?;
+        crate::logging::wrote(&path);
+        Ok(())
+    }
+
+    /// Remove the entry of `key`, if there is one.
+    pub fn invalidate(&self, key: &Key) -> Result<(), AocError> {
+        let path = self.dir.join(key.path());
+        match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(AocError::io(format!("Failed to remove {}", path.display()), e))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The entry of `key` while it's younger than `ttl` (forever without one), or else the one
+    /// `fetch` gives, which is cached.
+    pub fn get_or_fetch(
+        &self,
+        key: &Key,
+        ttl: Option<Duration>,
+        fetch: impl FnOnce() -> Result<Entry, AocError>,
+    ) -> Result<Entry, AocError> {
+        if let Some(entry) = self.get(key, ttl) {
+            return Ok(entry);
+        }
+        let entry = fetch()?;
+        self.put(key, &entry)?;
+        Ok(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fetched(payload: &str, ago: u64) -> Entry {
+        let mut entry = Entry::new(payload.to_owned());
+        entry.fetched_at -= ago;
+        entry
+    }
+
+    #[test]
+    fn test_ttl() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let cache = Cache::new(tmp_dir.path());
+        let key = Key::new(Kind::Leaderboard(42), 2022, 0, "default");
+        let ttl = Some(Duration::from_secs(15 * 60));
+
+        let entry = cache.get_or_fetch(&key, ttl, || Ok(Entry::new("first".to_owned()))).unwrap();
+        assert_eq!(entry.payload, "first");
+        let entry = cache.get_or_fetch(&key, ttl, || unreachable!()).unwrap();
+        assert_eq!(entry.payload, "first");
+
+        cache.put(&key, &fetched("first", 16 * 60)).unwrap();
+        assert_eq!(cache.get(&key, ttl), None);
+        let entry = cache.get_or_fetch(&key, ttl, || Ok(Entry::new("second".to_owned()))).unwrap();
+        assert_eq!(entry.payload, "second");
+        assert!(entry.age() < Duration::from_secs(60));
+
+        // Without a TTL, entries don't expire
+        let input = Key::new(Kind::Input, 2022, 1, "default");
+        cache.put(&input, &fetched("1000\n2000\n", 365 * 24 * 60 * 60)).unwrap();
+        assert_eq!(cache.get(&input, None).unwrap().payload, "1000\n2000\n");
+        // And fetch errors aren't cached
+        cache.invalidate(&input).unwrap();
+        assert!(cache.get_or_fetch(&input, None, || Err(AocError::refused("offline"))).is_err());
+        assert_eq!(cache.get(&input, None), None);
+    }
+
+    #[test]
+    fn test_invalidate() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let cache = Cache::new(tmp_dir.path());
+        let puzzle = Key::new(Kind::Puzzle, 2022, 1, "default");
+        let other = Key::new(Kind::Puzzle, 2022, 1, "other");
+        let entry = Entry::new("--- Day 1: Calorie Counting ---".to_owned()).with_etag(Some("\"abc\"".to_owned()));
+        cache.put(&puzzle, &entry).unwrap();
+        cache.put(&other, &entry).unwrap();
+        assert_eq!(cache.get(&puzzle, None), Some(entry.clone()));
+
+        cache.invalidate(&puzzle).unwrap();
+        assert_eq!(cache.get(&puzzle, None), None);
+        assert_eq!(cache.get(&other, None), Some(entry));
+        // Invalidating what isn't cached is fine
+        cache.invalidate(&puzzle).unwrap();
+    }
+
+    #[test]
+    fn test_corrupt_entry() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let cache = Cache::new(tmp_dir.path());
+        let key = Key::new(Kind::Input, 2022, 1, "default");
+        let path = tmp_dir.path().join("default/input/2022-01.json");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "{\"payload\": \"10").unwrap();
+
+        assert_eq!(cache.get(&key, None), None);
+        let entry = cache.get_or_fetch(&key, None, || Ok(Entry::new("1000\n".to_owned()))).unwrap();
+        assert_eq!(entry.payload, "1000\n");
+        assert_eq!(cache.get(&key, None).unwrap().payload, "1000\n");
+    }
+}
@@ -0,0 +1,201 @@
+//! Stars of each day of a year, for `aoc calendar`.
+use crate::error::AocError;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CalendarDay {
+    pub day: u8,
+    /// Title of the puzzle, cached in the day's `[package.metadata.aoc]`.
+    pub title: Option<String>,
+    pub stars: u8,
+}
+
+/// The 25 days of the year in `year_dir`, with stars for the parts with an answer in
+/// `answers.toml` or a correct submission.
+pub fn collect(year_dir: &Path, day_format: &str) -> Result<Vec<CalendarDay>, AocError> {
+    let day_dirs: BTreeMap<u8, _> = crate::scaffold::day_dirs(year_dir, day_format)?.into_iter().collect();
+    let mut days = Vec::new();
+    for day in 1..=25 {
+        let Some(day_dir) = day_dirs.get(&day) else {
+            days.push(CalendarDay { day, title: None, stars: 0 });
+            continue;
+        };
+        let answers = crate::report::read_answers(day_dir)?;
+        let submissions = crate::submissions::read(day_dir)?;
+        let stars = (1..=2)
+            .filter(|part| {
+                answers.contains_key(&format!("part_{}", part))
+                    || submissions
+                        .iter()
+                        .any(|submission| submission.part == *part && submission.verdict == "correct")
+            })
+            .count() as u8;
+        days.push(CalendarDay {
+            day,
+            title: crate::status::read_title(&day_dir.join("Cargo.toml")),
+            stars,
+        });
+    }
+    Ok(days)
+}
+
+/// Stars of the whole year.
+pub fn total_stars(days: &[CalendarDay]) -> u32 {
+    days.iter().map(|day| u32::from(day.stars)).sum()
+}
+
+/// Stars by day from the year's calendar page, whose days are links with a `calendar-day<N>`
+/// class, and `calendar-complete` for one star or `calendar-verycomplete` for two.
+pub fn parse_stars(html: &str) -> BTreeMap<u8, u8> {
+    let mut stars = BTreeMap::new();
+    for (start, _) in html.match_indices("class=\"calendar-day") {
+        let classes = &html[start + "class=\"".len()..];
+        let Some(end) = classes.find('"') else {
+            continue;
+        };
+        let mut classes = classes[..end].split_whitespace();
+        let Some(day) = classes.next().and_then(|class| class["calendar-day".len()..].parse::<u8>().ok()) else {
+            continue;
+        };
+        if !(1..=25).contains(&day) {
+            continue;
+        }
+        let count = classes
+            .map(|class| match class {
+                "calendar-verycomplete" => 2,
+                "calendar-complete" => 1,
+                _ => 0,
+            })
+            .max()
+            .unwrap_or(0);
+        stars.insert(day, count);
+    }
+    stars
+}
+
+/// Stars earned in the year from one of its pages, as counted in the header (`<span
+/// class="star-count">5*</span>`), or else by its calendar's days. An invalid session when logged
+/// out, as the pages don't show any stars then.
+pub fn parse_star_count(html: &str) -> Result<u32, AocError> {
+    crate::client::parse_username(html).ok_or(AocError::InvalidSession)?;
+    let document = scraper::Html::parse_document(html);
+    let selector = scraper::Selector::parse("span.star-count").unwrap();
+    match document.select(&selector).next() {
+        Some(count) => {
+            let count: String = count.text().collect();
+            let count = count.trim();
+            count
+                .trim_end_matches('*')
+                .parse()
+                .map_err(|_| AocError::Parse(format!("Invalid star count: {:?}", count)))
+        }
+        None => Ok(parse_stars(html).values().map(|stars| u32::from(*stars)).sum()),
+    }
+}
+
+/// Take the stars of `remote`, from the calendar page, over the local ones.
+pub fn reconcile(days: &mut [CalendarDay], remote: &BTreeMap<u8, u8>) {
+    for day in days {
+        if let Some(stars) = remote.get(&day.day) {
+            day.stars = *stars;
+        }
+    }
+}
+
+pub fn render(days: &[CalendarDay]) -> String {
+    use colored::*;
+
+    let mut calendar = String::new();
+    for day in days {
+        let stars = format!("{:<2}", "*".repeat(day.stars.into()));
+        let line = format!("Day {:>2} {} {}", day.day, stars, day.title.as_deref().unwrap_or_default());
+        let line = line.trim_end();
+        let line = match day.stars {
+            2 => line.yellow(),
+            1 => line.white(),
+            _ => line.dimmed(),
+        };
+        calendar.push_str(&format!("{}\n", line));
+    }
+    calendar
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = tmp_dir.path();
+        for day in ["day-01", "day-02", "day-03"] {
+            std::fs::create_dir(year_dir.join(day)).unwrap();
+        }
+        std::fs::write(year_dir.join("day-01/answers.toml"), "part_1 = 1\npart_2 = 2\n").unwrap();
+        std::fs::write(
+            year_dir.join("day-01/Cargo.toml"),
+            "[package]\nname = \"day-01\"\n\n[package.metadata.aoc]\ntitle = \"Calorie Counting\"\n",
+        )
+        .unwrap();
+        let submission = crate::submissions::Submission::now(1, "15", "correct");
+        crate::submissions::record(&year_dir.join("day-02"), &submission).unwrap();
+        let submission = crate::submissions::Submission::now(2, "10", "too low");
+        crate::submissions::record(&year_dir.join("day-02"), &submission).unwrap();
+
+        let days = collect(year_dir, "day-").unwrap();
+        assert_eq!(days.len(), 25);
+        assert_eq!(
+            days[..4],
+            [
+                CalendarDay { day: 1, title: Some("Calorie Counting".to_owned()), stars: 2 },
+                CalendarDay { day: 2, title: None, stars: 1 },
+                CalendarDay { day: 3, title: None, stars: 0 },
+                CalendarDay { day: 4, title: None, stars: 0 },
+            ]
+        );
+
+        assert_eq!(total_stars(&days), 3);
+
+        let calendar = render(&days);
+        assert_eq!(calendar.lines().count(), 25);
+        assert!(calendar.contains("Day  1 ** Calorie Counting"));
+        assert!(calendar.contains("Day  2 *"));
+    }
+
+    #[test]
+    fn test_parse_stars() {
+        let html = include_str!("../tests/fixtures/calendar.html");
+        let stars = parse_stars(html);
+        assert_eq!(stars.get(&1), Some(&2));
+        assert_eq!(stars.get(&2), Some(&1));
+        assert_eq!(stars.get(&10), Some(&2));
+        assert_eq!(stars.get(&11), Some(&0));
+        // Locked days have no link, but still a class
+        assert_eq!(stars.get(&25), Some(&0));
+        assert_eq!(stars.get(&0), None);
+        assert_eq!(stars.get(&24), None);
+
+        let mut days = vec![
+            CalendarDay { day: 1, title: None, stars: 0 },
+            CalendarDay { day: 24, title: None, stars: 1 },
+        ];
+        reconcile(&mut days, &stars);
+        assert_eq!((days[0].stars, days[1].stars), (2, 1));
+    }
+
+    #[test]
+    fn test_parse_star_count() {
+        let html = include_str!("../tests/fixtures/calendar.html");
+        assert_eq!(parse_star_count(html).unwrap(), 5);
+        let garbled = html.replace(r#"<span class="star-count">5*</span>"#, r#"<span class="star-count">five</span>"#);
+        assert!(matches!(parse_star_count(&garbled), Err(AocError::Parse(_))));
+        // Without the count in the header, from the calendar
+        let html = html.replace(r#"<span class="star-count">5*</span>"#, "");
+        assert_eq!(parse_star_count(&html).unwrap(), 5);
+        // Logged out
+        let html = html.replace(r#"<div class="user">AceofSpades5757 </div>"#, "");
+        assert!(matches!(parse_star_count(&html), Err(AocError::InvalidSession)));
+    }
+}
@@ -0,0 +1,270 @@
+//! Removing build artifacts of years and days, for `aoc clean` and `aoc cleanup-targets`.
+//!
+//! Only `target` directories of recognized year and day directories, each with a Cargo.toml, are
+//! ever removed.
+use crate::error::AocError;
+use colored::*;
+use std::path::{Path, PathBuf};
+
+/// A `target` directory and its size in bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Target {
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// Directory names for recognizing year and day directories.
+pub struct Formats<'a> {
+    pub year: &'a str,
+    pub day: &'a str,
+}
+
+/// Whether `path` is a `target` directory (and not a symlink) next to a Cargo.toml, in a year
+/// directory or a day directory inside of one.
+pub fn is_safe_target(path: &Path, formats: &Formats) -> bool {
+    let is_dir = std::fs::symlink_metadata(path).is_ok_and(|metadata| metadata.is_dir());
+    if !is_dir || path.file_name().is_none_or(|name| name != "target") {
+        return false;
+    }
+    let Some(parent) = path.parent() else {
+        return false;
+    };
+    if !parent.join("Cargo.toml").is_file() {
+        return false;
+    }
+    let is_year = |dir: &Path| crate::env::parse_year(dir, formats.year).is_ok();
+    is_year(parent)
+        || (crate::env::parse_day(parent, formats.day).is_ok() && parent.parent().is_some_and(is_year))
+}
+
+/// The `target` directories of `year_dir` and its days, or only of `day`, which are safe to
+/// remove.
+pub fn targets(year_dir: &Path, day: Option<u8>, formats: &Formats) -> Result<Vec<Target>, AocError> {
+    let mut dirs = Vec::new();
+    if day.is_none() {
+        dirs.push(year_dir.join("target"));
+    }
+    for (day_number, day_dir) in crate::scaffold::day_dirs(year_dir, formats.day)? {
+        if day.is_none_or(|day| day == day_number) {
+            dirs.push(day_dir.join("target"));
+        }
+    }
+    Ok(dirs
+        .into_iter()
+        .filter(|dir| is_safe_target(dir, formats))
+        .map(|path| Target { size: dir_size(&path), path })
+        .collect())
+}
+
+/// The `target` directories of the days of `year_dir`, without the year's own, which are safe to
+/// remove.
+pub fn day_targets(year_dir: &Path, formats: &Formats) -> Result<Vec<Target>, AocError> {
+    let year_target = year_dir.join("target");
+    Ok(targets(year_dir, None, formats)?
+        .into_iter()
+        .filter(|target| target.path != year_target)
+        .collect())
+}
+
+/// Year directories next to `year_dir`, including itself, sorted.
+pub fn sibling_years(year_dir: &Path, formats: &Formats) -> Result<Vec<PathBuf>, AocError> {
+    match year_dir.parent() {
+        Some(parent) => year_dirs(parent, formats),
+        None => Ok(vec![year_dir.to_owned()]),
+    }
+}
+
+/// Year directories in `dir`, sorted.
+pub fn year_dirs(dir: &Path, formats: &Formats) -> Result<Vec<PathBuf>, AocError> {
+    let entries = std::fs::read_dir(dir).map_err(|e| AocError::io(format!("Failed to read {}", dir.display()), e))?;
+    let mut years: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir() && crate::env::parse_year(path, formats.year).is_ok())
+        .collect();
+    years.sort();
+    Ok(years)
+}
+
+/// The year directories for `aoc cleanup-targets` from `dir`: the one of `year`, or else every
+/// year next to the one `dir` is in, or in `dir` itself.
+pub fn cleanup_years(dir: &Path, year: Option<u16>, formats: &Formats) -> Result<Vec<PathBuf>, AocError> {
+    match (year, crate::env::resolve_year_dir(dir, formats.day, formats.year)) {
+        (Some(_), _) => Ok(vec![crate::env::find_year_dir(dir, year, formats.day, formats.year)?.1]),
+        (None, Ok(year_dir)) => sibling_years(&year_dir, formats),
+        (None, Err(_)) => year_dirs(dir, formats),
+    }
+}
+
+/// Remove `targets`, or only list them with `dry_run`, printing each and the space reclaimed.
+pub fn clean(targets: &[Target], formats: &Formats, dry_run: bool) -> Result<(), AocError> {
+    if targets.is_empty() {
+        println!("{}", "Nothing to clean".yellow());
+        return Ok(());
+    }
+    let verb = if dry_run { "Would remove" } else { "Remove" };
+    for target in targets {
+        println!("{} {} ({})", verb, target.path.display(), format_size(target.size));
+    }
+    let total = format_size(targets.iter().map(|target| target.size).sum());
+    if dry_run {
+        println!("Would reclaim {}", total);
+    } else {
+        remove(targets, formats)?;
+        println!("Reclaimed {}: {}", total, "Success".green());
+    }
+    Ok(())
+}
+
+/// Remove `targets`, checking again that each is safe to remove.
+pub fn remove(targets: &[Target], formats: &Formats) -> Result<(), AocError> {
+    for target in targets {
+        if !is_safe_target(&target.path, formats) {
+            return Err(AocError::Environment(format!("Refusing to remove {}", target.path.display())));
+        }
+        std::fs::remove_dir_all(&target.path)
+            .map_err(|e| AocError::io(format!("Failed to remove {}", target.path.display()), e))?;
+    }
+    Ok(())
+}
+
+/// Total size of the files in `path`, without following symlinks.
+fn dir_size(path: &Path) -> u64 {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    std::fs::read_dir(path)
+        .map(|entries| entries.filter_map(|entry| entry.ok()).map(|entry| dir_size(&entry.path())).sum())
+        .unwrap_or(0)
+}
+
+/// Format `bytes` like `1.5 MiB`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FORMATS: Formats = Formats {
+        year: "advent-of-code-",
+        day: "day-",
+    };
+
+    fn write(path: &Path, contents: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn test_targets() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = tmp_dir.path().join("advent-of-code-2022");
+        write(&year_dir.join("Cargo.toml"), "[workspace]\n");
+        write(&year_dir.join("target/debug/part_1"), "12345");
+        write(&year_dir.join("day-01/Cargo.toml"), "[package]\n");
+        write(&year_dir.join("day-01/target/release/part_1"), "123");
+        // Not a crate
+        write(&year_dir.join("day-02/target/keep"), "");
+
+        let targets = targets(&year_dir, None, &FORMATS).unwrap();
+        assert_eq!(
+            targets,
+            vec![
+                Target { path: year_dir.join("target"), size: 5 },
+                Target { path: year_dir.join("day-01/target"), size: 3 },
+            ]
+        );
+        let day = super::targets(&year_dir, Some(1), &FORMATS).unwrap();
+        assert_eq!(day, targets[1..]);
+
+        clean(&targets, &FORMATS, true).unwrap();
+        assert!(year_dir.join("target").exists());
+        clean(&targets, &FORMATS, false).unwrap();
+        assert!(!year_dir.join("target").exists());
+        assert!(!year_dir.join("day-01/target").exists());
+        assert!(year_dir.join("day-02/target/keep").exists());
+        assert!(year_dir.join("day-01/Cargo.toml").exists());
+    }
+
+    #[test]
+    fn test_day_targets() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        for year in ["advent-of-code-2021", "advent-of-code-2022"] {
+            let year_dir = tmp_dir.path().join(year);
+            write(&year_dir.join("Cargo.toml"), "[workspace]\n");
+            write(&year_dir.join("target/debug/part_1"), "12345");
+            write(&year_dir.join("day-01/Cargo.toml"), "[package]\n");
+            write(&year_dir.join("day-01/target/release/part_1"), "123");
+        }
+        write(&tmp_dir.path().join("notes/keep"), "");
+
+        let year_dirs = year_dirs(tmp_dir.path(), &FORMATS).unwrap();
+        assert_eq!(
+            year_dirs,
+            [tmp_dir.path().join("advent-of-code-2021"), tmp_dir.path().join("advent-of-code-2022")]
+        );
+        assert_eq!(sibling_years(&year_dirs[1], &FORMATS).unwrap(), year_dirs);
+        assert_eq!(cleanup_years(&year_dirs[1].join("day-01"), None, &FORMATS).unwrap(), year_dirs);
+        assert_eq!(cleanup_years(tmp_dir.path(), None, &FORMATS).unwrap(), year_dirs);
+        assert_eq!(cleanup_years(tmp_dir.path(), Some(2021), &FORMATS).unwrap(), year_dirs[..1]);
+        assert_eq!(
+            day_targets(&year_dirs[0], &FORMATS).unwrap(),
+            [Target { path: year_dirs[0].join("day-01/target"), size: 3 }]
+        );
+    }
+
+    #[test]
+    fn test_is_safe_target() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = tmp_dir.path().join("advent-of-code-2022");
+        write(&tmp_dir.path().join("Cargo.toml"), "");
+        write(&tmp_dir.path().join("target/keep"), "");
+        write(&year_dir.join("Cargo.toml"), "");
+        write(&year_dir.join("src/keep"), "");
+        write(&year_dir.join("target/debug/part_1"), "");
+        write(&year_dir.join("other/Cargo.toml"), "");
+        write(&year_dir.join("other/target/keep"), "");
+
+        assert!(is_safe_target(&year_dir.join("target"), &FORMATS));
+        // Not in a year or day directory
+        assert!(!is_safe_target(&tmp_dir.path().join("target"), &FORMATS));
+        assert!(!is_safe_target(&year_dir.join("other/target"), &FORMATS));
+        // Not a target directory
+        assert!(!is_safe_target(&year_dir.join("src"), &FORMATS));
+        assert!(!is_safe_target(&year_dir.join("target/debug/part_1"), &FORMATS));
+        assert!(!is_safe_target(&year_dir.join("day-03/target"), &FORMATS));
+        #[cfg(unix)]
+        {
+            let day_dir = year_dir.join("day-01");
+            write(&day_dir.join("Cargo.toml"), "");
+            std::os::unix::fs::symlink(tmp_dir.path().join("target"), day_dir.join("target")).unwrap();
+            assert!(!is_safe_target(&day_dir.join("target"), &FORMATS));
+        }
+
+        let unsafe_target = Target { path: tmp_dir.path().join("target"), size: 0 };
+        assert!(remove(&[unsafe_target], &FORMATS).is_err());
+        assert!(tmp_dir.path().join("target/keep").exists());
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+}
@@ -0,0 +1,1054 @@
+//! Requests to adventofcode.com: inputs, answers, pages and private leaderboards.
+use crate::error::AocError;
+use crate::cache::{Cache, Entry, Key, Kind};
+use crate::config::NetworkConfig;
+use crate::urls::Page;
+use colored::*;
+use serde::Serialize;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Correct,
+    Incorrect(Option<Hint>),
+    /// Not the level to solve, with how many parts are completed, per the puzzle's page, if any.
+    AlreadySubmitted(Option<u8>),
+    /// Too soon to submit, with how long is left to wait when AoC says.
+    RateLimited(Option<Duration>),
+}
+
+impl Answer {
+    /// Short description, as recorded in the submission history.
+    pub fn verdict(&self) -> &'static str {
+        match self {
+            Answer::Correct => "correct",
+            Answer::Incorrect(None) => "incorrect",
+            Answer::Incorrect(Some(Hint::TooHigh)) => "too high",
+            Answer::Incorrect(Some(Hint::TooLow)) => "too low",
+            Answer::AlreadySubmitted(_) => "already submitted",
+            Answer::RateLimited(_) => "rate limited",
+        }
+    }
+}
+
+impl std::fmt::Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.verdict())
+    }
+}
+
+/// As its verdict, e.g. `"too high"`.
+impl Serialize for Answer {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.verdict())
+    }
+}
+
+/// Which way an incorrect answer is off, if AoC says so.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Hint {
+    TooHigh,
+    TooLow,
+}
+
+impl FromStr for Answer {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_response(s).map(|(answer, _)| answer)
+    }
+}
+
+/// What came of submitting an answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubmissionResult {
+    pub part: u8,
+    /// The answer submitted.
+    pub answer: String,
+    pub verdict: Answer,
+    /// The text of the response's `<article>`.
+    pub message: String,
+    /// Seconds since the Unix epoch.
+    pub submitted_at: u64,
+}
+
+impl SubmissionResult {
+    /// The result of submitting `answer` to `part` at `submitted_at`, from the response `page`.
+    pub fn parse(page: &str, part: u8, answer: &str, submitted_at: u64) -> Result<Self, AocError> {
+        let (verdict, message) = parse_response(page)?;
+        Ok(SubmissionResult { part, answer: answer.to_owned(), verdict, message, submitted_at })
+    }
+
+    pub fn hint(&self) -> Option<Hint> {
+        match self.verdict {
+            Answer::Incorrect(hint) => hint,
+            _ => None,
+        }
+    }
+
+    /// How long is left to wait before submitting again, when rate limited.
+    pub fn wait(&self) -> Option<Duration> {
+        match self.verdict {
+            Answer::RateLimited(wait) => wait,
+            _ => None,
+        }
+    }
+
+    /// Exit code of `aoc submit`: 0 when correct, 1 when incorrect, 2 when already completed, and 3
+    /// when rate limited.
+    pub fn exit_code(&self) -> i32 {
+        match self.verdict {
+            Answer::Correct => 0,
+            Answer::Incorrect(_) => 1,
+            Answer::AlreadySubmitted(_) => 2,
+            Answer::RateLimited(_) => 3,
+        }
+    }
+
+    /// As recorded in the submission history.
+    pub fn submission(&self) -> crate::submissions::Submission {
+        crate::submissions::Submission {
+            timestamp: self.submitted_at,
+            part: self.part,
+            answer: self.answer.clone(),
+            verdict: self.verdict.to_string(),
+        }
+    }
+}
+
+/// With the hint and the time to wait spelled out.
+impl Serialize for SubmissionResult {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Json<'a> {
+            part: u8,
+            answer: &'a str,
+            verdict: &'a Answer,
+            hint: Option<Hint>,
+            wait_secs: Option<u64>,
+            message: &'a str,
+            submitted_at: u64,
+        }
+        Json {
+            part: self.part,
+            answer: &self.answer,
+            verdict: &self.verdict,
+            hint: self.hint(),
+            wait_secs: self.wait().map(|wait| wait.as_secs()),
+            message: &self.message,
+            submitted_at: self.submitted_at,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// The verdict of a response page to an answer, and its message. Only the `<article>` of the page
+/// counts, so that the phrases elsewhere on it (in the sponsors, say) don't.
+fn parse_response(page: &str) -> Result<(Answer, String), AocError> {
+    let message = response_message(page);
+    let answer = if message.contains("That's the right answer!") {
+        Answer::Correct
+    } else if message.contains("That's not the right answer") {
+        let hint = if message.contains("your answer is too high") {
+            Some(Hint::TooHigh)
+        } else if message.contains("your answer is too low") {
+            Some(Hint::TooLow)
+        } else {
+            None
+        };
+        Answer::Incorrect(hint)
+    } else if message.contains("You don't seem to be solving") {
+        Answer::AlreadySubmitted(None)
+    } else if message.contains("You gave an answer too recently") {
+        Answer::RateLimited(parse_wait(&message))
+    } else {
+        let message = match message.char_indices().nth(200) {
+            Some((end, _)) => format!("{}...", &message[..end]),
+            None => message,
+        };
+        return Err(AocError::Parse(format!(
+            "Unexpected response to the answer, is the session cookie still valid? {}",
+            message
+        )));
+    };
+    Ok((answer, message))
+}
+
+/// The cache profile of a session cookie: the start of a hash of it, tagged so it's unlike the key
+/// `aoc encrypt` derives from the same cookie.
+fn cache_profile(session_cookie: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    let hash = Sha256::new().chain_update(b"aoc-cache\0").chain_update(session_cookie.as_bytes()).finalize();
+    hash[..8].iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Path of the "[Return to Day N]" link of a response page, e.g. `/2022/day/1`.
+fn return_link(page: &str) -> Option<String> {
+    let document = scraper::Html::parse_document(page);
+    let selector = scraper::Selector::parse("article a").unwrap();
+    let link = document
+        .select(&selector)
+        .find(|link| link.text().collect::<String>().starts_with("[Return to Day"))?;
+    link.value().attr("href").filter(|href| href.starts_with('/')).map(str::to_owned)
+}
+
+/// How many parts the puzzle `page` shows as completed: both once it has its `day-success`,
+/// otherwise one per answer shown. `None` when there are none.
+pub fn completed_parts(page: &str) -> Option<u8> {
+    if page.contains(r#"class="day-success""#) {
+        return Some(2);
+    }
+    let answers = page.matches("Your puzzle answer was").count().min(2) as u8;
+    (answers > 0).then_some(answers)
+}
+
+/// The text of the `<article>` of a response page, or of the whole page when there's none, with
+/// its whitespace collapsed.
+fn response_message(page: &str) -> String {
+    let document = scraper::Html::parse_document(page);
+    let selector = scraper::Selector::parse("article").unwrap();
+    let text = match document.select(&selector).next() {
+        Some(article) => article.text().collect::<String>(),
+        None => document.root_element().text().collect(),
+    };
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The time left to wait in "You have 1m 5s left to wait."
+fn parse_wait(message: &str) -> Option<Duration> {
+    let start = message.find("You have ")? + "You have ".len();
+    let end = start + message[start..].find(" left to wait")?;
+    let mut secs = 0;
+    for amount in message[start..end].split_whitespace() {
+        let unit = amount.chars().last()?;
+        let number: u64 = amount[..amount.len() - unit.len_utf8()].parse().ok()?;
+        secs += number
+            * match unit {
+                'h' => 60 * 60,
+                'm' => 60,
+                's' => 1,
+                _ => return None,
+            };
+    }
+    Some(Duration::from_secs(secs))
+}
+
+/// Bisect the candidates from `low` to `high` until `check` finds the right one, narrowing the
+/// range by the hints of incorrect answers.
+pub fn bisect(
+    mut low: i64,
+    mut high: i64,
+    mut check: impl FnMut(i64) -> Result<Answer, AocError>,
+) -> Result<i64, AocError> {
+    while low <= high {
+        let candidate = low + (high - low) / 2;
+        match check(candidate)? {
+            Answer::Correct => return Ok(candidate),
+            Answer::Incorrect(Some(Hint::TooHigh)) => high = candidate - 1,
+            Answer::Incorrect(Some(Hint::TooLow)) => low = candidate + 1,
+            Answer::Incorrect(None) => {
+                return Err(AocError::Parse(format!("{} is incorrect, without a hint to go on", candidate)))
+            }
+            answer => return Err(AocError::Parse(format!("Can't bisect: {:?}", answer))),
+        }
+    }
+    Err(AocError::Config("The right answer isn't in the range".to_owned()))
+}
+
+/// Retries on 502 and 503 responses, which AoC gives under heavy load, with an exponential
+/// backoff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Backoff {
+    pub max_tries: u32,
+    /// Delay before the first retry, doubled for each one after.
+    pub base_ms: u64,
+    /// Longest delay between retries.
+    pub cap_ms: u64,
+}
+
+impl Backoff {
+    /// Up to `max_tries` retries, from 1s up to 30s apart.
+    pub fn new(max_tries: u32) -> Self {
+        Backoff { max_tries, base_ms: 1_000, cap_ms: 30_000 }
+    }
+}
+
+/// Delay before retry number `attempt`, counting from 0: `base_ms` doubled `attempt` times, up to
+/// `cap_ms`.
+fn backoff_delay(attempt: u32, base_ms: u64, cap_ms: u64) -> std::time::Duration {
+    let ms = 2u64.checked_pow(attempt).and_then(|factor| base_ms.checked_mul(factor)).unwrap_or(u64::MAX);
+    std::time::Duration::from_millis(ms.min(cap_ms))
+}
+
+/// Wait before retry number `attempt`, without blocking the runtime's other requests.
+async fn backoff_sleep(attempt: u32, base_ms: u64, cap_ms: u64) {
+    tokio::time::sleep(backoff_delay(attempt, base_ms, cap_ms)).await;
+}
+
+/// Requests running at a time, unless configured otherwise.
+const DEFAULT_MAX_CONCURRENT: usize = 4;
+
+/// Sent with every request, so AoC can tell who's asking.
+pub const USER_AGENT: &str = "AceofSpades5757";
+
+/// Requests to Advent of Code. Commands go through this instead of making requests themselves, so
+/// they can be tested against a mock server.
+pub trait AocHttp {
+    /// The puzzle input of `day`.
+    fn fetch_input(&self, year: u16, day: u8) -> Result<String, AocError>;
+
+    /// A page, as logged in, which shows the answers given and stars earned.
+    fn fetch_page(&self, page: Page) -> Result<String, AocError>;
+
+    /// The puzzle description of `day`.
+    fn fetch_puzzle(&self, year: u16, day: u8) -> Result<String, AocError> {
+        self.fetch_page(Page::Puzzle { year, day })
+    }
+
+    /// Submit `answer` to `part` of `day`.
+    fn post_answer(&self, year: u16, day: u8, part: u8, answer: &str) -> Result<SubmissionResult, AocError>;
+
+    /// Username the session cookie is logged in as, or `None` if the server doesn't accept it.
+    fn fetch_username(&self) -> Result<Option<String>, AocError>;
+
+    /// The JSON of private leaderboard `id` of `year`, and how long ago it was fetched.
+    fn fetch_leaderboard_json(&self, year: u16, id: u64) -> Result<(String, Duration), AocError> {
+        Ok((self.fetch_page(Page::LeaderboardJson { year, id })?, Duration::ZERO))
+    }
+}
+
+/// Where a download of `AocClient::fetch_inputs` is at.
+#[derive(Debug)]
+pub enum Fetch {
+    /// The download of the day at this index started.
+    Started(usize),
+    /// The download of the day at this index finished.
+    Finished(usize, Result<String, AocError>),
+}
+
+/// `AocHttp` over a single async reqwest client, whose connections are reused between requests,
+/// logged in with a session cookie. Its methods block on its own runtime, so the commands stay
+/// synchronous, while bulk operations like `fetch_inputs` run their requests concurrently.
+///
+/// However they're made, at most `max_concurrent` requests run at a time, and they start at least
+/// `delay` apart, to go easy on the server.
+#[derive(Clone)]
+pub struct AocClient {
+    base_url: String,
+    user_agent: String,
+    /// Looked up on the first request which needs it, so commands without requests don't.
+    session_cookie: Arc<std::sync::OnceLock<Result<String, AocError>>>,
+    client: reqwest::Client,
+    runtime: Arc<tokio::runtime::Runtime>,
+    permits: Arc<tokio::sync::Semaphore>,
+    throttle: Arc<Throttle>,
+    max_tries: u32,
+    retry_delay: Duration,
+    backoff: Option<Backoff>,
+    cache: Option<Arc<Cache>>,
+}
+
+/// The outcome of one of the checks of `AocClient::diagnose`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub name: &'static str,
+    pub passed: bool,
+    /// Status of the response, `None` when there was none.
+    pub status: Option<u16>,
+    pub detail: String,
+}
+
+/// A line per check of `AocClient::diagnose`, marked as passed or failed.
+pub fn render_checks(checks: &[Check]) -> String {
+    checks
+        .iter()
+        .map(|check| {
+            let mark = if check.passed { "✓".green() } else { "✗".red() };
+            let status = check.status.map_or_else(|| "-".to_owned(), |status| status.to_string());
+            format!("{} {} ({}): {}\n", mark, check.name, status, check.detail)
+        })
+        .collect()
+}
+
+/// Spaces out the start of requests.
+struct Throttle {
+    delay: Duration,
+    next: tokio::sync::Mutex<Option<tokio::time::Instant>>,
+}
+
+impl Throttle {
+    /// Wait until `delay` after the start of the previous request.
+    async fn wait(&self) {
+        let mut next = self.next.lock().await;
+        if let Some(next) = *next {
+            tokio::time::sleep_until(next).await;
+        }
+        *next = Some(tokio::time::Instant::now() + self.delay);
+    }
+}
+
+impl AocClient {
+    /// A client of the server at `base_url`, which doesn't retry nor wait between requests, with
+    /// the session cookie of the environment.
+    pub fn new(base_url: &str) -> Result<Self, AocError> {
+        AocClient::with_client(base_url, reqwest::Client::new())
+    }
+
+    fn with_client(base_url: &str, client: reqwest::Client) -> Result<Self, AocError> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .enable_all()
+            .build()
+            .map_err(|e| AocError::io("Failed to start the HTTP runtime", e))?;
+        Ok(AocClient {
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            user_agent: USER_AGENT.to_owned(),
+            session_cookie: Arc::new(std::sync::OnceLock::new()),
+            client,
+            runtime: Arc::new(runtime),
+            permits: Arc::new(tokio::sync::Semaphore::new(DEFAULT_MAX_CONCURRENT)),
+            throttle: Arc::new(Throttle { delay: Duration::ZERO, next: Default::default() }),
+            max_tries: 0,
+            retry_delay: Duration::from_secs(1),
+            backoff: None,
+            cache: None,
+        })
+    }
+
+    /// A client as configured in `network`: of adventofcode.com unless `base_url` is set, with its
+    /// user agent, timeout, retries and limits, caching in the user's cache directory.
+    pub fn from_config(network: &NetworkConfig) -> Result<Self, AocError> {
+        let mut builder = reqwest::Client::builder();
+        if let Some(timeout_secs) = network.timeout_secs {
+            builder = builder.timeout(Duration::from_secs(timeout_secs));
+        }
+        let client = builder.build().map_err(|e| AocError::network("Failed to create the HTTP client", e))?;
+        let base_url = network.base_url.as_deref().unwrap_or(crate::urls::BASE_URL);
+        let mut aoc_client = AocClient::with_client(base_url, client)?
+            .with_retries(network.max_retries.unwrap_or(5), Duration::from_secs(1))
+            .with_backoff(network.backoff())
+            .with_limits(
+                network.max_concurrent_requests.unwrap_or(DEFAULT_MAX_CONCURRENT),
+                Duration::from_millis(network.request_delay_ms.unwrap_or(1000)),
+            );
+        if let Some(user_agent) = &network.user_agent {
+            aoc_client.user_agent = user_agent.clone();
+        }
+        if let Some(dir) = Cache::default_dir() {
+            aoc_client = aoc_client.with_cache(Cache::new(dir));
+        }
+        Ok(aoc_client)
+    }
+
+    /// Log in with `session_cookie` instead of the environment's.
+    pub fn with_session(mut self, session_cookie: &str) -> Self {
+        self.session_cookie = Arc::new(std::sync::OnceLock::from(Ok(session_cookie.to_owned())));
+        self
+    }
+
+    /// Retry up to `max_tries` times, `retry_delay` apart, while the puzzle hasn't opened yet.
+    pub fn with_retries(mut self, max_tries: u32, retry_delay: Duration) -> Self {
+        self.max_tries = max_tries;
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// Retry with `backoff` on server errors.
+    pub fn with_backoff(mut self, backoff: Option<Backoff>) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Run at most `max_concurrent` requests at a time, starting them at least `delay` apart.
+    pub fn with_limits(mut self, max_concurrent: usize, delay: Duration) -> Self {
+        self.permits = Arc::new(tokio::sync::Semaphore::new(max_concurrent.max(1)));
+        self.throttle = Arc::new(Throttle { delay, next: Default::default() });
+        self
+    }
+
+    /// Cache inputs, puzzle pages and leaderboards in `cache`.
+    pub fn with_cache(mut self, cache: Cache) -> Self {
+        self.cache = Some(Arc::new(cache));
+        self
+    }
+
+    /// URL of `page` on the server.
+    pub fn url(&self, page: Page) -> String {
+        page.url(&self.base_url)
+    }
+
+    /// `Cookie` header logging in with the session cookie.
+    pub fn cookie_header(&self) -> Result<String, AocError> {
+        let session_cookie = self
+            .session_cookie
+            .get_or_init(crate::env::session_cookie)
+            .as_ref()
+            .map_err(|e| AocError::Config(e.to_string()))?;
+        Ok(format!("session={}", session_cookie))
+    }
+
+    /// The cache, with the profile its entries are under, so that accounts don't share them.
+    /// `None` without a cache or a session cookie.
+    fn cache(&self) -> Option<(&Cache, String)> {
+        let cache = self.cache.as_deref()?;
+        let session_cookie = self.session_cookie.get_or_init(crate::env::session_cookie).as_ref().ok()?;
+        Some((cache, cache_profile(session_cookie)))
+    }
+
+    /// A request to `url`, with the session cookie and user agent. The cookie is marked sensitive,
+    /// so it's left out of the headers' `Debug`, and so of the log.
+    pub fn request(&self, method: reqwest::Method, url: &str) -> Result<reqwest::RequestBuilder, AocError> {
+        let mut cookie = reqwest::header::HeaderValue::from_str(&self.cookie_header()?)
+            .map_err(|e| AocError::Parse(format!("Invalid session cookie: {}", e)))?;
+        cookie.set_sensitive(true);
+        Ok(self
+            .client
+            .request(method, url)
+            .header("Cookie", cookie)
+            .header("User-Agent", &self.user_agent))
+    }
+
+    /// Download the inputs of `days`, by year and day, concurrently within the limits, telling
+    /// `on_fetch` as each download starts and finishes.
+    pub fn fetch_inputs(&self, days: &[(u16, u8)], mut on_fetch: impl FnMut(Fetch)) {
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        for (index, &(year, day)) in days.iter().enumerate() {
+            let client = self.clone();
+            let sender = sender.clone();
+            self.runtime.spawn(async move {
+                let _permit = client.permits.acquire().await;
+                let _ = sender.send(Fetch::Started(index));
+                let result = client.download_input(year, day, false).await;
+                let _ = sender.send(Fetch::Finished(index, result));
+            });
+        }
+        drop(sender);
+        while let Some(fetch) = self.runtime.block_on(receiver.recv()) {
+            on_fetch(fetch);
+        }
+    }
+
+    /// Send `request`, after waiting for its turn, retrying with the backoff while the server
+    /// answers 502 or 503. `error` describes a request that failed.
+    async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+        error: impl Fn(reqwest::Error) -> AocError,
+    ) -> Result<reqwest::Response, AocError> {
+        let mut attempt = 0;
+        loop {
+            let retry = request
+                .try_clone()
+                .ok_or_else(|| AocError::refused("Failed to retry the request"))?
+                .build()
+                .map_err(&error)?;
+            let (method, url, headers) = (retry.method().clone(), retry.url().clone(), retry.headers().clone());
+            self.throttle.wait().await;
+            let response = match self.client.execute(retry).await {
+                Ok(response) => response,
+                Err(e) => {
+                    tracing::warn!(%method, %url, ?headers, error = %e, "request");
+                    return Err(error(e));
+                }
+            };
+            tracing::info!(%method, %url, ?headers, status = response.status().as_u16(), "request");
+            let Some(backoff) = self.backoff.filter(|backoff| attempt < backoff.max_tries) else {
+                return Ok(response);
+            };
+            if !matches!(response.status().as_u16(), 502 | 503) {
+                return Ok(response);
+            }
+            eprintln!("{}", format!("Server error ({}), retrying...", response.status()).yellow());
+            backoff_sleep(attempt, backoff.base_ms, backoff.cap_ms).await;
+            attempt += 1;
+        }
+    }
+
+    async fn get(&self, url: &str) -> Result<reqwest::Response, AocError> {
+        let request = self.request(reqwest::Method::GET, url)?;
+        self.send(request, |e| AocError::network(format!("Failed to download {}", url), e)).await
+    }
+
+    /// Retries while the puzzle hasn't opened yet, with a progress bar while downloading if
+    /// `progress` is set.
+    #[tracing::instrument(skip(self, progress))]
+    async fn download_input(&self, year: u16, day: u8, progress: bool) -> Result<String, AocError> {
+        let url = self.url(Page::Input { year, day });
+        let mut response = self.get(&url).await?;
+
+        // if code is 404, try up to `max_tries` times
+        let mut tries = 0;
+        while response.status() == 404 && tries < self.max_tries {
+            eprintln!("{}", "Puzzle has not yet opened, retrying...".yellow());
+            tokio::time::sleep(self.retry_delay).await;
+            response = self.get(&url).await?;
+            tries += 1;
+        }
+        if response.status() == 404 {
+            return Err(AocError::refused("Puzzle has not yet opened, please try again later."));
+        }
+        // AoC answers 400 when it can't tell who's asking
+        if response.status() == 400 {
+            return Err(AocError::InvalidSession);
+        }
+
+        let mut response = response
+            .error_for_status()
+            .map_err(|e| AocError::network(format!("Failed to download {}", url), e))?;
+        let progress = match progress {
+            true => download_progress(response.content_length()),
+            false => indicatif::ProgressBar::hidden(),
+        };
+        let mut input = Vec::new();
+        let read = async {
+            while let Some(chunk) = response.chunk().await? {
+                progress.inc(chunk.len() as u64);
+                input.extend_from_slice(&chunk);
+            }
+            Ok::<_, reqwest::Error>(())
+        }
+        .await;
+        progress.finish_and_clear();
+        read.map_err(|e| AocError::network(format!("Failed to download {}", url), e))?;
+        String::from_utf8(input).map_err(|e| AocError::Parse(format!("Invalid input from {}: {}", url, e)))
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn download_page(&self, page: Page) -> Result<String, AocError> {
+        let url = self.url(page);
+        let response = self.get(&url).await?;
+        async { response.error_for_status()?.text().await }
+            .await
+            .map_err(|e| AocError::network(format!("Failed to download {}", url), e))
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn submit(&self, year: u16, day: u8, part: u8, answer: &str) -> Result<SubmissionResult, AocError> {
+        let url = self.url(Page::Answer { year, day });
+        let request = self
+            .request(reqwest::Method::POST, &url)?
+            .form(&[("level", part.to_string().as_str()), ("answer", answer)]);
+        let error = |e| AocError::network(format!("Failed to submit to {}", url), e);
+        let submitted_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        let response = self.send(request, error).await?.error_for_status().map_err(error)?;
+
+        let page = response.text().await.map_err(error)?;
+        let mut result = SubmissionResult::parse(&page, part, answer, submitted_at)?;
+        // AoC says the same when the part was completed and when it isn't unlocked yet, so which
+        // parts are completed is read from the puzzle's page
+        if result.verdict == Answer::AlreadySubmitted(None) {
+            if let Some(path) = return_link(&page) {
+                let completed = match self.get(&format!("{}{}", self.base_url, path)).await {
+                    Ok(response) if response.status().is_success() => {
+                        response.text().await.ok().and_then(|puzzle| completed_parts(&puzzle))
+                    }
+                    _ => None,
+                };
+                result.verdict = Answer::AlreadySubmitted(completed);
+            }
+        }
+        Ok(result)
+    }
+
+    async fn username(&self) -> Result<Option<String>, AocError> {
+        let request = self.request(reqwest::Method::GET, &self.url(Page::Settings))?;
+        let response = self.send(request, |e| AocError::network("Failed to reach Advent of Code", e)).await?;
+        // A server error says nothing about the session
+        if response.status().is_server_error() {
+            return Err(AocError::refused(format!("Advent of Code is unavailable: {}", response.status())));
+        }
+        if !response.status().is_success() {
+            return Ok(None);
+        }
+        let html = response.text().await.map_err(|e| AocError::network("Failed to read the response", e))?;
+        Ok(crate::login::settings_username(&html))
+    }
+
+    /// Check the session cookie against the server: that it logs in, that it gives access to the
+    /// input of day 1 of `year`, and, given the known answer of a part already completed, that
+    /// answers can be submitted. Resubmitting a completed part doesn't count as an attempt.
+    pub fn diagnose(&self, year: u16, known: Option<(u8, u8, &str)>) -> Vec<Check> {
+        let mut checks = vec![self.block_on(async {
+            let response = self.get(&self.url(Page::Settings)).await;
+            check("Authentication", response, |html| match crate::login::settings_username(html) {
+                Some(username) => Ok(format!("logged in as {}", username)),
+                None => Err("not logged in".to_owned()),
+            })
+            .await
+        })];
+        checks.push(self.block_on(async {
+            let response = self.get(&self.url(Page::Input { year, day: 1 })).await;
+            check("Input access", response, |input| Ok(format!("{} bytes of input", input.len()))).await
+        }));
+        if let Some((day, part, answer)) = known {
+            checks.push(self.block_on(async {
+                let request = self
+                    .request(reqwest::Method::POST, &self.url(Page::Answer { year, day }))
+                    .map(|request| request.form(&[("level", part.to_string().as_str()), ("answer", answer)]));
+                let response = match request {
+                    Ok(request) => self.send(request, |e| AocError::network("Failed to submit", e)).await,
+                    Err(err) => Err(err),
+                };
+                check("Answer submission", response, |html| {
+                    html.parse::<Answer>()
+                        .map(|answer| format!("day {} part {}: {}", day, part, answer.verdict()))
+                        .map_err(|e| e.to_string())
+                })
+                .await
+            }));
+        }
+        checks
+    }
+
+    /// Run `request` on the runtime, within the concurrency limit.
+    fn block_on<T>(&self, request: impl std::future::Future<Output = T>) -> T {
+        self.runtime.block_on(async {
+            let _permit = self.permits.acquire().await;
+            request.await
+        })
+    }
+}
+
+impl AocHttp for AocClient {
+    /// Retries while the puzzle hasn't opened yet, with a progress bar while downloading.
+    /// Cached for good, as inputs never change.
+    fn fetch_input(&self, year: u16, day: u8) -> Result<String, AocError> {
+        let download = || self.block_on(self.download_input(year, day, true));
+        match self.cache() {
+            Some((cache, profile)) => Ok(cache
+                .get_or_fetch(&Key::new(Kind::Input, year, day, &profile), None, || download().map(Entry::new))?
+                .payload),
+            None => download(),
+        }
+    }
+
+    /// Puzzle pages are cached until a part is completed, which adds to them.
+    fn fetch_page(&self, page: Page) -> Result<String, AocError> {
+        let download = || self.block_on(self.download_page(page));
+        match (page, self.cache()) {
+            (Page::Puzzle { year, day }, Some((cache, profile))) => Ok(cache
+                .get_or_fetch(&Key::new(Kind::Puzzle, year, day, &profile), None, || download().map(Entry::new))?
+                .payload),
+            _ => download(),
+        }
+    }
+
+    /// Sends the level and answer in a single form.
+    fn post_answer(&self, year: u16, day: u8, part: u8, answer: &str) -> Result<SubmissionResult, AocError> {
+        let result = self.block_on(self.submit(year, day, part, answer))?;
+        if let (Answer::Correct | Answer::AlreadySubmitted(Some(_)), Some((cache, profile))) =
+            (&result.verdict, self.cache())
+        {
+            cache.invalidate(&Key::new(Kind::Puzzle, year, day, &profile))?;
+        }
+        Ok(result)
+    }
+
+    /// From the settings page, which only answers with the username when logged in.
+    fn fetch_username(&self) -> Result<Option<String>, AocError> {
+        self.block_on(self.username())
+    }
+
+    /// Cached for `leaderboard::CACHE_MAX_AGE`, as AoC asks.
+    fn fetch_leaderboard_json(&self, year: u16, id: u64) -> Result<(String, Duration), AocError> {
+        let page = Page::LeaderboardJson { year, id };
+        let Some((cache, profile)) = self.cache() else {
+            return Ok((self.fetch_page(page)?, Duration::ZERO));
+        };
+        let key = Key::new(Kind::Leaderboard(id), year, 0, &profile);
+        let entry = cache.get_or_fetch(&key, Some(crate::leaderboard::CACHE_MAX_AGE), || {
+            self.fetch_page(page).map(Entry::new)
+        })?;
+        Ok((entry.payload.clone(), entry.age()))
+    }
+}
+
+/// A check of `response`, passing when it's successful and `inspect` accepts its body.
+async fn check(
+    name: &'static str,
+    response: Result<reqwest::Response, AocError>,
+    inspect: impl FnOnce(&str) -> Result<String, String>,
+) -> Check {
+    let response = match response {
+        Ok(response) => response,
+        Err(e) => return Check { name, passed: false, status: None, detail: e.to_string() },
+    };
+    let status = response.status();
+    let body = response.text().await;
+    let (passed, detail) = match body {
+        Ok(_) if !status.is_success() => (false, status.canonical_reason().unwrap_or_default().to_owned()),
+        Ok(body) => match inspect(&body) {
+            Ok(detail) => (true, detail),
+            Err(detail) => (false, detail),
+        },
+        Err(e) => (false, format!("Failed to read the response: {}", e)),
+    };
+    Check { name, passed, status: Some(status.as_u16()), detail }
+}
+
+/// A progress bar on stderr, so it stays out of the input printed with `--stdout`, filling up to
+/// the `Content-Length` of a download, or a spinner when it's unknown. Hidden outside of a terminal.
+fn download_progress(content_length: Option<u64>) -> indicatif::ProgressBar {
+    match content_length {
+        Some(length) => indicatif::ProgressBar::new(length).with_style(
+            indicatif::ProgressStyle::with_template("Downloading {bar:40.cyan/blue} {bytes}/{total_bytes}").unwrap(),
+        ),
+        None => indicatif::ProgressBar::new_spinner()
+            .with_style(indicatif::ProgressStyle::with_template("Downloading {spinner} {bytes}").unwrap()),
+    }
+}
+
+/// Private leaderboard `id` of `year`, and the age of its cached copy.
+pub fn fetch_leaderboard(
+    http: &impl AocHttp,
+    year: u16,
+    id: u64,
+) -> Result<(crate::leaderboard::Leaderboard, std::time::Duration), AocError> {
+    let (json, age) = http.fetch_leaderboard_json(year, id)?;
+    Ok((crate::leaderboard::parse(&json)?, age))
+}
+
+/// Check the session cookie against the AoC server, returning the username it's logged in as, or
+/// `None` when it's invalid or expired. Errors when the server can't tell, e.g. offline.
+pub fn check_session(http: &impl AocHttp) -> Result<Option<String>, AocError> {
+    http.fetch_username()
+}
+
+/// Username from the page header, which is only shown when logged in.
+pub fn parse_username(html: &str) -> Option<String> {
+    let start = html.find(r#"<div class="user">"#)? + r#"<div class="user">"#.len();
+    let end = html[start..].find('<').map_or(html.len(), |end| start + end);
+    let username = html[start..end].trim();
+    if username.is_empty() {
+        None
+    } else {
+        Some(username.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_username() {
+        let html = r#"<header><div><div class="user">AceofSpades5757 <span class="star-count">50*</span></div></div></header>"#;
+        assert_eq!(parse_username(html), Some("AceofSpades5757".to_owned()));
+        let html = r#"<header><div><p class="wrap">[<a href="/2022/auth/login">Log In</a>]</p></div></header>"#;
+        assert_eq!(parse_username(html), None);
+    }
+
+    #[test]
+    fn test_unexpected_response() {
+        let err = "<html><body><main><article><p>Please log in.</p></article></main></body></html>"
+            .parse::<Answer>()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unexpected response to the answer, is the session cookie still valid? Please log in."
+        );
+        let err = "x".repeat(300).parse::<Answer>().unwrap_err();
+        assert!(err.to_string().ends_with(&format!(" {}...", "x".repeat(200))), "{}", err);
+    }
+
+    #[test]
+    fn test_request_headers() {
+        let client = AocClient::new("http://localhost:8080/").unwrap().with_session("53616c74");
+        let url = client.url(Page::Input { year: 2022, day: 1 });
+        assert_eq!(url, "http://localhost:8080/2022/day/1/input");
+        let request = client.request(reqwest::Method::GET, &url).unwrap().build().unwrap();
+        assert_eq!(request.url().as_str(), url);
+        assert_eq!(request.headers()["Cookie"], "session=53616c74");
+        assert_eq!(request.headers()["User-Agent"], USER_AGENT);
+
+        let network = NetworkConfig {
+            base_url: Some("http://localhost:8080".to_owned()),
+            user_agent: Some("github.com/AceofSpades5757/aoc by someone@example.com".to_owned()),
+            ..NetworkConfig::default()
+        };
+        let client = AocClient::from_config(&network).unwrap().with_session("53616c74");
+        assert_eq!(client.url(Page::Settings), "http://localhost:8080/settings");
+        let request = client.request(reqwest::Method::GET, &client.url(Page::Settings)).unwrap().build().unwrap();
+        assert_eq!(request.headers()["User-Agent"], "github.com/AceofSpades5757/aoc by someone@example.com");
+        let client = AocClient::from_config(&NetworkConfig::default()).unwrap();
+        assert_eq!(client.url(Page::Settings), "https://adventofcode.com/settings");
+    }
+
+    #[test]
+    fn test_download_progress() {
+        assert_eq!(download_progress(Some(1024)).length(), Some(1024));
+        assert_eq!(download_progress(None).length(), None);
+    }
+
+    #[test]
+    fn test_backoff_delay() {
+        let delays: Vec<u64> = (0..7).map(|attempt| backoff_delay(attempt, 1_000, 30_000).as_millis() as u64).collect();
+        assert_eq!(delays, [1_000, 2_000, 4_000, 8_000, 16_000, 30_000, 30_000]);
+        assert_eq!(backoff_delay(100, 1_000, 30_000), std::time::Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_answer_hints() {
+        let incorrect = "<p>That's not the right answer; your answer is too high.  If you're stuck";
+        assert_eq!(incorrect.parse::<Answer>().unwrap(), Answer::Incorrect(Some(Hint::TooHigh)));
+        let incorrect = "<p>That's not the right answer; your answer is too low.";
+        assert_eq!(incorrect.parse::<Answer>().unwrap(), Answer::Incorrect(Some(Hint::TooLow)));
+        let incorrect = "<p>That's not the right answer.  If you're stuck";
+        assert_eq!(incorrect.parse::<Answer>().unwrap(), Answer::Incorrect(None));
+    }
+
+    #[test]
+    fn test_submission_results() {
+        let parse = |page: &str, part| SubmissionResult::parse(page, part, "24000", 1_670_000_000).unwrap();
+        let result = |part, verdict, message: &str| SubmissionResult {
+            part,
+            answer: "24000".to_owned(),
+            verdict,
+            message: message.to_owned(),
+            submitted_at: 1_670_000_000,
+        };
+        let wait = "Please wait one minute before trying again. [Return to Day 1]";
+
+        let correct = parse(include_str!("../tests/fixtures/answer/correct.html"), 1);
+        let message = "That's the right answer! You are one gold star closer to collecting enough star fruit. \
+            [Continue to Part Two]";
+        assert_eq!(correct, result(1, Answer::Correct, message));
+        assert_eq!((correct.hint(), correct.wait(), correct.exit_code()), (None, None, 0));
+
+        let too_high = parse(include_str!("../tests/fixtures/answer/too_high.html"), 1);
+        let message = format!(
+            "That's not the right answer; your answer is too high. If you're stuck, make sure you're using the \
+            full input data; there are also some general tips on the about page, or you can ask for hints on the \
+            subreddit. {}",
+            wait
+        );
+        assert_eq!(too_high, result(1, Answer::Incorrect(Some(Hint::TooHigh)), &message));
+        assert_eq!((too_high.hint(), too_high.exit_code()), (Some(Hint::TooHigh), 1));
+        let too_low = parse(include_str!("../tests/fixtures/answer/too_low.html"), 2);
+        let message = format!(
+            "That's not the right answer; your answer is too low. If you're stuck, make sure you're using the full \
+            input data. {}",
+            wait
+        );
+        assert_eq!(too_low, result(2, Answer::Incorrect(Some(Hint::TooLow)), &message));
+        let incorrect = parse(include_str!("../tests/fixtures/answer/incorrect.html"), 1);
+        let message = format!(
+            "That's not the right answer. If you're stuck, make sure you're using the full input data. {}",
+            wait
+        );
+        assert_eq!(incorrect, result(1, Answer::Incorrect(None), &message));
+        assert_eq!(incorrect.hint(), None);
+
+        let wrong_level_page = include_str!("../tests/fixtures/answer/wrong_level.html");
+        let wrong_level = parse(wrong_level_page, 1);
+        let message = "You don't seem to be solving the right level. Did you already complete it? [Return to Day 1]";
+        assert_eq!(wrong_level, result(1, Answer::AlreadySubmitted(None), message));
+        assert_eq!(return_link(wrong_level_page), Some("/2022/day/1".to_owned()));
+        assert_eq!(return_link(include_str!("../tests/fixtures/answer/correct.html")), None);
+        assert_eq!(wrong_level.exit_code(), 2);
+
+        let rate_limited = parse(include_str!("../tests/fixtures/answer/rate_limited.html"), 1);
+        let message = "You gave an answer too recently; you have to wait after submitting an answer before trying \
+            again. You have 1m 5s left to wait. [Return to Day 1]";
+        assert_eq!(rate_limited, result(1, Answer::RateLimited(Some(Duration::from_secs(65))), message));
+        assert_eq!((rate_limited.wait(), rate_limited.exit_code()), (Some(Duration::from_secs(65)), 3));
+
+        // The sponsor saying it's the right answer doesn't make it so
+        let sponsor = parse(include_str!("../tests/fixtures/answer/sponsor.html"), 1);
+        let message = format!("That's not the right answer; your answer is too low. {}", wait);
+        assert_eq!(sponsor, result(1, Answer::Incorrect(Some(Hint::TooLow)), &message));
+
+        assert_eq!(
+            too_low.submission(),
+            crate::submissions::Submission {
+                timestamp: 1_670_000_000,
+                part: 2,
+                answer: "24000".to_owned(),
+                verdict: "too low".to_owned()
+            }
+        );
+        let json = serde_json::to_value(&rate_limited).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "part": 1,
+                "answer": "24000",
+                "verdict": "rate limited",
+                "hint": null,
+                "wait_secs": 65,
+                "message": rate_limited.message,
+                "submitted_at": 1_670_000_000
+            })
+        );
+        assert_eq!(serde_json::to_value(&too_high).unwrap()["hint"], "too_high");
+    }
+
+    #[test]
+    fn test_cache_profile() {
+        let profile = cache_profile("abc123");
+        assert_eq!(profile.len(), 16);
+        assert_ne!(profile, cache_profile("def456"));
+        let encryption_key: String = crate::crypt::key("abc123").iter().map(|byte| format!("{:02x}", byte)).collect();
+        assert!(!encryption_key.starts_with(&profile));
+    }
+
+    #[test]
+    fn test_completed_parts() {
+        let answer = "<p>Your puzzle answer was <code>24000</code>.</p>";
+        assert_eq!(completed_parts(include_str!("../tests/fixtures/puzzle.html")), None);
+        assert_eq!(completed_parts(answer), Some(1));
+        assert_eq!(completed_parts(&answer.repeat(2)), Some(2));
+        assert_eq!(completed_parts(r#"<p class="day-success">Both parts of this puzzle are complete!</p>"#), Some(2));
+    }
+
+    #[test]
+    fn test_parse_wait() {
+        assert_eq!(parse_wait("You have 36s left to wait."), Some(Duration::from_secs(36)));
+        assert_eq!(parse_wait("You have 1h 2m 3s left to wait."), Some(Duration::from_secs(3723)));
+        assert_eq!(parse_wait("You have to wait after submitting an answer."), None);
+    }
+
+    #[test]
+    fn test_bisect() {
+        let check = |answer: i64| {
+            move |candidate: i64| {
+                Ok(match candidate.cmp(&answer) {
+                    std::cmp::Ordering::Equal => Answer::Correct,
+                    std::cmp::Ordering::Greater => Answer::Incorrect(Some(Hint::TooHigh)),
+                    std::cmp::Ordering::Less => Answer::Incorrect(Some(Hint::TooLow)),
+                })
+            }
+        };
+        for answer in [-5, 0, 1, 999, 1000] {
+            assert_eq!(bisect(-5, 1000, check(answer)).unwrap(), answer);
+        }
+        assert!(bisect(0, 10, check(11)).is_err());
+
+        let mut tried = Vec::new();
+        let result = bisect(0, 100, |candidate| {
+            tried.push(candidate);
+            Ok(Answer::Incorrect(None))
+        });
+        assert!(result.is_err());
+        assert_eq!(tried, vec![50]);
+    }
+
+    #[test]
+    fn test_render_checks() {
+        let checks = [
+            Check { name: "Authentication", passed: true, status: Some(200), detail: "logged in as Alice".to_owned() },
+            Check { name: "Input access", passed: false, status: None, detail: "timed out".to_owned() },
+        ];
+        let text = render_checks(&checks);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("Authentication (200): logged in as Alice"));
+        assert!(lines[1].ends_with("Input access (-): timed out"));
+    }
+}
@@ -0,0 +1,592 @@
+//! The config: the defaults of `config.toml`, the user's config file and `AOC_` environment
+//! variables.
+use crate::error::AocError;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use toml_edit::Document;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Formats {
+    pub day: Option<String>,
+    pub year: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DayConfig {
+    /// Register new days as members of the year's Cargo workspace.
+    pub workspace: Option<bool>,
+}
+
+/// How `aoc part` starts part 2.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PartMode {
+    /// Copy part 1.
+    #[default]
+    Copy,
+    /// Start from the part template.
+    Fresh,
+    /// Start from a part template which uses the shared parsing in src/lib.rs.
+    Lib,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PartConfig {
+    /// Default for `aoc part`: "copy", "fresh" or "lib".
+    pub default: Option<PartMode>,
+}
+
+/// Rust edition of new day crates.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "toml::Value", into = "String")]
+pub enum Edition {
+    E2015,
+    E2018,
+    #[default]
+    E2021,
+    E2024,
+}
+
+impl Edition {
+    pub const ALL: [Edition; 4] = [Edition::E2015, Edition::E2018, Edition::E2021, Edition::E2024];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Edition::E2015 => "2015",
+            Edition::E2018 => "2018",
+            Edition::E2021 => "2021",
+            Edition::E2024 => "2024",
+        }
+    }
+}
+
+/// Editions are strings in Cargo.toml, but integers are accepted too, as `aoc config set` and
+/// `AOC_` variables parse `2021` as one.
+impl TryFrom<toml::Value> for Edition {
+    type Error = String;
+
+    fn try_from(value: toml::Value) -> Result<Self, String> {
+        let edition = match &value {
+            toml::Value::String(edition) => edition.clone(),
+            toml::Value::Integer(edition) => edition.to_string(),
+            _ => String::new(),
+        };
+        Edition::ALL
+            .into_iter()
+            .find(|known| known.as_str() == edition)
+            .ok_or_else(|| format!("unknown edition {}, expected 2015, 2018, 2021 or 2024", value))
+    }
+}
+
+impl From<Edition> for String {
+    fn from(edition: Edition) -> String {
+        edition.as_str().to_owned()
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct WorkspaceConfig {
+    /// Author recorded in new workspaces' `[workspace.metadata.aoc]`.
+    pub author: Option<String>,
+    /// Rust edition of new day crates, "2021" by default.
+    pub edition: Option<Edition>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TemplatesConfig {
+    /// Create new days with a src/lib.rs, with a `utils` module used by the parts.
+    pub create_lib: Option<bool>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct LeaderboardConfig {
+    /// Id of the private leaderboard shown by `aoc leaderboard` and opened by `aoc open --leaderboard`.
+    pub id: Option<u64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct LogConfig {
+    /// File to append a JSON line to for each request, file write and command, as with `--log-file`.
+    pub file: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SessionConfig {
+    /// Session cookie, when it isn't in the environment or a .env file. Set with `aoc login`.
+    pub cookie: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    /// How many times to retry downloading input for a puzzle that hasn't opened yet.
+    pub max_retries: Option<u32>,
+    /// Retry on 502 and 503 from the server too, backing off exponentially.
+    pub retry_on_server_error: Option<bool>,
+    /// Milliseconds to wait between the start of requests.
+    pub request_delay_ms: Option<u64>,
+    /// Requests running at a time, as when downloading several inputs.
+    pub max_concurrent_requests: Option<usize>,
+    /// Server to send requests to instead of adventofcode.com, e.g. a mock server.
+    pub base_url: Option<String>,
+    /// User agent of the requests.
+    pub user_agent: Option<String>,
+    /// Seconds before a request times out.
+    pub timeout_secs: Option<u64>,
+}
+
+impl NetworkConfig {
+    /// How to retry on server errors, unless `retry_on_server_error` is off.
+    pub fn backoff(&self) -> Option<crate::client::Backoff> {
+        self.retry_on_server_error
+            .unwrap_or(true)
+            .then(|| crate::client::Backoff::new(self.max_retries.unwrap_or(5)))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Config {
+    /// Version of the layout of the config, see `CONFIG_VERSION`.
+    #[serde(default)]
+    pub version: u32,
+    /// Version the user's config file had when loaded, before it was migrated.
+    #[serde(skip)]
+    pub file_version: Option<u32>,
+    pub formats: Formats,
+    #[serde(default)]
+    pub day: DayConfig,
+    #[serde(default)]
+    pub part: PartConfig,
+    #[serde(default)]
+    pub network: NetworkConfig,
+    #[serde(default)]
+    pub templates: TemplatesConfig,
+    #[serde(default)]
+    pub workspace: WorkspaceConfig,
+    #[serde(default)]
+    pub leaderboard: LeaderboardConfig,
+    #[serde(default)]
+    pub session: SessionConfig,
+    #[serde(default)]
+    pub log: LogConfig,
+}
+
+/// Version of the layout of the config, written as `version` at the top of config files. Files
+/// without one are version 0.
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Upgrades of the layout of config files, the one at index `n` from version `n` to `n + 1`.
+const MIGRATIONS: [fn(Document) -> Document; CONFIG_VERSION as usize] = [migrate_v0];
+
+/// Config sections which can be overridden with `AOC_<SECTION>_<KEY>` environment variables.
+const CONFIG_SECTIONS: [&str; 9] =
+    ["formats", "day", "part", "network", "templates", "workspace", "leaderboard", "session", "log"];
+
+/// Keys of the config, as `<section>.<key>`.
+pub const CONFIG_KEYS: [&str; 17] = [
+    "formats.day",
+    "formats.year",
+    "day.workspace",
+    "part.default",
+    "network.max_retries",
+    "network.retry_on_server_error",
+    "network.request_delay_ms",
+    "network.max_concurrent_requests",
+    "network.base_url",
+    "network.user_agent",
+    "network.timeout_secs",
+    "templates.create_lib",
+    "workspace.author",
+    "workspace.edition",
+    "leaderboard.id",
+    "session.cookie",
+    "log.file",
+];
+
+impl Config {
+    /// Load the config, with `AOC_` environment variables taking precedence over the user's config
+    /// file, which takes precedence over config.toml.
+    pub fn load() -> Result<Self, AocError> {
+        let (mut config, file_version) = Config::load_file_versioned(config_path().as_deref())?;
+        apply_env_overrides(&mut config, std::env::vars());
+        let mut config: Config = config.try_into().map_err(|e| AocError::Parse(format!("Invalid config: {}", e)))?;
+        config.file_version = file_version;
+        Ok(config)
+    }
+
+    /// config.toml, with the values of the config file at `path`, if there is one, migrated to the
+    /// current layout.
+    pub fn load_file(path: Option<&Path>) -> Result<toml::Value, AocError> {
+        Config::load_file_versioned(path).map(|(config, _)| config)
+    }
+
+    /// The config, as with `load_file`, and the version of the config file before migrating it.
+    fn load_file_versioned(path: Option<&Path>) -> Result<(toml::Value, Option<u32>), AocError> {
+        let mut config: toml::Value = toml::from_str(include_str!("../config.toml"))
+            .map_err(|e| AocError::Parse(format!("Invalid config.toml: {}", e)))?;
+        let Some((path, contents)) = path.and_then(|path| Some((path, std::fs::read_to_string(path).ok()?))) else {
+            return Ok((config, None));
+        };
+        let invalid = |e: &dyn std::fmt::Display| AocError::Config(format!("Invalid {}: {}", path.display(), e));
+        let user_config = contents.parse::<Document>().map_err(|e| invalid(&e))?;
+        let version = config_version(&user_config).map_err(|e| invalid(&e))?;
+        let user_config: toml::Value =
+            toml::from_str(&migrate_config(user_config)?.to_string()).map_err(|e| invalid(&e))?;
+        if let (Some(config), Some(user_config)) = (config.as_table_mut(), user_config.as_table()) {
+            for (section, values) in user_config {
+                let Some(values) = values.as_table() else {
+                    // Top-level values, like `version`
+                    config.insert(section.clone(), values.clone());
+                    continue;
+                };
+                let section = config
+                    .entry(section)
+                    .or_insert_with(|| toml::Value::Table(Default::default()));
+                if let Some(section) = section.as_table_mut() {
+                    section.extend(values.clone());
+                }
+            }
+        }
+        Ok((config, Some(version)))
+    }
+
+    /// Whether the user's config file has an older layout, which `aoc config migrate` upgrades.
+    pub fn needs_migration(&self) -> bool {
+        self.file_version.is_some_and(|version| version < CONFIG_VERSION)
+    }
+}
+
+/// The version of the layout of `config`, 0 without one, failing for versions newer than this
+/// aoc knows.
+pub fn config_version(config: &Document) -> Result<u32, AocError> {
+    let Some(version) = config.get("version") else {
+        return Ok(0);
+    };
+    let version = version
+        .as_integer()
+        .and_then(|version| u32::try_from(version).ok())
+        .ok_or_else(|| AocError::Config(format!("version should be a positive integer, not {}", version)))?;
+    if version > CONFIG_VERSION {
+        return Err(AocError::Config(format!(
+            "this config was written by a newer aoc (config version {}, this aoc knows up to {}), please upgrade aoc",
+            version, CONFIG_VERSION
+        )));
+    }
+    Ok(version)
+}
+
+/// `config` upgraded to the current layout, keeping its formatting and comments.
+pub fn migrate_config(config: Document) -> Result<Document, AocError> {
+    let version = config_version(&config)?;
+    Ok(MIGRATIONS
+        .iter()
+        .enumerate()
+        .skip(version as usize)
+        .fold(config, |config, (index, migrate)| {
+            let mut config = migrate(config);
+            config["version"] = toml_edit::value(index as i64 + 1);
+            config
+        }))
+}
+
+/// What `migrate_file` found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Migration {
+    /// There's no config file.
+    Missing,
+    /// The file is at `version`, the current one.
+    UpToDate(u32),
+    /// The file was at `from`, and `migrated` is it upgraded to the current layout.
+    Migrated { from: u32, migrated: String },
+}
+
+/// Upgrade the config file at `path` to the current layout, writing it back with `write`.
+pub fn migrate_file(path: &Path, write: bool) -> Result<Migration, AocError> {
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return Ok(Migration::Missing);
+    };
+    let invalid = |e: &dyn std::fmt::Display| AocError::Config(format!("Invalid {}: {}", path.display(), e));
+    let config = contents.parse::<Document>().map_err(|e| invalid(&e))?;
+    let version = config_version(&config).map_err(|e| invalid(&e))?;
+    if version == CONFIG_VERSION {
+        return Ok(Migration::UpToDate(version));
+    }
+    let migrated = migrate_config(config).map_err(|e| invalid(&e))?.to_string();
+    if write {
+        std::fs::write(path, &migrated).map_err(|e| AocError::io(format!("Failed to write {}", path.display()), e))?;
+    }
+    Ok(Migration::Migrated { from: version, migrated })
+}
+
+/// Version 0 to 1: the session cookie moves from a top-level `session` into `[session] cookie`.
+fn migrate_v0(mut config: Document) -> Document {
+    if config.get("session").is_some_and(|session| session.is_value()) {
+        let cookie = config.remove("session").unwrap();
+        config["session"] = toml_edit::table();
+        config["session"]["cookie"] = cookie;
+    }
+    config
+}
+
+/// The user's config file, `aoc/config.toml` in the config directory.
+pub fn config_path() -> Option<PathBuf> {
+    crate::template::config_dir().map(|dir| dir.join("aoc").join("config.toml"))
+}
+
+/// The user's config file, for the commands writing to it.
+pub fn require_config_path() -> Result<PathBuf, AocError> {
+    config_path().ok_or_else(|| AocError::Config("No config directory, set XDG_CONFIG_HOME".to_owned()))
+}
+
+/// Parse a config value given on the command line as a TOML value where possible, so `10` is an
+/// integer, and otherwise as a string.
+fn parse_config_value(value: &str) -> toml::Value {
+    format!("value = {}", value)
+        .parse::<toml::Value>()
+        .ok()
+        .and_then(|parsed| parsed.get("value").cloned())
+        .unwrap_or_else(|| toml::Value::String(value.to_owned()))
+}
+
+/// Set `key` (e.g. `formats.day`) to `value` in the config file at `path`, checking that the
+/// resulting config is valid.
+pub fn set_config_value(path: &Path, key: &str, value: &str) -> Result<(), AocError> {
+    let Some((section, name)) = key.split_once('.').filter(|_| CONFIG_KEYS.contains(&key)) else {
+        return Err(AocError::Config(format!(
+            "Unknown config key: {}. Expected one of: {}",
+            key,
+            CONFIG_KEYS.join(", ")
+        )));
+    };
+    let mut config = match std::fs::read_to_string(path) {
+        Ok(contents) => contents
+            .parse::<Document>()
+            .map_err(|e| AocError::Parse(format!("Invalid {}: {}", path.display(), e)))?,
+        Err(_) => {
+            let mut config = Document::new();
+            config["version"] = toml_edit::value(CONFIG_VERSION as i64);
+            config
+        }
+    };
+    let value = parse_config_value(value)
+        .to_string()
+        .parse::<toml_edit::Value>()
+        .map_err(|e| AocError::Parse(format!("Invalid value: {}", e)))?;
+    if !config.contains_table(section) {
+        config[section] = toml_edit::table();
+    }
+    config[section][name] = toml_edit::Item::Value(value);
+
+    // Check the value's type before writing
+    let mut merged = Config::load_file(None)?;
+    let user_config: toml::Value =
+        toml::from_str(&config.to_string()).map_err(|e| AocError::Parse(format!("Invalid value: {}", e)))?;
+    if let (Some(merged), Some(section_values)) = (merged.as_table_mut(), user_config.get(section)) {
+        merged.insert(section.to_owned(), section_values.clone());
+    }
+    if let Err(e) = merged.try_into::<Config>() {
+        return Err(AocError::Config(format!("Invalid value for {}: {}", key, e)));
+    }
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| AocError::io(format!("Failed to create {}", dir.display()), e))?;
+    }
+    std::fs::write(path, config.to_string())
+        .map_err(|e| AocError::io(format!("Failed to write {}", path.display()), e))
+}
+
+/// The value of `key` (e.g. `formats.day`) in `config`, if it's set.
+pub fn get_config_value(config: &toml::Value, key: &str) -> Result<Option<String>, AocError> {
+    let Some((section, name)) = key.split_once('.').filter(|_| CONFIG_KEYS.contains(&key)) else {
+        return Err(AocError::Config(format!(
+            "Unknown config key: {}. Expected one of: {}",
+            key,
+            CONFIG_KEYS.join(", ")
+        )));
+    };
+    Ok(config.get(section).and_then(|section| section.get(name)).map(|value| match value {
+        toml::Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }))
+}
+
+/// The value of `key` in effect: from the environment `vars`, the config file at `path` or the
+/// defaults.
+pub fn effective_value(
+    path: Option<&Path>,
+    key: &str,
+    vars: impl Iterator<Item = (String, String)>,
+) -> Result<Option<String>, AocError> {
+    let mut config = Config::load_file(path)?;
+    apply_env_overrides(&mut config, vars);
+    get_config_value(&config, key)
+}
+
+/// Override config values with environment variables, e.g. `AOC_FORMATS_DAY=puzzle-` sets
+/// `formats.day`.
+///
+/// Values are parsed as TOML values where possible, so `AOC_NETWORK_MAX_RETRIES=10` is an integer,
+/// and otherwise used as strings.
+pub fn apply_env_overrides(config: &mut toml::Value, vars: impl Iterator<Item = (String, String)>) {
+    for (name, value) in vars {
+        let Some(name) = name.strip_prefix("AOC_") else {
+            continue;
+        };
+        let name = name.to_lowercase();
+        let Some((section, key)) = name.split_once('_') else {
+            continue;
+        };
+        if !CONFIG_SECTIONS.contains(&section) {
+            continue;
+        }
+
+        let value = parse_config_value(&value);
+        let Some(config) = config.as_table_mut() else {
+            return;
+        };
+        let section = config
+            .entry(section)
+            .or_insert_with(|| toml::Value::Table(Default::default()));
+        if let Some(section) = section.as_table_mut() {
+            section.insert(key.to_owned(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_overrides() {
+        let mut config: toml::Value = toml::from_str(include_str!("../config.toml")).unwrap();
+        let vars = vec![
+            ("AOC_FORMATS_DAY".to_owned(), "puzzle-".to_owned()),
+            ("AOC_NETWORK_MAX_RETRIES".to_owned(), "10".to_owned()),
+            ("AOC_DAY_WORKSPACE".to_owned(), "false".to_owned()),
+            ("AOC_INPUT_PATH".to_owned(), "example.txt".to_owned()),
+            ("HOME".to_owned(), "/root".to_owned()),
+        ];
+        apply_env_overrides(&mut config, vars.into_iter());
+        assert!(config.get("input").is_none());
+
+        let config: Config = config.try_into().unwrap();
+        assert_eq!(config.formats.day.as_deref(), Some("puzzle-"));
+        assert_eq!(config.formats.year.as_deref(), Some("advent-of-code-"));
+        assert_eq!(config.network.max_retries, Some(10));
+        assert_eq!(config.day.workspace, Some(false));
+    }
+
+    #[test]
+    fn test_set_config_value() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let path = tmp_dir.path().join("aoc/config.toml");
+        set_config_value(&path, "formats.day", "puzzle-").unwrap();
+        set_config_value(&path, "network.max_retries", "10").unwrap();
+        set_config_value(&path, "formats.day", "day_").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "version = 1\n\n[formats]\nday = \"day_\"\n\n[network]\nmax_retries = 10\n"
+        );
+
+        let config = Config::load_file(Some(&path)).unwrap();
+        assert_eq!(get_config_value(&config, "formats.day").unwrap(), Some("day_".to_owned()));
+        assert_eq!(get_config_value(&config, "formats.year").unwrap(), Some("advent-of-code-".to_owned()));
+        assert_eq!(get_config_value(&config, "network.max_retries").unwrap(), Some("10".to_owned()));
+        assert_eq!(get_config_value(&config, "workspace.author").unwrap(), None);
+        assert!(get_config_value(&config, "formats.nope").is_err());
+
+        assert!(set_config_value(&path, "formats", "day-").is_err());
+        assert!(set_config_value(&path, "network.max_retries", "many").is_err());
+        assert!(set_config_value(&path, "part.default", "sometimes").is_err());
+        assert!(set_config_value(&path, "workspace.edition", "2019").is_err());
+        set_config_value(&path, "workspace.edition", "2018").unwrap();
+        let config: Config = Config::load_file(Some(&path)).unwrap().try_into().unwrap();
+        assert_eq!(config.network.max_retries, Some(10));
+        assert_eq!(config.workspace.edition, Some(Edition::E2018));
+    }
+
+    #[test]
+    fn test_effective_value() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let path = tmp_dir.path().join("config.toml");
+        std::fs::write(&path, "version = 1\n\n[formats]\nday = \"day_\"\n").unwrap();
+        let value = |key, vars: Vec<(String, String)>| effective_value(Some(&path), key, vars.into_iter()).unwrap();
+
+        assert_eq!(value("formats.day", vec![]), Some("day_".to_owned()));
+        let vars = vec![("AOC_FORMATS_DAY".to_owned(), "puzzle-".to_owned())];
+        assert_eq!(value("formats.day", vars), Some("puzzle-".to_owned()));
+        assert_eq!(value("formats.year", vec![]), Some("advent-of-code-".to_owned()));
+        assert_eq!(value("workspace.author", vec![]), None);
+    }
+
+    fn migrated(config: &str) -> Result<String, String> {
+        migrate_config(config.parse::<Document>().unwrap())
+            .map(|config| config.to_string())
+            .map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn test_migrate_config() {
+        // Version 0, with the session cookie at the top
+        assert_eq!(
+            migrated("session = \"53616c74\"\n\n[formats]\nday = \"day_\" # underscores\n"),
+            Ok(concat!(
+                "version = 1\n\n[formats]\nday = \"day_\" # underscores\n\n",
+                "[session]\ncookie = \"53616c74\"\n"
+            )
+            .to_owned())
+        );
+        // Version 0 with the cookie in [session] already
+        assert_eq!(
+            migrated("[session]\ncookie = \"53616c74\"\n"),
+            Ok("version = 1\n[session]\ncookie = \"53616c74\"\n".to_owned())
+        );
+        // Version 0 without anything to move
+        assert_eq!(
+            migrated("[network]\nmax_retries = 3\n"),
+            Ok("version = 1\n[network]\nmax_retries = 3\n".to_owned())
+        );
+        // The current version is left alone
+        let current = "version = 1\n\n[session]\ncookie = \"53616c74\"\n";
+        assert_eq!(migrated(current).unwrap(), current.to_owned());
+
+        let err = migrated("version = 2\n").unwrap_err();
+        assert!(err.contains("written by a newer aoc"), "{}", err);
+        assert!(migrated("version = \"one\"\n").is_err());
+    }
+
+    #[test]
+    fn test_migrate_file() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let path = tmp_dir.path().join("config.toml");
+        assert_eq!(migrate_file(&path, true).unwrap(), Migration::Missing);
+
+        std::fs::write(&path, "session = \"53616c74\"\n").unwrap();
+        let migrated = "version = 1\n\n[session]\ncookie = \"53616c74\"\n".to_owned();
+        let migration = Migration::Migrated { from: 0, migrated: migrated.clone() };
+        assert_eq!(migrate_file(&path, false).unwrap(), migration);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "session = \"53616c74\"\n");
+        assert_eq!(migrate_file(&path, true).unwrap(), migration);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), migrated);
+        assert_eq!(migrate_file(&path, true).unwrap(), Migration::UpToDate(CONFIG_VERSION));
+    }
+
+    #[test]
+    fn test_load_old_config() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let path = tmp_dir.path().join("config.toml");
+        std::fs::write(&path, "session = \"53616c74\"\n").unwrap();
+        let (config, version) = Config::load_file_versioned(Some(&path)).unwrap();
+        assert_eq!(version, Some(0));
+        let mut config: Config = config.try_into().unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.session.cookie.as_deref(), Some("53616c74"));
+        config.file_version = version;
+        assert!(config.needs_migration());
+        // The file is only read
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "session = \"53616c74\"\n");
+
+        std::fs::write(&path, "version = 7\n").unwrap();
+        assert!(Config::load_file(Some(&path)).unwrap_err().to_string().contains("written by a newer aoc"));
+    }
+}
@@ -0,0 +1,88 @@
+//! Encrypting `input.txt` at rest, with AES-256-GCM and a key derived from the session cookie.
+use crate::error::AocError;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+const INPUT_FILE: &str = "input.txt";
+const ENCRYPTED_FILE: &str = "input.txt.enc";
+/// Length of the nonce, which is stored before the ciphertext.
+const NONCE_LEN: usize = 12;
+
+/// The SHA-256 hash of the session cookie.
+pub fn key(session_cookie: &str) -> [u8; 32] {
+    Sha256::digest(session_cookie.as_bytes()).into()
+}
+
+pub fn encrypt(key: &[u8; 32], plaintext: &[u8]) -> Result<Vec<u8>, AocError> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|e| AocError::Parse(format!("Failed to encrypt: {}", e)))?;
+    Ok([nonce.as_slice(), &ciphertext].concat())
+}
+
+pub fn decrypt(key: &[u8; 32], data: &[u8]) -> Result<Vec<u8>, AocError> {
+    if data.len() < NONCE_LEN {
+        return Err(AocError::Parse("Encrypted data is too short".to_owned()));
+    }
+    let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| AocError::Parse("Failed to decrypt, was it encrypted with another session cookie?".to_owned()))
+}
+
+/// Encrypt `input.txt` in `day_dir` to `input.txt.enc`, and remove `input.txt`. Returns the path
+/// of the encrypted file.
+pub fn encrypt_input(day_dir: &Path, key: &[u8; 32]) -> Result<PathBuf, AocError> {
+    convert(&day_dir.join(INPUT_FILE), &day_dir.join(ENCRYPTED_FILE), |data| encrypt(key, data))
+}
+
+/// Decrypt `input.txt.enc` in `day_dir` to `input.txt`, and remove `input.txt.enc`. Returns the
+/// path of the decrypted file.
+pub fn decrypt_input(day_dir: &Path, key: &[u8; 32]) -> Result<PathBuf, AocError> {
+    convert(&day_dir.join(ENCRYPTED_FILE), &day_dir.join(INPUT_FILE), |data| decrypt(key, data))
+}
+
+/// Write `from` converted by `convert` to `to`, refusing to replace `to`, and then remove `from`.
+fn convert(
+    from: &Path,
+    to: &Path,
+    convert: impl FnOnce(&[u8]) -> Result<Vec<u8>, AocError>,
+) -> Result<PathBuf, AocError> {
+    if to.exists() {
+        return Err(AocError::Environment(format!("{} already exists", to.display())));
+    }
+    let data = std::fs::read(from).map_err(|e| AocError::io(format!("Failed to read {}", from.display()), e))?;
+    std::fs::write(to, convert(&data)?).map_err(|e| AocError::io(format!("Failed to write {}", to.display()), e))?;
+    std::fs::remove_file(from).map_err(|e| AocError::io(format!("Failed to remove {}", from.display()), e))?;
+    Ok(to.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_and_decrypt_input() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let day_dir = tmp_dir.path();
+        std::fs::write(day_dir.join(INPUT_FILE), "1000\n2000\n").unwrap();
+        let key = key("53616c7465645f5f");
+
+        let path = encrypt_input(day_dir, &key).unwrap();
+        assert_eq!(path, day_dir.join(ENCRYPTED_FILE));
+        assert!(!day_dir.join(INPUT_FILE).exists());
+        assert!(!String::from_utf8_lossy(&std::fs::read(&path).unwrap()).contains("1000"));
+        assert!(encrypt_input(day_dir, &key).is_err());
+
+        assert!(decrypt_input(day_dir, &super::key("another session")).is_err());
+        assert!(path.exists());
+        let path = decrypt_input(day_dir, &key).unwrap();
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "1000\n2000\n");
+        assert!(!day_dir.join(ENCRYPTED_FILE).exists());
+    }
+}
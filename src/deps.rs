@@ -0,0 +1,300 @@
+//! Bumping the dependencies of the days to their latest versions on crates.io, for
+//! `aoc update-deps`.
+use crate::error::AocError;
+use colored::*;
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use toml_edit::Document;
+
+const CRATES_URL: &str = "https://crates.io/api/v1/crates";
+/// Pause between queries, to go easy on crates.io.
+const QUERY_DELAY: Duration = Duration::from_millis(100);
+const TABLES: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// A dependency of a day with a version requirement, e.g. `itertools = "0.10"`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dependency {
+    pub cargo_toml: PathBuf,
+    pub table: String,
+    /// Key of the dependency in the table, which differs from the package when it's renamed.
+    pub name: String,
+    pub package: String,
+    pub version: String,
+}
+
+/// A dependency to bump from its version requirement to `new`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Update {
+    pub dependency: Dependency,
+    pub new: String,
+}
+
+#[derive(Deserialize)]
+struct CrateResponse {
+    #[serde(rename = "crate")]
+    krate: CrateInfo,
+}
+
+#[derive(Deserialize)]
+struct CrateInfo {
+    max_stable_version: Option<String>,
+    max_version: String,
+}
+
+/// The dependencies with a version requirement of the `Cargo.toml` of each of `day_dirs`, leaving
+/// out those from a path or git repository.
+pub fn collect(day_dirs: &[PathBuf]) -> Result<Vec<Dependency>, AocError> {
+    let mut dependencies = Vec::new();
+    for day_dir in day_dirs {
+        let cargo_toml_path = day_dir.join("Cargo.toml");
+        let Ok(contents) = std::fs::read_to_string(&cargo_toml_path) else {
+            continue;
+        };
+        let cargo_toml = contents
+            .parse::<Document>()
+            .map_err(|e| AocError::Parse(format!("Invalid {}: {}", cargo_toml_path.display(), e)))?;
+        for table in TABLES {
+            let Some(entries) = cargo_toml.get(table).and_then(|entries| entries.as_table_like()) else {
+                continue;
+            };
+            for (name, item) in entries.iter() {
+                let (version, package) = match item.as_table_like() {
+                    Some(entry) if entry.contains_key("path") || entry.contains_key("git") => continue,
+                    Some(entry) => (
+                        entry.get("version").and_then(|version| version.as_str()),
+                        entry.get("package").and_then(|package| package.as_str()),
+                    ),
+                    None => (item.as_str(), None),
+                };
+                let Some(version) = version else {
+                    continue;
+                };
+                dependencies.push(Dependency {
+                    cargo_toml: cargo_toml_path.clone(),
+                    table: table.to_owned(),
+                    name: name.to_owned(),
+                    package: package.unwrap_or(name).to_owned(),
+                    version: version.to_owned(),
+                });
+            }
+        }
+    }
+    Ok(dependencies)
+}
+
+/// The latest stable version of each of `packages` on crates.io, querying them one at a time.
+pub fn latest_versions(packages: &BTreeSet<String>) -> BTreeMap<String, Result<String, AocError>> {
+    let client = reqwest::blocking::Client::new();
+    let mut versions = BTreeMap::new();
+    for (index, package) in packages.iter().enumerate() {
+        if index > 0 {
+            std::thread::sleep(QUERY_DELAY);
+        }
+        let latest = client
+            .get(format!("{}/{}", CRATES_URL, package))
+            .header("User-Agent", crate::client::USER_AGENT)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.json::<CrateResponse>())
+            .map(|response| response.krate.max_stable_version.unwrap_or(response.krate.max_version))
+            .map_err(|e| AocError::network(format!("Failed to query {}", package), e));
+        versions.insert(package.clone(), latest);
+    }
+    versions
+}
+
+/// The dependencies whose requirement is older than the `latest` version of their package.
+pub fn updates(dependencies: &[Dependency], latest: &BTreeMap<String, Result<String, AocError>>) -> Vec<Update> {
+    dependencies
+        .iter()
+        .filter_map(|dependency| {
+            let latest = latest.get(&dependency.package)?.as_ref().ok()?;
+            let new = bump(&dependency.version, latest)?;
+            Some(Update { dependency: dependency.clone(), new })
+        })
+        .collect()
+}
+
+/// `requirement` bumped to `latest`, keeping its operator (`^`, `~` or `=`). `None` when it's
+/// already there or newer (e.g. a pre-release), or when it's a range or wildcard which is left
+/// alone.
+fn bump(requirement: &str, latest: &str) -> Option<String> {
+    let version = requirement.trim_start_matches(['^', '~', '=', ' ']);
+    if version.contains([',', '<', '>', '*']) {
+        return None;
+    }
+    let numbers = |version: &str| -> Vec<u64> {
+        version
+            .split(['-', '+'])
+            .next()
+            .unwrap_or_default()
+            .split('.')
+            .map(|number| number.parse().unwrap_or(0))
+            .collect()
+    };
+    if version == latest || numbers(version) >= numbers(latest) {
+        return None;
+    }
+    let operator = &requirement[..requirement.len() - version.len()];
+    Some(format!("{}{}", operator, latest))
+}
+
+/// Write the new versions to the `Cargo.toml` files, keeping their formatting and comments. No
+/// `Cargo.toml.bak` is left behind, as the table of updates says what changed.
+pub fn apply(updates: &[Update]) -> Result<(), AocError> {
+    let mut by_manifest: BTreeMap<&Path, Vec<&Update>> = BTreeMap::new();
+    for update in updates {
+        by_manifest.entry(&update.dependency.cargo_toml).or_default().push(update);
+    }
+    for (cargo_toml_path, updates) in by_manifest {
+        let contents = std::fs::read_to_string(cargo_toml_path)
+            .map_err(|e| AocError::io(format!("Failed to read {}", cargo_toml_path.display()), e))?;
+        let mut cargo_toml = contents
+            .parse::<Document>()
+            .map_err(|e| AocError::Parse(format!("Invalid {}: {}", cargo_toml_path.display(), e)))?;
+        for update in updates {
+            let item = &mut cargo_toml[&update.dependency.table][&update.dependency.name];
+            let version = match item.as_table_like_mut() {
+                Some(entry) => entry.get_mut("version").and_then(|version| version.as_value_mut()),
+                None => item.as_value_mut(),
+            };
+            if let Some(version) = version {
+                let decor = version.decor().clone();
+                *version = update.new.as_str().into();
+                *version.decor_mut() = decor;
+            }
+        }
+        crate::scaffold::replace_manifest(cargo_toml_path, &cargo_toml.to_string())?;
+    }
+    Ok(())
+}
+
+/// Look up the latest versions of the dependencies of the days in `year_dir` with
+/// `latest_versions`, and bump the requirements behind, or only list them with `dry_run`.
+pub fn update(
+    year_dir: &Path,
+    day_format: &str,
+    dry_run: bool,
+    latest_versions: impl FnOnce(&BTreeSet<String>) -> BTreeMap<String, Result<String, AocError>>,
+) -> Result<Vec<Update>, AocError> {
+    let day_dirs: Vec<PathBuf> = crate::scaffold::day_dirs(year_dir, day_format)?
+        .into_iter()
+        .map(|(_, day_dir)| day_dir)
+        .collect();
+    let dependencies = collect(&day_dirs)?;
+    let latest = latest_versions(&dependencies.iter().map(|dependency| dependency.package.clone()).collect());
+    let failed = latest.iter().filter_map(|(package, latest)| Some((package, latest.as_ref().err()?)));
+    for (package, err) in failed {
+        eprintln!("{}", format!("Failed to look up {} on crates.io: {}", package, err).yellow());
+    }
+    let updates = updates(&dependencies, &latest);
+    if updates.is_empty() {
+        println!("{}", "All dependencies are up to date".green());
+        return Ok(updates);
+    }
+    print!("{}", render_table(&updates));
+    if dry_run {
+        println!("{}", "Dry run, no Cargo.toml was changed".yellow());
+    } else {
+        apply(&updates)?;
+        println!("Update {} dependencies: {}", updates.len(), "Success".green());
+    }
+    Ok(updates)
+}
+
+/// A row per package and version bumped, which several days may share.
+pub fn render_table(updates: &[Update]) -> String {
+    let rows: BTreeSet<(&str, &str, &str)> = updates
+        .iter()
+        .map(|update| (update.dependency.package.as_str(), update.dependency.version.as_str(), update.new.as_str()))
+        .collect();
+    let width = |column: usize, header: &str| {
+        rows.iter()
+            .map(|row| [row.0, row.1, row.2][column].len())
+            .chain([header.len()])
+            .max()
+            .unwrap_or_default()
+    };
+    let (package_width, old_width) = (width(0, "Package"), width(1, "Old"));
+    let mut table = format!("{:<package_width$} {:<old_width$} {}\n", "Package", "Old", "New");
+    for (package, old, new) in rows {
+        table.push_str(&format!("{:<package_width$} {:<old_width$} {}\n", package, old, new));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bump() {
+        assert_eq!(bump("0.10", "0.12.1"), Some("0.12.1".to_owned()));
+        assert_eq!(bump("^1.0", "1.0.200"), Some("^1.0.200".to_owned()));
+        assert_eq!(bump("=0.4.3", "0.4.4"), Some("=0.4.4".to_owned()));
+        assert_eq!(bump("1.0.200", "1.0.200"), None);
+        assert_eq!(bump("2.0.0-rc.1", "1.9.0"), None);
+        assert_eq!(bump(">=1, <2", "2.1.0"), None);
+        assert_eq!(bump("*", "2.1.0"), None);
+    }
+
+    #[test]
+    fn test_update_day() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let day_dir = tmp_dir.path().join("day-01");
+        std::fs::create_dir(&day_dir).unwrap();
+        std::fs::write(
+            day_dir.join("Cargo.toml"),
+            "[package]\nname = \"day-01\"\n\n[dependencies]\n\
+             itertools = \"0.10\" # iterators\n\
+             regex = { version = \"1.5\", default-features = false }\n\
+             utils = { path = \"../utils\", version = \"0.1\" }\n\
+             fx = { package = \"rustc-hash\", version = \"1.1\" }\n\n\
+             [dev-dependencies]\npretty_assertions = \"1.4.0\"\n",
+        )
+        .unwrap();
+
+        let dependencies = collect(&[day_dir.clone(), tmp_dir.path().join("day-02")]).unwrap();
+        let names: Vec<_> = dependencies.iter().map(|dependency| dependency.package.as_str()).collect();
+        assert_eq!(names, ["itertools", "regex", "rustc-hash", "pretty_assertions"]);
+
+        let latest = BTreeMap::from([
+            ("itertools".to_owned(), Ok("0.13.0".to_owned())),
+            ("regex".to_owned(), Ok("1.11.1".to_owned())),
+            ("rustc-hash".to_owned(), Err(AocError::refused("404 Not Found"))),
+            ("pretty_assertions".to_owned(), Ok("1.4.0".to_owned())),
+        ]);
+        let updates = updates(&dependencies, &latest);
+        assert_eq!(updates.len(), 2);
+        let table = render_table(&updates);
+        assert_eq!(table, "Package   Old  New\nitertools 0.10 0.13.0\nregex     1.5  1.11.1\n");
+
+        apply(&updates).unwrap();
+        let contents = std::fs::read_to_string(day_dir.join("Cargo.toml")).unwrap();
+        assert!(contents.contains("itertools = \"0.13.0\" # iterators\n"));
+        assert!(contents.contains("regex = { version = \"1.11.1\", default-features = false }\n"));
+        assert!(contents.contains("fx = { package = \"rustc-hash\", version = \"1.1\" }\n"));
+        assert!(contents.contains("pretty_assertions = \"1.4.0\"\n"));
+        assert!(!day_dir.join("Cargo.toml.bak").exists());
+    }
+
+    #[test]
+    fn test_update() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let day_dir = tmp_dir.path().join("day-01");
+        std::fs::create_dir(&day_dir).unwrap();
+        let manifest = "[package]\nname = \"day-01\"\n\n[dependencies]\nitertools = \"0.10\"\n";
+        std::fs::write(day_dir.join("Cargo.toml"), manifest).unwrap();
+        let latest = |packages: &BTreeSet<String>| {
+            assert_eq!(packages, &BTreeSet::from(["itertools".to_owned()]));
+            BTreeMap::from([("itertools".to_owned(), Ok("0.13.0".to_owned()))])
+        };
+
+        assert_eq!(update(tmp_dir.path(), "day-", true, latest).unwrap().len(), 1);
+        assert_eq!(std::fs::read_to_string(day_dir.join("Cargo.toml")).unwrap(), manifest);
+        assert_eq!(update(tmp_dir.path(), "day-", false, latest).unwrap().len(), 1);
+        assert!(update(tmp_dir.path(), "day-", false, latest).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,265 @@
+//! Comparing solutions: the answers of two binaries of a day, for `aoc diff`, and a part's code
+//! across years, for `aoc diff-year`, whose diffs `aoc apply` applies.
+use crate::error::AocError;
+use colored::*;
+use similar::TextDiff;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// The answer and runtime of a binary, e.g. `part_2_fast`.
+pub struct BinOutput {
+    pub name: String,
+    pub answer: String,
+    pub elapsed: Duration,
+}
+
+/// Whether the answers match, and a line per binary with its runtime, followed by both outputs
+/// when they don't.
+pub fn report(from: &BinOutput, to: &BinOutput) -> (bool, String) {
+    let matched = from.answer == to.answer;
+    let mut text = String::new();
+    for output in [from, to] {
+        text.push_str(&format!("{} ({:.2?})", output.name, output.elapsed));
+        if matched {
+            text.push_str(&format!(": {}", output.answer));
+        }
+        text.push('\n');
+    }
+    if matched {
+        text.push_str(&format!("{}\n", "Answers match".green()));
+    } else {
+        for output in [from, to] {
+            text.push_str(&format!("--- {}\n{}\n", output.name, output.answer));
+        }
+        text.push_str(&format!("{}\n", "Answers differ".red()));
+    }
+    (matched, text)
+}
+
+/// Run the binaries `from` and `to` of `day_dir` against `input_path`, built with `cargo_args`,
+/// and report on their answers like `report`.
+pub fn run_bins(
+    day_dir: &Path,
+    from: &str,
+    to: &str,
+    input_path: &Path,
+    cargo_args: &[String],
+) -> Result<(bool, String), AocError> {
+    if !input_path.exists() {
+        return Err(AocError::Environment(format!("No {}", input_path.display())));
+    }
+    let envs = [("AOC_INPUT_PATH", input_path.as_os_str())];
+    let mut outputs = Vec::new();
+    for bin in [from, to] {
+        let executable = crate::runner::build_bin(day_dir, bin, cargo_args)?;
+        let output = crate::runner::execute_part(&executable, day_dir, &envs)?;
+        if !output.status.success() {
+            return Err(AocError::Build(format!("{} failed: {}", bin, output.status)));
+        }
+        outputs.push(BinOutput { name: bin.to_owned(), answer: output.answer().to_owned(), elapsed: output.elapsed });
+    }
+    Ok(report(&outputs[0], &outputs[1]))
+}
+
+/// Unified diff of `part` of `day` from year `from` to year `to`, in the year directories found
+/// from `dir` with `formats`, or `None` when they're identical.
+pub fn across_years(
+    dir: &Path,
+    formats: &crate::clean::Formats,
+    from: u16,
+    to: u16,
+    day: u8,
+    part: u8,
+) -> Result<Option<String>, AocError> {
+    let mut files = Vec::new();
+    for year in [from, to] {
+        let (_, year_dir) = crate::env::find_year_dir(dir, Some(year), formats.day, formats.year)?;
+        let path = part_file(&year_dir, formats.day, day, part)?;
+        let code =
+            std::fs::read_to_string(&path).map_err(|e| AocError::io(format!("Failed to read {}", path.display()), e))?;
+        let name = format!("{}/{}", year, path.strip_prefix(&year_dir).unwrap_or(&path).display());
+        files.push((name, code));
+    }
+    Ok(unified(&files[0].0, &files[1].0, &files[0].1, &files[1].1))
+}
+
+/// `src/bin/part_{part}.rs` of `day` in `year_dir`.
+pub fn part_file(year_dir: &Path, day_format: &str, day: u8, part: u8) -> Result<PathBuf, AocError> {
+    let day_dir = crate::scaffold::day_dirs(year_dir, day_format)?
+        .into_iter()
+        .find(|(existing, _)| *existing == day)
+        .map(|(_, day_dir)| day_dir)
+        .ok_or_else(|| AocError::Environment(format!("Day {} doesn't exist in {}", day, year_dir.display())))?;
+    let path = day_dir.join(format!("src/bin/part_{}.rs", part));
+    if !path.is_file() {
+        return Err(AocError::Environment(format!("Part {} doesn't exist: {}", part, path.display())));
+    }
+    Ok(path)
+}
+
+/// Unified diff from `old` to `new`, with 3 lines of context, or `None` when they're identical.
+pub fn unified(old_name: &str, new_name: &str, old: &str, new: &str) -> Option<String> {
+    if old == new {
+        return None;
+    }
+    Some(TextDiff::from_lines(old, new).unified_diff().context_radius(3).header(old_name, new_name).to_string())
+}
+
+/// Color the lines of a unified diff: removals red, additions green, hunk headers cyan.
+pub fn colorize(diff: &str) -> String {
+    diff.lines()
+        .map(|line| {
+            let colored = if line.starts_with("---") || line.starts_with("+++") {
+                line.bold()
+            } else if line.starts_with("@@") {
+                line.cyan()
+            } else if line.starts_with('-') {
+                line.red()
+            } else if line.starts_with('+') {
+                line.green()
+            } else {
+                line.normal()
+            };
+            format!("{}\n", colored)
+        })
+        .collect()
+}
+
+/// Outcome of applying a patch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Applied {
+    Applied,
+    /// Some hunks didn't apply, with the rejected ones.
+    Rejected(String),
+}
+
+/// Apply the unified diff at `patch` to `part` of `day_dir` like `apply`, returning the part's path.
+pub fn apply_to_part(patch: &Path, day_dir: &Path, part: u8) -> Result<(PathBuf, Applied), AocError> {
+    let target = day_dir.join(format!("src/bin/part_{}.rs", part));
+    if !target.is_file() {
+        return Err(AocError::Environment(format!("Part {} doesn't exist: {}", part, target.display())));
+    }
+    let applied = apply(patch, &target)?;
+    Ok((target, applied))
+}
+
+/// Apply the unified diff at `patch` to `target` with `patch`, whatever the file names in its
+/// headers. Rejected hunks, including ones already applied, are left in `<target>.rej`.
+pub fn apply(patch: &Path, target: &Path) -> Result<Applied, AocError> {
+    let reject_path = PathBuf::from(format!("{}.rej", target.display()));
+    let _ = std::fs::remove_file(&reject_path);
+    let output = std::process::Command::new("patch")
+        .args(["--forward", "--batch", "--input"])
+        .arg(patch)
+        .arg("--reject-file")
+        .arg(&reject_path)
+        .arg(target)
+        .output()
+        .map_err(|e| AocError::io("Failed to run patch", e))?;
+    match output.status.code() {
+        Some(0) => Ok(Applied::Applied),
+        Some(1) => Ok(Applied::Rejected(
+            std::fs::read_to_string(&reject_path).unwrap_or_else(|_| String::from_utf8_lossy(&output.stdout).into_owned()),
+        )),
+        _ => {
+            let message = [output.stderr, output.stdout].map(|out| String::from_utf8_lossy(&out).trim().to_owned());
+            Err(AocError::Parse(format!("Failed to apply {}: {}", patch.display(), message.join("\n").trim())))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let output = |name: &str, answer: &str, ms: u64| BinOutput {
+            name: name.to_owned(),
+            answer: answer.to_owned(),
+            elapsed: Duration::from_millis(ms),
+        };
+        let (matched, text) = report(&output("part_2", "45000", 120), &output("part_2_fast", "45000", 3));
+        assert!(matched);
+        assert!(text.starts_with("part_2 (120.00ms): 45000\npart_2_fast (3.00ms): 45000\n"));
+
+        let (matched, text) = report(&output("part_1", "24000", 1), &output("part_2", "1\n45000", 1));
+        assert!(!matched);
+        assert!(text.contains("--- part_1\n24000\n--- part_2\n1\n45000\n"));
+    }
+
+    #[test]
+    fn test_part_file() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = tmp_dir.path().join("advent-of-code-2022");
+        std::fs::create_dir_all(year_dir.join("day-01/src/bin")).unwrap();
+        std::fs::write(year_dir.join("day-01/src/bin/part_1.rs"), "fn main() {}\n").unwrap();
+
+        assert_eq!(
+            part_file(&year_dir, "day-", 1, 1).unwrap(),
+            year_dir.join("day-01/src/bin/part_1.rs")
+        );
+        assert!(part_file(&year_dir, "day-", 1, 2).unwrap_err().to_string().starts_with("Part 2 doesn't exist"));
+        assert!(part_file(&year_dir, "day-", 2, 1).unwrap_err().to_string().starts_with("Day 2 doesn't exist"));
+    }
+
+    #[test]
+    fn test_across_years() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        for (year, code) in [(2015, "fn main() {}\n"), (2022, "fn main() {}\n"), (2023, "fn main() { todo!() }\n")] {
+            let day_dir = tmp_dir.path().join(format!("advent-of-code-{}/day-01", year));
+            std::fs::create_dir_all(day_dir.join("src/bin")).unwrap();
+            std::fs::write(day_dir.join("src/bin/part_1.rs"), code).unwrap();
+        }
+        let formats = crate::clean::Formats { year: "advent-of-code-", day: "day-" };
+
+        assert_eq!(across_years(tmp_dir.path(), &formats, 2015, 2022, 1, 1).unwrap(), None);
+        let diff = across_years(tmp_dir.path(), &formats, 2015, 2023, 1, 1).unwrap().unwrap();
+        assert!(diff.starts_with("--- 2015/day-01/src/bin/part_1.rs\n+++ 2023/day-01/src/bin/part_1.rs\n"));
+        assert!(across_years(tmp_dir.path(), &formats, 2015, 2022, 1, 2).is_err());
+    }
+
+    #[test]
+    fn test_unified() {
+        let old = "fn main() {\n    let total = 0;\n    println!(\"{}\", total);\n}\n";
+        let new = "fn main() {\n    let total = 24000;\n    println!(\"{}\", total);\n}\n";
+        assert_eq!(unified("a", "b", old, old), None);
+        assert_eq!(
+            unified("2015/day-01/part_1.rs", "2022/day-01/part_1.rs", old, new).unwrap(),
+            "--- 2015/day-01/part_1.rs\n\
+             +++ 2022/day-01/part_1.rs\n\
+             @@ -1,4 +1,4 @@\n \
+             fn main() {\n\
+             -    let total = 0;\n\
+             +    let total = 24000;\n     \
+             println!(\"{}\", total);\n \
+             }\n"
+        );
+    }
+
+    #[test]
+    fn test_apply() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let (target, patch) = (tmp_dir.path().join("part_1.rs"), tmp_dir.path().join("fix.patch"));
+        let old = "fn main() {\n    let total = 0;\n    println!(\"{}\", total);\n}\n";
+        let new = old.replace("= 0", "= 24000");
+        std::fs::write(&target, old).unwrap();
+        std::fs::write(&patch, unified("a/part_1.rs", "b/part_1.rs", old, &new).unwrap()).unwrap();
+
+        assert_eq!(apply(&patch, &target).unwrap(), Applied::Applied);
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), new);
+
+        // Already applied
+        let Applied::Rejected(rejected) = apply(&patch, &target).unwrap() else {
+            panic!("Applied twice");
+        };
+        assert!(rejected.contains("+    let total = 24000;"));
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), new);
+
+        std::fs::write(&patch, "not a diff\n").unwrap();
+        assert!(apply(&patch, &target).is_err());
+
+        let err = apply_to_part(&patch, tmp_dir.path(), 2).unwrap_err().to_string();
+        assert!(err.starts_with("Part 2 doesn't exist"));
+    }
+}
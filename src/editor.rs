@@ -0,0 +1,84 @@
+//! Opening files in the user's editor, for `aoc next`.
+use crate::error::AocError;
+use std::ffi::OsStr;
+use std::path::Path;
+
+/// Editors tried in order when `$EDITOR` isn't set.
+const FALLBACKS: [&str; 3] = ["code", "vim", "nano"];
+
+/// The editor command: `editor` (from `$EDITOR`) when it's set, or else the first of the fallbacks
+/// found on `path` (from `$PATH`).
+pub fn find_editor(editor: Option<&OsStr>, path: Option<&OsStr>) -> Option<String> {
+    if let Some(editor) = editor.and_then(OsStr::to_str).map(str::trim).filter(|editor| !editor.is_empty()) {
+        return Some(editor.to_owned());
+    }
+    let path = path?;
+    FALLBACKS.into_iter().find(|name| on_path(name, Some(path))).map(str::to_owned)
+}
+
+/// Whether the program `name` is in one of the directories of `path` (from `$PATH`).
+pub fn on_path(name: &str, path: Option<&OsStr>) -> bool {
+    path.is_some_and(|path| std::env::split_paths(path).any(|dir| is_program(&dir.join(name))))
+}
+
+/// Whether `path` is a program, also trying the extensions of programs on Windows.
+fn is_program(path: &Path) -> bool {
+    if cfg!(windows) {
+        ["exe", "cmd", "bat"].iter().any(|extension| path.with_extension(extension).is_file())
+    } else {
+        path.is_file()
+    }
+}
+
+/// Open `file` with `editor`, which can have arguments like `code --wait`, waiting for it to exit.
+pub fn open(editor: &str, file: &Path, dir: &Path) -> Result<(), AocError> {
+    let mut words = editor.split_whitespace();
+    let program = words.next().ok_or_else(|| AocError::Config("No editor".to_owned()))?;
+    let status = std::process::Command::new(program)
+        .args(words)
+        .arg(file)
+        .current_dir(dir)
+        .status()
+        .map_err(|e| AocError::io(format!("Failed to run {}", editor), e))?;
+    if !status.success() {
+        return Err(AocError::Build(format!("{} failed: {}", editor, status)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_editor() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let (first, second) = (tmp_dir.path().join("first"), tmp_dir.path().join("second"));
+        std::fs::create_dir_all(&first).unwrap();
+        std::fs::create_dir_all(&second).unwrap();
+        let program = if cfg!(windows) { "nano.exe" } else { "nano" };
+        std::fs::write(second.join(program), "").unwrap();
+        let path = std::env::join_paths([&first, &second]).unwrap();
+
+        assert_eq!(find_editor(Some(OsStr::new("hx")), Some(&path)), Some("hx".to_owned()));
+        assert_eq!(find_editor(Some(OsStr::new(" ")), Some(&path)), Some("nano".to_owned()));
+        assert_eq!(find_editor(None, Some(&path)), Some("nano".to_owned()));
+        // Earlier fallbacks come first, wherever they are on the path
+        let program = if cfg!(windows) { "vim.exe" } else { "vim" };
+        std::fs::write(second.join(program), "").unwrap();
+        assert_eq!(find_editor(None, Some(&path)), Some("vim".to_owned()));
+        assert_eq!(find_editor(None, Some(first.as_os_str())), None);
+        assert_eq!(find_editor(None, None), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_open() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(tmp_dir.path().join("part_1.rs"), "").unwrap();
+        assert!(open("test -f", Path::new("part_1.rs"), tmp_dir.path()).is_ok());
+        let err = open("test -f", Path::new("part_2.rs"), tmp_dir.path()).unwrap_err().to_string();
+        assert!(err.ends_with("failed: exit status: 1"), "{}", err);
+        assert!(open("", Path::new("part_1.rs"), tmp_dir.path()).is_err());
+    }
+}
@@ -0,0 +1,346 @@
+//! The year and day directories aoc runs in, found from the current directory and the formats
+//! of the config.
+use crate::error::AocError;
+use std::path::{Path, PathBuf};
+
+/// The year of the current directory, and its day when it's a day directory.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Environment {
+    pub day: Option<u8>,
+    pub year: u16,
+}
+
+/// Constructors
+impl Environment {
+    /// The environment of `dir`, a year directory or a day directory in one.
+    pub fn new(dir: &Path, day_format: &str, year_format: &str) -> Result<Self, AocError> {
+        let (current_dir, parent_dir) = names(dir);
+        if parent_dir.contains(year_format) {
+            Ok(Environment {
+                year: parse_name(&parent_dir, year_format)?,
+                day: Some(parse_name(&current_dir, day_format)?),
+            })
+        } else if current_dir.contains(year_format) {
+            Ok(Environment { year: parse_name(&current_dir, year_format)?, day: None })
+        } else {
+            Err(AocError::Environment(format!(
+                "Not in a year directory: {}. Should look like <{}>",
+                current_dir, year_format
+            )))
+        }
+    }
+
+    /// The environment of the current directory.
+    pub fn current(day_format: &str, year_format: &str) -> Result<Self, AocError> {
+        Environment::new(&current_dir()?, day_format, year_format)
+    }
+
+    /// The day, failing outside of a day directory.
+    pub fn require_day(&self) -> Result<u8, AocError> {
+        self.day.ok_or_else(|| AocError::Environment("Not in a day directory".to_owned()))
+    }
+}
+
+/// Validators
+impl Environment {
+    pub fn check_day(dir: &Path, day_format: &str) -> Result<(), AocError> {
+        let (current_dir, _) = names(dir);
+
+        if !current_dir.contains(day_format) {
+            Err(AocError::Environment(format!(
+                "Current directory not valid, <{}>. Should look like <{}>",
+                current_dir, day_format
+            )))
+        } else {
+            Ok(())
+        }
+    }
+    pub fn check_year(dir: &Path, year_format: &str) -> Result<(), AocError> {
+        let (current_dir, parent_dir) = names(dir);
+
+        if !parent_dir.contains(year_format) && !current_dir.contains(year_format) {
+            Err(AocError::Environment(format!(
+                "Parent directory not valid: {}. Should look like <{}>",
+                parent_dir, year_format
+            )))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// The current directory, failing when it was removed or can't be accessed.
+pub fn current_dir() -> Result<PathBuf, AocError> {
+    std::env::current_dir().map_err(|e| AocError::Environment(format!("Invalid current directory: {}", e)))
+}
+
+/// Names of `dir` and of its parent, empty when there's none.
+fn names(dir: &Path) -> (String, String) {
+    let name = |dir: Option<&Path>| {
+        dir.and_then(Path::file_name)
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+    (name(Some(dir)), name(dir.parent()))
+}
+
+/// The number in the directory name `name`, after `format`.
+fn parse_name<T: std::str::FromStr>(name: &str, format: &str) -> Result<T, AocError> {
+    name.replace(format, "")
+        .parse()
+        .map_err(|_| AocError::Parse(format!("Invalid directory name: {}. Should look like <{}>", name, format)))
+}
+
+pub fn check_day_and_year_dirs(dir: &Path, day_format: &str, year_format: &str) -> Result<(), AocError> {
+    // Verify user is in the correct directory.
+    // 1. advent-of-code-{year}
+    // 2. day-XX
+    Environment::check_day(dir, day_format)?;
+    Environment::check_year(dir, year_format)
+}
+
+/// Find the year directory from `dir`, which is either the year directory itself or a day
+/// directory inside of it.
+pub fn resolve_year_dir(dir: &Path, day_format: &str, year_format: &str) -> Result<PathBuf, AocError> {
+    let dir_name = dir.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    if dir_name.contains(year_format) {
+        return Ok(dir.to_owned());
+    }
+    if let Some(parent) = dir.parent() {
+        let parent_name = parent.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        if dir_name.contains(day_format) && parent_name.contains(year_format) {
+            return Ok(parent.to_owned());
+        }
+    }
+    Err(AocError::Environment(format!(
+        "Directory not valid: {}. Should look like <{}> or <{}>/<{}>",
+        dir.display(),
+        year_format,
+        year_format,
+        day_format
+    )))
+}
+
+/// Find the day directory containing `dir`, which can be the day directory or any directory
+/// inside of it.
+pub fn resolve_day_dir(dir: &Path, day_format: &str, year_format: &str) -> Result<PathBuf, AocError> {
+    for ancestor in dir.ancestors() {
+        let name = ancestor.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        let parent_name = ancestor
+            .parent()
+            .and_then(|parent| parent.file_name())
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+        if name.contains(day_format) && parent_name.contains(year_format) {
+            return Ok(ancestor.to_owned());
+        }
+    }
+    Err(AocError::Environment(format!(
+        "Directory not valid: {}. Should be inside <{}>/<{}>",
+        dir.display(),
+        year_format,
+        day_format
+    )))
+}
+
+/// Find the directory for `year`: the current year directory (from `dir`), a sibling of it,
+/// or a year directory inside `dir`. Defaults to the current year directory.
+pub fn find_year_dir(
+    dir: &Path,
+    year: Option<u16>,
+    day_format: &str,
+    year_format: &str,
+) -> Result<(u16, PathBuf), AocError> {
+    let year_dir = match (resolve_year_dir(dir, day_format, year_format), year) {
+        (Ok(year_dir), None) => return Ok((parse_year(&year_dir, year_format)?, year_dir)),
+        (Ok(year_dir), Some(year)) => match year_dir.parent() {
+            Some(parent) if parse_year(&year_dir, year_format).ok() != Some(year) => {
+                parent.join(format!("{}{}", year_format, year))
+            }
+            _ => year_dir,
+        },
+        (Err(_), Some(year)) => dir.join(format!("{}{}", year_format, year)),
+        (Err(err), None) => return Err(err),
+    };
+    if !year_dir.is_dir() {
+        return Err(AocError::Environment(format!("Year directory doesn't exist: {}", year_dir.display())));
+    }
+    Ok((parse_year(&year_dir, year_format)?, year_dir))
+}
+
+/// The directory with the year directories: the parent of the year directory of `dir`, or else
+/// `dir` itself.
+pub fn root_dir(dir: &Path, day_format: &str, year_format: &str) -> PathBuf {
+    match resolve_year_dir(dir, day_format, year_format) {
+        Ok(year_dir) => year_dir.parent().map_or(dir.to_owned(), Path::to_owned),
+        Err(_) => dir.to_owned(),
+    }
+}
+
+/// `year`, or else the year of the current directory.
+pub fn year_or_current(year: Option<u16>, day_format: &str, year_format: &str) -> Result<u16, AocError> {
+    match year {
+        Some(year) => Ok(year),
+        None => Environment::current(day_format, year_format)
+            .map(|environment| environment.year)
+            .map_err(|_| AocError::Environment("No year, use --year or run from a year directory".to_owned())),
+    }
+}
+
+/// Ask the user a question on stdin, returning the trimmed answer.
+pub fn prompt(question: &str) -> String {
+    use std::io::Write;
+
+    print!("{} ", question);
+    std::io::stdout().flush().ok();
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer).ok();
+    answer.trim().to_owned()
+}
+
+/// Year from a year directory's name.
+pub fn parse_year(year_dir: &Path, year_format: &str) -> Result<u16, AocError> {
+    let name = year_dir.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    name.replace(year_format, "").parse().map_err(|_| {
+        AocError::Environment(format!(
+            "Not a year directory: {}. Should look like <{}>",
+            year_dir.display(),
+            year_format
+        ))
+    })
+}
+
+/// The year directory containing `day_dir`.
+pub fn year_dir_of(day_dir: &Path) -> Result<&Path, AocError> {
+    day_dir
+        .parent()
+        .ok_or_else(|| AocError::Environment(format!("Not inside a year directory: {}", day_dir.display())))
+}
+
+/// Name of the directory `dir`, e.g. `day-01`.
+pub fn dir_name(dir: &Path) -> Result<String, AocError> {
+    dir.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| AocError::Environment(format!("Directory has no name: {}", dir.display())))
+}
+
+/// Day from a day directory's name.
+pub fn parse_day(day_dir: &Path, day_format: &str) -> Result<u8, AocError> {
+    let name = day_dir.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    name.replace(day_format, "").parse().map_err(|_| {
+        AocError::Environment(format!("Not a day directory: {}. Should look like <{}>", day_dir.display(), day_format))
+    })
+}
+
+/// Find the directory of `day` in the year directory containing `dir`.
+pub fn find_day_dir(dir: &Path, day: u8, day_format: &str, year_format: &str) -> Result<PathBuf, AocError> {
+    let year_dir = match resolve_day_dir(dir, day_format, year_format) {
+        Ok(day_dir) => year_dir_of(&day_dir)?.to_owned(),
+        Err(_) => resolve_year_dir(dir, day_format, year_format)?,
+    };
+    crate::scaffold::day_dirs(&year_dir, day_format)?
+        .into_iter()
+        .find(|(existing, _)| *existing == day)
+        .map(|(_, day_dir)| day_dir)
+        .ok_or_else(|| {
+            AocError::Environment(format!(
+                "Day {} doesn't exist in {}. Create it with `aoc day --day {}`",
+                day,
+                year_dir.display(),
+                day
+            ))
+        })
+}
+
+/// Session cookie value, loaded from the environment or a .env file, then the config file,
+/// then the OS keyring.
+pub fn session_cookie() -> Result<String, AocError> {
+    dotenv::dotenv().ok();
+    if let Ok(session_cookie) = std::env::var("session") {
+        return Ok(session_cookie);
+    }
+    crate::config::Config::load()
+        .ok()
+        .and_then(|config| config.session.cookie)
+        .or_else(crate::login::from_keyring)
+        .ok_or_else(|| AocError::Config("No session cookie, set one with `aoc login`".to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_day() {
+        // Set Up
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let day_dir = tmp_dir.path().join("day-01");
+        std::fs::create_dir_all(&day_dir).unwrap();
+
+        let day_format = "day-";
+        assert!(Environment::check_day(&day_dir, day_format).is_ok());
+        assert!(Environment::check_day(tmp_dir.path(), day_format).is_err());
+    }
+
+    #[test]
+    fn test_check_year() {
+        // Set Up
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = tmp_dir.path().join("advent-of-code-2020");
+        let day_dir = year_dir.join("day-01");
+        std::fs::create_dir_all(&day_dir).unwrap();
+
+        let year_format = "advent-of-code-";
+        assert!(Environment::check_year(&day_dir, year_format).is_ok());
+        assert!(Environment::check_year(&year_dir, year_format).is_ok());
+        assert!(Environment::check_year(tmp_dir.path(), year_format).is_err());
+        assert!(check_day_and_year_dirs(&day_dir, "day-", year_format).is_ok());
+        assert!(check_day_and_year_dirs(&year_dir, "day-", year_format).is_err());
+    }
+
+    #[test]
+    fn test_environment_from_dir() {
+        let from_dir = |dir: &str| Environment::new(Path::new(dir), "day-", "advent-of-code-");
+        assert_eq!(from_dir("/aoc/advent-of-code-2022/day-03").unwrap(), Environment { day: Some(3), year: 2022 });
+        assert_eq!(from_dir("/aoc/advent-of-code-2022").unwrap(), Environment { day: None, year: 2022 });
+        assert!(from_dir("/aoc/advent-of-code-2022").unwrap().require_day().is_err());
+
+        // Used to panic
+        assert!(matches!(from_dir("/aoc/advent-of-code-2022/day-xx"), Err(AocError::Parse(_))));
+        assert!(matches!(from_dir("/aoc/advent-of-code-next"), Err(AocError::Parse(_))));
+        assert!(matches!(from_dir("/"), Err(AocError::Environment(_))));
+        assert!(matches!(from_dir("/aoc/notes"), Err(AocError::Environment(_))));
+    }
+
+    #[test]
+    fn test_resolve_year_dir_invalid() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let other_dir = tmp_dir.path().join("day-01");
+        assert!(resolve_year_dir(&other_dir, "day-", "advent-of-code-").is_err());
+    }
+
+    #[test]
+    fn test_find_year_dir() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_2021 = tmp_dir.path().join("advent-of-code-2021");
+        let year_2022 = tmp_dir.path().join("advent-of-code-2022");
+        std::fs::create_dir_all(year_2021.join("day-01")).unwrap();
+        std::fs::create_dir_all(&year_2022).unwrap();
+        let find = |dir: &Path, year| find_year_dir(dir, year, "day-", "advent-of-code-");
+
+        assert_eq!(find(&year_2021.join("day-01"), None).unwrap(), (2021, year_2021.clone()));
+        assert_eq!(find(&year_2021.join("day-01"), Some(2022)).unwrap(), (2022, year_2022.clone()));
+        assert_eq!(find(tmp_dir.path(), Some(2021)).unwrap(), (2021, year_2021.clone()));
+        assert!(find(tmp_dir.path(), None).is_err());
+        assert!(find(&year_2022, Some(2020)).is_err());
+    }
+
+    #[test]
+    fn test_root_dir() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let root = |dir: &Path| root_dir(dir, "day-", "advent-of-code-");
+        assert_eq!(root(&tmp_dir.path().join("advent-of-code-2022/day-01")), tmp_dir.path());
+        assert_eq!(root(&tmp_dir.path().join("advent-of-code-2022")), tmp_dir.path());
+        assert_eq!(root(tmp_dir.path()), tmp_dir.path());
+    }
+}
@@ -0,0 +1,96 @@
+//! Errors of the commands, turned into messages and exit codes by `main`.
+//!
+//! Each is made where the failure happens, so its variant says what failed, keeping the
+//! underlying error of a failed request or file operation as its source.
+use std::process::ExitStatus;
+
+#[derive(Debug, thiserror::Error)]
+pub enum AocError {
+    /// Not run from a year or day directory, or the one asked for doesn't exist.
+    #[error("{0}")]
+    Environment(String),
+    /// Invalid config, or a setting the command needs is missing.
+    #[error("{0}")]
+    Config(String),
+    /// Failed to talk to adventofcode.com, GitHub or crates.io over HTTP, or the server refused.
+    #[error("{message}{}", suffix(.source))]
+    Network { message: String, source: Option<reqwest::Error> },
+    /// The session cookie was rejected.
+    #[error("Session invalid or expired")]
+    InvalidSession,
+    /// Failed to build or run a part.
+    #[error("{0}")]
+    Build(String),
+    /// A part ran but exited unsuccessfully.
+    #[error("part_{part} failed: {status}")]
+    PartFailed { part: u8, status: ExitStatus },
+    /// Failed to read or write files of the project.
+    #[error("{message}{}", suffix(.source))]
+    Io { message: String, source: Option<std::io::Error> },
+    /// Failed to parse a directory name, a file or a response.
+    #[error("{0}")]
+    Parse(String),
+    /// The user answered no to a prompt.
+    #[error("Aborted")]
+    Aborted,
+    /// The command already reported the failure and only needs to exit with `code`.
+    #[error("Exited with code {0}")]
+    Exit(i32),
+}
+
+/// `: <source>` after the message of an error with a source.
+fn suffix(source: &Option<impl std::fmt::Display>) -> String {
+    source.as_ref().map(|source| format!(": {}", source)).unwrap_or_default()
+}
+
+impl AocError {
+    /// A request which failed with `source`, while doing what `message` says.
+    pub fn network(message: impl Into<String>, source: reqwest::Error) -> Self {
+        AocError::Network { message: message.into(), source: Some(source) }
+    }
+
+    /// A file operation which failed with `source`, while doing what `message` says.
+    pub fn io(message: impl Into<String>, source: std::io::Error) -> Self {
+        AocError::Io { message: message.into(), source: Some(source) }
+    }
+
+    /// A response of the server refusing the request.
+    pub fn refused(message: impl Into<String>) -> Self {
+        AocError::Network { message: message.into(), source: None }
+    }
+
+    /// Exit code of `aoc` for the error.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            AocError::PartFailed { status, .. } => status.code().unwrap_or(1),
+            AocError::Exit(code) => *code,
+            _ => 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_code() {
+        assert_eq!(AocError::Environment("Not a day directory".to_owned()).exit_code(), 1);
+        assert_eq!(AocError::Exit(101).exit_code(), 101);
+        let error = AocError::Parse("Invalid directory name: day-xx".to_owned());
+        assert_eq!((error.exit_code(), error.to_string().as_str()), (1, "Invalid directory name: day-xx"));
+        assert_eq!(AocError::InvalidSession.to_string(), "Session invalid or expired");
+        let error = AocError::io("Failed to read input.txt", std::io::Error::from(std::io::ErrorKind::NotFound));
+        assert_eq!(error.to_string(), "Failed to read input.txt: entity not found");
+        assert!(std::error::Error::source(&error).is_some());
+        assert_eq!(AocError::refused("Puzzle not open yet").to_string(), "Puzzle not open yet");
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+
+            let error = AocError::PartFailed { part: 2, status: ExitStatus::from_raw(3 << 8) };
+            assert_eq!(error.exit_code(), 3);
+            assert_eq!(error.to_string(), "part_2 failed: exit status: 3");
+        }
+    }
+}
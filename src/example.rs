@@ -0,0 +1,207 @@
+//! Example inputs from the puzzle description and their expected answers, for `aoc example`.
+use crate::error::AocError;
+use colored::*;
+use scraper::{Html, Selector};
+use std::path::{Path, PathBuf};
+
+/// Contents of the `<pre><code>` blocks of a puzzle page, in order.
+pub fn code_blocks(html: &str) -> Vec<String> {
+    let selector = Selector::parse("pre > code").unwrap();
+    Html::parse_document(html)
+        .select(&selector)
+        .map(|code| code.text().collect())
+        .collect()
+}
+
+/// Write the code block at `index` (0 is the first) of the puzzle page to `example.txt` in
+/// `day_dir`, returning its path.
+pub fn save(day_dir: &Path, html: &str, index: usize) -> Result<PathBuf, AocError> {
+    let blocks = code_blocks(html);
+    let example = blocks.get(index).ok_or_else(|| match blocks.len() {
+        0 => AocError::Parse("The puzzle has no example".to_owned()),
+        count => AocError::Config(format!("The puzzle has {} examples, the last index is {}", count, count - 1)),
+    })?;
+    let path = day_dir.join("example.txt");
+    std::fs::write(&path, example).map_err(|e| AocError::io(format!("Failed to write {}", path.display()), e))?;
+    Ok(path)
+}
+
+/// The example to run `day_dir` against: `file` if given, or else example.txt, unless another
+/// example is picked with `index`. A missing example.txt, or a picked example, is saved from the
+/// puzzle description `fetch_puzzle` gives for the year and day, named with `formats`.
+pub fn locate(
+    day_dir: &Path,
+    formats: &crate::clean::Formats,
+    file: Option<&Path>,
+    index: Option<usize>,
+    fetch_puzzle: impl FnOnce(u16, u8) -> Result<String, AocError>,
+) -> Result<PathBuf, AocError> {
+    if let Some(file) = file {
+        if !file.is_file() {
+            return Err(AocError::Environment(format!("No example at {}", file.display())));
+        }
+        return Ok(file.to_owned());
+    }
+    let example_path = day_dir.join("example.txt");
+    if index.is_none() && example_path.is_file() {
+        return Ok(example_path);
+    }
+    let year = crate::env::year_dir_of(day_dir).and_then(|year_dir| crate::env::parse_year(year_dir, formats.year))?;
+    let html = fetch_puzzle(year, crate::env::parse_day(day_dir, formats.day)?)?;
+    save(day_dir, &html, index.unwrap_or(0))
+}
+
+/// Run `parts` of `day_dir` against `example_path`, with `cargo_args`, printing their output and
+/// whether their answers are `expect`, or else the ones recorded for example.txt. Other `custom`
+/// examples have no recorded answers, otherwise `expect` is recorded for the first part. Returns
+/// whether the answers are all as expected.
+pub fn check(
+    day_dir: &Path,
+    parts: &[u8],
+    example_path: &Path,
+    custom: bool,
+    expect: Option<&str>,
+    cargo_args: &[String],
+) -> Result<bool, AocError> {
+    if let (Some(expect), false, Some(&part)) = (expect, custom, parts.first()) {
+        record(day_dir, part, expect)?;
+    }
+    let mut matched = true;
+    for &part in parts {
+        let output = crate::runner::run_part(day_dir, part, cargo_args, Some(example_path))?;
+        print!("{}", output.stdout);
+        if !output.status.success() {
+            return Err(AocError::PartFailed { part, status: output.status });
+        }
+        let expected = match expect {
+            Some(expect) => Some(expect.to_owned()),
+            None if custom => None,
+            None => expected(day_dir, part),
+        };
+        match expected {
+            Some(expected) if output.answer() == expected => {
+                eprintln!("{}", format!("Part {}: matches the expected answer {}", part, expected).green());
+            }
+            Some(expected) => {
+                eprintln!("{}", format!("Part {}: expected {}, got {}", part, expected, output.answer()).red());
+                matched = false;
+            }
+            None => eprintln!("{}", format!("Part {}: no expected answer, set one with --expect", part).yellow()),
+        }
+    }
+    Ok(matched)
+}
+
+/// Expected answer of `part` for example.txt in `day_dir`, recorded in the day's manifest, or else
+/// from the `EXAMPLE_ANSWER` constant of the part's tests.
+pub fn expected(day_dir: &Path, part: u8) -> Option<String> {
+    recorded(&day_dir.join("Cargo.toml"), part).or_else(|| {
+        let code = std::fs::read_to_string(day_dir.join(format!("src/bin/part_{}.rs", part))).ok()?;
+        answer_constant(&code)
+    })
+}
+
+/// Record the expected answer of `part` for example.txt, in `[package.metadata.aoc.example]` of the
+/// manifest in `day_dir`.
+pub fn record(day_dir: &Path, part: u8, answer: &str) -> Result<(), AocError> {
+    crate::scaffold::update_aoc_metadata(&day_dir.join("Cargo.toml"), |aoc| {
+        if let Some(aoc) = aoc.as_table_mut() {
+            let example = aoc.entry("example").or_insert_with(toml_edit::table);
+            example[format!("part_{}", part).as_str()] = toml_edit::value(answer);
+        }
+    })
+}
+
+/// Expected answer of `part` in `[package.metadata.aoc.example]` of the manifest at `cargo_toml`.
+fn recorded(cargo_toml: &Path, part: u8) -> Option<String> {
+    let cargo_toml = std::fs::read_to_string(cargo_toml).ok()?.parse::<toml_edit::Document>().ok()?;
+    let aoc = cargo_toml.get("package")?.get("metadata")?.get("aoc")?;
+    aoc.get("example")?.get(format!("part_{}", part).as_str())?.as_str().map(str::to_owned)
+}
+
+/// Value of the `EXAMPLE_ANSWER` constant in `code`, unless it's empty, e.g. `41` for
+/// `const EXAMPLE_ANSWER: &str = "41";`.
+fn answer_constant(code: &str) -> Option<String> {
+    let start = code.find("const EXAMPLE_ANSWER")?;
+    let declaration = &code[start..start + code[start..].find(';')?];
+    let (_, value) = declaration.split_once('=')?;
+    let value = value.trim().trim_matches('"');
+    (!value.is_empty()).then(|| value.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_blocks() {
+        let blocks = code_blocks(include_str!("../tests/fixtures/puzzle.html"));
+        assert_eq!(blocks, ["1000\n2000\n3000\n\n4000\n\n5000\n6000\n", "a < b\nb > c\n"]);
+    }
+
+    #[test]
+    fn test_save() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let html = include_str!("../tests/fixtures/puzzle.html");
+        let path = save(tmp_dir.path(), html, 1).unwrap();
+        assert_eq!(path, tmp_dir.path().join("example.txt"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "a < b\nb > c\n");
+
+        assert_eq!(
+            save(tmp_dir.path(), html, 2).unwrap_err().to_string(),
+            "The puzzle has 2 examples, the last index is 1"
+        );
+        assert_eq!(save(tmp_dir.path(), "<p>Nothing</p>", 0).unwrap_err().to_string(), "The puzzle has no example");
+    }
+
+    #[test]
+    fn test_locate() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let day_dir = tmp_dir.path().join("advent-of-code-2022/day-01");
+        std::fs::create_dir_all(&day_dir).unwrap();
+        let formats = crate::clean::Formats { year: "advent-of-code-", day: "day-" };
+        let puzzle = |year, day| {
+            assert_eq!((year, day), (2022, 1));
+            Ok(include_str!("../tests/fixtures/puzzle.html").to_owned())
+        };
+        let offline = |_, _| -> Result<String, AocError> { panic!("Fetched the puzzle") };
+
+        let err = locate(&day_dir, &formats, Some(&day_dir.join("mine.txt")), None, offline).unwrap_err();
+        assert!(matches!(err, AocError::Environment(_)));
+        // Saved from the puzzle only while there's no example.txt
+        let example_path = locate(&day_dir, &formats, None, None, puzzle).unwrap();
+        assert_eq!(example_path, day_dir.join("example.txt"));
+        assert_eq!(std::fs::read_to_string(&example_path).unwrap(), "1000\n2000\n3000\n\n4000\n\n5000\n6000\n");
+        assert_eq!(locate(&day_dir, &formats, None, None, offline).unwrap(), example_path);
+        locate(&day_dir, &formats, None, Some(1), puzzle).unwrap();
+        assert_eq!(std::fs::read_to_string(&example_path).unwrap(), "a < b\nb > c\n");
+        let mine = day_dir.join("mine.txt");
+        std::fs::write(&mine, "1\n").unwrap();
+        assert_eq!(locate(&day_dir, &formats, Some(&mine), Some(1), offline).unwrap(), mine);
+    }
+
+    #[test]
+    fn test_expected() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let day_dir = tmp_dir.path();
+        std::fs::create_dir_all(day_dir.join("src/bin")).unwrap();
+        std::fs::write(day_dir.join("Cargo.toml"), "[package]\nname = \"day-01\"\n").unwrap();
+        let part = "fn main() {}\n\n#[cfg(test)]\nmod tests {\n    const EXAMPLE_ANSWER: &str = \"\";\n}\n";
+        std::fs::write(day_dir.join("src/bin/part_1.rs"), part).unwrap();
+        std::fs::write(day_dir.join("src/bin/part_2.rs"), part.replace("\"\"", "\"7\"")).unwrap();
+
+        // Left empty in the scaffold
+        assert_eq!(expected(day_dir, 1), None);
+        assert_eq!(expected(day_dir, 2), Some("7".to_owned()));
+        assert_eq!(expected(day_dir, 3), None);
+
+        // Recorded answers come first
+        record(day_dir, 1, "41").unwrap();
+        record(day_dir, 2, "6").unwrap();
+        assert_eq!(expected(day_dir, 1), Some("41".to_owned()));
+        assert_eq!(expected(day_dir, 2), Some("6".to_owned()));
+        assert!(std::fs::read_to_string(day_dir.join("Cargo.toml"))
+            .unwrap()
+            .contains("[package.metadata.aoc.example]\npart_1 = \"41\"\npart_2 = \"6\"\n"));
+    }
+}
@@ -0,0 +1,175 @@
+//! Publishing solutions as GitHub Gists.
+use crate::error::AocError;
+use crate::submissions::Puzzle;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+const GISTS_URL: &str = "https://api.github.com/gists";
+
+#[derive(Debug, Serialize)]
+pub struct GistRequest {
+    pub description: String,
+    /// Only set when creating a gist, as visibility can't be changed afterwards.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public: Option<bool>,
+    pub files: HashMap<String, GistFile>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GistFile {
+    pub content: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GistResponse {
+    pub id: String,
+    pub html_url: String,
+    pub description: Option<String>,
+}
+
+pub fn description(year: u16, day: u8, part: u8) -> String {
+    format!("AoC {} Day {} Part {}", year, day, part)
+}
+
+/// Create a gist for the solution, or update the one with the same description.
+pub fn publish(
+    token: &str,
+    description: &str,
+    file_name: &str,
+    content: &str,
+    public: bool,
+) -> Result<GistResponse, AocError> {
+    let client = reqwest::blocking::Client::new();
+    let request = |method: reqwest::Method, url: &str| {
+        client
+            .request(method, url)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Accept", "application/vnd.github+json")
+            .header("User-Agent", crate::client::USER_AGENT)
+    };
+
+    // Through every page of gists, until the one to update
+    let mut existing = None;
+    let mut url = Some(format!("{}?per_page=100", GISTS_URL));
+    while let Some(page_url) = url.take() {
+        let response = request(reqwest::Method::GET, &page_url)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| AocError::network("Failed to list gists", e))?;
+        url = response
+            .headers()
+            .get(reqwest::header::LINK)
+            .and_then(|link| link.to_str().ok())
+            .and_then(next_link);
+        let gists: Vec<GistResponse> = response.json().map_err(|e| AocError::network("Failed to list gists", e))?;
+        if let Some(gist) = find_gist(&gists, description) {
+            existing = Some(gist.id.clone());
+            break;
+        }
+    }
+
+    let mut body = GistRequest {
+        description: description.to_owned(),
+        public: None,
+        files: HashMap::from([(
+            file_name.to_owned(),
+            GistFile {
+                content: content.to_owned(),
+            },
+        )]),
+    };
+    let response = match existing {
+        Some(id) => request(reqwest::Method::PATCH, &format!("{}/{}", GISTS_URL, id)),
+        None => {
+            body.public = Some(public);
+            request(reqwest::Method::POST, GISTS_URL)
+        }
+    };
+    response
+        .json(&body)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.json())
+        .map_err(|e| AocError::network("Failed to publish gist", e))
+}
+
+/// `$GITHUB_TOKEN`, from the environment or `.env`.
+pub fn token() -> Result<String, AocError> {
+    dotenv::dotenv().ok();
+    std::env::var("GITHUB_TOKEN").map_err(|e| AocError::Config(format!("GITHUB_TOKEN not set: {}", e)))
+}
+
+/// Publish the solution to `puzzle` in `day_dir`, as a file named after the puzzle.
+pub fn publish_part(token: &str, day_dir: &Path, puzzle: Puzzle, public: bool) -> Result<GistResponse, AocError> {
+    let (file_name, content) = solution(day_dir, puzzle)?;
+    let description = description(puzzle.year, puzzle.day, puzzle.part);
+    publish(token, &description, &file_name, &content, public)
+}
+
+/// The gist file name and code of the solution to `puzzle` in `day_dir`.
+fn solution(day_dir: &Path, puzzle: Puzzle) -> Result<(String, String), AocError> {
+    let file_name = format!("part_{}.rs", puzzle.part);
+    let path = day_dir.join("src/bin").join(&file_name);
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| AocError::io(format!("Failed to read {}", path.display()), e))?;
+    Ok((format!("aoc_{}_day_{:02}_{}", puzzle.year, puzzle.day, file_name), content))
+}
+
+/// The URL of the next page from a `Link` header, e.g. `<https://...?page=2>; rel="next"`.
+fn next_link(link: &str) -> Option<String> {
+    link.split(',').find_map(|link| {
+        let (url, params) = link.split_once(';')?;
+        params
+            .split(';')
+            .any(|param| param.trim() == r#"rel="next""#)
+            .then(|| url.trim().trim_start_matches('<').trim_end_matches('>').to_owned())
+    })
+}
+
+fn find_gist<'a>(gists: &'a [GistResponse], description: &str) -> Option<&'a GistResponse> {
+    gists
+        .iter()
+        .find(|gist| gist.description.as_deref() == Some(description))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_gist() {
+        let gists: Vec<GistResponse> = serde_json::from_str(
+            r#"[
+                {"id": "1", "html_url": "https://gist.github.com/1", "description": null},
+                {"id": "2", "html_url": "https://gist.github.com/2", "description": "AoC 2022 Day 1 Part 1"}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(find_gist(&gists, &description(2022, 1, 1)).unwrap().id, "2");
+        assert!(find_gist(&gists, &description(2022, 1, 2)).is_none());
+    }
+
+    #[test]
+    fn test_next_link() {
+        let link = concat!(
+            r#"<https://api.github.com/gists?per_page=100&page=2>; rel="next", "#,
+            r#"<https://api.github.com/gists?per_page=100&page=5>; rel="last""#,
+        );
+        assert_eq!(next_link(link), Some("https://api.github.com/gists?per_page=100&page=2".to_owned()));
+        assert_eq!(next_link(r#"<https://api.github.com/gists?per_page=100&page=1>; rel="prev""#), None);
+    }
+
+    #[test]
+    fn test_solution() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(tmp_dir.path().join("src/bin")).unwrap();
+        std::fs::write(tmp_dir.path().join("src/bin/part_1.rs"), "fn main() {}\n").unwrap();
+
+        let puzzle = Puzzle { year: 2022, day: 1, part: 1 };
+        let (file_name, content) = solution(tmp_dir.path(), puzzle).unwrap();
+        assert_eq!(file_name, "aoc_2022_day_01_part_1.rs");
+        assert_eq!(content, "fn main() {}\n");
+        assert!(solution(tmp_dir.path(), Puzzle { part: 2, ..puzzle }).is_err());
+    }
+}
@@ -0,0 +1,209 @@
+//! Which days use which modules and items, from the `use` declarations of their code, for
+//! `aoc graph`.
+use crate::error::AocError;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Days using each path, by path.
+pub type Graph = BTreeMap<String, BTreeSet<u8>>;
+
+/// Paths imported by the `use` declarations of `code`, with groups expanded, like
+/// `std::collections::HashMap` for `use std::collections::{HashMap, HashSet};`. Relative paths
+/// (`self`, `super` and `crate`) are left out.
+pub fn uses(code: &str) -> Vec<String> {
+    let mut paths = Vec::new();
+    let mut rest = code;
+    while let Some(start) = find_use(rest) {
+        let declaration = &rest[start + "use ".len()..];
+        let Some(end) = declaration.find(';') else {
+            break;
+        };
+        let tree: String = declaration[..end].split_whitespace().collect::<Vec<_>>().join(" ");
+        expand("", &tree, &mut paths);
+        rest = &declaration[end + 1..];
+    }
+    paths.retain(|path| !["self", "super", "crate"].contains(&path.split("::").next().unwrap_or_default()));
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+/// Start of the next `use` declaration of `code`, at the start of a line.
+fn find_use(code: &str) -> Option<usize> {
+    let mut offset = 0;
+    for line in code.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let declaration = trimmed.strip_prefix("pub ").unwrap_or(trimmed);
+        if declaration.starts_with("use ") {
+            return Some(offset + line.len() - declaration.len());
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Add the paths of the use tree `tree` under `prefix` to `paths`.
+fn expand(prefix: &str, tree: &str, paths: &mut Vec<String>) {
+    let tree = tree.trim().trim_start_matches("::");
+    let join = |path: &str| if prefix.is_empty() { path.to_owned() } else { format!("{}::{}", prefix, path) };
+    match tree.find('{') {
+        Some(open) if tree.ends_with('}') => {
+            let prefix = join(tree[..open].trim().trim_end_matches("::"));
+            for item in split_group(&tree[open + 1..tree.len() - 1]) {
+                expand(&prefix, item, paths);
+            }
+        }
+        _ => {
+            let path = tree.split(" as ").next().unwrap_or_default().trim();
+            let path = path.trim_end_matches("::*").trim_end_matches("::self");
+            if path == "self" {
+                paths.push(prefix.to_owned());
+            } else if !path.is_empty() && path != "*" {
+                paths.push(join(path));
+            }
+        }
+    }
+}
+
+/// Items of a use group, split at its top level commas.
+fn split_group(group: &str) -> Vec<&str> {
+    let (mut items, mut depth, mut start) = (Vec::new(), 0, 0);
+    for (index, c) in group.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                items.push(&group[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(&group[start..]);
+    items.into_iter().filter(|item| !item.trim().is_empty()).collect()
+}
+
+/// The paths used by the parts and `src/lib.rs` of each day in `year_dir`.
+pub fn collect(year_dir: &Path, day_format: &str) -> Result<Graph, AocError> {
+    let mut graph = Graph::new();
+    for (day, day_dir) in crate::scaffold::day_dirs(year_dir, day_format)? {
+        let lib = day_dir.join("src/lib.rs");
+        let files = crate::scaffold::part_files(&day_dir).into_iter().map(|(_, path)| path).chain(lib.exists().then_some(lib));
+        for path in files {
+            let code = std::fs::read_to_string(&path)
+                .map_err(|e| AocError::io(format!("Failed to read {}", path.display()), e))?;
+            for used in uses(&code) {
+                graph.entry(used).or_default().insert(day);
+            }
+        }
+    }
+    Ok(graph)
+}
+
+/// Paths by how many days use them, most first.
+fn by_use(graph: &Graph) -> Vec<(&String, &BTreeSet<u8>)> {
+    let mut paths: Vec<_> = graph.iter().collect();
+    paths.sort_by(|(a_path, a_days), (b_path, b_days)| b_days.len().cmp(&a_days.len()).then(a_path.cmp(b_path)));
+    paths
+}
+
+/// A tree of the paths, most used first, with the days using them.
+pub fn render_tree(year: u16, graph: &Graph) -> String {
+    let mut tree = termtree::Tree::new(year.to_string());
+    for (path, days) in by_use(graph) {
+        let plural = if days.len() == 1 { "" } else { "s" };
+        let mut node = termtree::Tree::new(format!("{} ({} day{})", path, days.len(), plural));
+        node.extend(days.iter().map(|day| format!("day {}", day)));
+        tree.push(node);
+    }
+    tree.to_string()
+}
+
+/// The graph in Graphviz's DOT format, with an edge from each day to each path it uses.
+pub fn render_dot(graph: &Graph) -> String {
+    let mut dot = String::from("digraph uses {\n    rankdir=LR;\n");
+    let days: BTreeSet<u8> = graph.values().flatten().copied().collect();
+    for day in days {
+        dot.push_str(&format!("    \"day {}\" [shape=box];\n", day));
+    }
+    for (path, days) in by_use(graph) {
+        for day in days {
+            dot.push_str(&format!("    \"day {}\" -> \"{}\";\n", day, path));
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uses() {
+        let code = "use std::collections::{HashMap, HashSet};
+use itertools::Itertools as _;
+pub use day_01::utils;
+use std::{
+    cmp::Reverse,
+    io::{self, Read},
+};
+use super::*;
+
+fn main() {
+    // use nothing;
+    let used = \"use nothing;\";
+}
+";
+        assert_eq!(
+            uses(code),
+            [
+                "day_01::utils",
+                "itertools::Itertools",
+                "std::cmp::Reverse",
+                "std::collections::HashMap",
+                "std::collections::HashSet",
+                "std::io",
+                "std::io::Read",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collect_and_render() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = tmp_dir.path();
+        for (day, code) in [("day-01", "use std::collections::HashMap;\n"), ("day-02", "use std::collections::HashMap;\nuse regex::Regex;\n")] {
+            std::fs::create_dir_all(year_dir.join(day).join("src/bin")).unwrap();
+            std::fs::write(year_dir.join(day).join("src/bin/part_1.rs"), code).unwrap();
+        }
+        std::fs::write(year_dir.join("day-02/src/lib.rs"), "use regex::Regex;\n").unwrap();
+
+        let graph = collect(year_dir, "day-").unwrap();
+        assert_eq!(graph["std::collections::HashMap"], BTreeSet::from([1, 2]));
+        assert_eq!(graph["regex::Regex"], BTreeSet::from([2]));
+
+        assert_eq!(
+            render_tree(2022, &graph),
+            "2022
+├── std::collections::HashMap (2 days)
+│   ├── day 1
+│   └── day 2
+└── regex::Regex (1 day)
+    └── day 2
+"
+        );
+        assert_eq!(
+            render_dot(&graph),
+            "digraph uses {
+    rankdir=LR;
+    \"day 1\" [shape=box];
+    \"day 2\" [shape=box];
+    \"day 1\" -> \"std::collections::HashMap\";
+    \"day 2\" -> \"std::collections::HashMap\";
+    \"day 2\" -> \"regex::Regex\";
+}
+"
+        );
+    }
+}
@@ -0,0 +1,132 @@
+//! Git hooks checking formatting and lints, for `aoc hook`.
+use crate::error::AocError;
+use std::path::{Path, PathBuf};
+
+/// Marks hooks installed by `aoc hook`, which are the only ones it replaces or removes.
+const MARKER: &str = "# Installed by `aoc hook`";
+
+/// The hook, run by git from the top of the repository. That's a year directory, or one with the
+/// year directories and no Cargo.toml of its own, in which case each year's workspace is checked.
+fn script() -> String {
+    format!(
+        r#"#!/bin/sh
+{}
+if [ -f Cargo.toml ]; then set -- Cargo.toml; else set -- */Cargo.toml; fi
+for manifest in "$@"; do
+    [ -f "$manifest" ] || continue
+    cargo fmt --all --check --manifest-path "$manifest" || exit 1
+    cargo clippy --quiet --workspace --manifest-path "$manifest" -- -D warnings || exit 1
+done
+"#,
+        MARKER
+    )
+}
+
+/// The hooks directory of the git repository containing `dir`.
+pub fn hooks_dir(dir: &Path) -> Result<PathBuf, AocError> {
+    let output = std::process::Command::new("git")
+        .args(["rev-parse", "--git-path", "hooks"])
+        .current_dir(dir)
+        .output()
+        .map_err(|e| AocError::io("Failed to run git", e))?;
+    if !output.status.success() {
+        return Err(AocError::Environment(format!("Not in a git repository: {}", dir.display())));
+    }
+    Ok(dir.join(String::from_utf8_lossy(&output.stdout).trim()))
+}
+
+/// Write the `hook_type` hook (e.g. `pre-commit`) to `hooks_dir`, returning its path. An existing
+/// hook is only replaced if `aoc hook` installed it.
+pub fn install(hooks_dir: &Path, hook_type: &str) -> Result<PathBuf, AocError> {
+    let path = hooks_dir.join(hook_type);
+    if let Ok(existing) = std::fs::read_to_string(&path) {
+        if !existing.contains(MARKER) {
+            return Err(AocError::Environment(format!(
+                "{} already exists, and wasn't installed by aoc",
+                path.display()
+            )));
+        }
+    }
+    std::fs::create_dir_all(hooks_dir)
+        .and_then(|_| std::fs::write(&path, script()))
+        .map_err(|e| AocError::io(format!("Failed to write {}", path.display()), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))
+            .map_err(|e| AocError::io(format!("Failed to make {} executable", path.display()), e))?;
+    }
+    Ok(path)
+}
+
+/// Remove the `hook_type` hook from `hooks_dir`, returning its path, if `aoc hook` installed it.
+pub fn uninstall(hooks_dir: &Path, hook_type: &str) -> Result<PathBuf, AocError> {
+    let path = hooks_dir.join(hook_type);
+    let existing = std::fs::read_to_string(&path)
+        .map_err(|e| AocError::io(format!("Failed to read {}", path.display()), e))?;
+    if !existing.contains(MARKER) {
+        return Err(AocError::Environment(format!("{} wasn't installed by aoc", path.display())));
+    }
+    std::fs::remove_file(&path).map_err(|e| AocError::io(format!("Failed to remove {}", path.display()), e))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_install_and_uninstall() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        assert!(hooks_dir(tmp_dir.path()).is_err());
+        let status = std::process::Command::new("git")
+            .args(["init", "--quiet"])
+            .current_dir(tmp_dir.path())
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let subdir = tmp_dir.path().join("day-01");
+        std::fs::create_dir(&subdir).unwrap();
+
+        let dir = hooks_dir(&subdir).unwrap();
+        let path = install(&dir, "pre-commit").unwrap();
+        assert_eq!(
+            path.canonicalize().unwrap(),
+            tmp_dir.path().join(".git/hooks/pre-commit").canonicalize().unwrap()
+        );
+        assert!(std::fs::read_to_string(&path).unwrap().contains("cargo fmt --all --check"));
+        // Reinstalling replaces it
+        install(&dir, "pre-commit").unwrap();
+        uninstall(&dir, "pre-commit").unwrap();
+        assert!(!path.exists());
+
+        std::fs::write(dir.join("pre-push"), "#!/bin/sh\nmake test\n").unwrap();
+        assert!(install(&dir, "pre-push").is_err());
+        assert!(uninstall(&dir, "pre-push").is_err());
+        assert!(dir.join("pre-push").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_script_per_year() {
+        // Without a Cargo.toml at the top of the repository, each year's workspace is checked
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = tmp_dir.path().join("advent-of-code-2022");
+        std::fs::create_dir_all(year_dir.join("day-01/src")).unwrap();
+        std::fs::write(year_dir.join("Cargo.toml"), "[workspace]\nmembers = [\"day-01\"]\n").unwrap();
+        std::fs::write(
+            year_dir.join("day-01/Cargo.toml"),
+            "[package]\nname = \"day-01\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .unwrap();
+        let hook = tmp_dir.path().join("pre-commit");
+        std::fs::write(&hook, script()).unwrap();
+        let run = || std::process::Command::new("sh").arg(&hook).current_dir(tmp_dir.path()).output().unwrap();
+
+        std::fs::write(year_dir.join("day-01/src/main.rs"), "fn main() {}\n").unwrap();
+        let output = run();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        std::fs::write(year_dir.join("day-01/src/main.rs"), "fn main( ) {}\n").unwrap();
+        assert!(!run().status.success());
+    }
+}
@@ -0,0 +1,182 @@
+//! Puzzle inputs on disk.
+use crate::client::Fetch;
+use crate::error::AocError;
+use colored::*;
+use std::path::{Path, PathBuf};
+
+/// How the downloads of `download_missing` went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Downloads {
+    pub downloaded: usize,
+    /// Days skipped as they already have an input.
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+/// Download the inputs missing in the year directories next to the one `dir` is in, or else in
+/// `dir`, named with `formats`, printing how each download goes.
+pub fn download_missing(
+    client: &crate::client::AocClient,
+    dir: &Path,
+    formats: &crate::clean::Formats,
+) -> Result<Downloads, AocError> {
+    let root = crate::env::root_dir(dir, formats.day, formats.year);
+    let (missing, skipped) = missing_inputs(&root, formats)?;
+    let days: Vec<(u16, u8)> = missing.iter().map(|missing| (missing.year, missing.day)).collect();
+    let mut downloads = Downloads { downloaded: 0, skipped, failed: 0 };
+    client.fetch_inputs(&days, |fetch| match fetch {
+        Fetch::Started(index) => println!("[{}][Day {}] Downloading…", days[index].0, days[index].1),
+        Fetch::Finished(index, input) => {
+            match input.and_then(|input| save_input(&missing[index].day_dir, Path::new("input.txt"), &input)) {
+                Ok(path) => {
+                    println!("New {}: {}", path.display(), "Success".green());
+                    downloads.downloaded += 1;
+                }
+                Err(err) => {
+                    eprintln!("{}", format!("[{}][Day {}] {}", days[index].0, days[index].1, err).red());
+                    downloads.failed += 1;
+                }
+            }
+        }
+    });
+    Ok(downloads)
+}
+
+/// Test input given on the command line, with `\n` read as a newline.
+pub fn mock_input(input: &str) -> String {
+    input.replace("\\n", "\n")
+}
+
+/// Run `part` of `day_dir` against the test input `input`, as read by `mock_input`.
+pub fn run_mock(day_dir: &Path, part: u8, input: &str) -> Result<crate::runner::RunOutput, AocError> {
+    // The part runs in the day directory, so the input needs an absolute path
+    let path = std::env::temp_dir().join(format!("aoc-mock-{}.txt", std::process::id()));
+    std::fs::write(&path, mock_input(input))
+        .map_err(|e| AocError::io(format!("Failed to write {}", path.display()), e))?;
+    let output = crate::runner::run_part(day_dir, part, &[], Some(&path));
+    let _ = std::fs::remove_file(&path);
+    output
+}
+
+/// The first and last `lines` lines of `input`, separated by `…` when there are more lines in
+/// between, followed by its line and character counts.
+pub fn preview(input: &str, lines: usize) -> String {
+    let all: Vec<&str> = input.lines().collect();
+    let mut shown: Vec<&str> = if all.len() <= 2 * lines {
+        all.clone()
+    } else {
+        [&all[..lines], &["…"], &all[all.len() - lines..]].concat()
+    };
+    let counts = format!("{} lines, {} characters", all.len(), input.chars().count());
+    shown.push(&counts);
+    shown.join("\n") + "\n"
+}
+
+/// A day directory without an input.txt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingInput {
+    pub year: u16,
+    pub day: u8,
+    pub day_dir: PathBuf,
+}
+
+/// The days of every year directory in `root` missing their input.txt, by year and day, and the
+/// number of days which already have one.
+pub fn missing_inputs(root: &Path, formats: &crate::clean::Formats) -> Result<(Vec<MissingInput>, usize), AocError> {
+    let entries = std::fs::read_dir(root).map_err(|e| AocError::io(format!("Failed to read {}", root.display()), e))?;
+    let mut year_dirs: Vec<(u16, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .filter_map(|path| Some((crate::env::parse_year(&path, formats.year).ok()?, path)))
+        .collect();
+    year_dirs.sort();
+
+    let mut missing = Vec::new();
+    let mut existing = 0;
+    for (year, year_dir) in year_dirs {
+        for (day, day_dir) in crate::scaffold::day_dirs(&year_dir, formats.day)? {
+            if day_dir.join("input.txt").exists() {
+                existing += 1;
+            } else {
+                missing.push(MissingInput { year, day, day_dir });
+            }
+        }
+    }
+    Ok((missing, existing))
+}
+
+/// Write `input` to `path`, relative to `dir`, creating its directory. Returns the absolute path.
+pub fn save_input(dir: &Path, path: &Path, input: &str) -> Result<PathBuf, AocError> {
+    let path = dir.join(path);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| AocError::io(format!("Failed to create {}", parent.display()), e))?;
+    }
+    std::fs::write(&path, input).map_err(|e| AocError::io(format!("Failed to write {}", path.display()), e))?;
+    crate::logging::wrote(&path);
+    std::fs::canonicalize(&path).map_err(|e| AocError::io(format!("Failed to resolve {}", path.display()), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::WorkspaceConfig;
+    use crate::runner::run_part;
+    use crate::scaffold::new_year;
+
+    #[test]
+    fn test_preview() {
+        let input: String = (1..=12).map(|line| format!("{}\n", line)).collect();
+        assert_eq!(preview(&input, 2), "1\n2\n…\n11\n12\n12 lines, 27 characters\n");
+        assert_eq!(preview(&input, 6), format!("{}12 lines, 27 characters\n", input));
+        assert_eq!(preview("", 5), "0 lines, 0 characters\n");
+    }
+
+    #[test]
+    fn test_save_input() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let path = save_input(tmp_dir.path(), Path::new("puzzles/2024/day01.txt"), "1\n2\n").unwrap();
+        assert_eq!(path, tmp_dir.path().canonicalize().unwrap().join("puzzles/2024/day01.txt"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "1\n2\n");
+    }
+
+    #[test]
+    fn test_missing_inputs() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let root = tmp_dir.path();
+        for day_dir in ["advent-of-code-2023/day-03", "advent-of-code-2022/day-01", "advent-of-code-2022/day-02"] {
+            std::fs::create_dir_all(root.join(day_dir)).unwrap();
+        }
+        std::fs::write(root.join("advent-of-code-2022/day-01/input.txt"), "1\n").unwrap();
+        std::fs::create_dir_all(root.join("notes/day-04")).unwrap();
+
+        let formats = crate::clean::Formats { year: "advent-of-code-", day: "day-" };
+        let (missing, existing) = missing_inputs(root, &formats).unwrap();
+        assert_eq!(
+            missing,
+            [
+                MissingInput { year: 2022, day: 2, day_dir: root.join("advent-of-code-2022/day-02") },
+                MissingInput { year: 2023, day: 3, day_dir: root.join("advent-of-code-2023/day-03") },
+            ]
+        );
+        assert_eq!(existing, 1);
+    }
+
+    #[test]
+    fn test_mock_input() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = new_year(tmp_dir.path(), "advent-of-code-", "day-", 2022, false, false, &WorkspaceConfig::default()).unwrap();
+        let day_dir = year_dir.join("day-01");
+        // Count the lines of the input, in a part otherwise like the template's
+        let part_1 = std::fs::read_to_string(day_dir.join("src/bin/part_1.rs")).unwrap();
+        std::fs::write(
+            day_dir.join("src/bin/part_1.rs"),
+            part_1.replace("todo!()", "lines.len() as i32"),
+        )
+        .unwrap();
+        std::fs::write(day_dir.join("input.txt"), "1\n2\n3\n").unwrap();
+
+        assert_eq!(run_mock(&day_dir, 1, "1 2 3\\n4 5 6").unwrap().stdout, "2\n");
+        assert_eq!(run_part(&day_dir, 1, &[], None).unwrap().stdout, "3\n");
+    }
+}
@@ -0,0 +1,109 @@
+//! Private leaderboards, from their JSON API, for `aoc leaderboard`.
+use crate::error::AocError;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// AoC asks not to fetch a leaderboard more than once every 15 minutes.
+pub const CACHE_MAX_AGE: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Leaderboard {
+    /// The year, e.g. `"2022"`.
+    pub event: String,
+    pub owner_id: u64,
+    /// Members by their id.
+    pub members: BTreeMap<String, Member>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Member {
+    pub id: u64,
+    /// Missing for anonymous users.
+    pub name: Option<String>,
+    pub stars: u32,
+    pub local_score: u64,
+    pub global_score: u64,
+    /// Seconds since the Unix epoch, or 0 without stars.
+    pub last_star_ts: u64,
+    /// Stars by day, then by part.
+    pub completion_day_level: BTreeMap<u8, BTreeMap<u8, Star>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Star {
+    /// Seconds since the Unix epoch.
+    pub get_star_ts: u64,
+    pub star_index: u64,
+}
+
+impl Leaderboard {
+    /// Members by local score, highest first, ties going to who got their last star first.
+    pub fn ranked(&self) -> Vec<&Member> {
+        let mut members: Vec<&Member> = self.members.values().collect();
+        members.sort_by_key(|member| (std::cmp::Reverse(member.local_score), member.last_star_ts, member.id));
+        members
+    }
+}
+
+impl Member {
+    pub fn display_name(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("(anonymous user #{})", self.id),
+        }
+    }
+
+    pub fn stars_on(&self, day: u8) -> usize {
+        self.completion_day_level.get(&day).map_or(0, |parts| parts.len())
+    }
+}
+
+pub fn parse(json: &str) -> Result<Leaderboard, AocError> {
+    serde_json::from_str(json).map_err(|e| AocError::Parse(format!("Invalid leaderboard: {}", e)))
+}
+
+/// Table of the members by rank, with their score and stars for each day.
+pub fn render(leaderboard: &Leaderboard) -> String {
+    use colored::*;
+
+    let mut table = format!("{:>4} {:>5} {}\n", "", "Score", "1234567890123456789012345");
+    for (rank, member) in leaderboard.ranked().into_iter().enumerate() {
+        let stars: String = (1..=25)
+            .map(|day| match member.stars_on(day) {
+                2 => "*".yellow().to_string(),
+                1 => "*".white().to_string(),
+                _ => ".".dimmed().to_string(),
+            })
+            .collect();
+        table.push_str(&format!(
+            "{:>3}) {:>5} {} {}\n",
+            rank + 1,
+            member.local_score,
+            stars,
+            member.display_name()
+        ));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let leaderboard = parse(include_str!("../tests/fixtures/leaderboard.json")).unwrap();
+        assert_eq!(leaderboard.event, "2022");
+        assert_eq!(leaderboard.members.len(), 3);
+        let alice = &leaderboard.members["1001"];
+        assert_eq!(alice.completion_day_level[&1][&2].get_star_ts, 1669871700);
+        assert_eq!((alice.stars_on(1), alice.stars_on(2), alice.stars_on(3)), (2, 1, 0));
+
+        let ranked: Vec<String> = leaderboard.ranked().iter().map(|member| member.display_name()).collect();
+        assert_eq!(ranked, vec!["Bob", "Alice", "(anonymous user #2002)"]);
+        let table = render(&leaderboard);
+        assert_eq!(table.lines().count(), 4);
+        assert!(table.lines().nth(1).unwrap().ends_with("Bob"));
+    }
+}
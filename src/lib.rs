@@ -0,0 +1,50 @@
+//! Helpers for solving Advent of Code puzzles in Rust: scaffolding years, days and parts, running
+//! them, and talking to adventofcode.com. The `aoc` binary is a command line over them.
+pub mod answers;
+pub mod archive;
+pub mod badge;
+pub mod bench;
+pub mod bundle;
+pub mod cache;
+pub mod calendar;
+pub mod clean;
+pub mod client;
+pub mod config;
+pub mod crypt;
+pub mod deps;
+pub mod diff;
+pub mod editor;
+pub mod env;
+pub mod error;
+pub mod example;
+pub mod gist;
+pub mod graph;
+pub mod hook;
+pub mod input;
+pub mod leaderboard;
+pub mod link;
+pub mod logging;
+pub mod login;
+pub mod metrics;
+pub mod migrate;
+pub mod perf;
+pub mod profile;
+pub mod pull;
+pub mod readme;
+pub mod report;
+pub mod rewrite;
+pub mod rollover;
+pub mod run_all;
+pub mod runner;
+pub mod runtimes;
+pub mod scaffold;
+pub mod stats;
+pub mod status;
+pub mod submissions;
+pub mod summarize;
+pub mod template;
+pub mod tui;
+pub mod unlock;
+pub mod urls;
+pub mod verify;
+pub mod watch;
@@ -0,0 +1,106 @@
+//! Sharing a file between days by linking it into them, for `aoc link`.
+use crate::error::AocError;
+use std::path::{Component, Path, PathBuf};
+
+/// How a file was linked into a day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Linked {
+    Symlink,
+    /// Copied instead, when symlinks aren't allowed, as on Windows without developer mode.
+    Copied,
+}
+
+/// Link `source` to `day_dir/src/utils.rs`, with a path relative to it so the link still works when
+/// the repository moves. Fails if there already is a different file there.
+pub fn link_utils(source: &Path, day_dir: &Path) -> Result<(PathBuf, Linked), AocError> {
+    let source = source
+        .canonicalize()
+        .map_err(|e| AocError::io(format!("Failed to find {}", source.display()), e))?;
+    if !source.is_file() {
+        return Err(AocError::Environment(format!("Not a file: {}", source.display())));
+    }
+    let src_dir = day_dir.join("src");
+    std::fs::create_dir_all(&src_dir).map_err(|e| AocError::io(format!("Failed to create {}", src_dir.display()), e))?;
+    let src_dir = src_dir
+        .canonicalize()
+        .map_err(|e| AocError::io(format!("Failed to find {}", src_dir.display()), e))?;
+    let path = src_dir.join("utils.rs");
+    if std::fs::symlink_metadata(&path).is_ok() {
+        if path.canonicalize().is_ok_and(|target| target == source) {
+            return Ok((path, Linked::Symlink));
+        }
+        return Err(AocError::Environment(format!("{} already exists", path.display())));
+    }
+
+    let target = relative_path(&src_dir, &source);
+    match symlink_file(&target, &path) {
+        Ok(()) => Ok((path, Linked::Symlink)),
+        Err(e) if cfg!(windows) && e.kind() == std::io::ErrorKind::PermissionDenied => {
+            std::fs::copy(&source, &path)
+                .map_err(|e| AocError::io(format!("Failed to copy to {}", path.display()), e))?;
+            Ok((path, Linked::Copied))
+        }
+        Err(e) => Err(AocError::io(format!("Failed to link {} to {}", path.display(), source.display()), e)),
+    }
+}
+
+#[cfg(unix)]
+fn symlink_file(target: &Path, path: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, path)
+}
+
+/// Needs developer mode or an elevated prompt, otherwise fails with `ERROR_PRIVILEGE_NOT_HELD`.
+#[cfg(windows)]
+fn symlink_file(target: &Path, path: &Path) -> std::io::Result<()> {
+    const ERROR_PRIVILEGE_NOT_HELD: i32 = 1314;
+    std::os::windows::fs::symlink_file(target, path).map_err(|e| match e.raw_os_error() {
+        Some(ERROR_PRIVILEGE_NOT_HELD) => std::io::Error::new(std::io::ErrorKind::PermissionDenied, e),
+        _ => e,
+    })
+}
+
+/// Path to `to` from the directory `from`, both absolute.
+fn relative_path(from: &Path, to: &Path) -> PathBuf {
+    let from: Vec<Component> = from.components().collect();
+    let to: Vec<Component> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut path: PathBuf = from[common..].iter().map(|_| Component::ParentDir).collect();
+    path.extend(&to[common..]);
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_path() {
+        let path = relative_path(Path::new("/aoc/advent-of-code-2022/day-01/src"), Path::new("/aoc/shared/utils.rs"));
+        assert_eq!(path, Path::new("../../../shared/utils.rs"));
+        assert_eq!(relative_path(Path::new("/aoc"), Path::new("/aoc/utils.rs")), Path::new("utils.rs"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_link_utils() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = tmp_dir.path().join("advent-of-code-2022");
+        let source = year_dir.join("utils.rs");
+        std::fs::create_dir_all(year_dir.join("day-01/src")).unwrap();
+        std::fs::write(&source, "pub fn parse() {}\n").unwrap();
+
+        let (path, linked) = link_utils(&source, &year_dir.join("day-01")).unwrap();
+        assert_eq!(linked, Linked::Symlink);
+        assert_eq!(std::fs::read_link(&path).unwrap(), Path::new("../../utils.rs"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "pub fn parse() {}\n");
+        // Linking again changes nothing, and src/ is created when missing
+        assert!(link_utils(&source, &year_dir.join("day-01")).is_ok());
+        assert!(link_utils(&source, &year_dir.join("day-02")).is_ok());
+
+        // An existing file is kept
+        std::fs::create_dir_all(year_dir.join("day-03/src")).unwrap();
+        std::fs::write(year_dir.join("day-03/src/utils.rs"), "").unwrap();
+        assert!(link_utils(&source, &year_dir.join("day-03")).unwrap_err().to_string().ends_with("already exists"));
+        assert!(link_utils(&year_dir.join("missing.rs"), &year_dir.join("day-01")).is_err());
+    }
+}
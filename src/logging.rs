@@ -0,0 +1,33 @@
+//! A log of what aoc did, as JSON lines appended to `--log-file` (or `log.file` in the config).
+//!
+//! Each line is an event with the span of the command it happened in, along with the year, day and
+//! part it was for once they're known: a `request` for each call to adventofcode.com and a `write`
+//! for each file written. The session cookie is marked sensitive where the headers are built, so
+//! it's logged as `Sensitive`.
+use crate::error::AocError;
+use std::path::Path;
+
+/// Start logging to `path`, appending to it.
+pub fn init(path: &Path) -> Result<(), AocError> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).map_err(|e| AocError::io(format!("Failed to create {}", dir.display()), e))?;
+    }
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| AocError::io(format!("Failed to open {}", path.display()), e))?;
+    let subscriber = tracing_subscriber::fmt()
+        .json()
+        .with_current_span(true)
+        .with_span_list(true)
+        .with_writer(std::sync::Mutex::new(file))
+        .finish();
+    tracing::subscriber::set_global_default(subscriber)
+        .map_err(|e| AocError::Config(format!("Failed to log to {}: {}", path.display(), e)))
+}
+
+/// Log that `path` was written.
+pub fn wrote(path: &Path) {
+    tracing::info!(path = %path.display(), "write");
+}
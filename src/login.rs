@@ -0,0 +1,320 @@
+//! Setting up and checking the session cookie, for `aoc login` and `aoc whoami`.
+use crate::error::AocError;
+use clap::ValueEnum;
+use std::path::{Path, PathBuf};
+
+/// Service and user the session cookie is stored under in the OS keyring.
+#[cfg(feature = "keyring")]
+const KEYRING_SERVICE: &str = "aoc";
+#[cfg(feature = "keyring")]
+const KEYRING_USER: &str = "session";
+
+pub const INSTRUCTIONS: &str = "\
+To find your session cookie:
+  1. Log in at https://adventofcode.com
+  2. Open the browser's devtools (F12)
+  3. Go to Application (Chrome) or Storage (Firefox) > Cookies > https://adventofcode.com
+  4. Copy the value of the `session` cookie";
+
+/// Where `aoc login` stores the session cookie.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Backend {
+    /// `[session] cookie` in the user's config file.
+    Config,
+    /// `session=` in `.env` at the root of the repository.
+    Env,
+    /// The OS keyring.
+    Keyring,
+}
+
+/// Paths of the file backends.
+pub struct Locations {
+    pub env: PathBuf,
+    pub config: Option<PathBuf>,
+}
+
+impl Locations {
+    /// The `.env` of `dir` and the user's config file.
+    pub fn of(dir: &Path) -> Self {
+        Self { env: env_path(dir), config: crate::config::config_path() }
+    }
+}
+
+/// `.env` in the root of the git repository `dir` is in, or in `dir` outside of one.
+pub fn env_path(dir: &Path) -> PathBuf {
+    dir.ancestors()
+        .find(|ancestor| ancestor.join(".git").exists())
+        .unwrap_or(dir)
+        .join(".env")
+}
+
+/// The cookie's value as pasted, without whitespace, a `session=` prefix or a trailing `;`,
+/// checking it looks like one.
+pub fn normalize(cookie: &str) -> Result<String, AocError> {
+    let cookie = cookie.trim();
+    let cookie = cookie.strip_prefix("session=").unwrap_or(cookie).trim_end_matches(';').trim();
+    if cookie.is_empty() {
+        return Err(AocError::Config("The session cookie is empty".to_owned()));
+    }
+    if !cookie.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(AocError::Config(
+            "The session cookie should only have hexadecimal digits, copy its value from the devtools".to_owned(),
+        ));
+    }
+    Ok(cookie.to_owned())
+}
+
+/// Username from the settings page's username field, or else from the page header.
+pub fn settings_username(html: &str) -> Option<String> {
+    let selector = scraper::Selector::parse(r#"input[name="username"]"#).unwrap();
+    scraper::Html::parse_document(html)
+        .select(&selector)
+        .find_map(|input| input.value().attr("value"))
+        .map(str::trim)
+        .filter(|username| !username.is_empty())
+        .map(str::to_owned)
+        .or_else(|| crate::client::parse_username(html))
+}
+
+/// A hash of the cookie which tells the cookies of the whoami cache apart. It's tagged, so it's
+/// unlike the key `aoc encrypt` derives from the same cookie.
+fn cache_key(cookie: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    let hash = Sha256::new().chain_update(b"aoc-whoami\0").chain_update(cookie.as_bytes()).finalize();
+    hash.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// The cache of `whoami`, `aoc/whoami` in the cache directory.
+pub fn cache_path() -> Result<PathBuf, AocError> {
+    crate::template::cache_dir()
+        .map(|dir| dir.join("aoc").join("whoami"))
+        .ok_or_else(|| AocError::Config("No cache directory, set XDG_CACHE_HOME".to_owned()))
+}
+
+/// Check the `pasted` cookie logs in, with `fetch_username`, and store it in `backend`, returning
+/// the username.
+pub fn log_in(
+    backend: Backend,
+    locations: &Locations,
+    pasted: &str,
+    fetch_username: impl FnOnce(&str) -> Result<Option<String>, AocError>,
+) -> Result<String, AocError> {
+    let cookie = normalize(pasted)?;
+    let username = fetch_username(&cookie)?.ok_or(AocError::InvalidSession)?;
+    store(backend, locations, &cookie)?;
+    Ok(username)
+}
+
+/// Username the cookie is logged in as, from the cache at `cache_path` when it was cached for the
+/// same cookie, or else from `fetch`, caching it.
+pub fn whoami(
+    cache_path: &Path,
+    cookie: &str,
+    fetch: impl FnOnce() -> Result<Option<String>, AocError>,
+) -> Result<Option<String>, AocError> {
+    // The cookie's hash, so the cache is only used for the same cookie without storing it
+    let key = cache_key(cookie);
+    if let Ok(cached) = std::fs::read_to_string(cache_path) {
+        if let Some((cached_key, username)) = cached.trim_end().split_once('\n') {
+            if cached_key == key {
+                return Ok(Some(username.to_owned()));
+            }
+        }
+    }
+    let username = fetch()?;
+    if let Some(username) = &username {
+        if let Some(dir) = cache_path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| AocError::io(format!("Failed to create {}", dir.display()), e))?;
+        }
+        std::fs::write(cache_path, format!("{}\n{}\n", key, username))
+            .map_err(|e| AocError::io(format!("Failed to write {}", cache_path.display()), e))?;
+    }
+    Ok(username)
+}
+
+/// Where `backend` stores the cookie, for messages.
+pub fn describe(backend: Backend, locations: &Locations) -> String {
+    match backend {
+        Backend::Config => locations
+            .config
+            .as_ref()
+            .map_or_else(|| "the config file".to_owned(), |path| path.display().to_string()),
+        Backend::Env => locations.env.display().to_string(),
+        Backend::Keyring => "the OS keyring".to_owned(),
+    }
+}
+
+/// The cookie already stored in `backend`, if there is one.
+pub fn stored(backend: Backend, locations: &Locations) -> Option<String> {
+    match backend {
+        Backend::Config => {
+            let contents = std::fs::read_to_string(locations.config.as_ref()?).ok()?;
+            let config: toml::Value = toml::from_str(&contents).ok()?;
+            config.get("session")?.get("cookie")?.as_str().map(str::to_owned)
+        }
+        Backend::Env => read_env(&std::fs::read_to_string(&locations.env).ok()?),
+        Backend::Keyring => from_keyring(),
+    }
+}
+
+/// The cookie in the OS keyring, if there is one.
+#[cfg(feature = "keyring")]
+pub fn from_keyring() -> Option<String> {
+    keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER).ok()?.get_password().ok()
+}
+
+/// Never any, without the `keyring` feature.
+#[cfg(not(feature = "keyring"))]
+pub fn from_keyring() -> Option<String> {
+    None
+}
+
+#[cfg(feature = "keyring")]
+fn store_in_keyring(cookie: &str) -> Result<(), AocError> {
+    keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)
+        .and_then(|entry| entry.set_password(cookie))
+        .map_err(|e| AocError::Config(format!("Failed to store the session cookie in the OS keyring: {}", e)))
+}
+
+#[cfg(not(feature = "keyring"))]
+fn store_in_keyring(_cookie: &str) -> Result<(), AocError> {
+    Err(AocError::Config("aoc was built without the OS keyring, use another --backend".to_owned()))
+}
+
+/// Store the cookie in `backend`, replacing the one already there.
+pub fn store(backend: Backend, locations: &Locations, cookie: &str) -> Result<(), AocError> {
+    match backend {
+        Backend::Config => {
+            let path = locations
+                .config
+                .as_ref()
+                .ok_or_else(|| AocError::Config("No config directory, set XDG_CONFIG_HOME".to_owned()))?;
+            crate::config::set_config_value(path, "session.cookie", &format!("\"{}\"", cookie))
+        }
+        Backend::Env => {
+            let contents = std::fs::read_to_string(&locations.env).unwrap_or_default();
+            std::fs::write(&locations.env, write_env(&contents, cookie))
+                .map_err(|e| AocError::io(format!("Failed to write {}", locations.env.display()), e))
+        }
+        Backend::Keyring => store_in_keyring(cookie),
+    }
+}
+
+/// The `session` of the contents of a `.env` file.
+fn read_env(contents: &str) -> Option<String> {
+    contents.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        (key.trim() == "session").then(|| value.trim().trim_matches('"').to_owned())
+    })
+}
+
+/// The contents of a `.env` file with `session` set to `cookie`, keeping its other lines.
+fn write_env(contents: &str, cookie: &str) -> String {
+    let mut replaced = false;
+    let mut lines: Vec<String> = contents
+        .lines()
+        .map(|line| match line.split_once('=') {
+            Some((key, _)) if key.trim() == "session" => {
+                replaced = true;
+                format!("session={}", cookie)
+            }
+            _ => line.to_owned(),
+        })
+        .collect();
+    if !replaced {
+        lines.push(format!("session={}", cookie));
+    }
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("  53616c7465645f5f\n").unwrap(), "53616c7465645f5f".to_owned());
+        assert_eq!(normalize("session=53616c7465645f5f;").unwrap(), "53616c7465645f5f".to_owned());
+        assert_eq!(normalize("session=").unwrap_err().to_string(), "The session cookie is empty");
+        assert!(normalize("Cookie: session=abc").is_err());
+    }
+
+    #[test]
+    fn test_settings_username() {
+        let html = r#"<form><input type="text" name="username" value="AceofSpades5757"/></form>"#;
+        assert_eq!(settings_username(html), Some("AceofSpades5757".to_owned()));
+        let html = r#"<header><div><div class="user">AceofSpades5757</div></div></header>"#;
+        assert_eq!(settings_username(html), Some("AceofSpades5757".to_owned()));
+        assert_eq!(settings_username("<p>Log In</p>"), None);
+    }
+
+    #[test]
+    fn test_whoami() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let cache_path = tmp_dir.path().join("aoc/whoami");
+
+        assert_eq!(whoami(&cache_path, "abc123", || Ok(None)).unwrap(), None);
+        assert!(!cache_path.exists());
+        assert_eq!(
+            whoami(&cache_path, "abc123", || Ok(Some("AceofSpades5757".to_owned()))).unwrap(),
+            Some("AceofSpades5757".to_owned())
+        );
+        // Nothing in the cache gives away the key inputs are encrypted with
+        let cached = std::fs::read_to_string(&cache_path).unwrap();
+        let encryption_key: String = crate::crypt::key("abc123").iter().map(|byte| format!("{:02x}", byte)).collect();
+        assert!(!cached.contains(&encryption_key), "{}", cached);
+        // Cached for the same cookie, fetched again for another one
+        assert_eq!(
+            whoami(&cache_path, "abc123", || Err(AocError::refused("offline"))).unwrap(),
+            Some("AceofSpades5757".to_owned())
+        );
+        let err = whoami(&cache_path, "def456", || Err(AocError::refused("offline"))).unwrap_err();
+        assert_eq!(err.to_string(), "offline");
+    }
+
+    #[test]
+    fn test_env_path() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let day_dir = tmp_dir.path().join("advent-of-code-2022/day-01");
+        std::fs::create_dir_all(&day_dir).unwrap();
+        assert_eq!(env_path(&day_dir), day_dir.join(".env"));
+        std::fs::create_dir(tmp_dir.path().join(".git")).unwrap();
+        assert_eq!(env_path(&day_dir), tmp_dir.path().join(".env"));
+    }
+
+    #[test]
+    fn test_store() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let locations = Locations {
+            env: tmp_dir.path().join(".env"),
+            config: Some(tmp_dir.path().join("aoc/config.toml")),
+        };
+        assert_eq!(stored(Backend::Env, &locations), None);
+        assert_eq!(stored(Backend::Config, &locations), None);
+
+        std::fs::write(&locations.env, "RUST_LOG=debug\nsession=old\n").unwrap();
+        assert_eq!(stored(Backend::Env, &locations), Some("old".to_owned()));
+        store(Backend::Env, &locations, "abc123").unwrap();
+        assert_eq!(std::fs::read_to_string(&locations.env).unwrap(), "RUST_LOG=debug\nsession=abc123\n");
+
+        // All digits, which would be an integer without the quotes
+        store(Backend::Config, &locations, "123456").unwrap();
+        assert_eq!(stored(Backend::Config, &locations), Some("123456".to_owned()));
+    }
+
+    #[test]
+    fn test_log_in() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let locations = Locations { env: tmp_dir.path().join(".env"), config: None };
+        let fetch_username = |cookie: &str| Ok((cookie == "abc123").then(|| "someone".to_owned()));
+
+        let err = log_in(Backend::Env, &locations, "session=def456;", fetch_username).unwrap_err();
+        assert!(matches!(err, AocError::InvalidSession));
+        assert_eq!(stored(Backend::Env, &locations), None);
+        assert!(log_in(Backend::Env, &locations, "not a cookie", fetch_username).is_err());
+
+        assert_eq!(log_in(Backend::Env, &locations, " session=abc123;\n", fetch_username).unwrap(), "someone");
+        assert_eq!(stored(Backend::Env, &locations), Some("abc123".to_owned()));
+    }
+}
@@ -1,10 +1,30 @@
-use std::str::FromStr;
-use std::path::Path;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use clap::CommandFactory;
+use clap::FromArgMatches;
 use clap::Parser;
 use clap::Subcommand;
 use colored::*;
-use serde::{Deserialize, Serialize};
-use toml_edit::Document;
+
+use aoc::client::{check_session, fetch_leaderboard, render_checks, AocClient, AocHttp};
+use aoc::config::{
+    config_path, effective_value, migrate_file, require_config_path, set_config_value, Config, Migration,
+    NetworkConfig, PartMode, CONFIG_VERSION,
+};
+use aoc::env::{self, Environment};
+use aoc::error::AocError;
+use aoc::input::{download_missing, preview, run_mock, save_input};
+use aoc::runner::{build_part, candidate_answer, part_executable, run_part, test_day};
+use aoc::scaffold::{
+    create_day, create_part, detect_latest_part, new_year, part_files,
+    part_number, regen_parts, revert_part, undo_part, workspace_year, Existing,
+};
+use aoc::urls::{open_in_browser, open_page};
+use aoc::{
+    answers, archive, badge, bench, bundle, calendar, clean, crypt, deps, diff, editor, example, gist, graph, hook,
+    leaderboard, link, logging, login, metrics, migrate, perf, profile, pull, readme, report, rollover, run_all,
+    runtimes, stats, status, submissions, summarize, template, tui, unlock, urls, verify, watch,
+};
 
 /// Advent of Code command line tool to facilitate solving puzzles.
 #[derive(Parser)]
@@ -12,528 +32,1791 @@ use toml_edit::Document;
 struct Args {
     #[command(subcommand)]
     action: Action,
+    /// Disable colored output, as does setting NO_COLOR.
+    #[clap(long, global = true)]
+    no_color: bool,
+    /// Append a JSON log of the requests and file writes to this file, overriding `log.file` in
+    /// the config.
+    #[clap(long, global = true)]
+    log_file: Option<PathBuf>,
 }
 
-#[derive(Debug)]
-enum Answer {
-    Correct,
-    Incorrect,
-    AlreadySubmitted,
-    /// Too soon to submit
-    RateLimited,
-}
-
-impl FromStr for Answer {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.contains("That's the right answer!") {
-            Ok(Answer::Correct)
-        } else if s.contains("That's not the right answer") {
-            Ok(Answer::Incorrect)
-        } else if s.contains("You don't seem to be solving") {
-            Ok(Answer::AlreadySubmitted)
-        } else if s.contains("You gave an answer too recently") {
-            Ok(Answer::RateLimited)
-        } else {
-            Err(format!("Unknown response: {}", s))
-        }
-    }
-}
-
-
 #[derive(Subcommand)]
 enum Action {
     /// Automatically retrieve input file, based on the current working directory's day: day-XX/input.txt
-    Input,
+    Input {
+        /// Print the input to stdout instead of writing input.txt.
+        #[clap(long)]
+        stdout: bool,
+        /// Write the input to this file instead of input.txt, creating its directory.
+        #[clap(long, conflicts_with = "stdout")]
+        save_as: Option<PathBuf>,
+        /// Download the missing input.txt of every day of every year directory, from the directory
+        /// containing them.
+        #[clap(long, conflicts_with_all = ["stdout", "save_as"])]
+        year_all: bool,
+    },
+    /// Print the first and last lines of input.txt (or `AOC_INPUT_PATH`), and its size.
+    Preview {
+        /// Number of lines to print from the start and from the end.
+        #[clap(short, long, default_value_t = 5)]
+        lines: usize,
+    },
+    /// Encrypt input.txt to input.txt.enc, with a key derived from the session cookie.
+    Encrypt,
+    /// Decrypt input.txt.enc to input.txt.
+    Decrypt,
     /// Submit answer, based on the current working directory's day.
     Submit {
+        /// Part to submit, defaults to the latest part.
         #[clap(short, long)]
-        input: Option<String>,
+        part: Option<u8>,
+        /// Build in release mode, or with --no-compile use the release binary.
+        #[clap(long)]
+        release: bool,
+        /// Run the binary already built by cargo, instead of building it.
+        #[clap(long)]
+        no_compile: bool,
+        /// Seconds after which the part is stopped, without submitting. Recorded in perf.jsonl.
+        #[clap(long)]
+        timeout: Option<u64>,
+        /// Format of the result: the verdict, or JSON with the message, hint and time to wait.
+        #[clap(long, value_enum, default_value_t)]
+        format: run_all::OutputFormat,
+        /// Extra arguments for cargo, after `--`, e.g. `aoc submit -- --features fast`.
+        #[clap(last = true)]
+        cargo_args: Vec<String>,
+    },
+    /// Build and run a part, based on the current working directory's day.
+    Run {
+        /// Part to run, defaults to the latest part.
+        #[clap(short, long)]
+        part: Option<u8>,
+        /// Run every part of every day in the year, and print a summary.
+        #[clap(long, conflicts_with_all = ["part", "input"])]
+        all: bool,
+        /// Format of the summary of --all.
+        #[clap(long, value_enum, default_value_t, requires = "all")]
+        format: run_all::OutputFormat,
+        /// How many parts --all runs at the same time.
+        #[clap(long, default_value_t = 1, requires = "all")]
+        parallel: usize,
+        /// Build in debug mode, instead of release.
+        #[clap(long)]
+        debug: bool,
+        /// Input file to read instead of input.txt.
+        #[clap(short, long)]
+        input: Option<PathBuf>,
+        /// Arguments passed on to cargo, after `--`.
+        #[clap(last = true)]
+        cargo_args: Vec<String>,
+    },
+    /// Profile a part of the current day with cargo flamegraph (the default), perf or Instruments.
+    Profile {
+        /// Part to profile, defaults to the latest part.
+        #[clap(short, long)]
+        part: Option<u8>,
+        /// Profiler to use, perf in place of a missing flamegraph on Linux.
+        #[clap(short, long, value_enum, default_value_t)]
+        tool: profile::ProfileTool,
+    },
+    /// Benchmark a part of the current day, built in release mode.
+    Bench {
+        /// Part to benchmark, defaults to the latest part.
+        #[clap(short, long)]
+        part: Option<u8>,
+        /// Benchmark every part of the day.
+        #[clap(long, conflicts_with = "part")]
+        all_parts: bool,
+        /// How many timed runs.
+        #[clap(short, long, default_value_t = 10)]
+        runs: usize,
+        /// How many runs before the timed runs.
+        #[clap(long, default_value_t = 1)]
+        warmup: usize,
+        /// Format of the results.
+        #[clap(long, value_enum, default_value_t)]
+        format: run_all::OutputFormat,
+        /// Append the results to bench.toml in the day directory.
+        #[clap(long)]
+        save: bool,
+        /// Arguments passed on to cargo, after `--`.
+        #[clap(last = true)]
+        cargo_args: Vec<String>,
+    },
+    /// Run the tests of the current day, or of one part.
+    Test {
+        /// Only run the tests of this part's binary.
+        #[clap(short, long)]
+        part: Option<u8>,
+        /// Day to test, from anywhere in the year directory.
+        #[clap(short, long)]
+        day: Option<u8>,
+        /// Show cargo's output, even when all tests pass.
+        #[clap(short, long)]
+        verbose: bool,
+        /// Arguments passed on to cargo test, after `--`.
+        #[clap(last = true)]
+        cargo_args: Vec<String>,
     },
     /// Create a new day, based on previous days, up to 25.
-    Day,
+    Day {
+        /// Create this day, instead of the one after the latest day.
+        #[clap(short, long, value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: Option<u8>,
+        /// Don't register the day as a member of the year's Cargo workspace.
+        #[clap(long)]
+        no_workspace: bool,
+    },
+    /// Create the next day, and open its part 1 in `$EDITOR` (or else code, vim or nano).
+    Next,
     /// Copy part 1 to part 2, based on the current working directory's day.
-    Part,
+    Part {
+        /// Day to create part 2 for, from anywhere in the year directory.
+        #[clap(short, long)]
+        day: Option<u8>,
+        /// Binary to copy, e.g. part_2.
+        #[clap(long, default_value = "part_1", conflicts_with_all = ["fresh", "lib"])]
+        from: String,
+        /// Binary to create, e.g. part_2_fast.
+        #[clap(long, default_value = "part_2")]
+        to: String,
+        /// Start part 2 from the part template, instead of copying part 1.
+        #[clap(long)]
+        fresh: bool,
+        /// Copy part 1, even if `part.default` is something else.
+        #[clap(long, conflicts_with_all = ["fresh", "lib"])]
+        copy: bool,
+        /// Move parsing into a shared src/lib.rs, and start part 2 from a template using it.
+        #[clap(long, conflicts_with = "fresh")]
+        lib: bool,
+        /// Replace part 2 if it already exists.
+        #[clap(long)]
+        overwrite: bool,
+        /// Move an existing part 2 to part_2.rs.bak-<timestamp> before replacing it.
+        #[clap(long, conflicts_with = "overwrite")]
+        backup: bool,
+        /// Remove part 2 again, as long as it hasn't been changed since it was created.
+        #[clap(long, conflicts_with_all = ["fresh", "copy", "lib", "overwrite", "backup"])]
+        undo: bool,
+        /// Don't ask for confirmation before removing part 2.
+        #[clap(short, long, requires = "undo")]
+        yes: bool,
+        /// Remove part 2 even if it has been changed.
+        #[clap(long, requires = "undo")]
+        force: bool,
+    },
+    /// Publish a part's solution as a GitHub Gist, using the GITHUB_TOKEN environment variable.
+    Publish {
+        /// Create a public gist, instead of a secret one.
+        #[clap(long)]
+        public: bool,
+        /// Part to publish, defaults to the latest part.
+        #[clap(short, long)]
+        part: Option<u8>,
+    },
+    /// Render an HTML report of the year's completed days, from their answers.toml and notes.md.
+    Render {
+        /// File to write the report to.
+        #[clap(short, long, default_value = "index.html")]
+        output: PathBuf,
+        /// Year to report on, defaults to the current year directory.
+        #[clap(short, long)]
+        year: Option<u16>,
+    },
+    /// Run every day of the year, and print their answers, a line per day.
+    Summarize {
+        /// Year to summarize, defaults to the current year directory.
+        #[clap(short, long)]
+        year: Option<u16>,
+        /// Seconds after which a part is stopped.
+        #[clap(long, default_value_t = 10)]
+        timeout: u64,
+    },
+    /// Remove the build artifacts of the current year.
+    Clean {
+        /// Only list what would be removed, with sizes.
+        #[clap(long)]
+        dry_run: bool,
+        /// Only remove the artifacts of this day's own target directory.
+        #[clap(short, long, conflicts_with = "all_years")]
+        day: Option<u8>,
+        /// Clean every year directory next to the current one.
+        #[clap(long)]
+        all_years: bool,
+    },
+    /// Remove the target directories of the days of every year, printing their sizes and the total
+    /// freed.
+    CleanupTargets {
+        /// Only remove the days' target directories of this year.
+        #[clap(short, long)]
+        year: Option<u16>,
+        /// Only list what would be removed, with sizes.
+        #[clap(long)]
+        dry_run: bool,
+    },
+    /// List the accepted answer of each part, with the wrong attempts and hints before it.
+    Answers {
+        /// Year to list, defaults to the current year directory.
+        #[clap(short, long)]
+        year: Option<u16>,
+        /// Only list this day.
+        #[clap(short, long)]
+        day: Option<u8>,
+        /// List every wrong attempt too.
+        #[clap(long)]
+        wrong: bool,
+        /// Format of the list.
+        #[clap(long, value_enum, default_value_t)]
+        format: run_all::OutputFormat,
+    },
+    /// Show the lines of code of each day's parts.
+    Metrics {
+        /// Year to count, defaults to the current year directory.
+        #[clap(short, long)]
+        year: Option<u16>,
+        /// Sort by code lines, most first, instead of by day.
+        #[clap(long)]
+        sort: bool,
+    },
+    /// Show the recorded runtimes of solutions, from perf.jsonl in the year directory.
+    Perf {
+        /// Only show this day.
+        #[clap(short, long)]
+        day: Option<u8>,
+        /// Draw a bar chart of the average runtimes.
+        #[clap(long)]
+        chart: bool,
+    },
+    /// Rank the year's days by their latest recorded runtimes, slowest first, with the total.
+    Time {
+        /// Days taking longer than this, in milliseconds, are shown in red.
+        #[clap(long, default_value_t = 1000.0)]
+        threshold: f64,
+        /// Run every part again, in release mode, recording the new runtimes first.
+        #[clap(long)]
+        measure: bool,
+        #[clap(long, value_enum, default_value_t)]
+        format: run_all::OutputFormat,
+    },
+    /// Show the parts stopped by `aoc submit --timeout` and `aoc watch --timeout`, by day.
+    TimeoutHistory {
+        /// Year to show, defaults to the current year directory.
+        #[clap(short, long)]
+        year: Option<u16>,
+    },
+    /// Install or remove a git hook which checks formatting and lints.
+    Hook {
+        /// Install the hook.
+        #[clap(long, conflicts_with = "uninstall", required_unless_present = "uninstall")]
+        install: bool,
+        /// Remove the hook, if aoc installed it.
+        #[clap(long)]
+        uninstall: bool,
+        /// Which hook.
+        #[clap(long, default_value = "pre-commit")]
+        hook_type: String,
+    },
+    /// Print a shell completion script.
+    Completions {
+        shell: clap_complete::Shell,
+    },
+    /// Rerun the parts with an accepted answer in answers.toml, in release mode, and check they
+    /// still give it. Fails if any doesn't.
+    Verify {
+        /// Only verify this day.
+        #[clap(short, long)]
+        day: Option<u8>,
+        /// How many parts run at the same time.
+        #[clap(long, default_value_t = 1)]
+        parallel: usize,
+        /// Format of the report.
+        #[clap(long, value_enum, default_value_t)]
+        format: run_all::OutputFormat,
+    },
+    /// Browse the days of the year in a terminal UI, and run their latest part.
+    Tui,
+    /// Rebuild and rerun a part whenever the day's sources, input.txt or example.txt change.
+    Watch {
+        /// Part to run, defaults to the latest part.
+        #[clap(short, long)]
+        part: Option<u8>,
+        /// Run the tests instead.
+        #[clap(short, long)]
+        test: bool,
+        /// Run against example.txt instead of input.txt.
+        #[clap(short, long, conflicts_with = "test")]
+        example: bool,
+        /// Seconds after which the part is stopped. Recorded in perf.jsonl.
+        #[clap(long, conflicts_with = "test")]
+        timeout: Option<u64>,
+        /// Arguments passed on to cargo, after `--`.
+        #[clap(last = true)]
+        cargo_args: Vec<String>,
+    },
+    /// Show the time until the next puzzle unlocks, at midnight US Eastern time.
+    Countdown {
+        /// Puzzle to count down to, instead of the next one.
+        #[clap(short, long, value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: Option<u8>,
+        /// Year of the puzzle, defaults to the year of the next puzzle.
+        #[clap(short, long)]
+        year: Option<u16>,
+        /// Keep updating the time left every second, until the puzzle unlocks.
+        #[clap(short, long)]
+        watch: bool,
+    },
+    /// Run two binaries of the current day against the same input, and check their answers match.
+    Diff {
+        /// The binary to compare against, e.g. the old implementation.
+        #[clap(long, default_value = "part_1")]
+        from: String,
+        /// The binary to check, e.g. `part_2_fast`.
+        #[clap(long, default_value = "part_2")]
+        to: String,
+        /// Run against example.txt instead of input.txt.
+        #[clap(long)]
+        example: bool,
+        /// Build in debug mode, instead of release.
+        #[clap(long)]
+        debug: bool,
+        /// Extra arguments for cargo, after `--`.
+        #[clap(last = true)]
+        cargo_args: Vec<String>,
+    },
+    /// Show how a part's solution changed between two years, as a unified diff.
+    DiffYear {
+        /// Year of the old solution.
+        year_a: u16,
+        /// Year of the new solution.
+        year_b: u16,
+        /// Day to compare.
+        #[clap(short, long, value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: u8,
+        /// Part to compare.
+        #[clap(short, long, default_value_t = 1)]
+        part: u8,
+    },
+    /// Apply a unified diff, like one from `aoc diff-year`, to a part of the current day with
+    /// `patch`. Fails with the rejected hunks when it doesn't apply.
+    Apply {
+        /// The diff to apply.
+        patch: PathBuf,
+        /// Part to apply it to, defaults to the latest part.
+        #[clap(short, long)]
+        part: Option<u8>,
+    },
+    /// Set up the session cookie, checking it with the AoC server before storing it.
+    Login {
+        /// Where to store the cookie, asked for when not given.
+        #[clap(long, value_enum)]
+        backend: Option<login::Backend>,
+    },
+    /// Rename the year's day directories from an old format to a new one, e.g. `day_1` to `day-01`,
+    /// along with their packages and the workspace members.
+    Migrate {
+        /// Format the day directories are named with now, e.g. `day_`.
+        #[clap(long)]
+        from: String,
+        /// Format to rename them to, defaults to the config's `formats.day`.
+        #[clap(long)]
+        to: Option<String>,
+        /// Rename them, instead of only printing what would be renamed.
+        #[clap(long)]
+        apply: bool,
+    },
+    /// Bump the dependencies of the year's days to their latest versions on crates.io, printing a
+    /// table of what changed.
+    UpdateDeps {
+        /// Year to update, defaults to the current year directory.
+        #[clap(short, long)]
+        year: Option<u16>,
+        /// Only print what would change, without writing the Cargo.toml files.
+        #[clap(long)]
+        dry_run: bool,
+    },
+    /// Show the username the session cookie is logged in as, cached in the cache directory.
+    Whoami {
+        /// Ask the AoC server again instead of using the cached username.
+        #[clap(long)]
+        refresh: bool,
+    },
+    /// Update the progress table of the year in its README.md, between `<!-- aoc-table-start -->`
+    /// and `<!-- aoc-table-end -->`, adding them when they're missing.
+    Readme {
+        /// Print the updated README.md instead of writing it.
+        #[clap(long)]
+        stdout: bool,
+    },
+    /// Write a table of the answers and notes of each day to the year's README.md, between
+    /// `<!-- AOC-TABLE -->` comments.
+    GenerateReadme {
+        /// Year of the README, defaults to the current year directory.
+        #[clap(short, long)]
+        year: Option<u16>,
+        /// Minijinja template of the table, instead of the `readme.md` template.
+        #[clap(short, long)]
+        template: Option<PathBuf>,
+    },
+    /// Print a shields.io badge of the year's stars, like `AoC 2023: 38★`, or write it as an SVG.
+    Badge {
+        /// Year of the badge, defaults to the current year directory.
+        #[clap(short, long, conflicts_with = "all_years")]
+        year: Option<u16>,
+        /// A badge per year directory next to the current one, one per line.
+        #[clap(long)]
+        all_years: bool,
+        /// Count the stars on the AoC calendar instead of the local answers and submissions.
+        #[clap(long)]
+        remote: bool,
+        #[clap(long, value_enum, default_value_t)]
+        format: badge::Format,
+        /// Write a self-contained SVG badge to this file instead, from the `badge.svg` template.
+        #[clap(long, conflicts_with_all = ["all_years", "format"])]
+        svg: Option<PathBuf>,
+        /// Background of the star count, as hex or a name.
+        #[clap(long, default_value = badge::COLOR)]
+        color: String,
+        /// Background of the `AoC <year>` label, as hex or a name.
+        #[clap(long, default_value = badge::LABEL_COLOR)]
+        label_color: String,
+    },
+    /// Show which days use which modules and items, from the `use` declarations of their parts and
+    /// `src/lib.rs`, as a tree.
+    Graph {
+        /// Year to graph, defaults to the current year directory.
+        #[clap(short, long)]
+        year: Option<u16>,
+        /// Print the graph in Graphviz's DOT format instead.
+        #[clap(long)]
+        dot: bool,
+    },
+    /// Run a part against example.txt, downloading it from the puzzle description when it's missing,
+    /// and check its answer against the expected one.
+    Example {
+        /// Part to run, defaults to the latest part.
+        #[clap(short, long, conflicts_with = "all_parts")]
+        part: Option<u8>,
+        /// Run every part.
+        #[clap(long)]
+        all_parts: bool,
+        /// Example to run against instead of example.txt.
+        #[clap(long, conflicts_with = "example_index")]
+        file: Option<PathBuf>,
+        /// Which of the puzzle's code blocks is the example, 0 being the first, downloading it again.
+        #[clap(long)]
+        example_index: Option<usize>,
+        /// Expected answer, recorded in the day's Cargo.toml for example.txt. Defaults to the
+        /// recorded one, or else to the `EXAMPLE_ANSWER` constant of the part's tests.
+        #[clap(long, conflicts_with = "all_parts")]
+        expect: Option<String>,
+        /// Arguments passed on to cargo, after `--`.
+        #[clap(last = true)]
+        cargo_args: Vec<String>,
+    },
+    /// Run a part against a test input, instead of input.txt.
+    Mock {
+        /// The test input. `\n` is read as a newline.
+        #[clap(short, long, conflicts_with = "file", required_unless_present = "file")]
+        input: Option<String>,
+        /// File with the test input.
+        #[clap(short, long)]
+        file: Option<PathBuf>,
+        /// Part to run, defaults to the latest part.
+        #[clap(short, long)]
+        part: Option<u8>,
+    },
+    /// Find the right answer in a range by bisecting it, using the hints of incorrect answers.
+    ///
+    /// Each candidate is passed to the part as AOC_CANDIDATE, and its output is submitted.
+    Bisect {
+        /// Lowest candidate.
+        #[clap(allow_negative_numbers = true)]
+        low: i64,
+        /// Highest candidate.
+        #[clap(allow_negative_numbers = true)]
+        high: i64,
+        /// Part to submit, defaults to the latest part.
+        #[clap(short, long)]
+        part: Option<u8>,
+    },
+    /// Link a shared file to src/utils.rs of each of the days, copying it where symlinks aren't
+    /// allowed.
+    Link {
+        /// File to share, e.g. the year's utils.rs.
+        source: PathBuf,
+        /// Days to link it into, in the current year.
+        #[clap(required = true)]
+        target_days: Vec<u8>,
+    },
+    /// Restore a part of the current day from git's HEAD, discarding its changes.
+    Revert {
+        /// Part to restore.
+        part: u8,
+        /// Don't ask for confirmation.
+        #[clap(short, long)]
+        force: bool,
+    },
+    /// Re-scaffold the current day: remove every part in src/bin/ and create part_1.rs again from
+    /// the template. input.txt and Cargo.toml are kept.
+    Regen {
+        /// Don't ask for confirmation.
+        #[clap(short, long)]
+        force: bool,
+    },
+    /// Open the current day's puzzle in the browser.
+    Open {
+        /// Open the puzzle's input instead.
+        #[clap(short, long, conflicts_with = "leaderboard")]
+        input: bool,
+        /// Open the private leaderboard from the config instead.
+        #[clap(short, long)]
+        leaderboard: bool,
+        /// Day to open, instead of the current day.
+        #[clap(short, long)]
+        day: Option<u8>,
+        /// Year to open, instead of the current year.
+        #[clap(short, long)]
+        year: Option<u16>,
+        /// Only print the URL.
+        #[clap(long)]
+        print: bool,
+    },
+    /// Summarize the current day: its files, answers, submissions and whether it's unlocked.
+    Status {
+        /// Also check the puzzle's stars on adventofcode.com, caching its title.
+        #[clap(long)]
+        remote: bool,
+        /// Format of the summary.
+        #[clap(long, value_enum, default_value_t)]
+        format: run_all::OutputFormat,
+    },
+    /// Show the stars of each day of the current year.
+    Calendar {
+        /// Also check the stars on adventofcode.com, which take precedence over the local ones.
+        #[clap(long)]
+        remote: bool,
+        /// Format of the calendar.
+        #[clap(long, value_enum, default_value_t)]
+        format: run_all::OutputFormat,
+    },
+    /// Read or change the user's config file.
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Package the year's solutions as a .zip to share them, leaving out target/ and the inputs.
+    Zip {
+        /// Where to write the archive, defaults to <year format><year>.zip.
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+        /// Include the puzzle data of each day (input.txt, example.txt, answers...), which AoC asks
+        /// not to share.
+        #[clap(long)]
+        include_inputs: bool,
+    },
+    /// Export puzzle data (inputs, answers, submissions, runtimes) to a .tar.gz, or import it, to
+    /// move it between machines.
+    Archive {
+        #[command(subcommand)]
+        action: ArchiveAction,
+    },
+    /// List, show, create or check the templates used to scaffold days and parts.
+    Template {
+        #[command(subcommand)]
+        action: TemplateAction,
+    },
+    /// Show progress and timings of the current year, from recorded runtimes and submissions.
+    Stats {
+        /// Also show the times of the stars on the private leaderboard from the config.
+        #[clap(long)]
+        remote: bool,
+        /// Format of the stats.
+        #[clap(long, value_enum, default_value_t)]
+        format: run_all::OutputFormat,
+    },
+    /// Count the stars earned in a year, from its page on adventofcode.com.
+    Count {
+        /// Year to count the stars of, defaults to the current year directory.
+        #[clap(short, long)]
+        year: Option<u16>,
+    },
+    /// Show a private leaderboard, cached for 15 minutes.
+    Leaderboard {
+        /// Leaderboard id, instead of the one in the config.
+        #[clap(long)]
+        id: Option<u64>,
+        /// Year of the leaderboard, defaults to the current year directory.
+        #[clap(short, long)]
+        year: Option<u16>,
+    },
+    /// Check that the session cookie is valid.
+    Session,
+    /// Check the session cookie more thoroughly than `session`: logging in, downloading an input,
+    /// and submitting the known answer of a completed part of the year, if there is one.
+    DiagnoseSession {
+        /// Year to check, defaults to the current year directory.
+        #[clap(short, long)]
+        year: Option<u16>,
+    },
+    /// Pull the latest templates from upstream into your template directory.
+    Pull {
+        /// Only pull this template, e.g. part.rs.
+        template: Option<String>,
+        /// What to do with templates you have changed.
+        #[clap(long, value_enum, default_value_t)]
+        merge_strategy: pull::MergeStrategy,
+    },
+    /// Create a new year directory with a workspace and its first day.
+    New {
+        /// Year of the event, e.g. 2022.
+        year: u16,
+        /// Continue even if the year directory already exists.
+        #[clap(long)]
+        force: bool,
+    },
+    /// Create the next event's year directory with a workspace, beside the current year directory,
+    /// or in the current directory when it contains year directories.
+    NewYear {
+        /// Year of the event, defaults to the one after the latest year directory.
+        #[clap(short, long)]
+        year: Option<u16>,
+        /// Create a year more than a year in the future, or in a year directory that already exists.
+        #[clap(long)]
+        force: bool,
+        /// Copy this crate of the latest year directory, adding it to the workspace.
+        #[clap(long, num_args = 0..=1, default_missing_value = "utils", value_name = "CRATE")]
+        copy_utils: Option<String>,
+        /// Copy the latest year directory's rustfmt, clippy, toolchain, cargo and git config.
+        #[clap(long)]
+        copy_config: bool,
+    },
 }
 
-struct Environment {
-    day: Option<u8>,
-    year: u16,
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Set a value in the user's config file, e.g. `aoc config set formats.day puzzle-`.
+    Set { key: String, value: String },
+    /// Print a value of the config, including environment variables and defaults.
+    Get { key: String },
+    /// Upgrade the user's config file from the layout of an older aoc, printing the upgraded file.
+    Migrate {
+        /// Write the upgraded file, instead of only printing it.
+        #[clap(long)]
+        write: bool,
+    },
 }
 
-#[derive(Debug)]
-enum EnvironmentError {
-    InvalidYear,
+#[derive(Subcommand)]
+enum ArchiveAction {
+    /// Write the puzzle data of the year directories to a .tar.gz.
+    Export {
+        path: PathBuf,
+        /// Only export this year.
+        #[clap(long)]
+        year: Option<u16>,
+        /// Also export .env, with the session cookie.
+        #[clap(long)]
+        include_auth: bool,
+    },
+    /// Restore the puzzle data from a .tar.gz made by `aoc archive export`.
+    Import {
+        path: PathBuf,
+        /// What to do with files which already exist with other contents.
+        #[clap(long, value_enum, default_value_t)]
+        conflict: archive::Conflict,
+        /// Also import .env, with the session cookie.
+        #[clap(long)]
+        include_auth: bool,
+    },
 }
 
-#[derive(Debug)]
-enum Error {
-    EnvironmentError(EnvironmentError),
+#[derive(Subcommand)]
+enum TemplateAction {
+    /// List the templates, embedded or from the user's template directory, and the template sets
+    /// in its subdirectories.
+    List,
+    /// Print a template with its placeholders highlighted, unknown ones in red.
+    Show {
+        /// File name of the template, e.g. `part.rs`.
+        name: String,
+        /// Template set to show it from, a subdirectory of the user's template directory.
+        #[clap(long)]
+        template: Option<String>,
+    },
+    /// Copy the embedded templates into the user's template directory, to customize them.
+    Init {
+        /// Overwrite the templates already there.
+        #[clap(long)]
+        force: bool,
+    },
+    /// Check the user's templates only use known placeholders.
+    Check,
 }
 
-/// Constructors
-impl Environment {
-    fn new(day_format: &str, year_format: &str) -> Result<Self, Error> {
-        let day: Option<u8>;
-        let year: u16;
-
-        let current_dir: String = std::env::current_dir()
-            .unwrap()
-            .to_owned()
-            .file_name()
-            .unwrap()
-            .to_str()
-            .unwrap()
-            .to_owned();
-        let parent_dir: String = std::env::current_dir()
-            .unwrap()
-            .parent()
-            .unwrap()
-            .to_owned()
-            .file_name()
-            .unwrap()
-            .to_str()
-            .unwrap()
-            .to_owned();
+fn main() {
+    let matches = Args::command().get_matches();
+    let args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    if args.no_color || std::env::var_os("NO_COLOR").is_some() {
+        colored::control::set_override(false);
+    }
 
-        if parent_dir.contains(year_format) {
-            year = parent_dir.replace(year_format, "").parse().unwrap();
-            day = Some(current_dir.replace(day_format, "").parse().unwrap());
-        } else {
-            if !current_dir.contains(year_format) {
-                return Err(Error::EnvironmentError(EnvironmentError::InvalidYear));
-            } else {
-                year = current_dir.replace(year_format, "").parse().unwrap();
-                day = None
+    // Config
+    let result = Config::load().and_then(|config| {
+        let migrating = matches!(args.action, Action::Config { action: ConfigAction::Migrate { .. } });
+        if config.needs_migration() && !migrating {
+            eprintln!("{}", "The config file is from an older aoc, upgrade it with `aoc config migrate`".yellow());
+        }
+        if let Some(path) = args.log_file.as_ref().or(config.log.file.as_ref()) {
+            if let Err(err) = logging::init(path) {
+                eprintln!("{}", err.to_string().yellow());
             }
         }
-
-        Ok(Environment { day, year })
-    }
-}
-
-/// Validators
-impl Environment {
-    fn check_day(day_format: &str) -> Result<(), String> {
-        let current_dir: String = std::env::current_dir()
-            .unwrap()
-            .to_owned()
-            .file_name()
-            .unwrap()
-            .to_str()
-            .unwrap()
-            .to_owned();
-
-        if !current_dir.contains(day_format) {
-            Err(format!(
-                "Current directory not valid, <{}>. Should look like <{}>",
-                current_dir, day_format
-            ))
-        } else {
-            Ok(())
+        // The year, day and part are recorded once they're known
+        let span = tracing::info_span!(
+            "command",
+            name = matches.subcommand_name().unwrap_or_default(),
+            year = tracing::field::Empty,
+            day = tracing::field::Empty,
+            part = tracing::field::Empty,
+        );
+        record_environment(&span, &config);
+        let _entered = span.enter();
+        let client = LazyClient { network: &config.network, client: std::cell::OnceCell::new() };
+        run(args.action, &config, &client)
+    });
+    if let Err(err) = result {
+        match &err {
+            AocError::Exit(_) => {}
+            AocError::Aborted => println!("{}", err.to_string().yellow()),
+            _ => eprintln!("{}", format!("Error: {}", err).red()),
         }
+        std::process::exit(err.exit_code());
     }
-    fn check_year(year_format: &str) -> Result<(), String> {
-        let current_dir: String = std::env::current_dir()
-            .unwrap()
-            .to_owned()
-            .file_name()
-            .unwrap()
-            .to_str()
-            .unwrap()
-            .to_owned();
-        let parent_dir: String = std::env::current_dir()
-            .unwrap()
-            .parent()
-            .unwrap()
-            .to_owned()
-            .file_name()
-            .unwrap()
-            .to_str()
-            .unwrap()
-            .to_owned();
+}
 
-        if !parent_dir.contains(year_format) && !current_dir.contains(year_format) {
-            Err(format!(
-                "Parent directory not valid: {}. Should look like <{}>",
-                parent_dir, year_format
-            ))
-        } else {
-            Ok(())
+/// Record the year and day of the directory aoc runs in, when it's in one, in the command's `span`.
+fn record_environment(span: &tracing::Span, config: &Config) {
+    let day_format = config.formats.day.as_deref().unwrap_or("day-");
+    let year_format = config.formats.year.as_deref().unwrap_or("advent-of-code-");
+    let Ok(current_dir) = env::current_dir() else {
+        return;
+    };
+    let dir = env::resolve_day_dir(&current_dir, day_format, year_format)
+        .or_else(|_| env::resolve_year_dir(&current_dir, day_format, year_format));
+    let Ok(dir) = dir else {
+        return;
+    };
+    if let Ok(environment) = Environment::new(&dir, day_format, year_format) {
+        span.record("year", environment.year);
+        if let Some(day) = environment.day {
+            span.record("day", day);
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct Formats {
-    day: Option<String>,
-    year: Option<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct Config {
-    formats: Formats,
+/// The client of Advent of Code, only built (with its runtime) once a command needs it, so that
+/// offline commands don't start one.
+struct LazyClient<'a> {
+    network: &'a NetworkConfig,
+    client: std::cell::OnceCell<AocClient>,
 }
 
-fn main() {
-    // Config
-    let config: Config = toml::from_str(include_str!("../config.toml")).unwrap();
-
-    let day_format: String;
-    let year_format: String;
-    if config.formats.day.is_none() {
-        day_format = "day-".to_owned();
-    } else {
-        day_format = config.formats.day.unwrap();
-    }
-    if config.formats.year.is_none() {
-        year_format = "advent-of-code-".to_owned();
-    } else {
-        year_format = config.formats.year.unwrap();
-    }
-
-    // Environment
-    let environment: Environment;
-    match Environment::new(&day_format, &year_format) {
-        Ok(env) => environment = env,
-        Err(e) => {
-            eprintln!("{}", format!("Invalid environment: {:?}", e).red());
-            std::process::exit(1);
+impl LazyClient<'_> {
+    fn get(&self) -> Result<&AocClient, AocError> {
+        if let Some(client) = self.client.get() {
+            return Ok(client);
         }
+        let client = AocClient::from_config(self.network)?;
+        Ok(self.client.get_or_init(|| client))
     }
+}
+
+/// Run the command `action`, sending any requests to Advent of Code with `client`.
+fn run(action: Action, config: &Config, client: &LazyClient) -> Result<(), AocError> {
+    let day_format: String = config.formats.day.clone().unwrap_or_else(|| "day-".to_owned());
+    let year_format: String = config
+        .formats
+        .year
+        .clone()
+        .unwrap_or_else(|| "advent-of-code-".to_owned());
 
     // Commands
-    let args = Args::parse();
-    match args.action {
-        Action::Input => {
-            // Check CWD
-            helpers::check_day_and_year_dirs(&day_format, &year_format);
-            let input = get_input(environment.year, environment.day.unwrap());
-            let result = std::fs::write("input.txt", input);
-            if result.is_ok() {
-                println!("{}", "Success".green());
-            } else {
-                println!(
-                    "{}",
-                    format!("Failed to write input file: {:?}", result).red()
-                );
+    match action {
+        Action::Preview { lines } => {
+            let path = match std::env::var_os("AOC_INPUT_PATH") {
+                Some(path) => PathBuf::from(path),
+                None => {
+                    let current_dir = env::current_dir()?;
+                    env::resolve_day_dir(&current_dir, &day_format, &year_format)?
+                        .join("input.txt")
+                }
+            };
+            let input = std::fs::read_to_string(&path).map_err(|e| {
+                AocError::io(format!("Failed to read {}. Download it with `aoc input`", path.display()), e)
+            })?;
+            print!("{}", preview(&input, lines));
+        }
+        Action::Input { year_all: true, .. } => {
+            let current_dir = env::current_dir()?;
+            let formats = clean::Formats { year: &year_format, day: &day_format };
+            let downloads = download_missing(client.get()?, &current_dir, &formats)?;
+            println!(
+                "{} downloaded, {} skipped (already exist), {} failed",
+                downloads.downloaded, downloads.skipped, downloads.failed
+            );
+            if downloads.failed > 0 {
+                return Err(AocError::refused(format!("Failed to download {} of the inputs", downloads.failed)));
             }
         }
-        Action::Submit { input: _input } => {
+        Action::Input { stdout, save_as, .. } => {
             // Check CWD
-            helpers::check_day_and_year_dirs(&day_format, &year_format);
-
-            let day = environment.day.unwrap();
-            let year = environment.year;
-            let part_number: u8;
-
-            let part_2_path = Path::new("src/bin/part_2.rs");
-            if part_2_path.exists() {
-                part_number = 2;
+            let current_dir = env::current_dir()?;
+            env::check_day_and_year_dirs(&current_dir, &day_format, &year_format)?;
+            let environment = Environment::new(&current_dir, &day_format, &year_format)?;
+            let input = client.get()?
+                .fetch_input(environment.year, environment.require_day()?)?;
+            if stdout {
+                print!("{}", input);
+                return Ok(());
+            }
+            let path = save_as.unwrap_or_else(|| PathBuf::from("input.txt"));
+            let path = save_input(&current_dir, &path, &input)?;
+            println!("New {}: {}", path.display(), "Success".green());
+        }
+        Action::Encrypt | Action::Decrypt => {
+            let current_dir = env::current_dir()?;
+            let day_dir =
+                env::resolve_day_dir(&current_dir, &day_format, &year_format)?;
+            let key = crypt::key(&env::session_cookie()?);
+            let path = if matches!(action, Action::Encrypt) {
+                crypt::encrypt_input(&day_dir, &key)
             } else {
-                part_number = 1;
+                crypt::decrypt_input(&day_dir, &key)
+            }?;
+            println!("New {}: {}", path.display(), "Success".green());
+        }
+        Action::Submit { part, release, no_compile, timeout, format, cargo_args } => {
+            let current_dir = env::current_dir()?;
+            let day_dir =
+                env::resolve_day_dir(&current_dir, &day_format, &year_format)?;
+            let environment = Environment::new(&day_dir, &day_format, &year_format)?;
+            let puzzle = submissions::Puzzle {
+                year: environment.year,
+                day: environment.require_day()?,
+                part: part.unwrap_or_else(|| detect_latest_part(&day_dir)),
+            };
+            let executable = part_executable(&day_dir, puzzle.part, release, no_compile, &cargo_args)?;
+            let timeout = timeout.map(std::time::Duration::from_secs);
+            let result = submissions::submit(client.get()?, &day_dir, puzzle, &executable, timeout)?;
+            match format {
+                run_all::OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&result).unwrap()),
+                run_all::OutputFormat::Table => print!("{}", submissions::render(&result, &day_dir)),
             }
-
-            let mut command = std::process::Command::new("cargo");
-            command.arg("run").arg("--bin").arg(format!("part_{}", part_number));
-            let output = command.output().unwrap();
-            let answer = String::from_utf8(output.stdout).unwrap().trim().to_owned();
-            let result = submit_answer(year, day, part_number, &answer);
-            println!("{:?}", result);
-            match result {
-                Answer::Correct => println!("{}", "Correct".green()),
-                Answer::Incorrect => println!("{}", "Incorrect".red()),
-                Answer::AlreadySubmitted => println!("{}", "Already Submitted".yellow()),
-                Answer::RateLimited => println!("{}", "Rate Limited".red()),
+            if result.exit_code() != 0 {
+                return Err(AocError::Exit(result.exit_code()));
             }
-
-            /*
-            // Read from --input flag
-            if !input.is_none() {
-                todo!();
-                //let result = submit_answer(&input.unwrap(), year, day, part_number);
-                let result: Result<(), String> = Ok(());
-
-                if result.is_ok() {
-                    println!("{}", "Success".green());
-                } else {
-                    println!(
-                        "{}",
-                        format!("Failed to write input file: {:?}", result).red()
-                    );
+        }
+        Action::Run { part, all, format, parallel, debug, input, mut cargo_args } => {
+            let current_dir = env::current_dir()?;
+            if !debug {
+                cargo_args.insert(0, "--release".to_owned());
+            }
+            if all {
+                let year_dir = env::resolve_year_dir(&current_dir, &day_format, &year_format)?;
+                let results = run_all::run(&year_dir, &day_format, &cargo_args, parallel, |_, _| true)?;
+                match format {
+                    run_all::OutputFormat::Table => print!("{}", run_all::render_table(&results)),
+                    run_all::OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&results).unwrap()),
                 }
+                return Ok(());
+            }
+            let day_dir =
+                env::resolve_day_dir(&current_dir, &day_format, &year_format)?;
+            let part = part.unwrap_or_else(|| detect_latest_part(&day_dir));
+            let input_path = input.map(|input| current_dir.join(input));
 
-                return;
+            let output = run_part(&day_dir, part, &cargo_args, input_path.as_deref())?;
+            print!("{}", output.stdout);
+            // Only runs of release builds against the real input are comparable
+            if output.status.success() && input_path.is_none() && !debug {
+                let recorded =
+                    perf::record_day(&day_dir, &day_format, |day| perf::PerfEntry::now(day, part, output.elapsed));
+                if let Err(err) = recorded {
+                    eprintln!("{}", err.to_string().yellow());
+                }
             }
+            eprintln!(
+                "{}",
+                format!("Compiled in {:.2?}, ran in {:.2?}", output.build_elapsed, output.elapsed).yellow()
+            );
+            if !output.status.success() {
+                return Err(AocError::PartFailed { part, status: output.status });
+            }
+        }
+        Action::Profile { part, tool } => {
+            let current_dir = env::current_dir()?;
+            let day_dir =
+                env::resolve_day_dir(&current_dir, &day_format, &year_format)?;
+            let part = part.unwrap_or_else(|| detect_latest_part(&day_dir));
+            profile::profile(&day_dir, part, tool)?;
+        }
+        Action::Bench { part, all_parts, runs, warmup, format, save, cargo_args } => {
+            let current_dir = env::current_dir()?;
+            let day_dir =
+                env::resolve_day_dir(&current_dir, &day_format, &year_format)?;
+            let parts = if all_parts {
+                (1..=detect_latest_part(&day_dir)).collect()
+            } else {
+                vec![part.unwrap_or_else(|| detect_latest_part(&day_dir))]
+            };
+            let results = bench::run(&day_dir, &parts, runs, warmup, &cargo_args)?;
+            match format {
+                run_all::OutputFormat::Table => print!("{}", bench::render_table(&results)),
+                run_all::OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&results).unwrap()),
+            }
+            if save {
+                bench::record(&day_dir, &results)?;
+            }
+        }
+        Action::Test { part, day, verbose, cargo_args } => {
+            let current_dir = env::current_dir()?;
+            let day_dir = match day {
+                Some(day) => env::find_day_dir(&current_dir, day, &day_format, &year_format),
+                None => env::resolve_day_dir(&current_dir, &day_format, &year_format),
+            }?;
+            let output = test_day(&day_dir, part, &cargo_args)?;
 
-            // Read from stdin
-            let mut input = String::new();
-            for line in io::stdin().lock().lines() {
-                input.push_str(&line.unwrap());
+            let success = output.status.success();
+            if verbose || !success {
+                print!("{}", output.stdout);
+                eprint!("{}", output.stderr);
+            }
+            let summary = format!("{} passed, {} failed", output.passed, output.failed);
+            if success {
+                println!("{}", summary.green());
+            } else {
+                println!("{}", summary.red());
+                return Err(AocError::Exit(output.status.code().unwrap_or(1)));
             }
-            dbg!(input);
-            */
         }
-        Action::Day => {
-            // Check CWD
-            helpers::check_year_dir(&year_format);
-            // New Day Directory Name
-            let mut highest_day: u8 = 0;
-            for entry in std::fs::read_dir(".").unwrap() {
-                let entry = entry.unwrap();
-                let path = entry.path();
-                let path_str = path.file_name().unwrap().to_str().unwrap();
-                if path_str.contains(&day_format) {
-                    let day: u8 = path_str.replace(&day_format, "").parse().unwrap_or(0);
-                    if day > highest_day {
-                        highest_day = day;
-                    }
+        Action::Day { day, no_workspace } => {
+            // Check CWD: either the year directory, or one of its days
+            let current_dir = env::current_dir()?;
+            let year_dir =
+                env::resolve_year_dir(&current_dir, &day_format, &year_format)?;
+            let year = workspace_year(&year_dir, &year_format)?;
+            let workspace = !no_workspace && config.day.workspace.unwrap_or(true);
+            let lib = config.templates.create_lib.unwrap_or(true);
+            create_day(&year_dir, year, day, &day_format, workspace, lib, &config.workspace)?;
+        }
+        Action::Next => {
+            let current_dir = env::current_dir()?;
+            let year_dir =
+                env::resolve_year_dir(&current_dir, &day_format, &year_format)?;
+            let year = workspace_year(&year_dir, &year_format)?;
+            let workspace = config.day.workspace.unwrap_or(true);
+            let lib = config.templates.create_lib.unwrap_or(true);
+            let day_dir = create_day(&year_dir, year, None, &day_format, workspace, lib, &config.workspace)?;
+            let editor = editor::find_editor(
+                std::env::var_os("EDITOR").as_deref(),
+                std::env::var_os("PATH").as_deref(),
+            );
+            let Some(editor) = editor else {
+                println!("{}", "No editor found, set $EDITOR to open part 1".yellow());
+                return Ok(());
+            };
+            println!("Opening in {}…", editor);
+            editor::open(&editor, Path::new("src/bin/part_1.rs"), &day_dir)?;
+        }
+        Action::New { year, force } => {
+            let current_dir = env::current_dir()?;
+            let lib = config.templates.create_lib.unwrap_or(true);
+            new_year(&current_dir, &year_format, &day_format, year, force, lib, &config.workspace)?;
+            println!("{}", "Happy puzzling!".green());
+        }
+        Action::NewYear { year, force, copy_utils, copy_config } => {
+            let current_dir = env::current_dir()?;
+            let formats = clean::Formats { year: &year_format, day: &day_format };
+            let plan = rollover::plan(&current_dir, &formats, year)?;
+            if !force {
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |since| since.as_secs());
+                rollover::check_announced(plan.year, now)?;
+            }
+            let carry = rollover::Carry { utils: copy_utils, config: copy_config };
+            let year_dir = rollover::create(&plan, &year_format, &carry, force, config.workspace.author.as_deref())?;
+            println!("{}", format!("Hint: cd {} && aoc day", year_dir.display()).yellow());
+        }
+        Action::Publish { public, part } => {
+            let current_dir = env::current_dir()?;
+            env::check_day_and_year_dirs(&current_dir, &day_format, &year_format)?;
+            let environment = Environment::new(&current_dir, &day_format, &year_format)?;
+            let (year, day) = (environment.year, environment.require_day()?);
+            let part = part.unwrap_or_else(|| detect_latest_part(&current_dir));
+            let puzzle = submissions::Puzzle { year, day, part };
+            let gist = gist::publish_part(&gist::token()?, &current_dir, puzzle, public)?;
+            println!("{}", gist.html_url.green());
+        }
+        Action::Render { output, year } => {
+            let current_dir = env::current_dir()?;
+            let (year, year_dir) = env::find_year_dir(&current_dir, year, &day_format, &year_format)?;
+            report::write(&year_dir, year, &day_format, &output)?;
+            println!("Report ({}): {}", output.display(), "Success".green());
+        }
+        Action::Summarize { year, timeout } => {
+            let current_dir = env::current_dir()?;
+            let (_, year_dir) = env::find_year_dir(&current_dir, year, &day_format, &year_format)?;
+            summarize::year(&year_dir, &day_format, std::time::Duration::from_secs(timeout))?;
+        }
+        Action::Clean { dry_run, day, all_years } => {
+            let current_dir = env::current_dir()?;
+            let formats = clean::Formats { year: &year_format, day: &day_format };
+            let year_dir = env::resolve_year_dir(&current_dir, &day_format, &year_format)?;
+            let year_dirs = if all_years {
+                clean::sibling_years(&year_dir, &formats)?
+            } else {
+                vec![year_dir]
+            };
+            let mut targets = Vec::new();
+            for year_dir in year_dirs {
+                targets.extend(clean::targets(&year_dir, day, &formats)?);
+            }
+            clean::clean(&targets, &formats, dry_run)?;
+        }
+        Action::Answers { year, day, wrong, format } => {
+            let current_dir = env::current_dir()?;
+            let (_, year_dir) = env::find_year_dir(&current_dir, year, &day_format, &year_format)?;
+            let answers = answers::collect(&year_dir, &day_format, day, wrong)?;
+            match format {
+                run_all::OutputFormat::Table => print!("{}", answers::render(&answers)),
+                run_all::OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&answers).unwrap()),
+            }
+        }
+        Action::Metrics { year, sort } => {
+            let current_dir = env::current_dir()?;
+            let (_, year_dir) = env::find_year_dir(&current_dir, year, &day_format, &year_format)?;
+            let mut metrics = metrics::collect(&year_dir, &day_format)?;
+            if sort {
+                metrics.sort_by_key(|metrics| std::cmp::Reverse(metrics.code));
+            }
+            print!("{}", metrics::render_table(&metrics));
+        }
+        Action::Perf { day, chart } => {
+            let current_dir = env::current_dir()?;
+            let year_dir =
+                env::resolve_year_dir(&current_dir, &day_format, &year_format)?;
+            let entries = perf::read(&year_dir)?;
+            let summaries = perf::summarize(&entries, day);
+            if summaries.is_empty() {
+                println!("{}", "No runtimes recorded yet.".yellow());
+            } else if chart {
+                print!("{}", perf::render_chart(&summaries));
+            } else {
+                print!("{}", perf::render_table(&summaries));
+            }
+        }
+        Action::TimeoutHistory { year } => {
+            let current_dir = env::current_dir()?;
+            let (_, year_dir) = env::find_year_dir(&current_dir, year, &day_format, &year_format)?;
+            let entries = perf::read(&year_dir)?;
+            let timeouts = perf::timeouts(&entries);
+            if timeouts.is_empty() {
+                println!("{}", "No timeouts recorded.".yellow());
+            } else {
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |since| since.as_secs());
+                print!("{}", perf::render_timeouts(&timeouts, now));
+            }
+        }
+        Action::Time { threshold, measure, format } => {
+            let current_dir = env::current_dir()?;
+            let year_dir = env::resolve_year_dir(&current_dir, &day_format, &year_format)?;
+            if measure {
+                runtimes::measure(&year_dir, &day_format)?;
+            }
+            let recorded = runtimes::collect(&year_dir, &day_format)?;
+            let days = runtimes::rank(&runtimes::latest(&recorded), threshold);
+            match format {
+                run_all::OutputFormat::Table if days.is_empty() => {
+                    println!("{}", "No runtimes recorded yet, use --measure.".yellow());
+                }
+                run_all::OutputFormat::Table => print!("{}", runtimes::render_table(&days)),
+                run_all::OutputFormat::Json => {
+                    let json = serde_json::json!({ "days": days, "total_ms": runtimes::total_ms(&days) });
+                    println!("{}", serde_json::to_string_pretty(&json).unwrap());
                 }
             }
-            let new_day: u8 = highest_day + 1;
-            let new_day_str: String = format!("{}{:02}", day_format, new_day);
-            // Create new day directory
-            let result = std::fs::create_dir(&new_day_str);
-            if result.is_ok() {
-                println!("New Day Directory: {}", "Success".green());
+        }
+        Action::Hook { install, uninstall: _, hook_type } => {
+            let current_dir = env::current_dir()?;
+            let hooks_dir = hook::hooks_dir(&current_dir)?;
+            let (verb, path) = if install {
+                hook::install(&hooks_dir, &hook_type).map(|path| ("New", path))
             } else {
-                println!(
-                    "{}",
-                    format!("Failed to create new day directory: {:?}", result).red()
-                );
+                hook::uninstall(&hooks_dir, &hook_type).map(|path| ("Remove", path))
+            }?;
+            println!("{} {}: {}", verb, path.display(), "Success".green());
+        }
+        Action::Completions { shell } => {
+            clap_complete::generate(shell, &mut completion_command(), "aoc", &mut std::io::stdout());
+            eprintln!("{}", completions_hint(shell).yellow());
+        }
+        Action::Verify { day, parallel, format } => {
+            let current_dir = env::current_dir()?;
+            let year_dir =
+                env::resolve_year_dir(&current_dir, &day_format, &year_format)?;
+            let expected = verify::expected(&year_dir, &day_format, day)?;
+            let cargo_args = vec!["--release".to_owned()];
+            let results = run_all::run(&year_dir, &day_format, &cargo_args, parallel, |day, part| {
+                expected.get(&(day, part)).is_some_and(Option::is_some)
+            })?;
+            let verifications = verify::compare(&expected, &results);
+            match format {
+                run_all::OutputFormat::Table => print!("{}", verify::render(&verifications)),
+                run_all::OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&verifications).unwrap()),
             }
-
-            // update workspace Cargo.toml
-            let mut cargo_toml = if let Ok(_cargo_toml) = std::fs::read_to_string("Cargo.toml") {
-                std::fs::read_to_string("Cargo.toml").unwrap().parse::<Document>().unwrap()
+            if !verify::passed(&verifications) {
+                return Err(AocError::Exit(1));
+            }
+        }
+        Action::Tui => {
+            let current_dir = env::current_dir()?;
+            let (year, year_dir) = env::find_year_dir(&current_dir, None, &day_format, &year_format)?;
+            let app = tui::App::new(&year_dir, year, &day_format)?;
+            tui::run(app)?;
+        }
+        Action::Watch { part, test, example, timeout, cargo_args } => {
+            let current_dir = env::current_dir()?;
+            let day_dir =
+                env::resolve_day_dir(&current_dir, &day_format, &year_format)?;
+            let mode = if test {
+                watch::Mode::Test { part }
             } else {
-                println!("{}", "Creating new Cargo.toml".yellow());
-                // Create new Cargo.toml with [workspace] and members
-                let mut cargo_toml = Document::new();
-                cargo_toml["workspace"] = "{}".parse().unwrap();
-                cargo_toml["workspace"]["members"] = "[]".parse().unwrap();
-                cargo_toml
+                watch::Mode::Run {
+                    part: part.unwrap_or_else(|| detect_latest_part(&day_dir)),
+                    input_path: example.then(|| day_dir.join("example.txt")),
+                    timeout: timeout.map(std::time::Duration::from_secs),
+                }
             };
-            let workspace_members = cargo_toml["workspace"]["members"].as_array_mut().unwrap();
-            workspace_members.push(new_day_str.clone());
-            let result = std::fs::write("Cargo.toml", cargo_toml.to_string());
-            if result.is_ok() {
-                println!("Update Cargo.toml: {}", "Success".green());
+
+            // Ctrl-C stops the watch, killing the part or tests if they're running
+            let interrupted = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+            let handler_interrupted = interrupted.clone();
+            ctrlc::set_handler(move || handler_interrupted.store(true, std::sync::atomic::Ordering::SeqCst))
+                .map_err(|e| AocError::Environment(format!("Failed to handle Ctrl-C: {}", e)))?;
+            eprintln!("{}", format!("Watching {}, Ctrl-C to stop", day_dir.display()).yellow());
+            watch::watch(&day_dir, &interrupted, || {
+                watch::rerun(&day_dir, &day_format, &mode, &cargo_args, &interrupted)
+            })?;
+        }
+        Action::Countdown { day, year, watch } => {
+            let now = || {
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |since| since.as_secs())
+            };
+            let (year, day) = unlock::target(year, day, now());
+            if watch {
+                unlock::countdown(year, day, now);
             } else {
-                println!(
-                    "{}",
-                    format!("Failed to update Cargo.toml: {:?}", result).red()
-                );
+                println!("{}", unlock::describe(year, day, now()));
+            }
+        }
+        Action::Diff { from, to, example, debug, mut cargo_args } => {
+            let current_dir = env::current_dir()?;
+            let day_dir =
+                env::resolve_day_dir(&current_dir, &day_format, &year_format)?;
+            if !debug {
+                cargo_args.insert(0, "--release".to_owned());
+            }
+            let input_path = day_dir.join(if example { "example.txt" } else { "input.txt" });
+            let (matched, report) = diff::run_bins(&day_dir, &from, &to, &input_path, &cargo_args)?;
+            print!("{}", report);
+            if !matched {
+                return Err(AocError::Exit(1));
+            }
+        }
+        Action::DiffYear { year_a, year_b, day, part } => {
+            let current_dir = env::current_dir()?;
+            let formats = clean::Formats { year: &year_format, day: &day_format };
+            match diff::across_years(&current_dir, &formats, year_a, year_b, day, part)? {
+                Some(unified) => print!("{}", diff::colorize(&unified)),
+                None => println!("{}", "Files are identical".green()),
             }
+        }
+        Action::Apply { patch, part } => {
+            let current_dir = env::current_dir()?;
+            let day_dir =
+                env::resolve_day_dir(&current_dir, &day_format, &year_format)?;
+            let part = part.unwrap_or_else(|| detect_latest_part(&day_dir));
+            match diff::apply_to_part(&current_dir.join(&patch), &day_dir, part)? {
+                (target, diff::Applied::Applied) => println!("Update {}: {}", target.display(), "Success".green()),
+                (target, diff::Applied::Rejected(rejected)) => {
+                    print!("{}", diff::colorize(&rejected));
+                    eprintln!("{}", format!("Rejected hunks, left in {}.rej", target.display()).red());
+                    return Err(AocError::Exit(1));
+                }
+            }
+        }
+        Action::Login { backend } => {
+            use clap::ValueEnum;
 
-            // copy template from ./templates/Cargo.toml
-            let template_cargo_toml = include_str!("../templates/Cargo.toml");
-            let template_cargo_toml = template_cargo_toml.replace(r#"name = """#, &format!(r#"name = "{}""#, &new_day_str));
-            let result = std::fs::write(format!("{}/Cargo.toml", new_day_str), template_cargo_toml);
-            if result.is_ok() {
-                println!("New Cargo.toml: {}", "Success".green());
+            let locations = login::Locations::of(&env::current_dir()?);
+            let backend = match backend {
+                Some(backend) => backend,
+                None => {
+                    let backends = login::Backend::value_variants();
+                    let items: Vec<String> =
+                        backends.iter().map(|backend| login::describe(*backend, &locations)).collect();
+                    let index = dialoguer::Select::new()
+                        .with_prompt("Where should the session cookie be stored?")
+                        .items(&items)
+                        .default(0)
+                        .interact()
+                        .map_err(|_| AocError::Aborted)?;
+                    backends[index]
+                }
+            };
+            let location = login::describe(backend, &locations);
+            if login::stored(backend, &locations).is_some() {
+                let replace = dialoguer::Confirm::new()
+                    .with_prompt(format!("A session cookie is already stored in {}, replace it?", location))
+                    .default(false)
+                    .interact()
+                    .unwrap_or(false);
+                if !replace {
+                    return Err(AocError::Aborted);
+                }
+            }
+            println!("{}", login::INSTRUCTIONS);
+            let cookie = dialoguer::Password::new()
+                .with_prompt("Session cookie")
+                .interact()
+                .map_err(|_| AocError::Aborted)?;
+            let client = client.get()?;
+            let username = login::log_in(backend, &locations, &cookie, |cookie| {
+                client.clone().with_session(cookie).fetch_username()
+            })?;
+            println!("Logged in as {}, saved to {}: {}", username, location, "Success".green());
+        }
+        Action::Migrate { from, to, apply } => {
+            let current_dir = env::current_dir()?;
+            let (_, year_dir) = env::find_year_dir(&current_dir, None, &from, &year_format)?;
+            migrate::migrate(&year_dir, &from, to.as_deref().unwrap_or(&day_format), apply)?;
+        }
+        Action::UpdateDeps { year, dry_run } => {
+            let current_dir = env::current_dir()?;
+            let (_, year_dir) = env::find_year_dir(&current_dir, year, &day_format, &year_format)?;
+            deps::update(&year_dir, &day_format, dry_run, deps::latest_versions)?;
+        }
+        Action::Whoami { refresh } => {
+            let cache_path = login::cache_path()?;
+            if refresh {
+                let _ = std::fs::remove_file(&cache_path);
+            }
+            let client = client.get()?;
+            let username = match env::session_cookie() {
+                Ok(cookie) => login::whoami(&cache_path, &cookie, || client.fetch_username())?,
+                Err(_) => None,
+            };
+            match username {
+                Some(username) => println!("{}", format!("Logged in as: {}", username).green()),
+                None => {
+                    println!("{}", "Not logged in".red());
+                    return Err(AocError::Exit(1));
+                }
+            }
+        }
+        Action::Readme { stdout } => {
+            let current_dir = env::current_dir()?;
+            let (year, year_dir) = env::find_year_dir(&current_dir, None, &day_format, &year_format)?;
+            let readme = readme::with_table(&year_dir, year, &day_format)?;
+            if stdout {
+                print!("{}", readme);
             } else {
-                println!(
-                    "{}",
-                    format!("Failed to create new Cargo.toml: {:?}", result).red()
-                );
+                readme::write(&year_dir, &readme)?;
             }
-            // mkdir for src
-            let result = std::fs::create_dir(format!("{}/src", new_day_str));
-            if result.is_ok() {
-                println!("New src Directory: {}", "Success".green());
+        }
+        Action::GenerateReadme { year, template } => {
+            let current_dir = env::current_dir()?;
+            let (year, year_dir) = env::find_year_dir(&current_dir, year, &day_format, &year_format)?;
+            let template = match template {
+                Some(path) => std::fs::read_to_string(&path)
+                    .map_err(|e| AocError::io(format!("Failed to read {}", path.display()), e))?,
+                None => template::load("readme.md")?,
+            };
+            readme::write(&year_dir, &readme::with_answers(&year_dir, year, &day_format, &template)?)?;
+        }
+        Action::CleanupTargets { year, dry_run } => {
+            let current_dir = env::current_dir()?;
+            let formats = clean::Formats { year: &year_format, day: &day_format };
+            let mut targets = Vec::new();
+            for year_dir in clean::cleanup_years(&current_dir, year, &formats)? {
+                targets.extend(clean::day_targets(&year_dir, &formats)?);
+            }
+            clean::clean(&targets, &formats, dry_run)?;
+        }
+        Action::Badge { year, all_years, remote, format, svg, color, label_color } => {
+            let current_dir = env::current_dir()?;
+            let year_dirs = if all_years {
+                let year_dir = env::resolve_year_dir(&current_dir, &day_format, &year_format)?;
+                let formats = clean::Formats { year: &year_format, day: &day_format };
+                clean::sibling_years(&year_dir, &formats)?
             } else {
-                println!(
-                    "{}",
-                    format!("Failed to create new src directory: {:?}", result).red()
-                );
+                let (_, year_dir) = env::find_year_dir(&current_dir, year, &day_format, &year_format)?;
+                vec![year_dir]
+            };
+            for year_dir in year_dirs {
+                let year = env::parse_year(&year_dir, &year_format)?;
+                let mut days = calendar::collect(&year_dir, &day_format)?;
+                if remote {
+                    let html = client.get()?
+                        .fetch_page(urls::Page::Calendar { year })?;
+                    calendar::reconcile(&mut days, &calendar::parse_stars(&html));
+                }
+                let badge = badge::Badge::new(year, calendar::total_stars(&days), &color, &label_color);
+                match (&svg, format) {
+                    (Some(path), _) => {
+                        badge.write_svg(path)?;
+                        println!("New {}: {}", path.display(), "Success".green());
+                    }
+                    (None, badge::Format::Markdown) => println!("{}", badge.markdown()),
+                    (None, badge::Format::Url) => println!("{}", badge.url()),
+                }
             }
-            // mkdir for src/bin
-            let result = std::fs::create_dir(format!("{}/src/bin", new_day_str));
-            if result.is_ok() {
-                println!("New src/bin Directory: {}", "Success".green());
+        }
+        Action::Graph { year, dot } => {
+            let current_dir = env::current_dir()?;
+            let (year, year_dir) = env::find_year_dir(&current_dir, year, &day_format, &year_format)?;
+            let graph = graph::collect(&year_dir, &day_format)?;
+            if dot {
+                print!("{}", graph::render_dot(&graph));
             } else {
-                println!(
-                    "{}",
-                    format!("Failed to create new src/bin directory: {:?}", result).red()
-                );
+                print!("{}", graph::render_tree(year, &graph));
             }
-            // copy template part 1 from ./templates/part1.rs to src/bin/part_1.rs
-            let template_part_1 = include_str!("../templates/part.rs");
-            let result = std::fs::write(format!("{}/src/bin/part_1.rs", new_day_str), template_part_1);
-            if result.is_ok() {
-                println!("New src/bin/part_1.rs: {}", "Success".green());
+        }
+        Action::Example { part, all_parts, file, example_index, expect, cargo_args } => {
+            let current_dir = env::current_dir()?;
+            let day_dir =
+                env::resolve_day_dir(&current_dir, &day_format, &year_format)?;
+            let formats = clean::Formats { year: &year_format, day: &day_format };
+            let file = file.map(|file| current_dir.join(file));
+            let example_path = example::locate(&day_dir, &formats, file.as_deref(), example_index, |year, day| {
+                client.get()?.fetch_puzzle(year, day)
+            })?;
+            let parts: Vec<u8> = if all_parts {
+                part_files(&day_dir).into_iter().filter_map(|(part, _)| part.parse().ok()).collect()
             } else {
-                println!(
-                    "{}",
-                    format!("Failed to create new src/bin/part_1.rs: {:?}", result).red()
-                );
+                vec![part.unwrap_or_else(|| detect_latest_part(&day_dir))]
+            };
+            // Recorded answers are for example.txt, other examples are only checked against --expect
+            let custom = file.is_some();
+            if !example::check(&day_dir, &parts, &example_path, custom, expect.as_deref(), &cargo_args)? {
+                return Err(AocError::Exit(1));
+            }
+        }
+        Action::Mock { input, file, part } => {
+            let current_dir = env::current_dir()?;
+            let day_dir =
+                env::resolve_day_dir(&current_dir, &day_format, &year_format)?;
+            let part = part.unwrap_or_else(|| detect_latest_part(&day_dir));
+            let output = match (input, file) {
+                (Some(input), _) => run_mock(&day_dir, part, &input)?,
+                // The part runs in the day directory, so the input needs an absolute path
+                (None, Some(file)) => run_part(&day_dir, part, &[], Some(&current_dir.join(file)))?,
+                (None, None) => unreachable!("clap requires --input or --file"),
+            };
+            print!("{}", output.stdout);
+            if !output.status.success() {
+                return Err(AocError::Exit(output.status.code().unwrap_or(1)));
             }
         }
-        Action::Part => {
+        Action::Pull { template, merge_strategy } => {
+            let dir = template::require_dir()?;
+            let templates = pull::fetch(template.as_deref())?;
+            let outcomes = pull::apply(&dir, &templates, merge_strategy, |name| {
+                dialoguer::Confirm::new()
+                    .with_prompt(format!("{} has changed, overwrite it with upstream?", name))
+                    .default(false)
+                    .interact()
+                    .unwrap_or(false)
+            })?;
+            for (name, outcome) in outcomes {
+                println!("{}", outcome.render(&dir.join(name)));
+            }
+        }
+        Action::Bisect { low, high, part } => {
+            let current_dir = env::current_dir()?;
+            env::check_day_and_year_dirs(&current_dir, &day_format, &year_format)?;
+            let environment = Environment::new(&current_dir, &day_format, &year_format)?;
+            let day_dir = current_dir;
+            let puzzle = submissions::Puzzle {
+                year: environment.year,
+                day: environment.require_day()?,
+                part: part.unwrap_or_else(|| detect_latest_part(&day_dir)),
+            };
+            let executable = build_part(&day_dir, puzzle.part, &[])?;
+            let candidate = submissions::bisect(
+                client.get()?,
+                &day_dir,
+                puzzle,
+                low..=high,
+                |candidate| candidate_answer(&executable, &day_dir, puzzle.part, candidate),
+                std::thread::sleep,
+            )?;
+            println!("{}", format!("Correct: {}", candidate).green());
+        }
+        Action::Link { source, target_days } => {
+            let current_dir = env::current_dir()?;
+            for day in target_days {
+                let day_dir = env::find_day_dir(&current_dir, day, &day_format, &year_format)?;
+                match link::link_utils(&source, &day_dir)? {
+                    (path, link::Linked::Symlink) => {
+                        println!("New {} -> {}: {}", path.display(), source.display(), "Success".green())
+                    }
+                    (path, link::Linked::Copied) => eprintln!(
+                        "{}",
+                        format!(
+                            "Copied {} to {}, as symlinks need developer mode or an elevated prompt",
+                            source.display(),
+                            path.display()
+                        )
+                        .yellow()
+                    ),
+                }
+            }
+        }
+        Action::Revert { part, force } => {
+            let current_dir = env::current_dir()?;
+            let day_dir =
+                env::resolve_day_dir(&current_dir, &day_format, &year_format)?;
+            let question = format!("This will discard all unsaved changes to part_{}.rs. Continue? [y/N]", part);
+            if !force && env::prompt(&question).to_lowercase() != "y" {
+                return Err(AocError::Aborted);
+            }
+            let path = revert_part(&day_dir, part)?;
+            println!("Restore {}: {}", path.display(), "Success".green());
+            let source = std::fs::read_to_string(&path).unwrap_or_default();
+            for line in source.lines().take(5) {
+                println!("    {}", line);
+            }
+        }
+        Action::Regen { force } => {
+            let current_dir = env::current_dir()?;
+            let day_dir =
+                env::resolve_day_dir(&current_dir, &day_format, &year_format)?;
+            let question = "This will overwrite all part files in src/bin/. Continue? [y/N]";
+            if !force && env::prompt(question).to_lowercase() != "y" {
+                return Err(AocError::Aborted);
+            }
+            let formats = clean::Formats { year: &year_format, day: &day_format };
+            let edition = config.workspace.edition.unwrap_or_default().as_str();
+            let variables = template::Variables::of_day(&day_dir, &formats, 1, edition)?;
+            let (removed, part_1_path) = regen_parts(&day_dir, &variables)?;
+            for path in removed {
+                println!("Remove {}: {}", path.display(), "Success".green());
+            }
+            println!("New {}: {}", part_1_path.display(), "Success".green());
+        }
+        Action::Open { input, leaderboard, day, year, print } => {
+            let environment = Environment::current(&day_format, &year_format).ok();
+            let year = year.or(environment.as_ref().map(|environment| environment.year));
+            let day = day.or(environment.and_then(|environment| environment.day));
+            let page = open_page(year, day, input, leaderboard, config.leaderboard.id)?;
+            let url = page.url(urls::BASE_URL);
+            if print {
+                println!("{}", url);
+            } else if let Err(err) = open_in_browser(&url) {
+                eprintln!("{}", err.to_string().yellow());
+                println!("{}", url);
+            }
+        }
+        Action::Status { remote, format } => {
+            let current_dir = env::current_dir()?;
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |since| since.as_secs());
+            let day_dir =
+                env::resolve_day_dir(&current_dir, &day_format, &year_format)?;
+            let year = env::year_dir_of(&day_dir)
+                .and_then(|year_dir| env::parse_year(year_dir, &year_format))?;
+            let day = env::parse_day(&day_dir, &day_format)?;
+            let mut status = status::collect(&day_dir, year, day, now)?;
+            if remote {
+                let html = client.get()?.fetch_puzzle(year, day)?;
+                status::update_from_page(&mut status, &day_dir, &html)?;
+            }
+            match format {
+                run_all::OutputFormat::Table => print!("{}", status::render(&status)),
+                run_all::OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&status).unwrap()),
+            }
+        }
+        Action::Calendar { remote, format } => {
+            let current_dir = env::current_dir()?;
+            let (year, year_dir) = env::find_year_dir(&current_dir, None, &day_format, &year_format)?;
+            let mut days = calendar::collect(&year_dir, &day_format)?;
+            if remote {
+                let html = client.get()?.fetch_page(urls::Page::Calendar { year })?;
+                calendar::reconcile(&mut days, &calendar::parse_stars(&html));
+            }
+            match format {
+                run_all::OutputFormat::Table => print!("{}", calendar::render(&days)),
+                run_all::OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&days).unwrap()),
+            }
+        }
+        Action::Zip { output, include_inputs } => {
+            let current_dir = env::current_dir()?;
+            let (year, year_dir) = env::find_year_dir(&current_dir, None, &day_format, &year_format)?;
+            let output = current_dir.join(output.unwrap_or_else(|| format!("{}{}.zip", year_format, year).into()));
+            let mut files = bundle::files(&year_dir, include_inputs)?;
+            // Leave out a previous archive, when it's written in the year directory
+            files.retain(|file| year_dir.join(file) != output);
+            let size = bundle::write(&year_dir, &files, &output)?;
+            println!("New {}: {}", output.display(), "Success".green());
+            println!("{} files, {}", files.len(), clean::format_size(size));
+        }
+        Action::Archive { action } => {
+            let root = env::root_dir(&env::current_dir()?, &day_format, &year_format);
+            match action {
+                ArchiveAction::Export { path, year, include_auth } => {
+                    let formats = clean::Formats { year: &year_format, day: &day_format };
+                    let files = archive::files(&root, &formats, year, include_auth)?;
+                    if files.is_empty() {
+                        println!("{}", "Nothing to export".yellow());
+                        return Ok(());
+                    }
+                    archive::export(&root, &files, &path)?;
+                    println!("Export {} files to {}: {}", files.len(), path.display(), "Success".green());
+                }
+                ArchiveAction::Import { path, conflict, include_auth } => {
+                    let outcomes = archive::import(&path, &root, include_auth, |file| match conflict {
+                        archive::Conflict::Skip => false,
+                        archive::Conflict::Overwrite => true,
+                        archive::Conflict::Ask => dialoguer::Confirm::new()
+                            .with_prompt(format!("{} has changed, overwrite it?", file.display()))
+                            .default(false)
+                            .interact()
+                            .unwrap_or(false),
+                    })?;
+                    for (file, outcome) in outcomes {
+                        println!("{}", outcome.render(&root.join(file)));
+                    }
+                }
+            }
+        }
+        Action::Template { action } => match action {
+            TemplateAction::List => {
+                print!("{}", template::render_list(&template::list(template::template_dir().as_deref())));
+            }
+            TemplateAction::Show { name, template } => {
+                let contents = template::load_from(template::template_dir().as_deref(), template.as_deref(), &name)?;
+                print!("{}", template::highlight(&name, &contents));
+            }
+            TemplateAction::Init { force } => {
+                let dir = template::require_dir()?;
+                for (name, written) in template::init(&dir, force)? {
+                    let path = dir.join(name);
+                    if written {
+                        println!("New {}: {}", path.display(), "Success".green());
+                    } else {
+                        println!("{}: {}", path.display(), "Skipped, already exists".yellow());
+                    }
+                }
+            }
+            TemplateAction::Check => {
+                let unknown = template::check(&template::require_dir()?)?;
+                if !unknown.is_empty() {
+                    print!("{}", template::render_unknown(&unknown));
+                    return Err(AocError::Exit(1));
+                }
+                println!("{}", "All placeholders are known".green());
+            }
+        },
+        Action::Config { action } => match action {
+            ConfigAction::Set { key, value } => {
+                let path = require_config_path()?;
+                set_config_value(&path, &key, &value)?;
+                println!("Update {}: {}", path.display(), "Success".green());
+            }
+            ConfigAction::Get { key } => match effective_value(config_path().as_deref(), &key, std::env::vars())? {
+                Some(value) => println!("{}", value),
+                None => {
+                    eprintln!("{}", format!("{} isn't set", key).yellow());
+                    return Err(AocError::Exit(1));
+                }
+            },
+            ConfigAction::Migrate { write } => {
+                let path = require_config_path()?;
+                match migrate_file(&path, write)? {
+                    Migration::Missing => println!("{}", format!("No config file at {}", path.display()).yellow()),
+                    Migration::UpToDate(version) => println!("{} is up to date (version {})", path.display(), version),
+                    Migration::Migrated { from, .. } if write => println!(
+                        "Migrate {} from version {} to {}: {}",
+                        path.display(),
+                        from,
+                        CONFIG_VERSION,
+                        "Success".green()
+                    ),
+                    Migration::Migrated { migrated, .. } => {
+                        print!("{}", migrated);
+                        println!("{}", "Run again with --write to save it".yellow());
+                    }
+                }
+            }
+        },
+        Action::Stats { remote, format } => {
+            let current_dir = env::current_dir()?;
+            let (year, year_dir) = env::find_year_dir(&current_dir, None, &day_format, &year_format)?;
+            let mut days = stats::collect(&year_dir, &day_format)?;
+            if remote {
+                let Some(id) = config.leaderboard.id else {
+                    return Err(AocError::Config("No leaderboard id, set it in the config's [leaderboard] id".to_owned()));
+                };
+                let username = check_session(client.get()?)?.ok_or(AocError::InvalidSession)?;
+                let (leaderboard, _) = fetch_leaderboard(client.get()?, year, id)?;
+                stats::add_official_times(&mut days, &leaderboard, &username, year)?;
+            }
+            match format {
+                run_all::OutputFormat::Table => print!("{}", stats::render_table(&days)),
+                run_all::OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&days).unwrap()),
+            }
+        }
+        Action::Leaderboard { id, year } => {
+            let Some(id) = id.or(config.leaderboard.id) else {
+                return Err(AocError::Config(
+                    "No leaderboard id, use --id or set it in the config's [leaderboard] id".to_owned(),
+                ));
+            };
+            let year = env::year_or_current(year, &day_format, &year_format)?;
+            let (leaderboard, age) = fetch_leaderboard(client.get()?, year, id)?;
+            println!("{}", format!("Updated {} min ago", age.as_secs() / 60).yellow());
+            print!("{}", leaderboard::render(&leaderboard));
+        }
+        Action::Count { year } => {
+            let year = env::year_or_current(year, &day_format, &year_format)?;
+            let html = client.get()?.fetch_page(urls::Page::Calendar { year })?;
+            let stars = calendar::parse_star_count(&html)?;
+            println!("You have earned {}/50 stars this year ⭐", stars);
+        }
+        Action::Session => {
+            let username = check_session(client.get()?)?.ok_or(AocError::InvalidSession)?;
+            println!("{}", format!("Session valid for user: {}", username).green());
+        }
+        Action::DiagnoseSession { year } => {
+            let current_dir = env::current_dir()?;
+            let (year, year_dir) = env::find_year_dir(&current_dir, year, &day_format, &year_format)?;
+            let known = answers::first_known(&year_dir, &day_format)?;
+            let known_answer = known.as_ref().map(|(day, part, answer)| (*day, *part, answer.as_str()));
+            let checks = client.get()?.diagnose(year, known_answer);
+            print!("{}", render_checks(&checks));
+            if known.is_none() {
+                let skipped = format!("Skipped answer submission, no known answer in {}", year_dir.display());
+                println!("{}", skipped.yellow());
+            }
+            let failed = checks.iter().filter(|check| !check.passed).count();
+            if failed > 0 {
+                return Err(AocError::refused(format!("{} of {} checks failed", failed, checks.len())));
+            }
+        }
+        Action::Part { day, from, to, fresh, copy, lib, overwrite, backup, undo, yes, force } => {
             // Check CWD
-            helpers::check_day_and_year_dirs(&day_format, &year_format);
+            let current_dir = env::current_dir()?;
+            let day_dir = match day {
+                Some(day) => env::find_day_dir(&current_dir, day, &day_format, &year_format),
+                None => env::resolve_day_dir(&current_dir, &day_format, &year_format),
+            }?;
 
-            // Check: is there already a src/bin/part_2.rs?
-            let part_2_path = "./src/bin/part_2.rs";
-            if std::path::Path::new(&part_2_path).exists() {
-                println!(
-                    "{}",
-                    format!("{} already exists.", part_2_path).red()
-                );
-                std::process::exit(1);
-            }
+            let mode = if fresh {
+                PartMode::Fresh
+            } else if lib {
+                PartMode::Lib
+            } else if copy {
+                PartMode::Copy
+            } else {
+                config.part.default.unwrap_or_default()
+            };
+            let formats = clean::Formats { year: &year_format, day: &day_format };
+            let edition = config.workspace.edition.unwrap_or_default().as_str();
+            let variables = template::Variables::of_day(&day_dir, &formats, part_number(&to).unwrap_or(2), edition)?;
 
-            // Run: `cp src/bin/part_1.rs src/bin/part_2.rs`
-            let child = std::process::Command::new("cp")
-                .arg(format!("src/bin/part_1.rs"))
-                .arg(format!("src/bin/part_2.rs"))
-                .stdout(std::process::Stdio::null())
-                .stderr(std::process::Stdio::piped())
-                .spawn()
-                .expect("Run cp command");
-            let output = child.wait_with_output().expect("cp command finished");
+            if undo {
+                if !yes && env::prompt(&format!("Remove src/bin/{}.rs? [y/N]", to)).to_lowercase() != "y" {
+                    return Err(AocError::Aborted);
+                }
+                let part_2_path = undo_part(&day_dir, &from, &to, &variables, force)?;
+                println!("Remove {}: {}", part_2_path.display(), "Success".green());
+                return Ok(());
+            }
 
-            if output.status.success() {
-                println!("{}", "Success".green());
+            let existing = if overwrite {
+                Existing::Overwrite
+            } else if backup {
+                Existing::Backup
+            } else if day_dir.join(format!("src/bin/{}.rs", to)).exists() && std::io::stdin().is_terminal() {
+                let answer =
+                    env::prompt(&format!("src/bin/{}.rs already exists. [a]bort, [o]verwrite or [b]ackup?", to));
+                match answer.to_lowercase().as_str() {
+                    "o" | "overwrite" => Existing::Overwrite,
+                    "b" | "backup" => Existing::Backup,
+                    _ => return Err(AocError::Aborted),
+                }
             } else {
+                Existing::Refuse
+            };
+
+            let part_2_path = create_part(&day_dir, &from, &to, mode, &variables, existing)?;
+            println!("New {}: {}", part_2_path.display(), "Success".green());
+            if mode == PartMode::Lib {
                 println!(
                     "{}",
                     format!(
-                        "Failed to copy part 1 to part 2: {:?}",
-                        String::from_utf8(output.stderr.as_slice().to_vec())
+                        "Hint: part_1.rs is unchanged, it can use `{}::utils` as well.",
+                        variables.crate_name()
                     )
-                    .red()
+                    .yellow()
                 );
             }
         }
     }
+    Ok(())
 }
 
-pub mod helpers {
-    use crate::Environment;
-    use colored::*;
+/// The command line for completion scripts, completing `--day` with 1 to 25 and templates with
+/// the ones found when the script is generated.
+fn completion_command() -> clap::Command {
+    use clap::builder::PossibleValuesParser;
 
-    pub fn check_day_and_year_dirs(day_format: &str, year_format: &str) {
-        // Verify user is in the correct directory.
-        // 1. advent-of-code-{year}
-        // 2. day-XX
-        if let Err(err) = Environment::check_day(day_format) {
-            eprintln!("{}", format!("Error: {}", err).red());
-            std::process::exit(1);
-        }
-        if let Err(err) = Environment::check_year(year_format) {
-            eprintln!("{}", format!("Error: {}", err).red());
-            std::process::exit(1);
-        }
-    }
-
-    pub fn check_year_dir(year_format: &str) {
-        // Verify user is in the correct directory.
-        // 1. advent-of-code-{year}
-        if let Err(err) = Environment::check_year(year_format) {
-            eprintln!("{}", format!("Error: {}", err).red());
-            std::process::exit(1);
-        }
+    let mut command = Args::command();
+    let with_day: Vec<String> = command
+        .get_subcommands()
+        .filter(|subcommand| subcommand.get_arguments().any(|arg| arg.get_id() == "day"))
+        .map(|subcommand| subcommand.get_name().to_owned())
+        .collect();
+    for name in with_day {
+        command = command.mut_subcommand(name, |subcommand| {
+            subcommand.mut_arg("day", |arg| arg.value_parser(PossibleValuesParser::new((1..=25).map(|day| day.to_string()))))
+        });
     }
+    command.mut_subcommand("pull", |pull| {
+        pull.mut_arg("template", |arg| arg.value_parser(PossibleValuesParser::new(template::names())))
+    })
 }
 
-fn get_input(year: u16, day: u8) -> String {
-    use dotenv::dotenv;
-    use std::env;
-
-    let url = format!("https://adventofcode.com/{}/day/{}/input", year, day);
+/// How to install the completion script for `shell`.
+fn completions_hint(shell: clap_complete::Shell) -> &'static str {
+    use clap_complete::Shell;
 
-    dotenv().ok();
-
-    let session_cookie: String;
-    match env::var("session") {
-        Ok(val) => session_cookie = val,
-        Err(e) => {
-            eprintln!("{}", format!("session key not set in .env file: {}", e).red());
-            std::process::exit(1);
-        }
+    match shell {
+        Shell::Bash => "Add to ~/.bashrc: source <(aoc completions bash)",
+        Shell::Zsh => "Add to ~/.zshrc: source <(aoc completions zsh)",
+        Shell::Fish => "Install with: aoc completions fish > ~/.config/fish/completions/aoc.fish",
+        Shell::PowerShell => "Add to $PROFILE: aoc completions powershell | Out-String | Invoke-Expression",
+        Shell::Elvish => "Add to ~/.config/elvish/rc.elv: eval (aoc completions elvish | slurp)",
+        _ => "Load the script in your shell's startup file.",
     }
-    let client = reqwest::blocking::Client::new();
-    let mut response = client
-        .get(&url)
-        .header("Cookie", &session_cookie)
-        .header("User-Agent", "AceofSpades5757")
-        .send()
-        .unwrap();
-
-    // if code is 404, try up to 5 times
-    let max_tries = 5;
-    let mut tries = 0;
-    while response.status() == 404 && tries < max_tries {
-        eprintln!("{}", "Puzzle has not yet opened, retrying...".yellow());
-        response = client
-            .get(&url)
-            .header("Cookie", format!("session={}", &session_cookie))
-            .header("User-Agent", "AceofSpades5757")
-            .send()
-            .unwrap();
-        std::thread::sleep(std::time::Duration::from_millis(1_000));
-        tries += 1;
-    }
-    if response.status() == 404 {
-        eprintln!(
-            "{}",
-            "Puzzle has not yet opened, please try again later.".red()
-        );
-        std::process::exit(1);
-    }
-
-    let text = response.text().unwrap();
-    text
-}
-
-//fn submit_answer(year: u16, day: u8, part: u8, answer: &str) -> Result<String, String> {
-//fn submit_answer(year: u16, day: u8, part: u8, answer: &str) -> Result<Answer, Answer> {
-fn submit_answer(year: u16, day: u8, part: u8, answer: &str) -> Answer {
-    use dotenv::dotenv;
-    use std::env;
-
-    let url = format!("https://adventofcode.com/{}/day/{}/answer", year, day);
-
-    dotenv().ok();
-
-    let session_cookie: String;
-    match env::var("session") {
-        Ok(val) => session_cookie = val,
-        Err(e) => {
-            eprintln!("{}", format!("session key not set in .env file: {}", e).red());
-            std::process::exit(1);
-        }
-    }
-    let client = reqwest::blocking::Client::new();
-    let response = client
-        .post(&url)
-        .header("Cookie", format!("session={}", session_cookie))
-        .header("User-Agent", "AceofSpades5757")
-        .form(&[("level", part)])
-        .form(&[("answer", answer)])
-        .send()
-        .unwrap();
-
-    let text = response.text().unwrap();
-    let answer: Answer = text.parse().unwrap();
-    answer
-    //match answer {
-        //Answer::Correct => {
-            //Ok(answer)
-        //}
-        //Answer::Incorrect => {
-            //Err(answer)
-        //}
-        //Answer::AlreadySubmitted => {
-            //Err(answer)
-        //}
-        //Answer::RateLimited => {
-            //Err(answer)
-        //}
-    //}
 }
 
 #[cfg(test)]
@@ -541,27 +1824,27 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_check_day() {
-        // Set Up
-        let tmp_dir = std::env::temp_dir();
-        let day_dir = tmp_dir.join("day-01");
-        std::fs::create_dir_all(&day_dir).unwrap();
-        std::env::set_current_dir(&day_dir).unwrap();
+    fn test_completions() {
+        use clap_complete::Shell;
 
-        let day_format = "day-";
-        assert_eq!(Environment::check_day(day_format), Ok(()));
-    }
-    #[test]
-    fn test_check_year() {
-        // Set Up
-        let tmp_dir = std::env::temp_dir();
-        let year_dir = tmp_dir.join("advent-of-code-2020");
-        let day_dir = year_dir.join("day-01");
-        std::fs::create_dir_all(&year_dir).unwrap();
-        std::fs::create_dir_all(&day_dir).unwrap();
-        std::env::set_current_dir(&day_dir).unwrap();
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::PowerShell] {
+            let mut script = Vec::new();
+            clap_complete::generate(shell, &mut completion_command(), "aoc", &mut script);
+            let script = String::from_utf8(script).unwrap();
+            assert!(script.contains("submit"), "{:?}", shell);
+            assert!(script.contains("completions"), "{:?}", shell);
+            assert!(script.contains("watch"), "{:?}", shell);
+        }
 
-        let year_format = "advent-of-code-";
-        assert_eq!(Environment::check_year(year_format), Ok(()));
+        let command = completion_command();
+        let possible_values = |subcommand: &str, arg: &str| -> Vec<String> {
+            let subcommand = command.find_subcommand(subcommand).unwrap();
+            let arg = subcommand.get_arguments().find(|candidate| candidate.get_id() == arg).unwrap();
+            arg.get_possible_values().iter().map(|value| value.get_name().to_owned()).collect()
+        };
+        let days = possible_values("test", "day");
+        assert_eq!((days.len(), days[0].as_str(), days[24].as_str()), (25, "1", "25"));
+        assert_eq!(possible_values("open", "day").len(), 25);
+        assert!(possible_values("pull", "template").contains(&"part_lib.rs".to_owned()));
     }
 }
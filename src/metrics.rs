@@ -0,0 +1,126 @@
+//! Lines of code of each day's parts, for `aoc metrics`.
+use crate::error::AocError;
+use std::path::Path;
+
+/// Line counts of a part's `src/bin/part_*.rs`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartMetrics {
+    pub day: u8,
+    /// What follows `part_` in the binary's name, e.g. `2` or `2_fast`.
+    pub part: String,
+    pub total: usize,
+    pub blank: usize,
+    pub comment: usize,
+    pub code: usize,
+}
+
+/// Count the total, blank, comment and code lines of `source`, line by line. A line is a comment
+/// if it starts with `//`, or is in or starts a block comment. Code followed by a comment counts
+/// as code.
+pub fn count_lines(source: &str) -> (usize, usize, usize, usize) {
+    let (mut total, mut blank, mut comment, mut code) = (0, 0, 0, 0);
+    let mut in_block = false;
+    for line in source.lines() {
+        total += 1;
+        let line = line.trim();
+        if in_block {
+            comment += 1;
+            in_block = !line.contains("*/");
+        } else if line.is_empty() {
+            blank += 1;
+        } else if line.starts_with("//") {
+            comment += 1;
+        } else if let Some(rest) = line.strip_prefix("/*") {
+            comment += 1;
+            in_block = !rest.contains("*/");
+        } else {
+            code += 1;
+        }
+    }
+    (total, blank, comment, code)
+}
+
+/// Metrics of every part of the days in `year_dir`, ordered by day and part.
+pub fn collect(year_dir: &Path, day_format: &str) -> Result<Vec<PartMetrics>, AocError> {
+    let mut metrics = Vec::new();
+    for (day, day_dir) in crate::scaffold::day_dirs(year_dir, day_format)? {
+        for (part, path) in crate::scaffold::part_files(&day_dir) {
+            let source = std::fs::read_to_string(&path)
+                .map_err(|e| AocError::io(format!("Failed to read {}", path.display()), e))?;
+            let (total, blank, comment, code) = count_lines(&source);
+            metrics.push(PartMetrics {
+                day,
+                part,
+                total,
+                blank,
+                comment,
+                code,
+            });
+        }
+    }
+    Ok(metrics)
+}
+
+pub fn render_table(metrics: &[PartMetrics]) -> String {
+    let part_width = metrics
+        .iter()
+        .map(|metrics| metrics.part.len())
+        .chain(std::iter::once("Part".len()))
+        .max()
+        .unwrap_or_default();
+    let mut table = format!(
+        "{:>3} {:>part_width$} {:>6} {:>6} {:>7} {:>6}\n",
+        "Day", "Part", "Total", "Blank", "Comment", "Code"
+    );
+    for metrics in metrics {
+        table.push_str(&format!(
+            "{:>3} {:>part_width$} {:>6} {:>6} {:>7} {:>6}\n",
+            metrics.day, metrics.part, metrics.total, metrics.blank, metrics.comment, metrics.code
+        ));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_lines() {
+        let source = r#"//! Part 1
+use std::fs;
+
+/* Parsing,
+   then solving */
+fn main() {
+    /* inline */ let x = 1; // trailing
+    println!("{}", x);
+}
+"#;
+        assert_eq!(count_lines(source), (9, 1, 4, 4));
+    }
+
+    #[test]
+    fn test_collect() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        for (day, part, source) in [
+            ("day-02", "part_1.rs", "fn main() {}\n"),
+            ("day-01", "part_2.rs", "// Part 2\nfn main() {}\n\n"),
+            ("day-01", "part_1.rs", "fn main() {\n}\n"),
+        ] {
+            let bin_dir = tmp_dir.path().join(day).join("src/bin");
+            std::fs::create_dir_all(&bin_dir).unwrap();
+            std::fs::write(bin_dir.join(part), source).unwrap();
+        }
+        std::fs::write(tmp_dir.path().join("day-01/src/bin/helper.rs"), "").unwrap();
+
+        let metrics = collect(tmp_dir.path(), "day-").unwrap();
+        assert_eq!(
+            render_table(&metrics),
+            "Day Part  Total  Blank Comment   Code\n  \
+               1    1      2      0       0      2\n  \
+               1    2      3      1       1      1\n  \
+               2    1      1      0       0      1\n"
+        );
+    }
+}
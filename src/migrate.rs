@@ -0,0 +1,220 @@
+//! Renaming the day directories of a year to a new format, for `aoc migrate`.
+use crate::error::AocError;
+use colored::*;
+use std::path::{Path, PathBuf};
+use toml_edit::Document;
+
+/// A day directory to rename, e.g. `day_1` to `day-01`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rename {
+    pub day: u8,
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+impl Rename {
+    fn names(&self) -> (String, String) {
+        let name = |path: &Path| path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        (name(&self.from), name(&self.to))
+    }
+}
+
+/// The renames of the day directories in `year_dir` named with `old_format` to `new_format`,
+/// failing if any new name is taken, so nothing is renamed.
+pub fn plan(year_dir: &Path, old_format: &str, new_format: &str) -> Result<Vec<Rename>, AocError> {
+    let renames: Vec<Rename> = crate::scaffold::day_dirs(year_dir, old_format)?
+        .into_iter()
+        .map(|(day, from)| Rename {
+            day,
+            to: year_dir.join(format!("{}{:02}", new_format, day)),
+            from,
+        })
+        .filter(|rename| rename.from != rename.to)
+        .collect();
+    let mut collisions = Vec::new();
+    for (index, rename) in renames.iter().enumerate() {
+        let taken = rename.to.exists() || renames[..index].iter().any(|other| other.to == rename.to);
+        if taken {
+            collisions.push(format!("{} -> {}", rename.from.display(), rename.to.display()));
+        }
+    }
+    if !collisions.is_empty() {
+        return Err(AocError::Environment(format!(
+            "Already exists, nothing was renamed:\n  {}",
+            collisions.join("\n  ")
+        )));
+    }
+    Ok(renames)
+}
+
+/// Rename the day directories, then update their package names, the uses of their crates in
+/// their code, and the members of the workspace in `year_dir`.
+pub fn apply(year_dir: &Path, renames: &[Rename]) -> Result<(), AocError> {
+    for rename in renames {
+        std::fs::rename(&rename.from, &rename.to).map_err(|e| {
+            AocError::io(format!("Failed to rename {} to {}", rename.from.display(), rename.to.display()), e)
+        })?;
+        let (_, new_name) = rename.names();
+        rename_package(&rename.to, &new_name)?;
+    }
+
+    let cargo_toml_path = year_dir.join("Cargo.toml");
+    let Ok(contents) = std::fs::read_to_string(&cargo_toml_path) else {
+        return Ok(());
+    };
+    let mut cargo_toml = contents
+        .parse::<Document>()
+        .map_err(|e| AocError::Parse(format!("Invalid {}: {}", cargo_toml_path.display(), e)))?;
+    let Some(members) = cargo_toml
+        .get_mut("workspace")
+        .and_then(|workspace| workspace.get_mut("members"))
+        .and_then(|members| members.as_array_mut())
+    else {
+        return Ok(());
+    };
+    for member in members.iter_mut() {
+        let Some(renamed) = renames.iter().map(Rename::names).find(|(old, _)| Some(old.as_str()) == member.as_str())
+        else {
+            continue;
+        };
+        let decor = member.decor().clone();
+        *member = renamed.1.into();
+        *member.decor_mut() = decor;
+    }
+    crate::scaffold::write_manifest(&cargo_toml_path, &cargo_toml.to_string())
+}
+
+/// Set the package name of the crate in `day_dir` to `name`, and update the uses of the crate by
+/// the old name in its code.
+fn rename_package(day_dir: &Path, name: &str) -> Result<(), AocError> {
+    let cargo_toml_path = day_dir.join("Cargo.toml");
+    let Ok(contents) = std::fs::read_to_string(&cargo_toml_path) else {
+        return Ok(());
+    };
+    let mut cargo_toml = contents
+        .parse::<Document>()
+        .map_err(|e| AocError::Parse(format!("Invalid {}: {}", cargo_toml_path.display(), e)))?;
+    let Some(old_name) = cargo_toml.get("package").and_then(|package| package["name"].as_str()) else {
+        return Ok(());
+    };
+    let (old_crate, new_crate) = (old_name.replace('-', "_"), name.replace('-', "_"));
+    cargo_toml["package"]["name"] = toml_edit::value(name);
+    crate::scaffold::write_manifest(&cargo_toml_path, &cargo_toml.to_string())?;
+    if old_crate != new_crate {
+        for path in rust_files(&day_dir.join("src")) {
+            let code = std::fs::read_to_string(&path)
+                .map_err(|e| AocError::io(format!("Failed to read {}", path.display()), e))?;
+            let renamed = rename_crate(&code, &old_crate, &new_crate);
+            if renamed != code {
+                std::fs::write(&path, renamed)
+                    .map_err(|e| AocError::io(format!("Failed to write {}", path.display()), e))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The `.rs` files in `dir` and its subdirectories.
+fn rust_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files = Vec::new();
+    for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+        if path.is_dir() {
+            files.extend(rust_files(&path));
+        } else if path.extension().is_some_and(|extension| extension == "rs") {
+            files.push(path);
+        }
+    }
+    files
+}
+
+/// `code` with the paths starting with the crate `old` (like `day_1::utils`) starting with `new`.
+fn rename_crate(code: &str, old: &str, new: &str) -> String {
+    let pattern = format!("{}::", old);
+    let mut renamed = String::with_capacity(code.len());
+    let mut rest = code;
+    while let Some(index) = rest.find(&pattern) {
+        let in_identifier = rest[..index]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_');
+        renamed.push_str(&rest[..index]);
+        renamed.push_str(if in_identifier { old } else { new });
+        renamed.push_str("::");
+        rest = &rest[index + pattern.len()..];
+    }
+    renamed.push_str(rest);
+    renamed
+}
+
+/// Rename the days of `year_dir` from `old_format` to `new_format`, or only list the renames
+/// without `apply`.
+pub fn migrate(year_dir: &Path, old_format: &str, new_format: &str, apply: bool) -> Result<Vec<Rename>, AocError> {
+    let renames = plan(year_dir, old_format, new_format)?;
+    if renames.is_empty() {
+        println!("{}", "Nothing to migrate".yellow());
+        return Ok(renames);
+    }
+    let verb = if apply { "Rename" } else { "Would rename" };
+    for rename in &renames {
+        println!("{} {} -> {}", verb, rename.from.display(), rename.to.display());
+    }
+    if apply {
+        self::apply(year_dir, &renames)?;
+        println!("Migrate {} days: {}", renames.len(), "Success".green());
+    } else {
+        println!("{}", "Run again with --apply to rename them".yellow());
+    }
+    Ok(renames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = tmp_dir.path();
+        for day in ["day_1", "day_2", "day-02", "notes"] {
+            std::fs::create_dir(year_dir.join(day)).unwrap();
+        }
+
+        let err = plan(year_dir, "day_", "day-").unwrap_err().to_string();
+        assert!(err.contains("day_2 ->"));
+        assert!(!err.contains("day_1 ->"));
+
+        std::fs::remove_dir(year_dir.join("day-02")).unwrap();
+        let renames = plan(year_dir, "day_", "day-").unwrap();
+        assert_eq!(
+            renames.iter().map(Rename::names).collect::<Vec<_>>(),
+            [("day_1".to_owned(), "day-01".to_owned()), ("day_2".to_owned(), "day-02".to_owned())]
+        );
+        // Already in the new format
+        assert!(plan(year_dir, "day-", "day-").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_migrate() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = tmp_dir.path();
+        std::fs::create_dir(year_dir.join("day_1")).unwrap();
+
+        assert_eq!(migrate(year_dir, "day_", "day-", false).unwrap().len(), 1);
+        assert!(year_dir.join("day_1").exists());
+        assert_eq!(migrate(year_dir, "day_", "day-", true).unwrap().len(), 1);
+        assert!(year_dir.join("day-01").exists());
+        assert!(migrate(year_dir, "day_", "day-", true).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_rename_crate() {
+        let code = "use day_1::utils;\nuse myday_1::other;\nfn main() { day_1::parse(); day_10::parse(); }\n";
+        assert_eq!(
+            rename_crate(code, "day_1", "day_01"),
+            "use day_01::utils;\nuse myday_1::other;\nfn main() { day_01::parse(); day_10::parse(); }\n"
+        );
+    }
+}
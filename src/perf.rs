@@ -0,0 +1,254 @@
+//! Solution runtimes, recorded in `perf.jsonl` in the year directory.
+use crate::error::AocError;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+
+const PERF_FILE: &str = "perf.jsonl";
+const CHART_WIDTH: usize = 50;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PerfEntry {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub day: u8,
+    pub part: u8,
+    pub elapsed_ms: f64,
+    /// Whether the part was killed after running for `elapsed_ms`, by `aoc submit --timeout` or
+    /// `aoc watch --timeout`. Such entries aren't runtimes.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
+}
+
+impl PerfEntry {
+    pub fn now(day: u8, part: u8, elapsed: std::time::Duration) -> Self {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        PerfEntry {
+            timestamp,
+            day,
+            part,
+            elapsed_ms: elapsed.as_secs_f64() * 1_000.0,
+            timed_out: false,
+        }
+    }
+
+    /// A part killed now after running for `timeout`.
+    pub fn timed_out(day: u8, part: u8, timeout: std::time::Duration) -> Self {
+        PerfEntry { timed_out: true, ..PerfEntry::now(day, part, timeout) }
+    }
+}
+
+/// Minimum, maximum and average runtime of a part.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PerfSummary {
+    pub day: u8,
+    pub part: u8,
+    pub runs: usize,
+    pub min_ms: f64,
+    pub max_ms: f64,
+    pub avg_ms: f64,
+}
+
+pub fn record(year_dir: &Path, entry: &PerfEntry) -> Result<(), AocError> {
+    let path = year_dir.join(PERF_FILE);
+    let line = serde_json::to_string(entry).map_err(|e| AocError::Parse(format!("Failed to serialize: {}", e)))?;
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| writeln!(file, "{}", line))
+        .map_err(|e| AocError::io(format!("Failed to write {}", path.display()), e))?;
+    crate::logging::wrote(&path);
+    Ok(())
+}
+
+/// Record the entry `entry_of` gives for the day of `day_dir`, named with `day_format`, in the log
+/// of its year.
+pub fn record_day(day_dir: &Path, day_format: &str, entry_of: impl FnOnce(u8) -> PerfEntry) -> Result<(), AocError> {
+    let day = crate::env::parse_day(day_dir, day_format)?;
+    record(crate::env::year_dir_of(day_dir)?, &entry_of(day))
+}
+
+/// All recorded entries, skipping lines which can't be parsed.
+pub fn read(year_dir: &Path) -> Result<Vec<PerfEntry>, AocError> {
+    let path = year_dir.join(PERF_FILE);
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(AocError::io(format!("Failed to read {}", path.display()), e)),
+    };
+    Ok(contents
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Summarize entries per part per day, optionally only for `day`, leaving out timeouts.
+pub fn summarize(entries: &[PerfEntry], day: Option<u8>) -> Vec<PerfSummary> {
+    let mut runs: std::collections::BTreeMap<(u8, u8), Vec<f64>> = Default::default();
+    for entry in entries {
+        if !entry.timed_out && day.is_none_or(|day| day == entry.day) {
+            runs.entry((entry.day, entry.part))
+                .or_default()
+                .push(entry.elapsed_ms);
+        }
+    }
+    runs.into_iter()
+        .map(|((day, part), elapsed)| PerfSummary {
+            day,
+            part,
+            runs: elapsed.len(),
+            min_ms: elapsed.iter().copied().fold(f64::INFINITY, f64::min),
+            max_ms: elapsed.iter().copied().fold(0.0, f64::max),
+            avg_ms: elapsed.iter().sum::<f64>() / elapsed.len() as f64,
+        })
+        .collect()
+}
+
+pub fn render_table(summaries: &[PerfSummary]) -> String {
+    let mut table = format!(
+        "{:>3} {:>4} {:>4} {:>12} {:>12} {:>12}\n",
+        "Day", "Part", "Runs", "Min (ms)", "Max (ms)", "Avg (ms)"
+    );
+    for summary in summaries {
+        table.push_str(&format!(
+            "{:>3} {:>4} {:>4} {:>12.3} {:>12.3} {:>12.3}\n",
+            summary.day, summary.part, summary.runs, summary.min_ms, summary.max_ms, summary.avg_ms
+        ));
+    }
+    table
+}
+
+/// Bar chart of the average runtimes, scaled to the slowest part.
+pub fn render_chart(summaries: &[PerfSummary]) -> String {
+    let slowest = summaries.iter().map(|summary| summary.avg_ms).fold(0.0, f64::max);
+    let mut chart = String::new();
+    for summary in summaries {
+        let width = if slowest > 0.0 {
+            ((summary.avg_ms / slowest) * CHART_WIDTH as f64).round() as usize
+        } else {
+            0
+        };
+        chart.push_str(&format!(
+            "Day {:>2} Part {} | {} {:.3} ms\n",
+            summary.day,
+            summary.part,
+            "#".repeat(width.max(1)),
+            summary.avg_ms
+        ));
+    }
+    chart
+}
+
+/// Timed out entries by day, oldest first.
+pub fn timeouts(entries: &[PerfEntry]) -> std::collections::BTreeMap<u8, Vec<&PerfEntry>> {
+    let mut timeouts: std::collections::BTreeMap<u8, Vec<&PerfEntry>> = Default::default();
+    for entry in entries.iter().filter(|entry| entry.timed_out) {
+        timeouts.entry(entry.day).or_default().push(entry);
+    }
+    for entries in timeouts.values_mut() {
+        entries.sort_by_key(|entry| entry.timestamp);
+    }
+    timeouts
+}
+
+/// A block per day of when each of its parts timed out, `now` being seconds since the Unix epoch.
+pub fn render_timeouts(timeouts: &std::collections::BTreeMap<u8, Vec<&PerfEntry>>, now: u64) -> String {
+    let mut rendered = String::new();
+    for (day, entries) in timeouts {
+        rendered.push_str(&format!("Day {}\n", day));
+        for entry in entries {
+            rendered.push_str(&format!(
+                "  Part {} timed out after {:.3} s, {} ago\n",
+                entry.part,
+                entry.elapsed_ms / 1_000.0,
+                crate::unlock::format_remaining(now.saturating_sub(entry.timestamp))
+            ));
+        }
+    }
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(day: u8, part: u8, elapsed_ms: f64) -> PerfEntry {
+        PerfEntry {
+            timestamp: 0,
+            day,
+            part,
+            elapsed_ms,
+            timed_out: false,
+        }
+    }
+
+    #[test]
+    fn test_summarize() {
+        let entries = vec![entry(1, 1, 10.0), entry(1, 1, 30.0), entry(1, 2, 5.0), entry(2, 1, 100.0)];
+
+        let summaries = summarize(&entries, None);
+        assert_eq!(summaries.len(), 3);
+        assert_eq!(
+            summaries[0],
+            PerfSummary {
+                day: 1,
+                part: 1,
+                runs: 2,
+                min_ms: 10.0,
+                max_ms: 30.0,
+                avg_ms: 20.0
+            }
+        );
+        assert_eq!(summarize(&entries, Some(2)).len(), 1);
+
+        let chart = render_chart(&summaries);
+        assert_eq!(chart.lines().nth(2).unwrap(), format!("Day  2 Part 1 | {} 100.000 ms", "#".repeat(50)));
+        assert_eq!(chart.lines().next().unwrap(), format!("Day  1 Part 1 | {} 20.000 ms", "#".repeat(10)));
+    }
+
+    #[test]
+    fn test_record_and_read() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        record(tmp_dir.path(), &entry(1, 1, 1.5)).unwrap();
+        record(tmp_dir.path(), &entry(1, 2, 2.5)).unwrap();
+        assert_eq!(read(tmp_dir.path()).unwrap(), vec![entry(1, 1, 1.5), entry(1, 2, 2.5)]);
+
+        let day_dir = tmp_dir.path().join("day-03");
+        record_day(&day_dir, "day-", |day| entry(day, 1, 3.5)).unwrap();
+        assert_eq!(read(tmp_dir.path()).unwrap()[2], entry(3, 1, 3.5));
+        assert!(record_day(&day_dir, "aoc-", |day| entry(day, 1, 3.5)).is_err());
+    }
+
+    #[test]
+    fn test_timeouts() {
+        let timeout = |timestamp: u64, day: u8, part: u8| PerfEntry {
+            timestamp,
+            timed_out: true,
+            ..entry(day, part, 10_000.0)
+        };
+        let entries = vec![entry(3, 1, 5.0), timeout(120, 3, 2), timeout(60, 3, 1), timeout(30, 1, 2)];
+        // Timeouts aren't runtimes
+        assert_eq!(summarize(&entries, None).len(), 1);
+
+        let timeouts = timeouts(&entries);
+        assert_eq!(timeouts.keys().collect::<Vec<_>>(), [&1, &3]);
+        assert_eq!(timeouts[&3], [&entries[2], &entries[1]]);
+        assert_eq!(
+            render_timeouts(&timeouts, 150),
+            "Day 1
+  Part 2 timed out after 10.000 s, 2m 00s ago
+Day 3
+  Part 1 timed out after 10.000 s, 1m 30s ago
+  Part 2 timed out after 10.000 s, 30s ago
+"
+        );
+
+        // Only timeouts mention it in perf.jsonl, older lines without it still parse
+        assert!(!serde_json::to_string(&entries[0]).unwrap().contains("timed_out"));
+        let line = r#"{"timestamp":0,"day":3,"part":1,"elapsed_ms":5.0}"#;
+        assert_eq!(serde_json::from_str::<PerfEntry>(line).unwrap(), entries[0]);
+    }
+}
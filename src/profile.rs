@@ -0,0 +1,150 @@
+//! Profiling a part with an external profiler, for `aoc profile`.
+use crate::error::AocError;
+use clap::ValueEnum;
+use colored::*;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ProfileTool {
+    /// cargo flamegraph, writing flamegraph.svg.
+    #[default]
+    Flamegraph,
+    /// perf record on the release build, writing perf.data (Linux).
+    Perf,
+    /// cargo instruments with the Time Profiler template (macOS).
+    Instruments,
+}
+
+impl ProfileTool {
+    /// The program which has to be installed.
+    pub fn program(self) -> &'static str {
+        match self {
+            ProfileTool::Flamegraph => "cargo-flamegraph",
+            ProfileTool::Perf => "perf",
+            ProfileTool::Instruments => "cargo-instruments",
+        }
+    }
+
+    pub fn install_hint(self) -> &'static str {
+        match self {
+            ProfileTool::Flamegraph => "Install it with: cargo install flamegraph",
+            ProfileTool::Perf => "Install it with your package manager, e.g. apt install linux-tools-generic",
+            ProfileTool::Instruments => "Install it with: cargo install cargo-instruments (needs Xcode)",
+        }
+    }
+
+    /// Whether the program is in one of the directories of `path` (from `$PATH`).
+    pub fn is_installed(self, path: Option<&OsStr>) -> bool {
+        crate::editor::on_path(self.program(), path)
+    }
+}
+
+/// `tool` when it's installed, or else perf on Linux in place of flamegraph, which uses it anyway,
+/// along with a warning saying so. Errors with how to install `tool` when neither is.
+pub fn choose(
+    tool: ProfileTool,
+    installed: impl Fn(ProfileTool) -> bool,
+    linux: bool,
+) -> Result<(ProfileTool, Option<String>), AocError> {
+    if installed(tool) {
+        return Ok((tool, None));
+    }
+    let missing = format!("{} isn't installed. {}", tool.program(), tool.install_hint());
+    if tool == ProfileTool::Flamegraph && linux && installed(ProfileTool::Perf) {
+        return Ok((ProfileTool::Perf, Some(format!("{} Profiling with perf instead.", missing))));
+    }
+    Err(AocError::Environment(missing))
+}
+
+/// The command profiling `part` with `tool`, run from the day directory. perf profiles
+/// `executable`, the release build of the part, where the cargo subcommands build it themselves.
+pub fn command(tool: ProfileTool, part: u8, executable: &Path) -> Command {
+    let bin = format!("part_{}", part);
+    match tool {
+        ProfileTool::Flamegraph => {
+            let mut command = Command::new("cargo");
+            command.args(["flamegraph", "--bin", &bin, "-o", "flamegraph.svg"]);
+            command
+        }
+        ProfileTool::Perf => {
+            let mut command = Command::new("perf");
+            command.args(["record", "-g", "-o", "perf.data"]).arg(executable);
+            command
+        }
+        ProfileTool::Instruments => {
+            let mut command = Command::new("cargo");
+            command.args(["instruments", "--release", "--bin", &bin, "-t", "time"]);
+            command
+        }
+    }
+}
+
+/// Profile `part` of `day_dir` with `tool`, or the one `choose` falls back to, and point to the
+/// profile it wrote: flamegraphs are opened in the browser.
+pub fn profile(day_dir: &Path, part: u8, tool: ProfileTool) -> Result<(), AocError> {
+    let path = std::env::var_os("PATH");
+    let (tool, warning) = choose(tool, |tool| tool.is_installed(path.as_deref()), cfg!(target_os = "linux"))?;
+    if let Some(warning) = warning {
+        eprintln!("{}", warning.yellow());
+    }
+    let executable = match tool {
+        ProfileTool::Perf => crate::runner::build_part(day_dir, part, &["--release".to_owned()])?,
+        _ => PathBuf::new(),
+    };
+    let status = command(tool, part, &executable)
+        .current_dir(day_dir)
+        .status()
+        .map_err(|e| AocError::Build(format!("Failed to run {}: {}", tool.program(), e)))?;
+    if !status.success() {
+        return Err(AocError::Build(format!("{} failed: {}", tool.program(), status)));
+    }
+    match tool {
+        ProfileTool::Flamegraph => {
+            let svg = day_dir.join("flamegraph.svg");
+            println!("New {}: {}", svg.display(), "Success".green());
+            if let Err(err) = crate::urls::open_in_browser(&svg.to_string_lossy()) {
+                eprintln!("{}", err.to_string().yellow());
+            }
+        }
+        ProfileTool::Perf => {
+            println!("New {}: {}", day_dir.join("perf.data").display(), "Success".green());
+            println!("Inspect it with: perf report -i perf.data");
+        }
+        ProfileTool::Instruments => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choose() {
+        let installed = |tools: &'static [ProfileTool]| move |tool| tools.contains(&tool);
+        let choose = |tool, tools, linux| choose(tool, installed(tools), linux);
+        let chosen = choose(ProfileTool::Flamegraph, &[ProfileTool::Flamegraph], true);
+        assert_eq!(chosen.unwrap(), (ProfileTool::Flamegraph, None));
+        let (tool, warning) = choose(ProfileTool::Flamegraph, &[ProfileTool::Perf], true).unwrap();
+        assert_eq!(tool, ProfileTool::Perf);
+        assert!(warning.unwrap().ends_with("Profiling with perf instead."));
+        let err = choose(ProfileTool::Flamegraph, &[ProfileTool::Perf], false).unwrap_err().to_string();
+        assert_eq!(err, "cargo-flamegraph isn't installed. Install it with: cargo install flamegraph");
+        assert!(choose(ProfileTool::Instruments, &[ProfileTool::Perf], true).is_err());
+    }
+
+    #[test]
+    fn test_command() {
+        let args = |tool| {
+            let command = command(tool, 2, Path::new("target/release/part_2"));
+            let mut args = vec![command.get_program().to_string_lossy().into_owned()];
+            args.extend(command.get_args().map(|arg| arg.to_string_lossy().into_owned()));
+            args.join(" ")
+        };
+        assert_eq!(args(ProfileTool::Flamegraph), "cargo flamegraph --bin part_2 -o flamegraph.svg");
+        assert_eq!(args(ProfileTool::Perf), "perf record -g -o perf.data target/release/part_2");
+        assert_eq!(args(ProfileTool::Instruments), "cargo instruments --release --bin part_2 -t time");
+    }
+}
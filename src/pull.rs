@@ -0,0 +1,167 @@
+//! Pulling the latest templates from the upstream repository into the user's template directory.
+use crate::error::AocError;
+use colored::*;
+use serde::Deserialize;
+use std::path::Path;
+
+/// Repository with the upstream `templates/` directory.
+const TEMPLATE_REPO: &str = "AceofSpades5757/aoc";
+
+/// What to do with a template which exists locally, but differs from upstream.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum MergeStrategy {
+    /// Keep the local template.
+    #[default]
+    SkipExisting,
+    /// Replace the local template with upstream.
+    Overwrite,
+    /// Ask for each template.
+    Interactive,
+}
+
+/// What happened to a template.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Created,
+    Updated,
+    Unchanged,
+    Skipped,
+}
+
+impl Outcome {
+    /// What happened to the template at `path`, for the user.
+    pub fn render(self, path: &Path) -> String {
+        match self {
+            Outcome::Created => format!("New {}: {}", path.display(), "Success".green()),
+            Outcome::Updated => format!("Update {}: {}", path.display(), "Success".green()),
+            Outcome::Unchanged => format!("{}: {}", path.display(), "Up to date".green()),
+            Outcome::Skipped => format!("{}: {}", path.display(), "Skipped, changed locally".yellow()),
+        }
+    }
+}
+
+/// An entry of the GitHub contents API.
+#[derive(Debug, Deserialize)]
+struct Entry {
+    name: String,
+    #[serde(rename = "type")]
+    kind: String,
+    download_url: Option<String>,
+}
+
+/// Download the upstream templates as `(name, contents)`, optionally only the one named
+/// `template`.
+pub fn fetch(template: Option<&str>) -> Result<Vec<(String, String)>, AocError> {
+    let client = reqwest::blocking::Client::new();
+    let get = |url: &str| {
+        client
+            .get(url)
+            .header("Accept", "application/vnd.github+json")
+            .header("User-Agent", "AceofSpades5757")
+            .send()
+            .and_then(|response| response.error_for_status())
+    };
+
+    let url = format!("https://api.github.com/repos/{}/contents/templates", TEMPLATE_REPO);
+    let entries: Vec<Entry> = get(&url)
+        .and_then(|response| response.json())
+        .map_err(|e| AocError::network("Failed to list upstream templates", e))?;
+
+    let mut templates = Vec::new();
+    for entry in entries {
+        if entry.kind != "file" || template.is_some_and(|template| template != entry.name) {
+            continue;
+        }
+        let Some(download_url) = entry.download_url else {
+            continue;
+        };
+        let contents = get(&download_url)
+            .and_then(|response| response.text())
+            .map_err(|e| AocError::network(format!("Failed to download template {}", entry.name), e))?;
+        templates.push((entry.name, contents));
+    }
+
+    match template {
+        Some(template) if templates.is_empty() => {
+            Err(AocError::Config(format!("No upstream template named {}", template)))
+        }
+        _ => Ok(templates),
+    }
+}
+
+/// Write `templates` into `dir`, with `strategy` deciding about changed templates. For
+/// `MergeStrategy::Interactive`, `confirm` is asked whether to overwrite a template.
+pub fn apply(
+    dir: &Path,
+    templates: &[(String, String)],
+    strategy: MergeStrategy,
+    mut confirm: impl FnMut(&str) -> bool,
+) -> Result<Vec<(String, Outcome)>, AocError> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| AocError::io(format!("Failed to create {}", dir.display()), e))?;
+
+    let mut outcomes = Vec::new();
+    for (name, contents) in templates {
+        let path = dir.join(name);
+        let outcome = match std::fs::read_to_string(&path) {
+            Ok(existing) if existing == *contents => Outcome::Unchanged,
+            Ok(_) => {
+                let overwrite = match strategy {
+                    MergeStrategy::SkipExisting => false,
+                    MergeStrategy::Overwrite => true,
+                    MergeStrategy::Interactive => confirm(name),
+                };
+                if overwrite {
+                    Outcome::Updated
+                } else {
+                    Outcome::Skipped
+                }
+            }
+            Err(_) => Outcome::Created,
+        };
+        if matches!(outcome, Outcome::Created | Outcome::Updated) {
+            std::fs::write(&path, contents)
+                .map_err(|e| AocError::io(format!("Failed to write {}", path.display()), e))?;
+        }
+        outcomes.push((name.clone(), outcome));
+    }
+    Ok(outcomes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let dir = tmp_dir.path().join("templates");
+        let templates = vec![
+            ("part.rs".to_owned(), "new part".to_owned()),
+            ("lib.rs".to_owned(), "same lib".to_owned()),
+            ("Cargo.toml".to_owned(), "new manifest".to_owned()),
+        ];
+        std::fs::create_dir(&dir).unwrap();
+        std::fs::write(dir.join("part.rs"), "my part").unwrap();
+        std::fs::write(dir.join("lib.rs"), "same lib").unwrap();
+
+        let outcomes = apply(&dir, &templates, MergeStrategy::SkipExisting, |_| unreachable!()).unwrap();
+        assert_eq!(
+            outcomes.iter().map(|(_, outcome)| *outcome).collect::<Vec<_>>(),
+            vec![Outcome::Skipped, Outcome::Unchanged, Outcome::Created]
+        );
+        assert_eq!(std::fs::read_to_string(dir.join("part.rs")).unwrap(), "my part");
+        assert_eq!(std::fs::read_to_string(dir.join("Cargo.toml")).unwrap(), "new manifest");
+        assert!(Outcome::Skipped.render(&dir.join("part.rs")).contains("part.rs: "));
+
+        let mut asked = Vec::new();
+        let outcomes = apply(&dir, &templates, MergeStrategy::Interactive, |name| {
+            asked.push(name.to_owned());
+            true
+        })
+        .unwrap();
+        assert_eq!(asked, vec!["part.rs"]);
+        assert_eq!(outcomes[0], ("part.rs".to_owned(), Outcome::Updated));
+        assert_eq!(std::fs::read_to_string(dir.join("part.rs")).unwrap(), "new part");
+    }
+}
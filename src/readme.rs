@@ -0,0 +1,365 @@
+//! A progress table of the year in its README.md, for `aoc readme`, and a table of its answers
+//! and notes, for `aoc generate-readme`.
+use crate::error::AocError;
+use colored::*;
+use serde::Serialize;
+use std::path::Path;
+
+/// Comments around the table in README.md, so it can be updated without touching the rest.
+const START: &str = "<!-- aoc-table-start -->";
+const END: &str = "<!-- aoc-table-end -->";
+
+/// Comment at both ends of the table of `aoc generate-readme`.
+const MARKER: &str = "<!-- AOC-TABLE -->";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    pub day: u8,
+    /// Name of the day directory, relative to the year directory.
+    pub dir: String,
+    pub title: Option<String>,
+    pub stars: u8,
+    /// Average recorded runtime of each part.
+    pub runtime_ms: [Option<f64>; 2],
+}
+
+/// A row per day in `year_dir`, from the titles in the days' manifests, their stars and the
+/// recorded runtimes.
+pub fn collect(year_dir: &Path, day_format: &str) -> Result<Vec<Row>, AocError> {
+    let stats = crate::stats::collect(year_dir, day_format)?;
+    let mut rows = Vec::new();
+    for (day, day_dir) in crate::scaffold::day_dirs(year_dir, day_format)? {
+        let stats = stats.iter().find(|stats| stats.day == day);
+        rows.push(Row {
+            day,
+            dir: day_dir.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+            title: crate::status::read_title(&day_dir.join("Cargo.toml")),
+            stars: stats.map_or(0, |stats| stats.stars),
+            runtime_ms: stats.map_or([None, None], |stats| stats.runtime_ms),
+        });
+    }
+    Ok(rows)
+}
+
+/// The Markdown table, between the markers, linking to each day directory and puzzle.
+pub fn table(year: u16, rows: &[Row]) -> String {
+    let ms = |ms: Option<f64>| ms.map_or_else(|| "-".to_owned(), |ms| format!("{:.3}", ms));
+    let mut table = format!(
+        "{}\n| Day | Puzzle | Stars | Part 1 (ms) | Part 2 (ms) |\n|--:|---|:-:|--:|--:|\n",
+        START
+    );
+    for row in rows {
+        let url = crate::urls::Page::Puzzle { year, day: row.day }.url(crate::urls::BASE_URL);
+        table.push_str(&format!(
+            "| [{}]({}) | [{}]({}) | {} | {} | {} |\n",
+            row.day,
+            row.dir,
+            row.title.as_deref().unwrap_or("Puzzle"),
+            url,
+            "⭐".repeat(usize::from(row.stars)),
+            ms(row.runtime_ms[0]),
+            ms(row.runtime_ms[1])
+        ));
+    }
+    let stars: u32 = rows.iter().map(|row| u32::from(row.stars)).sum();
+    table.push_str(&format!("\nTotal stars: {}\n{}\n", stars, END));
+    table
+}
+
+/// `readme` with the table between the markers replaced, or appended when there are none.
+pub fn update(readme: &str, table: &str) -> Result<String, AocError> {
+    match (readme.find(START), readme.find(END)) {
+        (Some(start), Some(end)) if start < end => {
+            let after = &readme[end + END.len()..];
+            let after = after.strip_prefix('\n').unwrap_or(after);
+            Ok(format!("{}{}{}", &readme[..start], table, after))
+        }
+        (None, None) if readme.is_empty() => Ok(table.to_owned()),
+        (None, None) => {
+            let separator = if readme.ends_with("\n\n") {
+                ""
+            } else if readme.ends_with('\n') {
+                "\n"
+            } else {
+                "\n\n"
+            };
+            Ok(format!("{}{}{}", readme, separator, table))
+        }
+        _ => Err(AocError::Parse(format!(
+            "Expected {} followed by {}, leaving README.md as it is",
+            START, END
+        ))),
+    }
+}
+
+/// README.md of `year_dir`, if there is one.
+pub fn read(year_dir: &Path) -> Result<Option<String>, AocError> {
+    let path = year_dir.join("README.md");
+    match std::fs::read_to_string(&path) {
+        Ok(readme) => Ok(Some(readme)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(AocError::io(format!("Failed to read {}", path.display()), e)),
+    }
+}
+
+/// Write `readme` as README.md of `year_dir`.
+pub fn write(year_dir: &Path, readme: &str) -> Result<(), AocError> {
+    let path = year_dir.join("README.md");
+    let verb = if path.exists() { "Update" } else { "New" };
+    std::fs::write(&path, readme).map_err(|e| AocError::io(format!("Failed to write {}", path.display()), e))?;
+    println!("{} {}: {}", verb, path.display(), "Success".green());
+    Ok(())
+}
+
+/// README.md of `year_dir` with its progress table updated.
+pub fn with_table(year_dir: &Path, year: u16, day_format: &str) -> Result<String, AocError> {
+    let table = table(year, &collect(year_dir, day_format)?);
+    update(&read(year_dir)?.unwrap_or_default(), &table)
+}
+
+/// README.md of `year_dir` with its table of answers and notes, rendered with the minijinja
+/// `template`, updated.
+pub fn with_answers(year_dir: &Path, year: u16, day_format: &str, template: &str) -> Result<String, AocError> {
+    let table = render_answers(template, year, &collect_answers(year_dir, day_format)?)?;
+    replace_region(&read(year_dir)?.unwrap_or_default(), year, &table)
+}
+
+/// The answers and notes of a day, as Markdown table cells.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AnswerRow {
+    pub day: u8,
+    /// Name of the day directory, relative to the year directory.
+    pub dir: String,
+    pub part_1: Option<String>,
+    pub part_2: Option<String>,
+    /// The first line of `notes.md`.
+    pub notes: Option<String>,
+}
+
+/// A row per day in `year_dir`, from their `answers.toml` and `notes.md`.
+pub fn collect_answers(year_dir: &Path, day_format: &str) -> Result<Vec<AnswerRow>, AocError> {
+    let mut rows = Vec::new();
+    for (day, day_dir) in crate::scaffold::day_dirs(year_dir, day_format)? {
+        let answers = crate::report::read_answers(&day_dir)?;
+        let notes = std::fs::read_to_string(day_dir.join("notes.md")).ok();
+        rows.push(AnswerRow {
+            day,
+            dir: day_dir.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+            part_1: answers.get("part_1").map(|answer| cell(answer)),
+            part_2: answers.get("part_2").map(|answer| cell(answer)),
+            notes: notes.as_deref().and_then(first_line).map(cell),
+        });
+    }
+    Ok(rows)
+}
+
+/// The first line of `notes` with text, without Markdown heading marks.
+fn first_line(notes: &str) -> Option<&str> {
+    notes
+        .lines()
+        .map(|line| line.trim_start_matches('#').trim())
+        .find(|line| !line.is_empty())
+}
+
+/// `text` for a Markdown table cell, with pipes escaped.
+fn cell(text: &str) -> String {
+    text.trim().replace('|', "\\|")
+}
+
+/// The table of `rows` rendered with the minijinja `template`, between the markers.
+pub fn render_answers(template: &str, year: u16, rows: &[AnswerRow]) -> Result<String, AocError> {
+    let mut environment = minijinja::Environment::new();
+    environment
+        .add_template("readme.md", template)
+        .map_err(|e| AocError::Parse(format!("Invalid README template: {}", e)))?;
+    let table = environment
+        .get_template("readme.md")
+        .and_then(|template| template.render(minijinja::context! { year, days => rows }))
+        .map_err(|e| AocError::Parse(format!("Failed to render README: {}", e)))?;
+    let newline = if table.ends_with('\n') { "" } else { "\n" };
+    Ok(format!("{}\n{}{}{}\n", MARKER, table, newline, MARKER))
+}
+
+/// `readme` with the table between the markers replaced, or appended when there are none. An
+/// empty `readme` gets a heading for `year` first.
+pub fn replace_region(readme: &str, year: u16, table: &str) -> Result<String, AocError> {
+    let start = readme.find(MARKER);
+    let end = start.and_then(|start| {
+        let after = start + MARKER.len();
+        readme[after..].find(MARKER).map(|end| after + end)
+    });
+    match (start, end) {
+        (Some(start), Some(end)) => {
+            let after = &readme[end + MARKER.len()..];
+            let after = after.strip_prefix('\n').unwrap_or(after);
+            Ok(format!("{}{}{}", &readme[..start], table, after))
+        }
+        (Some(_), None) => Err(AocError::Parse(format!(
+            "Expected {} at both ends of the table, leaving README.md as it is",
+            MARKER
+        ))),
+        (None, _) if readme.is_empty() => Ok(format!("# Advent of Code {}\n\n{}", year, table)),
+        (None, _) => {
+            let separator = if readme.ends_with("\n\n") {
+                ""
+            } else if readme.ends_with('\n') {
+                "\n"
+            } else {
+                "\n\n"
+            };
+            Ok(format!("{}{}{}", readme, separator, table))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows() -> Vec<Row> {
+        vec![
+            Row {
+                day: 1,
+                dir: "day-01".to_owned(),
+                title: Some("Calorie Counting".to_owned()),
+                stars: 2,
+                runtime_ms: [Some(0.5), Some(1.25)],
+            },
+            Row {
+                day: 2,
+                dir: "day-02".to_owned(),
+                title: None,
+                stars: 0,
+                runtime_ms: [None, None],
+            },
+        ]
+    }
+
+    const TABLE: &str = "<!-- aoc-table-start -->
+| Day | Puzzle | Stars | Part 1 (ms) | Part 2 (ms) |
+|--:|---|:-:|--:|--:|
+| [1](day-01) | [Calorie Counting](https://adventofcode.com/2022/day/1) | ⭐⭐ | 0.500 | 1.250 |
+| [2](day-02) | [Puzzle](https://adventofcode.com/2022/day/2) |  | - | - |
+
+Total stars: 2
+<!-- aoc-table-end -->
+";
+
+    #[test]
+    fn test_table() {
+        assert_eq!(table(2022, &rows()), TABLE);
+    }
+
+    #[test]
+    fn test_update() {
+        let table = table(2022, &rows());
+        assert_eq!(update("", &table).unwrap(), TABLE);
+        let appended = update("# 2022\n\nMy solutions.\n", &table).unwrap();
+        assert_eq!(appended, format!("# 2022\n\nMy solutions.\n\n{}", TABLE));
+
+        // Only the table changes, and updating again changes nothing
+        let readme = "# 2022\n\n<!-- aoc-table-start -->\nold\n<!-- aoc-table-end -->\n\nWritten by hand.\n";
+        let updated = update(readme, &table).unwrap();
+        assert_eq!(updated, format!("# 2022\n\n{}\nWritten by hand.\n", TABLE));
+        assert_eq!(update(&updated, &table).unwrap(), updated);
+
+        assert!(update("<!-- aoc-table-start -->\nno end\n", &table).is_err());
+    }
+
+    #[test]
+    fn test_with_table() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = tmp_dir.path();
+        std::fs::create_dir(year_dir.join("day-01")).unwrap();
+        assert_eq!(read(year_dir).unwrap(), None);
+
+        let readme = with_table(year_dir, 2022, "day-").unwrap();
+        assert!(readme.starts_with(START) && readme.contains("Total stars: 0\n"));
+        write(year_dir, &readme).unwrap();
+        assert_eq!(read(year_dir).unwrap().as_deref(), Some(readme.as_str()));
+        assert_eq!(with_table(year_dir, 2022, "day-").unwrap(), readme);
+    }
+
+    #[test]
+    fn test_collect() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = tmp_dir.path().join("advent-of-code-2022");
+        let day_dir = year_dir.join("day-01");
+        std::fs::create_dir_all(&day_dir).unwrap();
+        std::fs::write(
+            day_dir.join("Cargo.toml"),
+            "[package]\nname = \"day-01\"\n\n[package.metadata.aoc]\ntitle = \"Calorie Counting\"\n",
+        )
+        .unwrap();
+        std::fs::write(day_dir.join("answers.toml"), "part_1 = \"24000\"\n").unwrap();
+
+        let rows = collect(&year_dir, "day-").unwrap();
+        assert_eq!(
+            rows,
+            [Row {
+                day: 1,
+                dir: "day-01".to_owned(),
+                title: Some("Calorie Counting".to_owned()),
+                stars: 1,
+                runtime_ms: [None, None],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_generate() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = tmp_dir.path().join("advent-of-code-2022");
+        for day in ["day-01", "day-02"] {
+            std::fs::create_dir_all(year_dir.join(day)).unwrap();
+        }
+        std::fs::write(year_dir.join("day-01/answers.toml"), "part_1 = \"24000\"\npart_2 = 45000\n").unwrap();
+        std::fs::write(year_dir.join("day-01/notes.md"), "\n# Sums | sorting\n\nMore details.\n").unwrap();
+
+        let rows = collect_answers(&year_dir, "day-").unwrap();
+        assert_eq!(
+            rows[0],
+            AnswerRow {
+                day: 1,
+                dir: "day-01".to_owned(),
+                part_1: Some("24000".to_owned()),
+                part_2: Some("45000".to_owned()),
+                notes: Some("Sums \\| sorting".to_owned()),
+            }
+        );
+
+        let template = include_str!("../templates/readme.md");
+        let readme = with_answers(&year_dir, 2022, "day-", template).unwrap();
+        assert!(readme.starts_with("# Advent of Code 2022\n\n<!-- AOC-TABLE -->\n"));
+
+        let table = render_answers(template, 2022, &rows).unwrap();
+        assert_eq!(
+            table,
+            "<!-- AOC-TABLE -->
+| Day | Part 1 | Part 2 | Notes |
+|--:|---|---|---|
+| [1](day-01) | 24000 | 45000 | Sums \\| sorting |
+| [2](day-02) | - | - |  |
+<!-- AOC-TABLE -->
+"
+        );
+        let table = render_answers("{% for day in days %}{{ day.day }} {% endfor %}", 2022, &rows).unwrap();
+        assert_eq!(table, "<!-- AOC-TABLE -->\n1 2 \n<!-- AOC-TABLE -->\n");
+        assert!(render_answers("{% for %}", 2022, &rows).is_err());
+    }
+
+    #[test]
+    fn test_replace_region() {
+        let table = "<!-- AOC-TABLE -->\nnew\n<!-- AOC-TABLE -->\n";
+        assert_eq!(replace_region("", 2022, table).unwrap(), format!("# Advent of Code 2022\n\n{}", table));
+        assert_eq!(replace_region("# Mine", 2022, table).unwrap(), format!("# Mine\n\n{}", table));
+
+        // Only the table changes, and replacing it again changes nothing
+        let readme = "# Mine\n\n<!-- AOC-TABLE -->\nold\n<!-- AOC-TABLE -->\n\nWritten by hand.\n";
+        let replaced = replace_region(readme, 2022, table).unwrap();
+        assert_eq!(replaced, format!("# Mine\n\n{}\nWritten by hand.\n", table));
+        assert_eq!(replace_region(&replaced, 2022, table).unwrap(), replaced);
+
+        assert!(replace_region("<!-- AOC-TABLE -->\nno end\n", 2022, table).is_err());
+    }
+}
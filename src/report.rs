@@ -0,0 +1,95 @@
+//! HTML report of a year's completed days.
+use crate::error::AocError;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// A completed day: one with an `answers.toml` or `notes.md`.
+#[derive(Debug, Serialize)]
+pub struct DayReport {
+    pub day: u8,
+    /// Answers by part, e.g. `part_1 = "1234"`.
+    pub answers: BTreeMap<String, String>,
+    pub notes: Option<String>,
+}
+
+/// Collect the completed days in `year_dir`, in order.
+pub fn collect(year_dir: &Path, day_format: &str) -> Result<Vec<DayReport>, AocError> {
+    let mut days = Vec::new();
+    for (day, day_dir) in crate::scaffold::day_dirs(year_dir, day_format)? {
+        let answers = read_answers(&day_dir)?;
+        let notes = std::fs::read_to_string(day_dir.join("notes.md")).ok();
+
+        if !answers.is_empty() || notes.is_some() {
+            days.push(DayReport { day, answers, notes });
+        }
+    }
+    Ok(days)
+}
+
+/// Answers by part from `answers.toml` in `day_dir`, if there is one.
+pub fn read_answers(day_dir: &Path) -> Result<BTreeMap<String, String>, AocError> {
+    let answers_path = day_dir.join("answers.toml");
+    let answers: BTreeMap<String, toml::Value> = match std::fs::read_to_string(&answers_path) {
+        Ok(answers) => toml::from_str(&answers)
+            .map_err(|e| AocError::Parse(format!("Invalid {}: {}", answers_path.display(), e)))?,
+        Err(_) => BTreeMap::new(),
+    };
+    Ok(answers
+        .into_iter()
+        .map(|(part, answer)| match answer {
+            toml::Value::String(answer) => (part, answer),
+            answer => (part, answer.to_string()),
+        })
+        .collect())
+}
+
+/// Render the report with the `report.html` template.
+pub fn render(template: &str, year: u16, days: &[DayReport]) -> Result<String, AocError> {
+    let mut environment = minijinja::Environment::new();
+    environment
+        .add_template("report.html", template)
+        .map_err(|e| AocError::Parse(format!("Invalid report template: {}", e)))?;
+    environment
+        .get_template("report.html")
+        .and_then(|template| template.render(minijinja::context! { year, days }))
+        .map_err(|e| AocError::Parse(format!("Failed to render report: {}", e)))
+}
+
+/// Render the report of `year_dir` with the `report.html` template to `output`.
+pub fn write(year_dir: &Path, year: u16, day_format: &str, output: &Path) -> Result<(), AocError> {
+    let html = render(&crate::template::load("report.html")?, year, &collect(year_dir, day_format)?)?;
+    std::fs::write(output, html).map_err(|e| AocError::io(format!("Failed to write {}", output.display()), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = tmp_dir.path();
+        for day in ["day-01", "day-02", "day-03"] {
+            std::fs::create_dir(year_dir.join(day)).unwrap();
+        }
+        std::fs::write(year_dir.join("day-01/answers.toml"), "part_1 = \"24000\"\npart_2 = 45000\n").unwrap();
+        std::fs::write(year_dir.join("day-01/notes.md"), "Sum <the> groups").unwrap();
+        std::fs::write(year_dir.join("day-03/notes.md"), "Only notes").unwrap();
+
+        let days = collect(year_dir, "day-").unwrap();
+        assert_eq!(days.iter().map(|day| day.day).collect::<Vec<_>>(), vec![1, 3]);
+        assert_eq!(days[0].answers["part_2"], "45000");
+
+        let html = render(include_str!("../templates/report.html"), 2022, &days).unwrap();
+        assert!(html.contains("<title>Advent of Code 2022</title>"));
+        assert!(html.contains("<td>24000</td>"));
+        assert!(html.contains("<td>45000</td>"));
+        assert!(html.contains("Sum &lt;the&gt; groups"));
+        assert!(html.contains("Only notes"));
+
+        let output = year_dir.join("report.html");
+        write(year_dir, 2022, "day-", &output).unwrap();
+        assert!(std::fs::read_to_string(output).unwrap().contains("Advent of Code 2022"));
+    }
+}
@@ -0,0 +1,291 @@
+//! Rewriting a copied part's source to refer to the new part.
+//!
+//! Mentions of the old part in comments and string literals are replaced, as are test function
+//! names and `EXPECTED*` constants, which are only used within the file. Any other identifier
+//! mentioning the old part could be used elsewhere, so it's kept and reported instead.
+
+/// Result of rewriting a part's source.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Rewrite {
+    pub source: String,
+    /// Mentions of the old part which were kept, e.g. "line 3: kept identifier `part_1`".
+    pub kept: Vec<String>,
+}
+
+/// Rewrite the source of part `from` for part `to`.
+pub fn rewrite_part(source: &str, from: u8, to: u8) -> Rewrite {
+    let mut rewritten = String::with_capacity(source.len());
+    let mut kept = Vec::new();
+    let chars: Vec<char> = source.chars().collect();
+    let mut i = 0;
+    let mut line = 1;
+    // Last identifier in code, to recognize the arguments of `include_str!` and friends
+    let mut last_ident = String::new();
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let start = i;
+
+        if c == '/' && next == Some('/') {
+            // Line comment
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            rewritten.push_str(&replace_text(&collect(&chars[start..i]), from, to));
+        } else if c == '/' && next == Some('*') {
+            // Block comment, which can be nested
+            let mut depth = 0;
+            while i < chars.len() {
+                if chars[i] == '/' && chars.get(i + 1) == Some(&'*') {
+                    depth += 1;
+                    i += 2;
+                } else if chars[i] == '*' && chars.get(i + 1) == Some(&'/') {
+                    depth -= 1;
+                    i += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    i += 1;
+                }
+            }
+            rewritten.push_str(&replace_text(&collect(&chars[start..i]), from, to));
+        } else if c == '"' || is_raw_string_start(&chars, i) {
+            i = string_end(&chars, i);
+            let literal = collect(&chars[start..i]);
+            if last_ident.starts_with("include") {
+                // Paths in `include_str!` and friends refer to files, leave them alone
+                if literal != replace_text(&literal, from, to) {
+                    kept.push(format!("line {}: kept path {}", line, literal));
+                }
+                rewritten.push_str(&literal);
+            } else {
+                rewritten.push_str(&replace_text(&literal, from, to));
+            }
+        } else if c == '\'' {
+            // Char literal or lifetime
+            i += 1;
+            if chars.get(i) == Some(&'\\') {
+                while i < chars.len() && chars[i] != '\'' {
+                    i += if chars[i] == '\\' { 2 } else { 1 };
+                }
+                i += 1;
+            } else if chars.get(i + 1) == Some(&'\'') {
+                i += 2;
+            }
+            rewritten.push_str(&collect(&chars[start..i.min(chars.len())]));
+        } else if c.is_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let ident = collect(&chars[start..i]);
+            let renamed = rename_ident(&ident, from, to);
+            if renamed == ident && mentions_part(&ident, from) {
+                kept.push(format!("line {}: kept identifier `{}`", line, ident));
+            }
+            rewritten.push_str(&renamed);
+            last_ident = ident;
+        } else {
+            i += 1;
+            rewritten.push(c);
+            if !c.is_whitespace() && c != '!' && c != '(' {
+                last_ident.clear();
+            }
+        }
+
+        line += chars[start..i.min(chars.len())]
+            .iter()
+            .filter(|c| **c == '\n')
+            .count();
+    }
+
+    Rewrite {
+        source: rewritten,
+        kept,
+    }
+}
+
+fn collect(chars: &[char]) -> String {
+    chars.iter().collect()
+}
+
+/// Whether a raw string (`r"..."`, `r#"..."#`, `br"..."`) starts at `i`.
+fn is_raw_string_start(chars: &[char], i: usize) -> bool {
+    let mut j = i;
+    if chars[j] == 'b' {
+        j += 1;
+    }
+    if chars.get(j) != Some(&'r') {
+        return false;
+    }
+    if i > 0 && (chars[i - 1].is_alphanumeric() || chars[i - 1] == '_') {
+        return false;
+    }
+    j += 1;
+    while chars.get(j) == Some(&'#') {
+        j += 1;
+    }
+    chars.get(j) == Some(&'"')
+}
+
+/// Index just past the end of the string literal starting at `i`.
+fn string_end(chars: &[char], i: usize) -> usize {
+    if chars[i] == '"' {
+        let mut j = i + 1;
+        while j < chars.len() {
+            match chars[j] {
+                '\\' => j += 2,
+                '"' => return j + 1,
+                _ => j += 1,
+            }
+        }
+        return chars.len();
+    }
+
+    // Raw string: count the hashes, and look for the closing quote followed by as many hashes
+    let mut j = i;
+    while chars[j] != '"' {
+        j += 1;
+    }
+    let hashes = chars[i..j].iter().filter(|c| **c == '#').count();
+    j += 1;
+    while j < chars.len() {
+        if chars[j] == '"' && chars[j + 1..].iter().take(hashes).filter(|c| **c == '#').count() == hashes {
+            return j + 1 + hashes;
+        }
+        j += 1;
+    }
+    chars.len()
+}
+
+/// Replace `needle` with `replacement`, but not where it's followed by another digit.
+fn replace_word(text: &str, needle: &str, replacement: &str) -> String {
+    let mut replaced = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(index) = rest.find(needle) {
+        let after = &rest[index + needle.len()..];
+        replaced.push_str(&rest[..index]);
+        if after.starts_with(|c: char| c.is_ascii_digit()) {
+            replaced.push_str(needle);
+        } else {
+            replaced.push_str(replacement);
+        }
+        rest = after;
+    }
+    replaced.push_str(rest);
+    replaced
+}
+
+/// Replace mentions of part `from` in prose or string literals.
+fn replace_text(text: &str, from: u8, to: u8) -> String {
+    ["part_{}", "part {}", "Part {}", "PART_{}", "PART {}", "part{}", "Part{}"]
+        .iter()
+        .fold(text.to_owned(), |text, pattern| {
+            replace_word(
+                &text,
+                &pattern.replace("{}", &from.to_string()),
+                &pattern.replace("{}", &to.to_string()),
+            )
+        })
+}
+
+fn mentions_part(ident: &str, part: u8) -> bool {
+    replace_text(ident, part, part.wrapping_add(1)) != ident
+}
+
+/// Rename test functions and `EXPECTED*` constants, leave other identifiers as they are.
+fn rename_ident(ident: &str, from: u8, to: u8) -> String {
+    if ident.starts_with("test") {
+        return replace_text(ident, from, to);
+    }
+    if ident.starts_with("EXPECTED") && ident.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_') {
+        if mentions_part(ident, from) {
+            return replace_text(ident, from, to);
+        }
+        if let Some(name) = ident.strip_suffix(&format!("_{}", from)) {
+            return format!("{}_{}", name, to);
+        }
+        return format!("{}_PART_{}", ident, to);
+    }
+    ident.to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrite_comments_and_strings() {
+        let source = r##"//! Advent of Code 2022, Day 1: Part 1
+/* Part 1 is about /* nested */ part 1 and part 10 */
+fn main() {
+    let label = "part_1";
+    let raw = r#"Part 1 answer: {}"#;
+    let c = '"';
+    println!("Part 1: {}", label);
+}
+"##;
+        let rewrite = rewrite_part(source, 1, 2);
+        assert_eq!(
+            rewrite.source,
+            r##"//! Advent of Code 2022, Day 1: Part 2
+/* Part 2 is about /* nested */ part 2 and part 10 */
+fn main() {
+    let label = "part_2";
+    let raw = r#"Part 2 answer: {}"#;
+    let c = '"';
+    println!("Part 2: {}", label);
+}
+"##
+        );
+        assert!(rewrite.kept.is_empty());
+    }
+
+    #[test]
+    fn test_rewrite_tests_and_constants() {
+        let source = r#"const EXPECTED_EXAMPLE: i32 = 24000;
+const EXPECTED_PART_1: i32 = 1;
+
+#[test]
+fn test_part_1_example() {
+    assert_eq!(solve(EXAMPLE), EXPECTED_EXAMPLE);
+    assert_eq!(solve(INPUT), EXPECTED_PART_1);
+}
+"#;
+        let rewrite = rewrite_part(source, 1, 2);
+        assert_eq!(
+            rewrite.source,
+            r#"const EXPECTED_EXAMPLE_PART_2: i32 = 24000;
+const EXPECTED_PART_2: i32 = 1;
+
+#[test]
+fn test_part_2_example() {
+    assert_eq!(solve(EXAMPLE), EXPECTED_EXAMPLE_PART_2);
+    assert_eq!(solve(INPUT), EXPECTED_PART_2);
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_rewrite_keeps_code_identifiers() {
+        let source = r#"use day_01::part_1;
+
+fn main() {
+    let input = include_str!("../../part_1.txt");
+    println!("{}", part_1::solve(input));
+}
+"#;
+        let rewrite = rewrite_part(source, 1, 2);
+        assert_eq!(rewrite.source, source);
+        assert_eq!(
+            rewrite.kept,
+            vec![
+                "line 1: kept identifier `part_1`",
+                "line 4: kept path \"../../part_1.txt\"",
+                "line 5: kept identifier `part_1`",
+            ]
+        );
+    }
+}
@@ -0,0 +1,298 @@
+//! Rolling over to the next event's year directory, for `aoc new-year`.
+use crate::error::AocError;
+use crate::clean::Formats;
+use crate::scaffold::{init_year, update_workspace_members};
+use colored::*;
+use std::path::{Path, PathBuf};
+use toml_edit::Document;
+
+/// Files of a year directory carried over to the next one with `--copy-config`.
+const CONFIG_FILES: [&str; 7] = [
+    ".cargo/config.toml",
+    ".gitignore",
+    ".rustfmt.toml",
+    "clippy.toml",
+    "rust-toolchain",
+    "rust-toolchain.toml",
+    "rustfmt.toml",
+];
+
+/// Where the next year directory goes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plan {
+    /// Directory to create the year directory in.
+    pub parent: PathBuf,
+    pub year: u16,
+    /// The latest year directory already there, to copy from.
+    pub previous: Option<PathBuf>,
+}
+
+/// What to carry over from the previous year directory.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Carry {
+    /// A crate of shared code, by its path in the year directory.
+    pub utils: Option<String>,
+    /// Whether to copy the config files, like `rustfmt.toml`.
+    pub config: bool,
+}
+
+/// The next year directory from `dir`: beside the year directory `dir` is in, or in `dir` when it
+/// contains year directories. Its year is `year`, or the one after the latest year there.
+pub fn plan(dir: &Path, formats: &Formats, year: Option<u16>) -> Result<Plan, AocError> {
+    let parent = match crate::env::resolve_year_dir(dir, formats.day, formats.year) {
+        Ok(year_dir) if crate::env::parse_year(&year_dir, formats.year).is_ok() => {
+            year_dir.parent().map_or_else(|| dir.to_owned(), Path::to_owned)
+        }
+        _ => dir.to_owned(),
+    };
+    let previous = year_dirs(&parent, formats)?.pop();
+    let year = match (year, &previous) {
+        (Some(year), _) => year,
+        (None, Some((latest, _))) => latest + 1,
+        (None, None) => {
+            return Err(AocError::Environment(format!(
+                "No year directory in {}, pass the year with --year",
+                parent.display()
+            )))
+        }
+    };
+    Ok(Plan { parent, year, previous: previous.map(|(_, path)| path) })
+}
+
+/// Year directories in `parent` with their year, sorted by year.
+fn year_dirs(parent: &Path, formats: &Formats) -> Result<Vec<(u16, PathBuf)>, AocError> {
+    let entries =
+        std::fs::read_dir(parent).map_err(|e| AocError::io(format!("Failed to read {}", parent.display()), e))?;
+    let mut years: Vec<(u16, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .filter_map(|path| Some((crate::env::parse_year(&path, formats.year).ok()?, path)))
+        .collect();
+    years.sort();
+    Ok(years)
+}
+
+/// The calendar year `now` is in, in seconds since the Unix epoch, in US Eastern time.
+fn current_year(now: u64) -> u16 {
+    // 1 January is 31 days after 1 December of the year before
+    (1971..u16::MAX)
+        .take_while(|year| crate::unlock::unlock_time(year - 1, 32) <= now)
+        .last()
+        .unwrap_or(1970)
+}
+
+/// Fail for a year more than one year in the future, whose event can't have been announced.
+pub fn check_announced(year: u16, now: u64) -> Result<(), AocError> {
+    if year > current_year(now) + 1 {
+        return Err(AocError::Config(format!(
+            "Advent of Code {} hasn't been announced yet. Use --force to create it anyway.",
+            year
+        )));
+    }
+    Ok(())
+}
+
+/// Create the year directory of `plan`, named with `year_format`, carrying `carry` over from the
+/// previous one. An existing year directory is only used with `force`.
+pub fn create(
+    plan: &Plan,
+    year_format: &str,
+    carry: &Carry,
+    force: bool,
+    author: Option<&str>,
+) -> Result<PathBuf, AocError> {
+    let year_dir = plan.parent.join(format!("{}{}", year_format, plan.year));
+    if year_dir.exists() && !force {
+        return Err(AocError::Environment(format!(
+            "{} already exists. Use --force to continue anyway.",
+            year_dir.display()
+        )));
+    }
+    let previous = || {
+        plan.previous
+            .as_deref()
+            .ok_or_else(|| AocError::Environment("No previous year directory to copy from".to_owned()))
+    };
+    if let Some(utils) = &carry.utils {
+        let from = previous()?.join(utils);
+        if !from.join("Cargo.toml").is_file() {
+            return Err(AocError::Environment(format!("{} isn't a crate", from.display())));
+        }
+    }
+
+    init_year(&year_dir, plan.year, author)?;
+    if let Some(utils) = &carry.utils {
+        let to = year_dir.join(utils);
+        copy_dir(&previous()?.join(utils), &to)?;
+        println!("New {}: {}", to.display(), "Success".green());
+        add_member(&year_dir, utils, plan.year, author)?;
+    }
+    if carry.config {
+        for path in copy_config(previous()?, &year_dir)? {
+            println!("New {}: {}", path.display(), "Success".green());
+        }
+    }
+    Ok(year_dir)
+}
+
+/// Add `member` to the workspace of `year_dir`, unless it's a member already.
+fn add_member(year_dir: &Path, member: &str, year: u16, author: Option<&str>) -> Result<(), AocError> {
+    let cargo_toml_path = year_dir.join("Cargo.toml");
+    let cargo_toml = std::fs::read_to_string(&cargo_toml_path)
+        .ok()
+        .and_then(|cargo_toml| cargo_toml.parse::<Document>().ok());
+    let is_member = cargo_toml.as_ref().is_some_and(|cargo_toml| {
+        cargo_toml["workspace"]["members"]
+            .as_array()
+            .is_some_and(|members| members.iter().any(|name| name.as_str() == Some(member)))
+    });
+    if is_member {
+        return Ok(());
+    }
+    update_workspace_members(&cargo_toml_path, cargo_toml, member, year, author)
+}
+
+/// Copy the directory `from` to `to`, leaving out build artifacts in `target`.
+pub fn copy_dir(from: &Path, to: &Path) -> Result<(), AocError> {
+    std::fs::create_dir_all(to).map_err(|e| AocError::io(format!("Failed to create {}", to.display()), e))?;
+    let entries = std::fs::read_dir(from).map_err(|e| AocError::io(format!("Failed to read {}", from.display()), e))?;
+    for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+        let Some(name) = path.file_name() else {
+            continue;
+        };
+        if path.is_dir() {
+            if name != "target" {
+                copy_dir(&path, &to.join(name))?;
+            }
+        } else {
+            std::fs::copy(&path, to.join(name))
+                .map_err(|e| AocError::io(format!("Failed to copy {}", path.display()), e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Copy the config files of the year directory `previous` to `year_dir`, returning the new paths.
+pub fn copy_config(previous: &Path, year_dir: &Path) -> Result<Vec<PathBuf>, AocError> {
+    let mut copied = Vec::new();
+    for name in CONFIG_FILES {
+        let from = previous.join(name);
+        if !from.is_file() {
+            continue;
+        }
+        let to = year_dir.join(name);
+        if let Some(dir) = to.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| AocError::io(format!("Failed to create {}", dir.display()), e))?;
+        }
+        std::fs::copy(&from, &to).map_err(|e| AocError::io(format!("Failed to copy {}", from.display()), e))?;
+        copied.push(to);
+    }
+    Ok(copied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scaffold::read_workspace_year;
+
+    const FORMATS: Formats = Formats { year: "advent-of-code-", day: "day-" };
+
+    #[test]
+    fn test_plan() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let root = tmp_dir.path();
+        for dir in ["advent-of-code-2021/day-01", "advent-of-code-2022/day-01", "notes"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        let expected = Plan { parent: root.to_owned(), year: 2023, previous: Some(root.join("advent-of-code-2022")) };
+
+        // Beside the year directory, from any year or day directory
+        assert_eq!(plan(&root.join("advent-of-code-2022"), &FORMATS, None).unwrap(), expected);
+        assert_eq!(plan(&root.join("advent-of-code-2021/day-01"), &FORMATS, None).unwrap(), expected);
+        // In the parent
+        assert_eq!(plan(root, &FORMATS, None).unwrap(), expected);
+        assert_eq!(plan(root, &FORMATS, Some(2025)).unwrap(), Plan { year: 2025, ..expected });
+
+        let err = plan(&root.join("notes"), &FORMATS, None).unwrap_err().to_string();
+        assert!(err.contains("--year"));
+        assert_eq!(
+            plan(&root.join("notes"), &FORMATS, Some(2023)).unwrap(),
+            Plan { parent: root.join("notes"), year: 2023, previous: None }
+        );
+    }
+
+    #[test]
+    fn test_check_announced() {
+        // 1 December 2023, and the last second of 2023 in EST
+        let december = crate::unlock::unlock_time(2023, 1);
+        let new_year = crate::unlock::unlock_time(2023, 32);
+        assert_eq!(current_year(december), 2023);
+        assert_eq!(current_year(new_year - 1), 2023);
+        assert_eq!(current_year(new_year), 2024);
+
+        assert!(check_announced(2023, december).is_ok());
+        assert!(check_announced(2024, december).is_ok());
+        assert!(check_announced(2025, december).unwrap_err().to_string().contains("--force"));
+    }
+
+    #[test]
+    fn test_copy() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let (previous, year_dir) = (tmp_dir.path().join("advent-of-code-2022"), tmp_dir.path().join("advent-of-code-2023"));
+        for (path, contents) in [
+            ("utils/Cargo.toml", "[package]\nname = \"utils\"\n"),
+            ("utils/src/lib.rs", "pub fn parse() {}\n"),
+            ("utils/target/debug/libutils.rlib", ""),
+            ("rustfmt.toml", "max_width = 120\n"),
+            (".cargo/config.toml", "[build]\n"),
+        ] {
+            std::fs::create_dir_all(previous.join(path).parent().unwrap()).unwrap();
+            std::fs::write(previous.join(path), contents).unwrap();
+        }
+
+        copy_dir(&previous.join("utils"), &year_dir.join("utils")).unwrap();
+        assert_eq!(std::fs::read_to_string(year_dir.join("utils/src/lib.rs")).unwrap(), "pub fn parse() {}\n");
+        assert!(year_dir.join("utils/Cargo.toml").exists());
+        assert!(!year_dir.join("utils/target").exists());
+
+        let copied = copy_config(&previous, &year_dir).unwrap();
+        assert_eq!(copied, [year_dir.join(".cargo/config.toml"), year_dir.join("rustfmt.toml")]);
+        assert_eq!(std::fs::read_to_string(year_dir.join("rustfmt.toml")).unwrap(), "max_width = 120\n");
+    }
+
+    #[test]
+    fn test_create() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let previous = tmp_dir.path().join("advent-of-code-2022");
+        for (path, contents) in [("utils/Cargo.toml", "[package]\nname = \"utils\"\n"), ("rustfmt.toml", "")] {
+            std::fs::create_dir_all(previous.join(path).parent().unwrap()).unwrap();
+            std::fs::write(previous.join(path), contents).unwrap();
+        }
+        let plan = plan(tmp_dir.path(), &FORMATS, None).unwrap();
+        let carry = Carry { utils: Some("utils".to_owned()), config: true };
+        let members = |year_dir: &Path| {
+            let cargo_toml = std::fs::read_to_string(year_dir.join("Cargo.toml")).unwrap();
+            let cargo_toml = cargo_toml.parse::<Document>().unwrap();
+            let members = cargo_toml["workspace"]["members"].as_array().unwrap();
+            members.iter().map(|member| member.as_str().unwrap().to_owned()).collect::<Vec<_>>()
+        };
+
+        let year_dir = create(&plan, FORMATS.year, &carry, false, None).unwrap();
+        assert_eq!(year_dir, tmp_dir.path().join("advent-of-code-2023"));
+        assert_eq!(read_workspace_year(&year_dir.join("Cargo.toml")), Some(2023));
+        assert_eq!(members(&year_dir), ["utils"]);
+        assert!(year_dir.join("utils/Cargo.toml").is_file());
+        assert!(year_dir.join("rustfmt.toml").is_file());
+
+        let err = create(&plan, FORMATS.year, &carry, false, None).unwrap_err();
+        assert!(err.to_string().contains("--force"));
+        // Carried over again, utils stays a member only once
+        create(&plan, FORMATS.year, &carry, true, None).unwrap();
+        assert_eq!(members(&year_dir), ["utils"]);
+
+        let carry = Carry { utils: Some("missing".to_owned()), config: false };
+        let err = create(&plan, FORMATS.year, &carry, true, None).unwrap_err();
+        assert!(matches!(err, AocError::Environment(_)));
+    }
+}
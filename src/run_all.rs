@@ -0,0 +1,178 @@
+//! Running every part of every day in a year, for `aoc run --all`.
+use crate::error::AocError;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// How to print the results.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Aligned table.
+    #[default]
+    Table,
+    /// JSON array, one object per part.
+    Json,
+}
+
+/// Result of running one part.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PartResult {
+    pub day: u8,
+    pub part: u8,
+    pub answer: Option<String>,
+    pub elapsed_ms: Option<f64>,
+    pub error: Option<String>,
+}
+
+impl PartResult {
+    fn error(day: u8, part: u8, error: String) -> Self {
+        PartResult {
+            day,
+            part,
+            answer: None,
+            elapsed_ms: None,
+            error: Some(error),
+        }
+    }
+}
+
+/// A part to run: its day, part number and day directory.
+struct Job {
+    day: u8,
+    part: u8,
+    day_dir: PathBuf,
+}
+
+/// Run the parts of all days in `year_dir` for which `include(day, part)`, `parallel` at a time,
+/// ordered by day and part.
+///
+/// The year's workspace is built once. As the days' binaries share names, and so their path in
+/// the workspace's target directory, each is then copied to a temporary directory to run from.
+/// Missing input and failing builds or runs are reported per part.
+pub fn run(
+    year_dir: &Path,
+    day_format: &str,
+    cargo_args: &[String],
+    parallel: usize,
+    include: impl Fn(u8, u8) -> bool,
+) -> Result<Vec<PartResult>, AocError> {
+    if year_dir.join("Cargo.toml").exists() {
+        // Failures show up again when building the parts
+        crate::runner::cargo_build(year_dir, &["--workspace", "--bins", "--keep-going"], cargo_args)?;
+    }
+
+    let tmp_dir = std::env::temp_dir().join(format!("aoc-run-all-{}", std::process::id()));
+    std::fs::create_dir_all(&tmp_dir).map_err(|e| AocError::io(format!("Failed to create {}", tmp_dir.display()), e))?;
+
+    let mut results = Vec::new();
+    let mut jobs = Vec::new();
+    for (day, day_dir) in crate::scaffold::day_dirs(year_dir, day_format)? {
+        for part in 1..=2 {
+            if !include(day, part) || !day_dir.join(format!("src/bin/part_{}.rs", part)).exists() {
+                continue;
+            }
+            if !day_dir.join("input.txt").exists() {
+                results.push(PartResult::error(day, part, "No input.txt".to_owned()));
+                continue;
+            }
+            // Already built, so this only puts the day's binary in place
+            let executable = crate::runner::build_part(&day_dir, part, cargo_args).and_then(|executable| {
+                let copy = tmp_dir.join(format!("day_{:02}_part_{}", day, part));
+                std::fs::copy(&executable, &copy)
+                    .map(|_| copy)
+                    .map_err(|e| AocError::io(format!("Failed to copy {}", executable.display()), e))
+            });
+            match executable {
+                Ok(executable) => jobs.push((Job { day, part, day_dir: day_dir.clone() }, executable)),
+                Err(err) => results.push(PartResult::error(day, part, err.to_string())),
+            }
+        }
+    }
+
+    let queue = std::sync::Mutex::new(jobs);
+    let results = std::sync::Mutex::new(results);
+    std::thread::scope(|scope| {
+        for _ in 0..parallel.max(1) {
+            scope.spawn(|| loop {
+                let Some((job, executable)) = queue.lock().unwrap().pop() else {
+                    break;
+                };
+                let result = run_job(&job, &executable);
+                results.lock().unwrap().push(result);
+            });
+        }
+    });
+    let _ = std::fs::remove_dir_all(&tmp_dir);
+
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|result| (result.day, result.part));
+    Ok(results)
+}
+
+fn run_job(job: &Job, executable: &Path) -> PartResult {
+    let output = match crate::runner::execute_part(executable, &job.day_dir, &[]) {
+        Ok(output) => output,
+        Err(err) => return PartResult::error(job.day, job.part, err.to_string()),
+    };
+    if !output.status.success() {
+        return PartResult::error(job.day, job.part, format!("part_{} failed: {}", job.part, output.status));
+    }
+
+    PartResult {
+        day: job.day,
+        part: job.part,
+        answer: Some(output.answer().to_owned()),
+        elapsed_ms: Some(output.elapsed.as_secs_f64() * 1_000.0),
+        error: None,
+    }
+}
+
+pub fn render_table(results: &[PartResult]) -> String {
+    let answer_width = results
+        .iter()
+        .filter_map(|result| result.answer.as_ref().map(|answer| answer.len()))
+        .chain(std::iter::once("Answer".len()))
+        .max()
+        .unwrap_or_default();
+    let mut table = format!(
+        "{:>3} {:>4} {:<answer_width$} {:>12}\n",
+        "Day", "Part", "Answer", "Time (ms)"
+    );
+    for result in results {
+        match (&result.answer, result.elapsed_ms) {
+            (Some(answer), Some(elapsed_ms)) => table.push_str(&format!(
+                "{:>3} {:>4} {:<answer_width$} {:>12.3}\n",
+                result.day, result.part, answer, elapsed_ms
+            )),
+            _ => table.push_str(&format!(
+                "{:>3} {:>4} Error: {}\n",
+                result.day,
+                result.part,
+                result.error.as_deref().unwrap_or_default()
+            )),
+        }
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_table() {
+        let results = vec![
+            PartResult {
+                day: 1,
+                part: 1,
+                answer: Some("24000".to_owned()),
+                elapsed_ms: Some(1.5),
+                error: None,
+            },
+            PartResult::error(2, 1, "No input.txt".to_owned()),
+        ];
+        assert_eq!(
+            render_table(&results),
+            "Day Part Answer    Time (ms)\n  1    1 24000         1.500\n  2    1 Error: No input.txt\n"
+        );
+    }
+}
@@ -0,0 +1,406 @@
+//! Building and running the parts and tests of a day with cargo.
+use crate::error::AocError;
+use colored::*;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use toml_edit::Document;
+
+/// Output of running a part's binary.
+pub struct RunOutput {
+    pub stdout: String,
+    pub status: std::process::ExitStatus,
+    /// Wall-clock runtime of the binary, without compile time.
+    pub elapsed: std::time::Duration,
+    /// Time spent in `cargo build`.
+    pub build_elapsed: std::time::Duration,
+}
+
+impl RunOutput {
+    /// The part's answer: its output, trimmed.
+    pub fn answer(&self) -> &str {
+        self.stdout.trim()
+    }
+}
+
+/// Build the binary for `part` in `day_dir`, returning the path to the executable.
+///
+/// `cargo_args` are passed on to `cargo build`.
+pub fn build_part(day_dir: &Path, part: u8, cargo_args: &[String]) -> Result<PathBuf, AocError> {
+    tracing::Span::current().record("part", part);
+    build_bin(day_dir, &format!("part_{}", part), cargo_args)
+}
+
+/// Build the binary `bin` (e.g. `part_2_fast`) in `day_dir`, returning the path to the executable.
+pub fn build_bin(day_dir: &Path, bin: &str, cargo_args: &[String]) -> Result<PathBuf, AocError> {
+    let (success, executables) = cargo_build(day_dir, &["--bin", bin], cargo_args)?;
+    if !success {
+        return Err(AocError::Build(format!("Failed to build {}", bin)));
+    }
+
+    executables
+        .into_iter()
+        .find_map(|executable| (executable.name == bin).then_some(executable.path))
+        .ok_or_else(|| AocError::Build(format!("Cargo didn't build an executable for {}", bin)))
+}
+
+/// The executable of `part` already built by cargo for `day_dir`, in debug or `release` mode, in
+/// the target directory of its crate or workspace.
+pub fn prebuilt_part(day_dir: &Path, part: u8, release: bool) -> Result<PathBuf, AocError> {
+    tracing::Span::current().record("part", part);
+    let output = std::process::Command::new("cargo")
+        .args(["metadata", "--no-deps", "--format-version", "1"])
+        .current_dir(day_dir)
+        .stderr(std::process::Stdio::inherit())
+        .output()
+        .map_err(|e| AocError::io("Failed to run cargo metadata", e))?;
+    let metadata: serde_json::Value =
+        serde_json::from_slice(&output.stdout).map_err(|e| AocError::Parse(format!("Invalid cargo metadata: {}", e)))?;
+    let target_dir = metadata["target_directory"]
+        .as_str()
+        .ok_or_else(|| AocError::Parse("cargo metadata has no target directory".to_owned()))?;
+    let profile = if release { "release" } else { "debug" };
+    let executable = Path::new(target_dir)
+        .join(profile)
+        .join(format!("part_{}{}", part, std::env::consts::EXE_SUFFIX));
+    if !executable.is_file() {
+        let release = if release { " --release" } else { "" };
+        return Err(AocError::Environment(format!(
+            "No pre-built {}, build it with `cargo build --bin part_{}{}`",
+            executable.display(),
+            part,
+            release
+        )));
+    }
+    Ok(executable)
+}
+
+/// Whether a file in `src` of `day_dir` was changed after `executable` was built.
+pub fn is_stale(executable: &Path, day_dir: &Path) -> bool {
+    fn newest(dir: &Path) -> Option<std::time::SystemTime> {
+        std::fs::read_dir(dir)
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let path = entry.path();
+                if path.is_dir() {
+                    newest(&path)
+                } else {
+                    entry.metadata().and_then(|metadata| metadata.modified()).ok()
+                }
+            })
+            .max()
+    }
+    let built = std::fs::metadata(executable).and_then(|metadata| metadata.modified());
+    match (built, newest(&day_dir.join("src"))) {
+        (Ok(built), Some(changed)) => changed > built,
+        _ => false,
+    }
+}
+
+/// The executable of `part` in `day_dir`, in release mode with `release`: with `no_compile` the one
+/// already built, warning when the code changed since, or else one built now with `cargo_args`.
+pub fn part_executable(
+    day_dir: &Path,
+    part: u8,
+    release: bool,
+    no_compile: bool,
+    cargo_args: &[String],
+) -> Result<PathBuf, AocError> {
+    if no_compile {
+        let executable = prebuilt_part(day_dir, part, release)?;
+        if is_stale(&executable, day_dir) {
+            eprintln!("{}", format!("{} is older than the code in src", executable.display()).yellow());
+        }
+        return Ok(executable);
+    }
+    let release = release.then(|| "--release".to_owned());
+    let cargo_args: Vec<String> = release.into_iter().chain(cargo_args.iter().cloned()).collect();
+    build_part(day_dir, part, &cargo_args)
+}
+
+/// An executable built by cargo.
+pub struct Executable {
+    /// Name of the binary target, e.g. `part_1`.
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// Run `cargo build` in `dir`, returning whether it succeeded and the executables it built, which
+/// can be some even if it failed with `--keep-going`.
+pub fn cargo_build(dir: &Path, args: &[&str], cargo_args: &[String]) -> Result<(bool, Vec<Executable>), AocError> {
+    let output = std::process::Command::new("cargo")
+        .args(["build", "--message-format=json-render-diagnostics"])
+        .args(args)
+        .args(cargo_args)
+        .current_dir(dir)
+        .stderr(std::process::Stdio::inherit())
+        .output()
+        .map_err(|e| AocError::io("Failed to run cargo build", e))?;
+
+    let executables = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter(|message| message["reason"] == "compiler-artifact")
+        .filter_map(|message| {
+            Some(Executable {
+                name: message["target"]["name"].as_str()?.to_owned(),
+                path: PathBuf::from(message["executable"].as_str()?),
+            })
+        })
+        .collect();
+    Ok((output.status.success(), executables))
+}
+
+/// Build and run the binary for `part` in `day_dir`, capturing its output. A failing build is an
+/// error, a failing binary is not: check the output's `status`.
+///
+/// With `input_path`, the binary gets it as `AOC_INPUT_PATH` to read instead of `input.txt`.
+pub fn run_part(
+    day_dir: &Path,
+    part: u8,
+    cargo_args: &[String],
+    input_path: Option<&Path>,
+) -> Result<RunOutput, AocError> {
+    let build_start = std::time::Instant::now();
+    let executable = build_part(day_dir, part, cargo_args)?;
+    let build_elapsed = build_start.elapsed();
+
+    let envs: Vec<(&str, &std::ffi::OsStr)> =
+        input_path.map(|input_path| ("AOC_INPUT_PATH", input_path.as_os_str())).into_iter().collect();
+    let mut output = execute_part(&executable, day_dir, &envs)?;
+    output.build_elapsed = build_elapsed;
+    Ok(output)
+}
+
+/// Run a part's built `executable` in `day_dir` with the environment variables `envs`, capturing
+/// its output.
+pub fn execute_part(
+    executable: &Path,
+    day_dir: &Path,
+    envs: &[(&str, &std::ffi::OsStr)],
+) -> Result<RunOutput, AocError> {
+    let start = std::time::Instant::now();
+    let output = std::process::Command::new(executable)
+        .envs(envs.iter().copied())
+        .current_dir(day_dir)
+        .stderr(std::process::Stdio::inherit())
+        .output()
+        .map_err(|e| AocError::io(format!("Failed to run {}", executable.display()), e))?;
+    let elapsed = start.elapsed();
+
+    Ok(RunOutput {
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        status: output.status,
+        elapsed,
+        build_elapsed: std::time::Duration::ZERO,
+    })
+}
+
+/// The answer of `part`'s built `executable` for `candidate`, given to it as `AOC_CANDIDATE`.
+pub fn candidate_answer(executable: &Path, day_dir: &Path, part: u8, candidate: i64) -> Result<String, AocError> {
+    let candidate = candidate.to_string();
+    let output = execute_part(executable, day_dir, &[("AOC_CANDIDATE", candidate.as_ref())])?;
+    if !output.status.success() {
+        return Err(AocError::PartFailed { part, status: output.status });
+    }
+    Ok(output.answer().to_owned())
+}
+
+/// Like `execute_part` without environment variables, but kill the part once it runs longer than
+/// `timeout`, returning `None` then.
+pub fn execute_part_with_timeout(
+    executable: &Path,
+    day_dir: &Path,
+    timeout: std::time::Duration,
+) -> Result<Option<RunOutput>, AocError> {
+    let start = std::time::Instant::now();
+    let mut child = std::process::Command::new(executable)
+        .current_dir(day_dir)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::inherit())
+        .spawn()
+        .map_err(|e| AocError::io(format!("Failed to run {}", executable.display()), e))?;
+    let mut stdout = child.stdout.take().unwrap();
+    let reader = std::thread::spawn(move || {
+        let mut output = Vec::new();
+        stdout.read_to_end(&mut output).map(|_| output)
+    });
+
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if start.elapsed() > timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Ok(None);
+            }
+            Ok(None) => std::thread::sleep(std::time::Duration::from_millis(10)),
+            Err(e) => return Err(AocError::io(format!("Failed to wait for {}", executable.display()), e)),
+        }
+    };
+    let elapsed = start.elapsed();
+    let stdout = reader
+        .join()
+        .unwrap()
+        .map_err(|e| AocError::io(format!("Failed to read the output of {}", executable.display()), e))?;
+
+    Ok(Some(RunOutput {
+        stdout: String::from_utf8_lossy(&stdout).into_owned(),
+        status,
+        elapsed,
+        build_elapsed: std::time::Duration::ZERO,
+    }))
+}
+
+/// Output of `cargo test`, with the counts of passed and failed tests.
+pub struct TestOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub status: std::process::ExitStatus,
+    pub passed: usize,
+    pub failed: usize,
+}
+
+/// Run `cargo test` for the package in `day_dir`, optionally only for `part`'s binary.
+///
+/// `cargo_args` are passed on to `cargo test`.
+pub fn test_day(day_dir: &Path, part: Option<u8>, cargo_args: &[String]) -> Result<TestOutput, AocError> {
+    let output = test_command(day_dir, part, cargo_args)?
+        .output()
+        .map_err(|e| AocError::io("Failed to run cargo test", e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    let (passed, failed) = count_tests(&stdout);
+    Ok(TestOutput {
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        stdout,
+        status: output.status,
+        passed,
+        failed,
+    })
+}
+
+/// `cargo test` for the day's package in `day_dir`, or only `part`'s binary.
+pub fn test_command(
+    day_dir: &Path,
+    part: Option<u8>,
+    cargo_args: &[String],
+) -> Result<std::process::Command, AocError> {
+    let cargo_toml_path = day_dir.join("Cargo.toml");
+    let cargo_toml = std::fs::read_to_string(&cargo_toml_path)
+        .map_err(|e| AocError::io(format!("Failed to read {}", cargo_toml_path.display()), e))?
+        .parse::<Document>()
+        .map_err(|e| AocError::Parse(format!("Invalid {}: {}", cargo_toml_path.display(), e)))?;
+    let package = cargo_toml
+        .get("package")
+        .and_then(|package| package.get("name"))
+        .and_then(|name| name.as_str())
+        .ok_or_else(|| AocError::Parse(format!("No package name in {}", cargo_toml_path.display())))?
+        .to_owned();
+
+    let mut command = std::process::Command::new("cargo");
+    command.args(["test", "-p", &package]);
+    if let Some(part) = part {
+        command.args(["--bin", &format!("part_{}", part)]);
+    }
+    command.args(cargo_args).current_dir(day_dir);
+    Ok(command)
+}
+
+/// Total passed and failed tests in the `test result:` lines of `cargo test` output.
+fn count_tests(output: &str) -> (usize, usize) {
+    let count = |result: &str, label: &str| -> usize {
+        result
+            .split(';')
+            .find_map(|field| field.trim().strip_suffix(label)?.trim().rsplit(' ').next()?.parse().ok())
+            .unwrap_or(0)
+    };
+    output
+        .lines()
+        .filter_map(|line| line.strip_prefix("test result:"))
+        .fold((0, 0), |(passed, failed), result| {
+            (passed + count(result, "passed"), failed + count(result, "failed"))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::WorkspaceConfig;
+    use crate::scaffold::new_year;
+
+    #[test]
+    fn test_prebuilt_part() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let day_dir = tmp_dir.path();
+        std::fs::create_dir_all(day_dir.join("src/bin")).unwrap();
+        std::fs::write(
+            day_dir.join("Cargo.toml"),
+            "[package]\nname = \"day-01\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .unwrap();
+        std::fs::write(day_dir.join("src/bin/part_1.rs"), "fn main() {}\n").unwrap();
+
+        let err = prebuilt_part(day_dir, 1, false).unwrap_err().to_string();
+        assert!(err.ends_with("build it with `cargo build --bin part_1`"), "{}", err);
+
+        let executable = day_dir.join(format!("target/debug/part_1{}", std::env::consts::EXE_SUFFIX));
+        std::fs::create_dir_all(executable.parent().unwrap()).unwrap();
+        std::fs::write(&executable, "").unwrap();
+        assert_eq!(prebuilt_part(day_dir, 1, false).unwrap().canonicalize().unwrap(), executable.canonicalize().unwrap());
+        assert!(!is_stale(&executable, day_dir));
+        assert!(prebuilt_part(day_dir, 1, true).unwrap_err().to_string().contains("--release"));
+    }
+
+    #[test]
+    fn test_count_tests() {
+        let output = "running 2 tests
+test tests::test_a ... ok
+test tests::test_b ... FAILED
+
+test result: FAILED. 1 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+running 3 tests
+test result: ok. 3 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+";
+        assert_eq!(count_tests(output), (4, 1));
+        assert_eq!(count_tests(""), (0, 0));
+    }
+
+    #[test]
+    fn test_run_part() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = new_year(tmp_dir.path(), "advent-of-code-", "day-", 2022, false, false, &WorkspaceConfig::default()).unwrap();
+        let day_dir = year_dir.join("day-01");
+        std::fs::write(day_dir.join("src/bin/part_1.rs"), "fn main() { println!(\"42\"); }\n").unwrap();
+
+        let output = run_part(&day_dir, 1, &[], None).unwrap();
+        assert_eq!(output.stdout, "42\n");
+        assert!(run_part(&day_dir, 2, &[], None).is_err());
+    }
+
+    #[test]
+    fn test_execute_part_with_timeout() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = new_year(tmp_dir.path(), "advent-of-code-", "day-", 2022, false, false, &WorkspaceConfig::default()).unwrap();
+        let day_dir = year_dir.join("day-01");
+        std::fs::write(
+            day_dir.join("src/bin/part_1.rs"),
+            "fn main() { println!(\"42\"); }\n",
+        )
+        .unwrap();
+        std::fs::write(
+            day_dir.join("src/bin/part_2.rs"),
+            "fn main() { std::thread::sleep(std::time::Duration::from_secs(10)); }\n",
+        )
+        .unwrap();
+
+        let timeout = std::time::Duration::from_millis(500);
+        let executable = build_part(&day_dir, 1, &[]).unwrap();
+        let output = execute_part_with_timeout(&executable, &day_dir, timeout).unwrap().unwrap();
+        assert_eq!(output.stdout, "42\n");
+        assert!(output.status.success());
+        let executable = build_part(&day_dir, 2, &[]).unwrap();
+        assert!(execute_part_with_timeout(&executable, &day_dir, timeout).unwrap().is_none());
+    }
+}
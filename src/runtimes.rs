@@ -0,0 +1,181 @@
+//! The days of a year ranked by runtime, for `aoc time`.
+//!
+//! Runtimes come from `perf.jsonl`, recorded by `aoc run` and `aoc submit`, and from the
+//! `bench.toml` of each day, recorded by `aoc bench --save`. The latest of either counts. Timeouts
+//! in `perf.jsonl` aren't runtimes, and are left out.
+use crate::error::AocError;
+use colored::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// A runtime recorded for a part.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recorded {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub day: u8,
+    pub part: u8,
+    pub ms: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DayTime {
+    pub day: u8,
+    pub parts_ms: [Option<f64>; 2],
+    pub total_ms: f64,
+    /// Whether the day takes longer than the threshold.
+    pub slow: bool,
+}
+
+/// The runtimes recorded for the days in `year_dir`.
+pub fn collect(year_dir: &Path, day_format: &str) -> Result<Vec<Recorded>, AocError> {
+    let mut recorded: Vec<Recorded> = crate::perf::read(year_dir)?
+        .into_iter()
+        .filter(|entry| !entry.timed_out)
+        .map(|entry| Recorded { timestamp: entry.timestamp, day: entry.day, part: entry.part, ms: entry.elapsed_ms })
+        .collect();
+    for (day, day_dir) in crate::scaffold::day_dirs(year_dir, day_format)? {
+        recorded.extend(crate::bench::read(&day_dir)?.into_iter().map(|(timestamp, result)| Recorded {
+            timestamp,
+            day,
+            part: result.part,
+            ms: result.mean_ms,
+        }));
+    }
+    Ok(recorded)
+}
+
+/// Run every part in `year_dir` for release, one at a time so they don't slow each other down, and
+/// record their runtimes.
+pub fn measure(year_dir: &Path, day_format: &str) -> Result<(), AocError> {
+    let cargo_args = vec!["--release".to_owned()];
+    for result in crate::run_all::run(year_dir, day_format, &cargo_args, 1, |_, _| true)? {
+        match (result.elapsed_ms, result.error) {
+            (Some(elapsed_ms), None) => {
+                let elapsed = std::time::Duration::from_secs_f64(elapsed_ms / 1_000.0);
+                crate::perf::record(year_dir, &crate::perf::PerfEntry::now(result.day, result.part, elapsed))?;
+            }
+            (_, error) => {
+                let error = error.unwrap_or_else(|| "Failed".to_owned());
+                eprintln!("{}", format!("Day {} part {}: {}", result.day, result.part, error).yellow());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The latest runtime of each part, by day and part. Of runtimes recorded at the same second, the
+/// last one counts.
+pub fn latest(recorded: &[Recorded]) -> BTreeMap<(u8, u8), f64> {
+    let mut latest: BTreeMap<(u8, u8), (u64, f64)> = BTreeMap::new();
+    for runtime in recorded {
+        let entry = latest.entry((runtime.day, runtime.part)).or_insert((runtime.timestamp, runtime.ms));
+        if runtime.timestamp >= entry.0 {
+            *entry = (runtime.timestamp, runtime.ms);
+        }
+    }
+    latest.into_iter().map(|(key, (_, ms))| (key, ms)).collect()
+}
+
+/// The days with a runtime for part 1 or 2, slowest first, flagging the ones taking longer than
+/// `threshold_ms`.
+pub fn rank(latest: &BTreeMap<(u8, u8), f64>, threshold_ms: f64) -> Vec<DayTime> {
+    let mut days: BTreeMap<u8, [Option<f64>; 2]> = BTreeMap::new();
+    for (&(day, part), &ms) in latest {
+        if let Some(index) = usize::from(part).checked_sub(1).filter(|index| *index < 2) {
+            days.entry(day).or_default()[index] = Some(ms);
+        }
+    }
+    let mut days: Vec<DayTime> = days
+        .into_iter()
+        .map(|(day, parts_ms)| {
+            let total_ms = parts_ms.iter().flatten().sum();
+            DayTime { day, parts_ms, total_ms, slow: total_ms > threshold_ms }
+        })
+        .collect();
+    days.sort_by(|a, b| b.total_ms.total_cmp(&a.total_ms).then(a.day.cmp(&b.day)));
+    days
+}
+
+pub fn total_ms(days: &[DayTime]) -> f64 {
+    days.iter().map(|day| day.total_ms).sum()
+}
+
+/// A row per day, slow ones in red, followed by the total.
+pub fn render_table(days: &[DayTime]) -> String {
+    let ms = |ms: Option<f64>| ms.map_or_else(|| "-".to_owned(), |ms| format!("{:.3}", ms));
+    let mut table = format!("{:>3} {:>12} {:>12} {:>12}\n", "Day", "Part 1 (ms)", "Part 2 (ms)", "Total (ms)");
+    for day in days {
+        let row = format!(
+            "{:>3} {:>12} {:>12} {:>12.3}",
+            day.day,
+            ms(day.parts_ms[0]),
+            ms(day.parts_ms[1]),
+            day.total_ms
+        );
+        if day.slow {
+            table.push_str(&format!("{}\n", row.red()));
+        } else {
+            table.push_str(&format!("{}\n", row));
+        }
+    }
+    table.push_str(&format!("{:<29} {:>12.3}\n", "Total", total_ms(days)));
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recorded(timestamp: u64, day: u8, part: u8, ms: f64) -> Recorded {
+        Recorded { timestamp, day, part, ms }
+    }
+
+    #[test]
+    fn test_latest() {
+        let latest = latest(&[
+            recorded(2, 1, 1, 10.0),
+            recorded(1, 1, 1, 99.0),
+            recorded(3, 1, 2, 5.0),
+            recorded(3, 1, 2, 6.0),
+        ]);
+        assert_eq!(latest, BTreeMap::from([((1, 1), 10.0), ((1, 2), 6.0)]));
+    }
+
+    #[test]
+    fn test_rank() {
+        let latest = BTreeMap::from([((1, 1), 1.0), ((1, 2), 2.0), ((2, 1), 800.0), ((3, 2), 3.0), ((3, 3), 900.0)]);
+        let days = rank(&latest, 100.0);
+        assert_eq!(
+            days,
+            [
+                DayTime { day: 2, parts_ms: [Some(800.0), None], total_ms: 800.0, slow: true },
+                DayTime { day: 1, parts_ms: [Some(1.0), Some(2.0)], total_ms: 3.0, slow: false },
+                // Only parts 1 and 2 count
+                DayTime { day: 3, parts_ms: [None, Some(3.0)], total_ms: 3.0, slow: false },
+            ]
+        );
+        assert_eq!(total_ms(&days), 806.0);
+
+        let table = render_table(&days);
+        assert!(table.lines().nth(2).unwrap().ends_with("  1        1.000        2.000        3.000"));
+        assert!(table.lines().nth(3).unwrap().ends_with("  3            -        3.000        3.000"));
+        assert_eq!(table.lines().last().unwrap(), format!("{:<29} {:>12}", "Total", "806.000"));
+    }
+
+    #[test]
+    fn test_collect() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = tmp_dir.path();
+        std::fs::create_dir(year_dir.join("day-01")).unwrap();
+        let entry = crate::perf::PerfEntry { timestamp: 1, day: 1, part: 1, elapsed_ms: 12.5, timed_out: false };
+        crate::perf::record(year_dir, &entry).unwrap();
+        crate::bench::record(&year_dir.join("day-01"), &[crate::bench::statistics(2, &[3.0])]).unwrap();
+
+        let recorded = collect(year_dir, "day-").unwrap();
+        assert_eq!(recorded.len(), 2);
+        assert_eq!(recorded[0], Recorded { timestamp: 1, day: 1, part: 1, ms: 12.5 });
+        assert_eq!((recorded[1].day, recorded[1].part, recorded[1].ms), (1, 2, 3.0));
+    }
+}
@@ -0,0 +1,1158 @@
+//! Creating and changing year workspaces, day crates and their parts.
+use crate::error::AocError;
+use crate::config::{PartMode, WorkspaceConfig};
+use colored::*;
+use std::path::{Path, PathBuf};
+use toml_edit::Document;
+
+/// What to do when a file about to be created already exists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Existing {
+    Refuse,
+    Overwrite,
+    /// Move the existing file to a timestamped backup first.
+    Backup,
+}
+
+/// The parts' binaries in `day_dir`'s `src/bin`, as what follows `part_` in their name (e.g. `2`
+/// or `2_fast`) and their path, sorted by name.
+pub fn part_files(day_dir: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(day_dir.join("src/bin")) else {
+        return Vec::new();
+    };
+    let mut parts: Vec<(String, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "rs"))
+        .filter_map(|path| {
+            let stem = path.file_stem()?.to_str()?;
+            Some((stem.strip_prefix("part_")?.to_owned(), path))
+        })
+        .collect();
+    parts.sort();
+    parts
+}
+
+/// Highest numbered `src/bin/part_<N>.rs` in `day_dir`, or 1 when there's none.
+pub fn detect_latest_part(day_dir: &Path) -> u8 {
+    part_files(day_dir)
+        .iter()
+        .filter_map(|(part, _)| part.parse().ok())
+        .max()
+        .unwrap_or(1)
+}
+
+/// Restore `src/bin/part_<part>.rs` in `day_dir` from git's HEAD, returning its path. Fails if git
+/// doesn't track it.
+pub fn revert_part(day_dir: &Path, part: u8) -> Result<PathBuf, AocError> {
+    let relative_path = format!("src/bin/part_{}.rs", part);
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(day_dir)
+            .output()
+            .map_err(|e| AocError::io("Failed to run git", e))
+    };
+
+    let tracked = git(&["ls-files", "--error-unmatch", &relative_path])?;
+    if !tracked.status.success() {
+        return Err(AocError::Environment(format!("{} isn't tracked by git", relative_path)));
+    }
+    let checkout = git(&["checkout", "HEAD", "--", &relative_path])?;
+    if !checkout.status.success() {
+        return Err(AocError::Environment(format!(
+            "Failed to restore {}: {}",
+            relative_path,
+            String::from_utf8_lossy(&checkout.stderr).trim()
+        )));
+    }
+    Ok(day_dir.join(relative_path))
+}
+
+/// Remove every `src/bin/part_*.rs` in `day_dir`, and create `part_1.rs` again from the part
+/// template, or the one using the day's library when it has `src/lib.rs`. Returns the removed
+/// paths and the new part's.
+pub fn regen_parts(
+    day_dir: &Path,
+    variables: &crate::template::Variables,
+) -> Result<(Vec<PathBuf>, PathBuf), AocError> {
+    let removed: Vec<PathBuf> = part_files(day_dir).into_iter().map(|(_, path)| path).collect();
+    for path in &removed {
+        std::fs::remove_file(path).map_err(|e| AocError::io(format!("Failed to remove {}", path.display()), e))?;
+    }
+    let template_name = if day_dir.join("src/lib.rs").exists() { "part_lib.rs" } else { "part.rs" };
+    let part_1 = crate::template::render(&crate::template::load(template_name)?, variables);
+    let part_1_path = day_dir.join("src/bin/part_1.rs");
+    std::fs::create_dir_all(day_dir.join("src/bin"))
+        .and_then(|()| std::fs::write(&part_1_path, part_1))
+        .map_err(|e| AocError::io(format!("Failed to create {}", part_1_path.display()), e))?;
+    crate::logging::wrote(&part_1_path);
+    Ok((removed, part_1_path))
+}
+
+/// Create the `to` binary (`src/bin/<to>.rs`) in `day_dir`, either as a copy of the `from` binary
+/// or from a part template. By default these are `part_1` and `part_2`.
+///
+/// A copy from one part to another, e.g. `part_1` to `part_2`, has mentions of the part in
+/// comments, strings, test names and `EXPECTED*` constants rewritten. Other identifiers are kept,
+/// and reported.
+///
+/// An existing `to` is handled according to `existing`. If the day lists its binaries explicitly,
+/// `to` is added to them.
+pub fn create_part(
+    day_dir: &Path,
+    from: &str,
+    to: &str,
+    mode: PartMode,
+    variables: &crate::template::Variables,
+    existing: Existing,
+) -> Result<PathBuf, AocError> {
+    validate_bin_name(from)?;
+    validate_bin_name(to)?;
+    if mode == PartMode::Copy && from == to {
+        return Err(AocError::Config(format!("Can't copy {} to itself.", from)));
+    }
+    let from_path = day_dir.join(format!("src/bin/{}.rs", from));
+    let to_path = day_dir.join(format!("src/bin/{}.rs", to));
+
+    if mode == PartMode::Copy && !from_path.exists() {
+        return Err(AocError::Environment(format!("{} doesn't exist, nothing to copy.", from_path.display())));
+    }
+    if to_path.exists() {
+        match existing {
+            Existing::Refuse => {
+                return Err(AocError::Environment(format!(
+                    "{} already exists. Use --overwrite or --backup to replace it.",
+                    to_path.display()
+                )))
+            }
+            Existing::Overwrite => {}
+            Existing::Backup => {
+                let timestamp = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |since| since.as_secs());
+                let backup_path = backup_path(&to_path, timestamp);
+                std::fs::rename(&to_path, &backup_path)
+                    .map_err(|e| AocError::io(format!("Failed to back up {}", to_path.display()), e))?;
+                println!("Backup {}: {}", backup_path.display(), "Success".green());
+            }
+        }
+    }
+    match mode {
+        PartMode::Copy => {
+            let source = std::fs::read_to_string(&from_path)
+                .map_err(|e| AocError::io(format!("Failed to read {}", from_path.display()), e))?;
+            let source = match (part_number(from), part_number(to)) {
+                (Some(from_part), Some(to_part)) if from_part != to_part => {
+                    let rewrite = crate::rewrite::rewrite_part(&source, from_part, to_part);
+                    for kept in rewrite.kept {
+                        println!("{}", format!("{}, which may be used elsewhere", kept).yellow());
+                    }
+                    rewrite.source
+                }
+                _ => source,
+            };
+            std::fs::write(&to_path, source).map_err(|e| {
+                AocError::io(format!("Failed to copy {} to {} ({})", from, to, to_path.display()), e)
+            })?;
+            crate::logging::wrote(&to_path);
+        }
+        PartMode::Fresh | PartMode::Lib => {
+            let template_name = if mode == PartMode::Lib {
+                create_lib(day_dir, variables)?;
+                "part_lib.rs"
+            } else {
+                "part.rs"
+            };
+            let template_part = crate::template::render(&crate::template::load(template_name)?, variables);
+            std::fs::write(&to_path, template_part)
+                .map_err(|e| AocError::io(format!("Failed to create {}", to_path.display()), e))?;
+            crate::logging::wrote(&to_path);
+        }
+    }
+    add_bin_target(day_dir, to, &format!("src/bin/{}.rs", to))?;
+
+    Ok(to_path)
+}
+
+/// Check that `name` can be used as a Cargo binary target name, e.g. `part_2_fast`.
+fn validate_bin_name(name: &str) -> Result<(), AocError> {
+    let valid = !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if valid {
+        Ok(())
+    } else {
+        Err(AocError::Config(format!(
+            "Invalid binary name: {:?}. Use letters, digits, `_` and `-`, e.g. part_2_fast.",
+            name
+        )))
+    }
+}
+
+/// Part number of a binary name, e.g. 2 for `part_2` and `part_2_fast`.
+pub fn part_number(name: &str) -> Option<u8> {
+    let rest = name.strip_prefix("part_")?;
+    let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+/// Whether the day's manifest lists its binaries explicitly, so new parts aren't discovered
+/// automatically: `autobins = false`, or existing `[[bin]]` entries.
+fn has_explicit_bins(cargo_toml: &Document) -> bool {
+    let autobins = cargo_toml
+        .get("package")
+        .and_then(|package| package.get("autobins"))
+        .and_then(|autobins| autobins.as_bool())
+        .unwrap_or(true);
+    let bins = cargo_toml
+        .get("bin")
+        .and_then(|bin| bin.as_array_of_tables())
+        .is_some_and(|bins| !bins.is_empty());
+    !autobins || bins
+}
+
+/// Add a `[[bin]]` target to the day's Cargo.toml, if it lists its binaries explicitly and doesn't
+/// have this one yet.
+fn add_bin_target(day_dir: &Path, name: &str, path: &str) -> Result<(), AocError> {
+    let cargo_toml_path = day_dir.join("Cargo.toml");
+    let mut cargo_toml = std::fs::read_to_string(&cargo_toml_path)
+        .map_err(|e| AocError::io(format!("Failed to read {}", cargo_toml_path.display()), e))?
+        .parse::<Document>()
+        .map_err(|e| AocError::Parse(format!("Invalid {}: {}", cargo_toml_path.display(), e)))?;
+    if !has_explicit_bins(&cargo_toml) {
+        return Ok(());
+    }
+
+    let bins = cargo_toml["bin"]
+        .or_insert(toml_edit::Item::ArrayOfTables(Default::default()))
+        .as_array_of_tables_mut()
+        .ok_or_else(|| {
+            AocError::Parse(format!("Invalid {}: bin should be an array of tables", cargo_toml_path.display()))
+        })?;
+    if bins.iter().any(|bin| bin.get("name").and_then(|n| n.as_str()) == Some(name)) {
+        return Ok(());
+    }
+    let mut bin = toml_edit::Table::new();
+    bin["name"] = toml_edit::value(name);
+    bin["path"] = toml_edit::value(path);
+    bins.push(bin);
+
+    write_manifest(&cargo_toml_path, &cargo_toml.to_string())?;
+    println!("Update {}: {}", cargo_toml_path.display(), "Success".green());
+    Ok(())
+}
+
+/// Remove the `[[bin]]` target with `name` from the day's Cargo.toml, if there is one.
+fn remove_bin_target(day_dir: &Path, name: &str) -> Result<(), AocError> {
+    let cargo_toml_path = day_dir.join("Cargo.toml");
+    let mut cargo_toml = std::fs::read_to_string(&cargo_toml_path)
+        .map_err(|e| AocError::io(format!("Failed to read {}", cargo_toml_path.display()), e))?
+        .parse::<Document>()
+        .map_err(|e| AocError::Parse(format!("Invalid {}: {}", cargo_toml_path.display(), e)))?;
+    let Some(bins) = cargo_toml.get_mut("bin").and_then(|bin| bin.as_array_of_tables_mut()) else {
+        return Ok(());
+    };
+    let Some(index) = bins.iter().position(|bin| bin.get("name").and_then(|n| n.as_str()) == Some(name)) else {
+        return Ok(());
+    };
+    bins.remove(index);
+
+    write_manifest(&cargo_toml_path, &cargo_toml.to_string())?;
+    println!("Update {}: {}", cargo_toml_path.display(), "Success".green());
+    Ok(())
+}
+
+/// Remove the `to` binary (`src/bin/<to>.rs`) from `day_dir`.
+///
+/// Unless `force` is set, this refuses when it differs from what `aoc part` could have created it
+/// from (the `from` binary, or a part template), ignoring whitespace.
+pub fn undo_part(
+    day_dir: &Path,
+    from: &str,
+    to: &str,
+    variables: &crate::template::Variables,
+    force: bool,
+) -> Result<PathBuf, AocError> {
+    validate_bin_name(to)?;
+    let to_path = day_dir.join(format!("src/bin/{}.rs", to));
+    let part = std::fs::read_to_string(&to_path)
+        .map_err(|e| AocError::io(format!("Failed to read {}", to_path.display()), e))?;
+
+    if !force {
+        let mut sources: Vec<String> = ["part.rs", "part_lib.rs"]
+            .iter()
+            .filter_map(|name| crate::template::load(name).ok())
+            .map(|template| crate::template::render(&template, variables))
+            .collect();
+        if let Ok(source) = std::fs::read_to_string(day_dir.join(format!("src/bin/{}.rs", from))) {
+            if let (Some(from_part), Some(to_part)) = (part_number(from), part_number(to)) {
+                sources.push(crate::rewrite::rewrite_part(&source, from_part, to_part).source);
+            }
+            sources.push(source);
+        }
+        let without_whitespace = |s: &str| s.split_whitespace().collect::<String>();
+        let part = without_whitespace(&part);
+        if !sources.iter().any(|source| without_whitespace(source) == part) {
+            return Err(AocError::Environment(format!(
+                "{} has been changed since it was created. Use --force to remove it anyway.",
+                to_path.display()
+            )));
+        }
+    }
+
+    std::fs::remove_file(&to_path)
+        .map_err(|e| AocError::io(format!("Failed to remove {}", to_path.display()), e))?;
+    remove_bin_target(day_dir, to)?;
+    Ok(to_path)
+}
+
+/// `path` with a `.bak-<timestamp>` suffix, e.g. `part_2.rs.bak-1670000000`.
+fn backup_path(path: &Path, timestamp: u64) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_owned();
+    file_name.push(format!(".bak-{}", timestamp));
+    path.with_file_name(file_name)
+}
+
+/// Create `src/lib.rs` in `day_dir` from the lib template, unless it already exists, and make
+/// sure the day's Cargo.toml has a `[lib]` target for it.
+pub fn create_lib(day_dir: &Path, variables: &crate::template::Variables) -> Result<(), AocError> {
+    let lib_path = day_dir.join("src/lib.rs");
+    if !lib_path.exists() {
+        let template_lib = crate::template::render(&crate::template::load("lib.rs")?, variables);
+        std::fs::write(&lib_path, template_lib)
+            .map_err(|e| AocError::io(format!("Failed to create {}", lib_path.display()), e))?;
+        crate::logging::wrote(&lib_path);
+        println!("New {}: {}", lib_path.display(), "Success".green());
+    }
+
+    let cargo_toml_path = day_dir.join("Cargo.toml");
+    let mut cargo_toml = std::fs::read_to_string(&cargo_toml_path)
+        .map_err(|e| AocError::io(format!("Failed to read {}", cargo_toml_path.display()), e))?
+        .parse::<Document>()
+        .map_err(|e| AocError::Parse(format!("Invalid {}: {}", cargo_toml_path.display(), e)))?;
+    if !cargo_toml.contains_key("lib") {
+        cargo_toml["lib"] = toml_edit::table();
+        cargo_toml["lib"]["path"] = toml_edit::value("src/lib.rs");
+        write_manifest(&cargo_toml_path, &cargo_toml.to_string())?;
+        println!("Update {}: {}", cargo_toml_path.display(), "Success".green());
+    }
+
+    Ok(())
+}
+
+/// Safely write a Cargo.toml manifest.
+///
+/// The contents must parse as TOML. They're written to a temporary file next to `path` which is
+/// then renamed into place, so a failed write never leaves a half-written manifest behind. The
+/// previous contents, if any, are kept in `Cargo.toml.bak`.
+pub fn write_manifest(path: &Path, contents: &str) -> Result<(), AocError> {
+    save_manifest(path, contents, true)
+}
+
+/// Safely write a Cargo.toml manifest like [`write_manifest`], without keeping a backup of its
+/// previous contents.
+pub fn replace_manifest(path: &Path, contents: &str) -> Result<(), AocError> {
+    save_manifest(path, contents, false)
+}
+
+fn save_manifest(path: &Path, contents: &str, backup: bool) -> Result<(), AocError> {
+    use std::io::Write;
+
+    if let Err(e) = contents.parse::<Document>() {
+        return Err(AocError::Parse(format!("New contents of {} are not valid TOML: {}", path.display(), e)));
+    }
+
+    let tmp_path = path.with_extension("toml.tmp");
+    let result = std::fs::File::create(&tmp_path)
+        .and_then(|mut file| {
+            file.write_all(contents.as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|_| {
+            if backup && path.exists() {
+                std::fs::copy(path, path.with_extension("toml.bak"))?;
+            }
+            std::fs::rename(&tmp_path, path)
+        });
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(AocError::io(format!("Failed to write {}", path.display()), e));
+    }
+    crate::logging::wrote(path);
+
+    Ok(())
+}
+
+/// Create a year directory containing a workspace Cargo.toml, if it doesn't have one yet.
+pub fn init_year(year_dir: &Path, year: u16, author: Option<&str>) -> Result<(), AocError> {
+    std::fs::create_dir_all(year_dir)
+        .map_err(|e| AocError::io("Failed to create year directory", e))?;
+    println!("New Year Directory ({}): {}", year_dir.display(), "Success".green());
+
+    let cargo_toml_path = year_dir.join("Cargo.toml");
+    if cargo_toml_path.exists() {
+        return Ok(());
+    }
+    let cargo_toml = workspace_manifest(year, author);
+    write_manifest(&cargo_toml_path, &cargo_toml.to_string())?;
+    println!("New Cargo.toml ({}): {}", cargo_toml_path.display(), "Success".green());
+
+    Ok(())
+}
+
+/// Create a year directory under `parent` and scaffold its first day.
+///
+/// If anything fails, a year directory created by this call is removed again. An existing year
+/// directory is only used with `force`, in which case the next free day is created instead.
+pub fn new_year(
+    parent: &Path,
+    year_format: &str,
+    day_format: &str,
+    year: u16,
+    force: bool,
+    lib: bool,
+    workspace_config: &WorkspaceConfig,
+) -> Result<PathBuf, AocError> {
+    let year_dir = parent.join(format!("{}{}", year_format, year));
+    let existed = year_dir.exists();
+    if existed && !force {
+        return Err(AocError::Environment(format!(
+            "{} already exists. Use --force to continue anyway.",
+            year_dir.display()
+        )));
+    }
+
+    let result = init_year(&year_dir, year, workspace_config.author.as_deref())
+        .and_then(|_| create_day(&year_dir, year, None, day_format, true, lib, workspace_config));
+    if result.is_err() && !existed {
+        let _ = std::fs::remove_dir_all(&year_dir);
+    }
+
+    result.map(|_| year_dir)
+}
+
+/// Create the next day directory inside `year_dir`, based on previous days.
+///
+/// Registers the new day as a workspace member, unless `workspace` is false or the year directory
+/// is a single crate, then copies the Cargo.toml and part 1 templates. With `lib`, the day also
+/// gets a src/lib.rs which part 1 uses.
+pub fn create_day(
+    year_dir: &Path,
+    year: u16,
+    day: Option<u8>,
+    day_format: &str,
+    workspace: bool,
+    lib: bool,
+    workspace_config: &WorkspaceConfig,
+) -> Result<PathBuf, AocError> {
+    // New Day Directory Name
+    let existing_days = day_dirs(year_dir, day_format)?;
+    let new_day: u8 = match day {
+        Some(day) if existing_days.iter().any(|(existing, _)| *existing == day) => {
+            return Err(AocError::Environment(format!("Day {} already exists in {}", day, year_dir.display())));
+        }
+        Some(day) => day,
+        None => existing_days.last().map_or(0, |(day, _)| *day) + 1,
+    };
+    if new_day > 25 {
+        return Err(AocError::Environment(format!("All 25 days already exist in {}", year_dir.display())));
+    }
+    let new_day_str: String = format!("{}{:02}", day_format, new_day);
+    let day_dir = year_dir.join(&new_day_str);
+
+    // Read the workspace Cargo.toml first, so a broken one doesn't leave a day half created
+    let cargo_toml_path = year_dir.join("Cargo.toml");
+    let existing_cargo_toml = match std::fs::read_to_string(&cargo_toml_path) {
+        Ok(cargo_toml) => Some(
+            cargo_toml
+                .parse::<Document>()
+                .map_err(|e| AocError::Parse(format!("Invalid {}: {}", cargo_toml_path.display(), e)))?,
+        ),
+        Err(_) => None,
+    };
+
+    // Create new day directory
+    std::fs::create_dir(&day_dir)
+        .map_err(|e| AocError::io("Failed to create new day directory", e))?;
+    println!("New Day Directory ({}): {}", day_dir.display(), "Success".green());
+
+    // update workspace Cargo.toml
+    // A root manifest with a [package] but no [workspace] is a single crate, not a workspace
+    let is_single_crate = existing_cargo_toml.as_ref().is_some_and(|cargo_toml| {
+        cargo_toml.contains_key("package") && !cargo_toml.contains_key("workspace")
+    });
+    if workspace && !is_single_crate {
+        let author = workspace_config.author.as_deref();
+        update_workspace_members(&cargo_toml_path, existing_cargo_toml, &new_day_str, year, author)?;
+    } else {
+        println!(
+            "Update Cargo.toml ({}): {}",
+            cargo_toml_path.display(),
+            "Skipped, not a workspace".yellow()
+        );
+    }
+
+    let variables = crate::template::Variables {
+        year,
+        day: new_day,
+        part: 1,
+        name: new_day_str.clone(),
+        edition: workspace_config.edition.unwrap_or_default().as_str(),
+    };
+
+    // copy template from ./templates/Cargo.toml
+    let template_cargo_toml = crate::template::render(&crate::template::load("Cargo.toml")?, &variables);
+    write_manifest(&day_dir.join("Cargo.toml"), &template_cargo_toml)?;
+    println!("New Cargo.toml: {}", "Success".green());
+    // mkdir for src
+    std::fs::create_dir(day_dir.join("src"))
+        .map_err(|e| AocError::io("Failed to create new src directory", e))?;
+    println!("New src Directory: {}", "Success".green());
+    // mkdir for src/bin
+    std::fs::create_dir(day_dir.join("src/bin"))
+        .map_err(|e| AocError::io("Failed to create new src/bin directory", e))?;
+    println!("New src/bin Directory: {}", "Success".green());
+    // src/lib.rs from ./templates/lib.rs, with a [lib] target in Cargo.toml
+    if lib {
+        create_lib(&day_dir, &variables)?;
+    }
+    // copy template part 1 from ./templates/part.rs (or part_lib.rs) to src/bin/part_1.rs
+    let template_name = if lib { "part_lib.rs" } else { "part.rs" };
+    let template_part_1 = crate::template::render(&crate::template::load(template_name)?, &variables);
+    let part_1_path = day_dir.join("src/bin/part_1.rs");
+    std::fs::write(&part_1_path, template_part_1)
+        .map_err(|e| AocError::io("Failed to create new src/bin/part_1.rs", e))?;
+    crate::logging::wrote(&part_1_path);
+    println!("New src/bin/part_1.rs: {}", "Success".green());
+
+    Ok(day_dir)
+}
+
+/// Day directories in `year_dir` with their day number, sorted by day.
+pub fn day_dirs(year_dir: &Path, day_format: &str) -> Result<Vec<(u8, PathBuf)>, AocError> {
+    let entries = std::fs::read_dir(year_dir)
+        .map_err(|e| AocError::io(format!("Failed to read year directory {}", year_dir.display()), e))?;
+    let mut days: Vec<(u8, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .filter_map(|path| {
+            let day = crate::env::parse_day(&path, day_format).ok()?;
+            Some((day, path))
+        })
+        .collect();
+    days.sort();
+    Ok(days)
+}
+
+/// A new workspace manifest without members, with the year and author in
+/// `[workspace.metadata.aoc]`.
+fn workspace_manifest(year: u16, author: Option<&str>) -> Document {
+    let mut cargo_toml = Document::new();
+    cargo_toml["workspace"] = toml_edit::table();
+    cargo_toml["workspace"]["members"] = "[]".parse().unwrap();
+    let mut aoc = toml_edit::Table::new();
+    aoc["year"] = toml_edit::value(i64::from(year));
+    if let Some(author) = author {
+        aoc["author"] = toml_edit::value(author);
+    }
+    let mut metadata = toml_edit::Table::new();
+    metadata.set_implicit(true);
+    metadata["aoc"] = toml_edit::Item::Table(aoc);
+    cargo_toml["workspace"]["metadata"] = toml_edit::Item::Table(metadata);
+    cargo_toml
+}
+
+/// The year of `year_dir`: the one its workspace knows, or else the one in its name, formatted
+/// with `year_format`.
+pub fn workspace_year(year_dir: &Path, year_format: &str) -> Result<u16, AocError> {
+    match read_workspace_year(&year_dir.join("Cargo.toml")) {
+        Some(year) => Ok(year),
+        None => crate::env::parse_year(year_dir, year_format),
+    }
+}
+
+/// The year in `[workspace.metadata.aoc]` of the workspace manifest at `cargo_toml`, if any.
+pub fn read_workspace_year(cargo_toml: &Path) -> Option<u16> {
+    let cargo_toml = std::fs::read_to_string(cargo_toml).ok()?.parse::<Document>().ok()?;
+    let year = cargo_toml.get("workspace")?.get("metadata")?.get("aoc")?.get("year")?;
+    u16::try_from(year.as_integer()?).ok()
+}
+
+/// Add `member` to the workspace members of the manifest at `cargo_toml_path`, creating the
+/// manifest if there isn't one yet.
+pub fn update_workspace_members(
+    cargo_toml_path: &Path,
+    existing_cargo_toml: Option<Document>,
+    member: &str,
+    year: u16,
+    author: Option<&str>,
+) -> Result<(), AocError> {
+    let mut cargo_toml = if let Some(cargo_toml) = existing_cargo_toml {
+        cargo_toml
+    } else {
+        println!("{}", "Creating new Cargo.toml".yellow());
+        workspace_manifest(year, author)
+    };
+    let Some(workspace_members) = cargo_toml["workspace"]["members"].as_array_mut() else {
+        return Err(AocError::Parse(format!(
+            "Expected workspace.members to be an array in {}",
+            cargo_toml_path.display()
+        )));
+    };
+    workspace_members.push(member);
+    write_manifest(cargo_toml_path, &cargo_toml.to_string())?;
+    println!("Update Cargo.toml ({}): {}", cargo_toml_path.display(), "Success".green());
+
+    Ok(())
+}
+
+/// Record the puzzle's `title` in `[package.metadata.aoc]` of the day's manifest at `cargo_toml`.
+pub fn cache_title(cargo_toml_path: &Path, title: &str) -> Result<(), AocError> {
+    update_aoc_metadata(cargo_toml_path, |aoc| aoc["title"] = toml_edit::value(title))
+}
+
+/// Change `[package.metadata.aoc]` of the day's manifest at `cargo_toml` with `update`, adding it
+/// when it's missing.
+pub fn update_aoc_metadata(cargo_toml_path: &Path, update: impl FnOnce(&mut toml_edit::Item)) -> Result<(), AocError> {
+    let mut cargo_toml = std::fs::read_to_string(cargo_toml_path)
+        .map_err(|e| AocError::io(format!("Failed to read {}", cargo_toml_path.display()), e))?
+        .parse::<Document>()
+        .map_err(|e| AocError::Parse(format!("Invalid {}: {}", cargo_toml_path.display(), e)))?;
+    let Some(package) = cargo_toml.get_mut("package").and_then(|package| package.as_table_mut()) else {
+        return Err(AocError::Parse(format!("No [package] in {}", cargo_toml_path.display())));
+    };
+    let metadata = package
+        .entry("metadata")
+        .or_insert_with(|| {
+            let mut metadata = toml_edit::Table::new();
+            metadata.set_implicit(true);
+            toml_edit::Item::Table(metadata)
+        });
+    let Some(metadata) = metadata.as_table_mut() else {
+        return Err(AocError::Parse(format!("package.metadata in {} isn't a table", cargo_toml_path.display())));
+    };
+    update(metadata.entry("aoc").or_insert_with(toml_edit::table));
+    write_manifest(cargo_toml_path, &cargo_toml.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Edition;
+
+    #[test]
+    fn test_new_year() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let kyle = WorkspaceConfig {
+            author: Some("Kyle".to_owned()),
+            edition: Some(Edition::E2018),
+        };
+
+        let year_dir = new_year(tmp_dir.path(), "advent-of-code-", "day-", 2022, false, false, &kyle).unwrap();
+        assert_eq!(year_dir, tmp_dir.path().join("advent-of-code-2022"));
+        let cargo_toml = std::fs::read_to_string(year_dir.join("Cargo.toml")).unwrap();
+        assert!(cargo_toml.contains(r#"members = ["day-01"]"#));
+        assert!(cargo_toml.contains("[workspace.metadata.aoc]\nyear = 2022\nauthor = \"Kyle\"\n"));
+        assert_eq!(read_workspace_year(&year_dir.join("Cargo.toml")), Some(2022));
+        assert_eq!(read_workspace_year(&year_dir.join("day-01/Cargo.toml")), None);
+        let day_cargo_toml = std::fs::read_to_string(year_dir.join("day-01/Cargo.toml")).unwrap();
+        assert!(day_cargo_toml.contains("edition = \"2018\"\n"));
+        assert!(year_dir.join("day-01/src/bin/part_1.rs").exists());
+    }
+
+    #[test]
+    fn test_workspace_year() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = tmp_dir.path().join("advent-of-code-2022");
+        std::fs::create_dir(&year_dir).unwrap();
+        assert_eq!(workspace_year(&year_dir, "advent-of-code-").unwrap(), 2022);
+        assert!(workspace_year(&year_dir, "aoc-").is_err());
+
+        // The workspace wins over the name
+        init_year(&year_dir, 2015, None).unwrap();
+        assert_eq!(workspace_year(&year_dir, "aoc-").unwrap(), 2015);
+    }
+
+    #[test]
+    fn test_new_year_already_exists() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = tmp_dir.path().join("advent-of-code-2022");
+        std::fs::create_dir(&year_dir).unwrap();
+
+        assert!(new_year(tmp_dir.path(), "advent-of-code-", "day-", 2022, false, false, &WorkspaceConfig::default()).is_err());
+        assert!(!year_dir.join("day-01").exists());
+
+        new_year(tmp_dir.path(), "advent-of-code-", "day-", 2022, true, false, &WorkspaceConfig::default()).unwrap();
+        assert!(year_dir.join("day-01/src/bin/part_1.rs").exists());
+    }
+
+    #[test]
+    fn test_day_from_year_and_day_dirs() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = new_year(tmp_dir.path(), "advent-of-code-", "day-", 2022, false, false, &WorkspaceConfig::default()).unwrap();
+
+        // From the year root
+        let resolved = crate::env::resolve_year_dir(&year_dir, "day-", "advent-of-code-").unwrap();
+        assert_eq!(resolved, year_dir);
+        let day_dir = create_day(&resolved, 2022, None, "day-", true, false, &WorkspaceConfig::default()).unwrap();
+        assert_eq!(day_dir, year_dir.join("day-02"));
+
+        // From inside a day
+        let resolved = crate::env::resolve_year_dir(&day_dir, "day-", "advent-of-code-").unwrap();
+        assert_eq!(resolved, year_dir);
+        let day_dir = create_day(&resolved, 2022, None, "day-", true, false, &WorkspaceConfig::default()).unwrap();
+        assert_eq!(day_dir, year_dir.join("day-03"));
+        assert!(!year_dir.join("day-02/day-03").exists());
+
+        let cargo_toml = std::fs::read_to_string(year_dir.join("Cargo.toml")).unwrap();
+        assert!(cargo_toml.contains(r#"members = ["day-01", "day-02", "day-03"]"#));
+    }
+
+    #[test]
+    fn test_detect_latest_part() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        assert_eq!(detect_latest_part(tmp_dir.path()), 1);
+        let bin_dir = tmp_dir.path().join("src/bin");
+        std::fs::create_dir_all(&bin_dir).unwrap();
+        for part in ["part_1.rs", "part_2.rs", "part_3.rs", "part_2_fast.rs"] {
+            std::fs::write(bin_dir.join(part), "fn main() {}\n").unwrap();
+        }
+        assert_eq!(detect_latest_part(tmp_dir.path()), 3);
+    }
+
+    #[test]
+    fn test_write_manifest() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let path = tmp_dir.path().join("Cargo.toml");
+        std::fs::write(&path, "[workspace]\nmembers = []\n").unwrap();
+
+        write_manifest(&path, "[workspace]\nmembers = [\"day-01\"]\n").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[workspace]\nmembers = [\"day-01\"]\n");
+        assert_eq!(
+            std::fs::read_to_string(tmp_dir.path().join("Cargo.toml.bak")).unwrap(),
+            "[workspace]\nmembers = []\n"
+        );
+        assert!(!tmp_dir.path().join("Cargo.toml.tmp").exists());
+    }
+
+    #[test]
+    fn test_write_manifest_failure() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let path = tmp_dir.path().join("Cargo.toml");
+        std::fs::write(&path, "[workspace]\nmembers = []\n").unwrap();
+
+        // Invalid contents are never written
+        assert!(write_manifest(&path, "[workspace\n").is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[workspace]\nmembers = []\n");
+
+        // Simulate a write failure: the temporary file can't be created
+        std::fs::create_dir(tmp_dir.path().join("Cargo.toml.tmp")).unwrap();
+        assert!(write_manifest(&path, "[workspace]\nmembers = [\"day-01\"]\n").is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[workspace]\nmembers = []\n");
+    }
+
+    #[test]
+    fn test_day_without_workspace() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = tmp_dir.path().join("advent-of-code-2022");
+        std::fs::create_dir(&year_dir).unwrap();
+
+        // Disabled explicitly
+        let workspace_cargo_toml = "[workspace]\nmembers = []\n";
+        std::fs::write(year_dir.join("Cargo.toml"), workspace_cargo_toml).unwrap();
+        create_day(&year_dir, 2022, None, "day-", false, false, &WorkspaceConfig::default()).unwrap();
+        assert_eq!(std::fs::read_to_string(year_dir.join("Cargo.toml")).unwrap(), workspace_cargo_toml);
+
+        // Detected from a single crate manifest
+        let crate_cargo_toml = "[package]\nname = \"advent-of-code-2022\"\n";
+        std::fs::write(year_dir.join("Cargo.toml"), crate_cargo_toml).unwrap();
+        create_day(&year_dir, 2022, None, "day-", true, false, &WorkspaceConfig::default()).unwrap();
+        assert_eq!(std::fs::read_to_string(year_dir.join("Cargo.toml")).unwrap(), crate_cargo_toml);
+        assert!(year_dir.join("day-02/src/bin/part_1.rs").exists());
+        assert!(!year_dir.join("Cargo.toml.bak").exists());
+    }
+
+    #[test]
+    fn test_day_with_invalid_workspace() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = tmp_dir.path().join("advent-of-code-2022");
+        std::fs::create_dir(&year_dir).unwrap();
+        let create = || create_day(&year_dir, 2022, None, "day-", true, false, &WorkspaceConfig::default());
+
+        // Fails before creating the day
+        std::fs::write(year_dir.join("Cargo.toml"), "[workspace\n").unwrap();
+        let err = create().unwrap_err().to_string();
+        assert!(err.starts_with(&format!("Invalid {}", year_dir.join("Cargo.toml").display())), "{}", err);
+        assert!(!year_dir.join("day-01").exists());
+
+        std::fs::write(year_dir.join("Cargo.toml"), "[workspace]\nmembers = \"day-01\"\n").unwrap();
+        let err = create().unwrap_err().to_string();
+        assert!(err.starts_with("Expected workspace.members to be an array"), "{}", err);
+    }
+
+    fn variables(part: u8) -> crate::template::Variables {
+        crate::template::Variables {
+            year: 2022,
+            day: 1,
+            part,
+            name: "day-01".to_owned(),
+            edition: "2021",
+        }
+    }
+
+    #[test]
+    fn test_copy_part() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = new_year(tmp_dir.path(), "advent-of-code-", "day-", 2022, false, false, &WorkspaceConfig::default()).unwrap();
+        let day_dir = year_dir.join("day-01");
+
+        let resolved = crate::env::resolve_day_dir(&day_dir.join("src/bin"), "day-", "advent-of-code-").unwrap();
+        assert_eq!(resolved, day_dir);
+
+        let part_2_path = create_part(&day_dir, "part_1", "part_2", PartMode::Copy, &variables(2), Existing::Refuse).unwrap();
+        assert_eq!(part_2_path, day_dir.join("src/bin/part_2.rs"));
+        let part_1 = std::fs::read_to_string(day_dir.join("src/bin/part_1.rs")).unwrap();
+        let part_2 = std::fs::read_to_string(&part_2_path).unwrap();
+        assert!(part_1.contains("Part 1"));
+        assert_eq!(part_2, part_1.replace("Part 1", "Part 2"));
+
+        // part_2.rs already exists
+        let err = create_part(&day_dir, "part_1", "part_2", PartMode::Copy, &variables(2), Existing::Refuse)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("already exists"));
+    }
+
+    #[test]
+    fn test_copy_part_missing_part_1() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let day_dir = tmp_dir.path().join("day-01");
+        std::fs::create_dir_all(day_dir.join("src/bin")).unwrap();
+
+        let err = create_part(&day_dir, "part_1", "part_2", PartMode::Copy, &variables(2), Existing::Refuse)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("doesn't exist"));
+        assert!(!day_dir.join("src/bin/part_2.rs").exists());
+    }
+
+    #[test]
+    fn test_fresh_part() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = new_year(tmp_dir.path(), "advent-of-code-", "day-", 2022, false, false, &WorkspaceConfig::default()).unwrap();
+        let day_dir = year_dir.join("day-01");
+        std::fs::write(day_dir.join("src/bin/part_1.rs"), "fn main() {}\n").unwrap();
+
+        let part_2_path = create_part(&day_dir, "part_1", "part_2", PartMode::Fresh, &variables(2), Existing::Refuse).unwrap();
+        let part_2 = std::fs::read_to_string(part_2_path).unwrap();
+        assert_eq!(part_2, crate::template::render(include_str!("../templates/part.rs"), &variables(2)));
+        assert!(part_2.starts_with("//! Advent of Code 2022, Day 1: Part 2\n"));
+
+        let status = std::process::Command::new("cargo")
+            .args(["check", "--quiet", "--bin", "part_2"])
+            .current_dir(&day_dir)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    fn test_lib_part() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = new_year(tmp_dir.path(), "advent-of-code-", "day-", 2022, false, false, &WorkspaceConfig::default()).unwrap();
+        let day_dir = year_dir.join("day-01");
+        let part_1 = std::fs::read_to_string(day_dir.join("src/bin/part_1.rs")).unwrap();
+
+        let part_2_path = create_part(&day_dir, "part_1", "part_2", PartMode::Lib, &variables(2), Existing::Refuse).unwrap();
+        let part_2 = std::fs::read_to_string(part_2_path).unwrap();
+        assert!(part_2.contains("use day_01::utils;"));
+        assert!(day_dir.join("src/lib.rs").exists());
+        let cargo_toml = std::fs::read_to_string(day_dir.join("Cargo.toml")).unwrap();
+        assert!(cargo_toml.contains("[lib]\npath = \"src/lib.rs\""));
+        assert_eq!(std::fs::read_to_string(day_dir.join("src/bin/part_1.rs")).unwrap(), part_1);
+
+        let status = std::process::Command::new("cargo")
+            .args(["check", "--quiet", "--lib", "--bins"])
+            .current_dir(&day_dir)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    fn test_create_day_with_lib() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = new_year(tmp_dir.path(), "advent-of-code-", "day-", 2022, false, true, &WorkspaceConfig::default()).unwrap();
+        let day_dir = year_dir.join("day-01");
+
+        let lib = std::fs::read_to_string(day_dir.join("src/lib.rs")).unwrap();
+        assert!(lib.contains("pub mod utils"));
+        let cargo_toml = std::fs::read_to_string(day_dir.join("Cargo.toml")).unwrap();
+        assert!(cargo_toml.contains("[lib]\npath = \"src/lib.rs\""));
+        let part_1 = std::fs::read_to_string(day_dir.join("src/bin/part_1.rs")).unwrap();
+        assert!(part_1.contains("use day_01::utils;"));
+
+        // Copying part 1 keeps using the lib
+        create_part(&day_dir, "part_1", "part_2", PartMode::Copy, &variables(2), Existing::Refuse).unwrap();
+        let status = std::process::Command::new("cargo")
+            .args(["check", "--quiet", "--lib", "--bins"])
+            .current_dir(&day_dir)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    fn test_part_bin_targets() {
+        let bin_names = |day_dir: &Path| -> Vec<String> {
+            let output = std::process::Command::new("cargo")
+                .args(["metadata", "--no-deps", "--format-version", "1"])
+                .current_dir(day_dir)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+            let metadata: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+            let mut names: Vec<String> = metadata["packages"][0]["targets"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|target| target["name"].as_str().unwrap().to_owned())
+                .collect();
+            names.sort();
+            names
+        };
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = new_year(tmp_dir.path(), "advent-of-code-", "day-", 2022, false, false, &WorkspaceConfig::default()).unwrap();
+        let day_dir = year_dir.join("day-01");
+        let cargo_toml_path = day_dir.join("Cargo.toml");
+        let cargo_toml = std::fs::read_to_string(&cargo_toml_path).unwrap();
+
+        // Discovered automatically, the manifest is left alone
+        create_part(&day_dir, "part_1", "part_2", PartMode::Fresh, &variables(2), Existing::Refuse).unwrap();
+        assert_eq!(std::fs::read_to_string(&cargo_toml_path).unwrap(), cargo_toml);
+        assert_eq!(bin_names(&day_dir), vec!["part_1", "part_2"]);
+        undo_part(&day_dir, "part_1", "part_2", &variables(2), true).unwrap();
+
+        // autobins = false, with an explicit part 1
+        let explicit = cargo_toml.replace("[package]\n", "[package]\nautobins = false\n")
+            + "\n[[bin]]\nname = \"part_1\"\npath = \"src/bin/part_1.rs\"\n";
+        std::fs::write(&cargo_toml_path, &explicit).unwrap();
+        assert_eq!(bin_names(&day_dir), vec!["part_1"]);
+        create_part(&day_dir, "part_1", "part_2", PartMode::Fresh, &variables(2), Existing::Refuse).unwrap();
+        assert_eq!(bin_names(&day_dir), vec!["part_1", "part_2"]);
+        // Not added twice
+        create_part(&day_dir, "part_1", "part_2", PartMode::Fresh, &variables(2), Existing::Overwrite).unwrap();
+        assert_eq!(std::fs::read_to_string(&cargo_toml_path).unwrap().matches("[[bin]]").count(), 2);
+
+        undo_part(&day_dir, "part_1", "part_2", &variables(2), true).unwrap();
+        assert_eq!(std::fs::read_to_string(&cargo_toml_path).unwrap(), explicit);
+    }
+
+    #[test]
+    fn test_copy_part_from_to() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = new_year(tmp_dir.path(), "advent-of-code-", "day-", 2022, false, false, &WorkspaceConfig::default()).unwrap();
+        let day_dir = year_dir.join("day-01");
+        create_part(&day_dir, "part_1", "part_2", PartMode::Copy, &variables(2), Existing::Refuse).unwrap();
+        let part_2 = std::fs::read_to_string(day_dir.join("src/bin/part_2.rs")).unwrap();
+
+        // Same part, so a plain copy
+        let fast_path =
+            create_part(&day_dir, "part_2", "part_2_fast", PartMode::Copy, &variables(2), Existing::Refuse).unwrap();
+        assert_eq!(fast_path, day_dir.join("src/bin/part_2_fast.rs"));
+        assert_eq!(std::fs::read_to_string(&fast_path).unwrap(), part_2);
+        assert!(create_part(&day_dir, "part_2", "part_2_fast", PartMode::Copy, &variables(2), Existing::Refuse)
+            .unwrap_err()
+            .to_string()
+            .contains("already exists"));
+        undo_part(&day_dir, "part_2", "part_2_fast", &variables(2), false).unwrap();
+        assert!(!fast_path.exists());
+
+        // Source must exist, names must be valid bin names
+        assert!(create_part(&day_dir, "part_3", "part_4", PartMode::Copy, &variables(4), Existing::Refuse)
+            .unwrap_err()
+            .to_string()
+            .contains("doesn't exist"));
+        for invalid in ["", "../part_3", "part 3", "3rd"] {
+            assert!(create_part(&day_dir, "part_1", invalid, PartMode::Copy, &variables(2), Existing::Refuse)
+                .unwrap_err()
+                .to_string()
+                .contains("Invalid binary name"));
+        }
+        assert!(create_part(&day_dir, "part_2", "part_2", PartMode::Copy, &variables(2), Existing::Overwrite).is_err());
+
+        assert_eq!(part_number("part_2_fast"), Some(2));
+        assert_eq!(part_number("part_10"), Some(10));
+        assert_eq!(part_number("fast"), None);
+    }
+
+    #[test]
+    fn test_revert_part() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let day_dir = tmp_dir.path();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(["-c", "user.name=aoc", "-c", "user.email=aoc@example.com"])
+                .args(args)
+                .current_dir(day_dir)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success());
+        };
+        std::fs::create_dir_all(day_dir.join("src/bin")).unwrap();
+        std::fs::write(day_dir.join("src/bin/part_1.rs"), "fn main() {}\n").unwrap();
+        git(&["init", "--quiet"]);
+        git(&["add", "src/bin/part_1.rs"]);
+        git(&["commit", "--quiet", "-m", "Part 1"]);
+
+        std::fs::write(day_dir.join("src/bin/part_1.rs"), "fn main() { changed }\n").unwrap();
+        let path = revert_part(day_dir, 1).unwrap();
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "fn main() {}\n");
+
+        std::fs::write(day_dir.join("src/bin/part_2.rs"), "fn main() {}\n").unwrap();
+        assert!(revert_part(day_dir, 2).unwrap_err().to_string().contains("isn't tracked"));
+    }
+
+    #[test]
+    fn test_cache_title() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let cargo_toml = tmp_dir.path().join("Cargo.toml");
+        std::fs::write(&cargo_toml, "[package]\nname = \"day-01\"\n\n[dependencies]\n").unwrap();
+        cache_title(&cargo_toml, "Calorie Counting").unwrap();
+        cache_title(&cargo_toml, "Calorie Counting, Again").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&cargo_toml).unwrap(),
+            "[package]\nname = \"day-01\"\n\n[package.metadata.aoc]\ntitle = \"Calorie Counting, Again\"\n\n[dependencies]\n"
+        );
+    }
+
+    #[test]
+    fn test_part_overwrite_and_backup() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = new_year(tmp_dir.path(), "advent-of-code-", "day-", 2022, false, false, &WorkspaceConfig::default()).unwrap();
+        let day_dir = year_dir.join("day-01");
+        let part_1 = std::fs::read_to_string(day_dir.join("src/bin/part_1.rs")).unwrap();
+        let copied = crate::rewrite::rewrite_part(&part_1, 1, 2).source;
+        let part_2_path = day_dir.join("src/bin/part_2.rs");
+
+        std::fs::write(&part_2_path, "// old part 2").unwrap();
+        create_part(&day_dir, "part_1", "part_2", PartMode::Copy, &variables(2), Existing::Overwrite).unwrap();
+        assert_eq!(std::fs::read_to_string(&part_2_path).unwrap(), copied);
+
+        std::fs::write(&part_2_path, "// old part 2").unwrap();
+        create_part(&day_dir, "part_1", "part_2", PartMode::Copy, &variables(2), Existing::Backup).unwrap();
+        assert_eq!(std::fs::read_to_string(&part_2_path).unwrap(), copied);
+        let backups: Vec<String> = std::fs::read_dir(day_dir.join("src/bin"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name.starts_with("part_2.rs.bak-"))
+            .collect();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(std::fs::read_to_string(day_dir.join("src/bin").join(&backups[0])).unwrap(), "// old part 2");
+    }
+
+    #[test]
+    fn test_backup_path() {
+        assert_eq!(
+            backup_path(Path::new("day-01/src/bin/part_2.rs"), 1670000000),
+            Path::new("day-01/src/bin/part_2.rs.bak-1670000000")
+        );
+    }
+
+    #[test]
+    fn test_undo_part() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = new_year(tmp_dir.path(), "advent-of-code-", "day-", 2022, false, false, &WorkspaceConfig::default()).unwrap();
+        let day_dir = year_dir.join("day-01");
+        let part_2_path = day_dir.join("src/bin/part_2.rs");
+
+        // Untouched, apart from whitespace
+        create_part(&day_dir, "part_1", "part_2", PartMode::Copy, &variables(2), Existing::Refuse).unwrap();
+        let part_2 = std::fs::read_to_string(&part_2_path).unwrap();
+        std::fs::write(&part_2_path, part_2.replace("    ", "\t") + "\n\n").unwrap();
+        undo_part(&day_dir, "part_1", "part_2", &variables(2), false).unwrap();
+        assert!(!part_2_path.exists());
+
+        // Untouched fresh template
+        create_part(&day_dir, "part_1", "part_2", PartMode::Fresh, &variables(2), Existing::Refuse).unwrap();
+        undo_part(&day_dir, "part_1", "part_2", &variables(2), false).unwrap();
+        assert!(!part_2_path.exists());
+
+        // Modified
+        create_part(&day_dir, "part_1", "part_2", PartMode::Copy, &variables(2), Existing::Refuse).unwrap();
+        let part_2 = std::fs::read_to_string(&part_2_path).unwrap();
+        std::fs::write(&part_2_path, part_2.replace("todo!()", "42")).unwrap();
+        assert!(undo_part(&day_dir, "part_1", "part_2", &variables(2), false).is_err());
+        assert!(part_2_path.exists());
+
+        // Forced
+        undo_part(&day_dir, "part_1", "part_2", &variables(2), true).unwrap();
+        assert!(!part_2_path.exists());
+    }
+
+    #[test]
+    fn test_regen_parts() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = new_year(tmp_dir.path(), "advent-of-code-", "day-", 2022, false, false, &WorkspaceConfig::default()).unwrap();
+        let day_dir = year_dir.join("day-01");
+        let cargo_toml = std::fs::read_to_string(day_dir.join("Cargo.toml")).unwrap();
+        std::fs::write(day_dir.join("input.txt"), "1\n").unwrap();
+        std::fs::write(day_dir.join("src/bin/part_1.rs"), "fn main() { solved }\n").unwrap();
+        std::fs::write(day_dir.join("src/bin/part_2.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(day_dir.join("src/bin/part_2_fast.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(day_dir.join("src/bin/helper.rs"), "fn main() {}\n").unwrap();
+
+        let (removed, part_1_path) = regen_parts(&day_dir, &variables(1)).unwrap();
+        assert_eq!(removed.len(), 3);
+        assert_eq!(
+            std::fs::read_to_string(part_1_path).unwrap(),
+            crate::template::render(include_str!("../templates/part.rs"), &variables(1))
+        );
+        assert!(!day_dir.join("src/bin/part_2.rs").exists());
+        assert!(!day_dir.join("src/bin/part_2_fast.rs").exists());
+        assert!(day_dir.join("src/bin/helper.rs").exists());
+        assert_eq!(std::fs::read_to_string(day_dir.join("input.txt")).unwrap(), "1\n");
+        assert_eq!(std::fs::read_to_string(day_dir.join("Cargo.toml")).unwrap(), cargo_toml);
+    }
+
+    #[test]
+    fn test_create_specific_day() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = new_year(tmp_dir.path(), "advent-of-code-", "day-", 2022, false, false, &WorkspaceConfig::default()).unwrap();
+
+        let day_dir = create_day(&year_dir, 2022, Some(12), "day-", true, false, &WorkspaceConfig::default()).unwrap();
+        assert_eq!(day_dir, year_dir.join("day-12"));
+        assert!(create_day(&year_dir, 2022, Some(12), "day-", true, false, &WorkspaceConfig::default()).is_err());
+        assert_eq!(create_day(&year_dir, 2022, None, "day-", true, false, &WorkspaceConfig::default()).unwrap(), year_dir.join("day-13"));
+    }
+
+    #[test]
+    fn test_find_day_dir() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let year_dir = new_year(tmp_dir.path(), "advent-of-code-", "day-", 2022, false, false, &WorkspaceConfig::default()).unwrap();
+        create_day(&year_dir, 2022, Some(12), "day-", true, false, &WorkspaceConfig::default()).unwrap();
+        let find = |dir: &Path, day| crate::env::find_day_dir(dir, day, "day-", "advent-of-code-");
+
+        // From the year root
+        assert_eq!(find(&year_dir, 12).unwrap(), year_dir.join("day-12"));
+        // From inside an unrelated day
+        assert_eq!(find(&year_dir.join("day-01/src"), 12).unwrap(), year_dir.join("day-12"));
+
+        let err = find(&year_dir, 3).unwrap_err().to_string();
+        assert!(err.contains("aoc day --day 3"));
+
+        // Part 2 is created in the resolved day
+        let day_dir = find(&year_dir.join("day-01"), 12).unwrap();
+        let variables = crate::template::Variables {
+            day: 12,
+            name: "day-12".to_owned(),
+            ..variables(2)
+        };
+        create_part(&day_dir, "part_1", "part_2", PartMode::Copy, &variables, Existing::Refuse).unwrap();
+        assert!(year_dir.join("day-12/src/bin/part_2.rs").exists());
+        assert!(!year_dir.join("day-01/src/bin/part_2.rs").exists());
+    }
+}
@@ -0,0 +1,232 @@
+//! Personal progress and timings of a year, for `aoc stats`.
+use crate::error::AocError;
+use crate::leaderboard::Member;
+use crate::perf::PerfSummary;
+use crate::submissions::Submission;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DayStats {
+    pub day: u8,
+    pub stars: u8,
+    /// Answers submitted, right or wrong.
+    pub attempts: usize,
+    /// Average recorded runtime of each part.
+    pub runtime_ms: [Option<f64>; 2],
+    /// Seconds from downloading the input to the first correct answer.
+    pub solve_secs: Option<u64>,
+    /// Seconds from the puzzle unlocking to each star, from the leaderboard.
+    pub official_secs: [Option<u64>; 2],
+}
+
+/// Stats of `day`, from its submissions, the runtime summaries of the year and when its input
+/// was downloaded, in seconds since the Unix epoch.
+pub fn day_stats(
+    day: u8,
+    stars: u8,
+    submissions: &[Submission],
+    summaries: &[PerfSummary],
+    input_downloaded: Option<u64>,
+) -> DayStats {
+    let runtime = |part: u8| {
+        summaries
+            .iter()
+            .find(|summary| summary.day == day && summary.part == part)
+            .map(|summary| summary.avg_ms)
+    };
+    let first_correct = submissions
+        .iter()
+        .filter(|submission| submission.verdict == "correct")
+        .map(|submission| submission.timestamp)
+        .min();
+    DayStats {
+        day,
+        stars,
+        attempts: submissions.len(),
+        runtime_ms: [runtime(1), runtime(2)],
+        solve_secs: first_correct
+            .zip(input_downloaded)
+            .and_then(|(correct, downloaded)| correct.checked_sub(downloaded)),
+        official_secs: [None, None],
+    }
+}
+
+/// Seconds from each puzzle of `year` unlocking to `member` getting each star, by day.
+pub fn official_times(member: &Member, year: u16) -> BTreeMap<u8, [Option<u64>; 2]> {
+    member
+        .completion_day_level
+        .iter()
+        .map(|(day, parts)| {
+            let unlock = crate::unlock::unlock_time(year, *day);
+            let time = |part: u8| {
+                parts
+                    .get(&part)
+                    .and_then(|star| star.get_star_ts.checked_sub(unlock))
+            };
+            (*day, [time(1), time(2)])
+        })
+        .collect()
+}
+
+/// Fill in the official times of `days` with those of the member named `username` on the private
+/// `leaderboard` of `year`.
+pub fn add_official_times(
+    days: &mut [DayStats],
+    leaderboard: &crate::leaderboard::Leaderboard,
+    username: &str,
+    year: u16,
+) -> Result<(), AocError> {
+    let member = leaderboard
+        .members
+        .values()
+        .find(|member| member.name.as_deref() == Some(username))
+        .ok_or_else(|| AocError::Config(format!("{} isn't on leaderboard {}", username, leaderboard.owner_id)))?;
+    let times = official_times(member, year);
+    for day in days {
+        if let Some(official_secs) = times.get(&day.day) {
+            day.official_secs = *official_secs;
+        }
+    }
+    Ok(())
+}
+
+/// Stats of every day in `year_dir`, ordered by day. Input is taken to have been downloaded when
+/// `input.txt` was last modified.
+pub fn collect(year_dir: &Path, day_format: &str) -> Result<Vec<DayStats>, AocError> {
+    let summaries = crate::perf::summarize(&crate::perf::read(year_dir)?, None);
+    let stars: BTreeMap<u8, u8> = crate::calendar::collect(year_dir, day_format)?
+        .into_iter()
+        .map(|day| (day.day, day.stars))
+        .collect();
+    let mut days = Vec::new();
+    for (day, day_dir) in crate::scaffold::day_dirs(year_dir, day_format)? {
+        let submissions = crate::submissions::read(&day_dir)?;
+        let input_downloaded = std::fs::metadata(day_dir.join("input.txt"))
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|since| since.as_secs());
+        let stars = stars.get(&day).copied().unwrap_or_default();
+        days.push(day_stats(day, stars, &submissions, &summaries, input_downloaded));
+    }
+    Ok(days)
+}
+
+/// Format `secs` like `1h 02m 03s`.
+fn format_secs(secs: u64) -> String {
+    match (secs / 3_600, secs / 60 % 60, secs % 60) {
+        (0, 0, seconds) => format!("{}s", seconds),
+        (0, minutes, seconds) => format!("{}m {:02}s", minutes, seconds),
+        (hours, minutes, seconds) => format!("{}h {:02}m {:02}s", hours, minutes, seconds),
+    }
+}
+
+pub fn render_table(days: &[DayStats]) -> String {
+    let or_dash = |value: Option<String>| value.unwrap_or_else(|| "-".to_owned());
+    let ms = |ms: Option<f64>| or_dash(ms.map(|ms| format!("{:.3}", ms)));
+    let secs = |secs: Option<u64>| or_dash(secs.map(format_secs));
+    let official = days.iter().any(|day| day.official_secs.iter().any(Option::is_some));
+
+    let mut table = format!(
+        "{:>3} {:>5} {:>8} {:>12} {:>12} {:>12}",
+        "Day", "Stars", "Attempts", "Part 1 (ms)", "Part 2 (ms)", "Solved in"
+    );
+    if official {
+        table.push_str(&format!(" {:>12} {:>12}", "Official 1", "Official 2"));
+    }
+    table.push('\n');
+    for day in days {
+        table.push_str(&format!(
+            "{:>3} {:>5} {:>8} {:>12} {:>12} {:>12}",
+            day.day,
+            day.stars,
+            day.attempts,
+            ms(day.runtime_ms[0]),
+            ms(day.runtime_ms[1]),
+            secs(day.solve_secs)
+        ));
+        if official {
+            table.push_str(&format!(
+                " {:>12} {:>12}",
+                secs(day.official_secs[0]),
+                secs(day.official_secs[1])
+            ));
+        }
+        table.push('\n');
+    }
+    let stars: u32 = days.iter().map(|day| u32::from(day.stars)).sum();
+    table.push_str(&format!("Total stars: {}\n", stars));
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn submission(timestamp: u64, part: u8, verdict: &str) -> Submission {
+        Submission {
+            timestamp,
+            part,
+            answer: "42".to_owned(),
+            verdict: verdict.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_day_stats() {
+        let summaries = crate::perf::summarize(
+            &[
+                crate::perf::PerfEntry { timestamp: 0, day: 1, part: 1, elapsed_ms: 2.0, timed_out: false },
+                crate::perf::PerfEntry { timestamp: 0, day: 1, part: 1, elapsed_ms: 4.0, timed_out: false },
+                crate::perf::PerfEntry { timestamp: 0, day: 2, part: 2, elapsed_ms: 8.0, timed_out: false },
+            ],
+            None,
+        );
+        let submissions = [
+            submission(1_300, 1, "too low"),
+            submission(1_500, 1, "correct"),
+            submission(1_900, 2, "correct"),
+        ];
+
+        let stats = day_stats(1, 2, &submissions, &summaries, Some(1_000));
+        assert_eq!(
+            stats,
+            DayStats {
+                day: 1,
+                stars: 2,
+                attempts: 3,
+                runtime_ms: [Some(3.0), None],
+                solve_secs: Some(500),
+                official_secs: [None, None],
+            }
+        );
+        assert_eq!(day_stats(1, 0, &submissions[..1], &summaries, Some(1_000)).solve_secs, None);
+        assert_eq!(day_stats(1, 2, &submissions, &summaries, None).solve_secs, None);
+
+        let table = render_table(&[stats]);
+        assert_eq!(
+            table,
+            "Day Stars Attempts  Part 1 (ms)  Part 2 (ms)    Solved in\n  \
+               1     2        3        3.000            -       8m 20s\n\
+             Total stars: 2\n"
+        );
+    }
+
+    #[test]
+    fn test_official_times() {
+        let leaderboard = crate::leaderboard::parse(include_str!("../tests/fixtures/leaderboard.json")).unwrap();
+        let times = official_times(&leaderboard.members["1001"], 2022);
+        assert_eq!(times[&1], [Some(600), Some(900)]);
+        assert_eq!(times[&2], [Some(600), None]);
+
+        let mut days = [day_stats(1, 2, &[], &[], None), day_stats(3, 0, &[], &[], None)];
+        add_official_times(&mut days, &leaderboard, "Alice", 2022).unwrap();
+        assert_eq!(days.clone().map(|day| day.official_secs), [[Some(600), Some(900)], [None, None]]);
+        let err = add_official_times(&mut days, &leaderboard, "Mallory", 2022).unwrap_err();
+        assert_eq!(err.to_string(), "Mallory isn't on leaderboard 1001");
+        assert_eq!(format_secs(3_723), "1h 02m 03s");
+        assert_eq!(format_secs(59), "59s");
+    }
+}
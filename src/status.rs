@@ -0,0 +1,179 @@
+//! Summary of the current day, for `aoc status`.
+use crate::error::AocError;
+use crate::submissions::Submission;
+use crate::unlock::unlock_time;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DayStatus {
+    pub year: u16,
+    pub day: u8,
+    /// Parts with a binary, e.g. `1` and `2`.
+    pub parts: Vec<String>,
+    pub input: bool,
+    pub example: bool,
+    /// Title of the puzzle, cached in the day's `[package.metadata.aoc]`.
+    pub title: Option<String>,
+    /// Correct answers by part, from `answers.toml`.
+    pub answers: BTreeMap<String, String>,
+    /// Latest submission of each part.
+    pub submissions: BTreeMap<u8, Submission>,
+    pub unlocked: bool,
+    /// Stars earned on the puzzle, only known when checked remotely.
+    pub stars: Option<usize>,
+}
+
+/// Collect the status of `day` of `year` from `day_dir`, with `now` as seconds since the Unix
+/// epoch.
+pub fn collect(day_dir: &Path, year: u16, day: u8, now: u64) -> Result<DayStatus, AocError> {
+    let mut submissions = BTreeMap::new();
+    for submission in crate::submissions::read(day_dir)? {
+        submissions.insert(submission.part, submission);
+    }
+    Ok(DayStatus {
+        year,
+        day,
+        parts: crate::scaffold::part_files(day_dir).into_iter().map(|(part, _)| part).collect(),
+        input: day_dir.join("input.txt").exists(),
+        example: day_dir.join("example.txt").exists(),
+        title: read_title(&day_dir.join("Cargo.toml")),
+        answers: crate::report::read_answers(day_dir)?,
+        submissions,
+        unlocked: now >= unlock_time(year, day),
+        stars: None,
+    })
+}
+
+/// Update `status` with the puzzle page `html` of its day: the stars earned and the title, cached
+/// in the manifest of `day_dir` when it changed.
+pub fn update_from_page(status: &mut DayStatus, day_dir: &Path, html: &str) -> Result<(), AocError> {
+    status.stars = Some(count_stars(html));
+    if let Some(title) = parse_title(html) {
+        if status.title.as_ref() != Some(&title) {
+            crate::scaffold::cache_title(&day_dir.join("Cargo.toml"), &title)?;
+        }
+        status.title = Some(title);
+    }
+    Ok(())
+}
+
+/// The title in `[package.metadata.aoc]` of the day's manifest at `cargo_toml`, if any.
+pub fn read_title(cargo_toml: &Path) -> Option<String> {
+    let cargo_toml = std::fs::read_to_string(cargo_toml).ok()?.parse::<toml_edit::Document>().ok()?;
+    let title = cargo_toml.get("package")?.get("metadata")?.get("aoc")?.get("title")?;
+    title.as_str().map(str::to_owned)
+}
+
+/// Title from the puzzle page, e.g. `Calorie Counting` from `<h2>--- Day 1: Calorie Counting ---</h2>`.
+pub fn parse_title(html: &str) -> Option<String> {
+    let start = html.find("<h2>")? + "<h2>".len();
+    let end = start + html[start..].find("</h2>")?;
+    let heading = html[start..end].trim().trim_matches('-').trim();
+    let title = heading.split_once(": ").map_or(heading, |(_, title)| title);
+    Some(title.to_owned())
+}
+
+/// Stars earned, from the answers shown on the puzzle page.
+pub fn count_stars(html: &str) -> usize {
+    html.matches("Your puzzle answer was").count()
+}
+
+pub fn render(status: &DayStatus) -> String {
+    let yes_no = |exists: bool| if exists { "yes" } else { "no" };
+    let mut text = format!("{} day {}", status.year, status.day);
+    if let Some(title) = &status.title {
+        text.push_str(&format!(": {}", title));
+    }
+    text.push('\n');
+    let parts = if status.parts.is_empty() {
+        "none".to_owned()
+    } else {
+        status.parts.join(", ")
+    };
+    text.push_str(&format!("Parts: {}\n", parts));
+    text.push_str(&format!("input.txt: {}\n", yes_no(status.input)));
+    text.push_str(&format!("example.txt: {}\n", yes_no(status.example)));
+    for part in 1..=2 {
+        let answer = status.answers.get(&format!("part_{}", part));
+        let submission = status.submissions.get(&part);
+        if answer.is_none() && submission.is_none() {
+            continue;
+        }
+        text.push_str(&format!("Part {}:", part));
+        match answer {
+            Some(answer) => text.push_str(&format!(" answer {}", answer)),
+            None => text.push_str(" no answer"),
+        }
+        if let Some(submission) = submission {
+            text.push_str(&format!(" (last submitted {}: {})", submission.answer, submission.verdict));
+        }
+        text.push('\n');
+    }
+    text.push_str(&format!("Unlocked: {}\n", yes_no(status.unlocked)));
+    if let Some(stars) = status.stars {
+        text.push_str(&format!("Stars: {}\n", stars));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_page() {
+        let html = r#"<main><article class="day-desc"><h2>--- Day 1: Calorie Counting ---</h2>
+<p>Your puzzle answer was <code>24000</code>.</p></article>"#;
+        assert_eq!(parse_title(html), Some("Calorie Counting".to_owned()));
+        assert_eq!(count_stars(html), 1);
+        assert_eq!(parse_title("<p>Not a puzzle</p>"), None);
+    }
+
+    #[test]
+    fn test_status() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let day_dir = tmp_dir.path().join("day-01");
+        std::fs::create_dir_all(day_dir.join("src/bin")).unwrap();
+        std::fs::write(
+            day_dir.join("Cargo.toml"),
+            "[package]\nname = \"day-01\"\n\n[package.metadata.aoc]\ntitle = \"Calorie Counting\"\n",
+        )
+        .unwrap();
+        std::fs::write(day_dir.join("src/bin/part_1.rs"), "").unwrap();
+        std::fs::write(day_dir.join("src/bin/part_2.rs"), "").unwrap();
+        std::fs::write(day_dir.join("input.txt"), "").unwrap();
+        std::fs::write(day_dir.join("answers.toml"), "part_1 = 24000\n").unwrap();
+        for (part, answer, verdict) in [(1, "24000", "correct"), (2, "10", "too low"), (2, "99", "too high")] {
+            crate::submissions::record(&day_dir, &Submission::now(part, answer, verdict)).unwrap();
+        }
+
+        let status = collect(&day_dir, 2022, 1, unlock_time(2022, 1)).unwrap();
+        assert_eq!(status.parts, vec!["1", "2"]);
+        assert!(status.unlocked);
+        assert!(!collect(&day_dir, 2022, 2, unlock_time(2022, 1)).unwrap().unlocked);
+        assert_eq!(
+            render(&status),
+            "2022 day 1: Calorie Counting\n\
+             Parts: 1, 2\n\
+             input.txt: yes\n\
+             example.txt: no\n\
+             Part 1: answer 24000 (last submitted 24000: correct)\n\
+             Part 2: no answer (last submitted 99: too high)\n\
+             Unlocked: yes\n"
+        );
+
+        let json = serde_json::to_value(&status).unwrap();
+        assert_eq!(json["title"], "Calorie Counting");
+        assert_eq!(json["answers"]["part_1"], "24000");
+        assert_eq!(json["submissions"]["2"]["verdict"], "too high");
+        assert_eq!(json["stars"], serde_json::Value::Null);
+
+        let mut status = status;
+        let html = "<h2>--- Day 1: Calorie Counting, Again ---</h2><p>Your puzzle answer was <code>24000</code>.</p>";
+        update_from_page(&mut status, &day_dir, html).unwrap();
+        assert_eq!((status.stars, status.title.as_deref()), (Some(1), Some("Calorie Counting, Again")));
+        assert_eq!(read_title(&day_dir.join("Cargo.toml")).as_deref(), Some("Calorie Counting, Again"));
+    }
+}
@@ -0,0 +1,303 @@
+//! Submitted answers and their verdicts, recorded in `submissions.jsonl` in the day directory.
+use crate::client::{Answer, AocHttp, Hint, SubmissionResult};
+use crate::error::AocError;
+use crate::{env, perf, runner};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::time::Duration;
+
+const SUBMISSIONS_FILE: &str = "submissions.jsonl";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Submission {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub part: u8,
+    pub answer: String,
+    /// What AoC said, e.g. `correct` or `too high`.
+    pub verdict: String,
+}
+
+impl Submission {
+    pub fn now(part: u8, answer: &str, verdict: &str) -> Self {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        Submission {
+            timestamp,
+            part,
+            answer: answer.to_owned(),
+            verdict: verdict.to_owned(),
+        }
+    }
+}
+
+pub fn record(day_dir: &Path, submission: &Submission) -> Result<(), AocError> {
+    let path = day_dir.join(SUBMISSIONS_FILE);
+    let line = serde_json::to_string(submission).map_err(|e| AocError::Parse(format!("Failed to serialize: {}", e)))?;
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| writeln!(file, "{}", line))
+        .map_err(|e| AocError::io(format!("Failed to write {}", path.display()), e))?;
+    crate::logging::wrote(&path);
+    Ok(())
+}
+
+/// A part of a day's puzzle to submit answers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Puzzle {
+    pub year: u16,
+    pub day: u8,
+    pub part: u8,
+}
+
+/// Run the part's built `executable` in `day_dir`, killed after `timeout`, recording its runtime in
+/// the year's performance log, and submit its answer.
+pub fn submit(
+    http: &impl AocHttp,
+    day_dir: &Path,
+    puzzle: Puzzle,
+    executable: &Path,
+    timeout: Option<Duration>,
+) -> Result<SubmissionResult, AocError> {
+    let year_dir = env::year_dir_of(day_dir)?;
+    let output = match timeout {
+        Some(timeout) => match runner::execute_part_with_timeout(executable, day_dir, timeout)? {
+            Some(output) => output,
+            None => {
+                let entry = perf::PerfEntry::timed_out(puzzle.day, puzzle.part, timeout);
+                if let Err(err) = perf::record(year_dir, &entry) {
+                    eprintln!("{}", err.to_string().yellow());
+                }
+                return Err(AocError::Build(format!("Part {} timed out after {:?}", puzzle.part, timeout)));
+            }
+        },
+        None => runner::execute_part(executable, day_dir, &[])?,
+    };
+    if !output.status.success() {
+        return Err(AocError::PartFailed { part: puzzle.part, status: output.status });
+    }
+    if let Err(err) = perf::record(year_dir, &perf::PerfEntry::now(puzzle.day, puzzle.part, output.elapsed)) {
+        eprintln!("{}", err.to_string().yellow());
+    }
+    post(http, day_dir, puzzle, output.answer())
+}
+
+/// Submit `answer` to `puzzle`, recording it in the submission history of `day_dir`.
+pub fn post(http: &impl AocHttp, day_dir: &Path, puzzle: Puzzle, answer: &str) -> Result<SubmissionResult, AocError> {
+    let result = http.post_answer(puzzle.year, puzzle.day, puzzle.part, answer)?;
+    if let Err(err) = record(day_dir, &result.submission()) {
+        eprintln!("{}", err.to_string().yellow());
+    }
+    Ok(result)
+}
+
+/// Bisect `candidates` for the one whose answer, as given by `answer_of`, is correct, submitting
+/// each. When rate limited, `wait` out the time left, a minute if AoC doesn't say, and retry.
+pub fn bisect(
+    http: &impl AocHttp,
+    day_dir: &Path,
+    puzzle: Puzzle,
+    candidates: RangeInclusive<i64>,
+    mut answer_of: impl FnMut(i64) -> Result<String, AocError>,
+    mut wait: impl FnMut(Duration),
+) -> Result<i64, AocError> {
+    crate::client::bisect(*candidates.start(), *candidates.end(), |candidate| {
+        let answer = answer_of(candidate)?;
+        loop {
+            let result = post(http, day_dir, puzzle, &answer)?;
+            println!("{} -> {}: {}", candidate, answer, result.verdict);
+            let Answer::RateLimited(left) = result.verdict else {
+                return Ok(result.verdict);
+            };
+            let left = left.unwrap_or(Duration::from_secs(60));
+            println!("{}", format!("Rate limited, waiting {}s", left.as_secs()).yellow());
+            wait(left);
+        }
+    })
+}
+
+/// AoC's message and the verdict of a submission from `day_dir`, suggesting to start part 2 once
+/// part 1 is done.
+pub fn render(result: &SubmissionResult, day_dir: &Path) -> String {
+    let verdict = match result.verdict {
+        Answer::Correct => "Correct".green(),
+        Answer::Incorrect(None) => "Incorrect".red(),
+        Answer::Incorrect(Some(Hint::TooHigh)) => "Incorrect, too high".red(),
+        Answer::Incorrect(Some(Hint::TooLow)) => "Incorrect, too low".red(),
+        Answer::AlreadySubmitted(None) => "Already Submitted".yellow(),
+        Answer::AlreadySubmitted(Some(completed)) => {
+            let completed = if completed == 2 {
+                "Both parts already completed"
+            } else if !day_dir.join("src/bin/part_2.rs").exists() {
+                "Part 1 already completed — run `aoc part` to start part 2"
+            } else {
+                "Part 1 already completed"
+            };
+            format!("Already Submitted\n{}", completed).yellow()
+        }
+        Answer::RateLimited(None) => "Rate Limited".red(),
+        Answer::RateLimited(Some(wait)) => format!("Rate Limited, {}s left to wait", wait.as_secs()).red(),
+    };
+    format!("{}\n{}\n", result.message, verdict)
+}
+
+/// All recorded submissions, oldest first, skipping lines which can't be parsed.
+pub fn read(day_dir: &Path) -> Result<Vec<Submission>, AocError> {
+    let path = day_dir.join(SUBMISSIONS_FILE);
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(AocError::io(format!("Failed to read {}", path.display()), e)),
+    };
+    Ok(contents
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::urls::Page;
+    use std::cell::{Cell, RefCell};
+
+    /// AoC with `correct` as the answer, rate limiting the first `rate_limits` submissions.
+    struct FakeAoc {
+        correct: i64,
+        rate_limits: Cell<usize>,
+        posted: RefCell<Vec<String>>,
+    }
+
+    impl FakeAoc {
+        fn new(correct: i64, rate_limits: usize) -> Self {
+            FakeAoc { correct, rate_limits: Cell::new(rate_limits), posted: RefCell::new(Vec::new()) }
+        }
+    }
+
+    impl AocHttp for FakeAoc {
+        fn fetch_input(&self, _year: u16, _day: u8) -> Result<String, AocError> {
+            unimplemented!()
+        }
+
+        fn fetch_page(&self, _page: Page) -> Result<String, AocError> {
+            unimplemented!()
+        }
+
+        fn post_answer(&self, _year: u16, _day: u8, part: u8, answer: &str) -> Result<SubmissionResult, AocError> {
+            self.posted.borrow_mut().push(answer.to_owned());
+            let verdict = if self.rate_limits.get() > 0 {
+                self.rate_limits.set(self.rate_limits.get() - 1);
+                Answer::RateLimited(Some(Duration::from_secs(30)))
+            } else {
+                let answer: i64 = answer.parse().map_err(|_| AocError::refused("Not a number"))?;
+                match answer.cmp(&self.correct) {
+                    std::cmp::Ordering::Less => Answer::Incorrect(Some(Hint::TooLow)),
+                    std::cmp::Ordering::Greater => Answer::Incorrect(Some(Hint::TooHigh)),
+                    std::cmp::Ordering::Equal => Answer::Correct,
+                }
+            };
+            let message = verdict.to_string();
+            Ok(SubmissionResult { part, answer: answer.to_owned(), verdict, message, submitted_at: 0 })
+        }
+
+        fn fetch_username(&self) -> Result<Option<String>, AocError> {
+            unimplemented!()
+        }
+    }
+
+    const PUZZLE: Puzzle = Puzzle { year: 2023, day: 1, part: 1 };
+
+    #[test]
+    fn test_post() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let aoc = FakeAoc::new(42, 0);
+
+        let result = post(&aoc, tmp_dir.path(), PUZZLE, "50").unwrap();
+        assert_eq!(result.verdict, Answer::Incorrect(Some(Hint::TooHigh)));
+        let result = post(&aoc, tmp_dir.path(), PUZZLE, "42").unwrap();
+        assert_eq!(result.exit_code(), 0);
+
+        let recorded: Vec<_> = read(tmp_dir.path()).unwrap().into_iter().map(|s| (s.answer, s.verdict)).collect();
+        assert_eq!(recorded, [("50".to_owned(), "too high".to_owned()), ("42".to_owned(), "correct".to_owned())]);
+    }
+
+    #[test]
+    fn test_bisect() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let aoc = FakeAoc::new(42, 0);
+
+        let found = bisect(&aoc, tmp_dir.path(), PUZZLE, 0..=100, |c| Ok((c * 2).to_string()), |_| {}).unwrap();
+        assert_eq!(found, 21);
+        assert_eq!(*aoc.posted.borrow(), ["100", "48", "22", "34", "40", "44", "42"]);
+        assert_eq!(read(tmp_dir.path()).unwrap().len(), 7);
+    }
+
+    #[test]
+    fn test_bisect_waits_out_rate_limits() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let aoc = FakeAoc::new(50, 2);
+        let mut waited = Vec::new();
+
+        let found = bisect(&aoc, tmp_dir.path(), PUZZLE, 0..=100, |c| Ok(c.to_string()), |left| waited.push(left));
+        assert_eq!(found.unwrap(), 50);
+        assert_eq!(*aoc.posted.borrow(), ["50", "50", "50"]);
+        assert_eq!(waited, [Duration::from_secs(30); 2]);
+    }
+
+    #[test]
+    fn test_bisect_stops_on_errors() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let aoc = FakeAoc::new(42, 0);
+
+        let err = bisect(&aoc, tmp_dir.path(), PUZZLE, 0..=100, |_| Ok("x".to_owned()), |_| {}).unwrap_err();
+        assert!(matches!(err, AocError::Network { source: None, .. }));
+        let err = bisect(&aoc, tmp_dir.path(), PUZZLE, 0..=100, |_| Err(AocError::Aborted), |_| {}).unwrap_err();
+        assert!(matches!(err, AocError::Aborted));
+        assert_eq!(*aoc.posted.borrow(), ["x"]);
+    }
+
+    #[test]
+    fn test_render() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let result = |verdict| SubmissionResult {
+            part: 1,
+            answer: "42".to_owned(),
+            verdict,
+            message: "That's the right answer!".to_owned(),
+            submitted_at: 0,
+        };
+
+        let text = render(&result(Answer::Correct), tmp_dir.path());
+        assert!(text.starts_with("That's the right answer!\n"));
+        assert!(text.contains("Correct"));
+        let text = render(&result(Answer::AlreadySubmitted(Some(1))), tmp_dir.path());
+        assert!(text.contains("Part 1 already completed — run `aoc part` to start part 2"));
+        std::fs::create_dir_all(tmp_dir.path().join("src/bin")).unwrap();
+        std::fs::write(tmp_dir.path().join("src/bin/part_2.rs"), "").unwrap();
+        let text = render(&result(Answer::AlreadySubmitted(Some(1))), tmp_dir.path());
+        assert!(text.contains("Part 1 already completed") && !text.contains("aoc part"));
+    }
+
+    #[test]
+    fn test_record_and_read() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        assert_eq!(read(tmp_dir.path()).unwrap(), Vec::new());
+
+        let first = Submission::now(1, "100", "too low");
+        let second = Submission::now(1, "150", "correct");
+        record(tmp_dir.path(), &first).unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(tmp_dir.path().join(SUBMISSIONS_FILE))
+            .and_then(|mut file| writeln!(file, "not json"))
+            .unwrap();
+        record(tmp_dir.path(), &second).unwrap();
+        assert_eq!(read(tmp_dir.path()).unwrap(), vec![first, second]);
+    }
+}
@@ -0,0 +1,130 @@
+//! One line of answers per day, for `aoc summarize`.
+use crate::error::AocError;
+use colored::*;
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PartOutcome {
+    /// The day has no binary for the part.
+    Missing,
+    Answer(String),
+    Failed(String),
+}
+
+/// Build and run both parts of the day in `day_dir`, stopping each after `timeout`.
+pub fn run_day(day_dir: &Path, cargo_args: &[String], timeout: Duration) -> [PartOutcome; 2] {
+    [1, 2].map(|part| {
+        if !day_dir.join(format!("src/bin/part_{}.rs", part)).exists() {
+            return PartOutcome::Missing;
+        }
+        match crate::runner::build_part(day_dir, part, cargo_args)
+            .and_then(|executable| run_with_timeout(&executable, day_dir, timeout))
+        {
+            Ok(answer) => PartOutcome::Answer(answer),
+            Err(err) => PartOutcome::Failed(err.to_string()),
+        }
+    })
+}
+
+/// Build the days of `year_dir` for release and print a line per day, with the answers of its
+/// parts against the known ones.
+pub fn year(year_dir: &Path, day_format: &str, timeout: Duration) -> Result<(), AocError> {
+    let cargo_args = vec!["--release".to_owned()];
+    if year_dir.join("Cargo.toml").exists() {
+        // Failures show up again when building the parts
+        let _ = crate::runner::cargo_build(year_dir, &["--workspace", "--bins", "--keep-going"], &cargo_args);
+    }
+    for (day, day_dir) in crate::scaffold::day_dirs(year_dir, day_format)? {
+        let outcomes = run_day(&day_dir, &cargo_args, timeout);
+        let known = crate::report::read_answers(&day_dir).unwrap_or_default();
+        println!("{}", render_line(day, &outcomes, &known));
+    }
+    Ok(())
+}
+
+/// Run `executable` in `day_dir`, returning its trimmed output, or an error if it fails or takes
+/// longer than `timeout`.
+fn run_with_timeout(executable: &Path, day_dir: &Path, timeout: Duration) -> Result<String, AocError> {
+    let mut child = std::process::Command::new(executable)
+        .current_dir(day_dir)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map_err(|e| AocError::io(format!("Failed to run {}", executable.display()), e))?;
+    let mut stdout = child.stdout.take().unwrap();
+    let reader = std::thread::spawn(move || {
+        let mut output = String::new();
+        stdout.read_to_string(&mut output).map(|_| output)
+    });
+
+    let start = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if start.elapsed() > timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(AocError::Build(format!("timed out after {:?}", timeout)));
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(10)),
+            Err(e) => return Err(AocError::io(format!("Failed to wait for {}", executable.display()), e)),
+        }
+    };
+    let output = reader
+        .join()
+        .unwrap()
+        .map_err(|e| AocError::io("Failed to read the output", e))?;
+    if !status.success() {
+        return Err(AocError::Build(format!("failed: {}", status)));
+    }
+    Ok(output.trim().to_owned())
+}
+
+/// `Day N: Part 1 = <answer>, Part 2 = <answer>`, with the answers matching `known` (from
+/// `answers.toml`) in green.
+pub fn render_line(day: u8, outcomes: &[PartOutcome; 2], known: &BTreeMap<String, String>) -> String {
+    let parts: Vec<String> = outcomes
+        .iter()
+        .enumerate()
+        .map(|(index, outcome)| {
+            let part = index + 1;
+            let answer = match outcome {
+                PartOutcome::Missing => "—".to_owned(),
+                PartOutcome::Answer(answer) if known.get(&format!("part_{}", part)) == Some(answer) => {
+                    answer.green().to_string()
+                }
+                PartOutcome::Answer(answer) => answer.clone(),
+                PartOutcome::Failed(err) => format!("({})", err).red().to_string(),
+            };
+            format!("Part {} = {}", part, answer)
+        })
+        .collect();
+    format!("Day {}: {}", day, parts.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_line() {
+        let known = BTreeMap::from([("part_1".to_owned(), "24000".to_owned())]);
+        let line = render_line(
+            1,
+            &[PartOutcome::Answer("24000".to_owned()), PartOutcome::Missing],
+            &known,
+        );
+        assert!(line.starts_with("Day 1: Part 1 = "));
+        assert!(line.contains("24000"));
+        assert!(line.ends_with(", Part 2 = —"));
+        let line = render_line(
+            2,
+            &[PartOutcome::Answer("1".to_owned()), PartOutcome::Answer("2".to_owned())],
+            &known,
+        );
+        assert_eq!(line, "Day 2: Part 1 = 1, Part 2 = 2");
+    }
+}
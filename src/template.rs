@@ -0,0 +1,464 @@
+//! Templates for scaffolding days and parts.
+//!
+//! The templates in `templates/` are embedded in the binary, and can be overridden by files with
+//! the same name in the user's template directory. Placeholders look like `{{year}}`.
+use crate::error::AocError;
+use colored::*;
+use std::path::{Path, PathBuf};
+
+/// Placeholders substituted by `render`.
+pub const PLACEHOLDERS: [&str; 6] = ["year", "day", "part", "name", "crate", "edition"];
+
+/// Values substituted into templates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variables {
+    pub year: u16,
+    pub day: u8,
+    pub part: u8,
+    /// Name of the day's crate, e.g. `day-01`.
+    pub name: String,
+    /// Rust edition of the day's crate, e.g. `2021`.
+    pub edition: &'static str,
+}
+
+impl Variables {
+    /// The variables of `part` of the day directory `day_dir`, named with `formats`, whose crate
+    /// uses `edition`.
+    pub fn of_day(
+        day_dir: &Path,
+        formats: &crate::clean::Formats,
+        part: u8,
+        edition: &'static str,
+    ) -> Result<Self, AocError> {
+        let year_dir = crate::env::year_dir_of(day_dir)?;
+        Ok(Variables {
+            year: crate::env::parse_year(year_dir, formats.year)?,
+            day: crate::env::parse_day(day_dir, formats.day)?,
+            part,
+            name: crate::env::dir_name(day_dir)?,
+            edition,
+        })
+    }
+
+    /// Name of the day's crate as used in Rust code, e.g. `day_01`.
+    pub fn crate_name(&self) -> String {
+        self.name.replace('-', "_")
+    }
+
+    fn get(&self, key: &str) -> Option<String> {
+        match key {
+            "year" => Some(self.year.to_string()),
+            "day" => Some(self.day.to_string()),
+            "part" => Some(self.part.to_string()),
+            "name" => Some(self.name.clone()),
+            "crate" => Some(self.crate_name()),
+            "edition" => Some(self.edition.to_owned()),
+            _ => None,
+        }
+    }
+}
+
+/// Directory with the user's templates: `$AOC_TEMPLATE_DIR`, or `aoc/templates` in the config
+/// directory.
+pub fn template_dir() -> Option<PathBuf> {
+    if let Ok(dir) = std::env::var("AOC_TEMPLATE_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    config_dir().map(|dir| dir.join("aoc").join("templates"))
+}
+
+/// The user's template directory, for the commands writing to it.
+pub fn require_dir() -> Result<PathBuf, AocError> {
+    template_dir().ok_or_else(|| AocError::Config("No template directory, set AOC_TEMPLATE_DIR".to_owned()))
+}
+
+/// `$XDG_CONFIG_HOME`, `%APPDATA%` on Windows, or `~/.config`.
+pub fn config_dir() -> Option<PathBuf> {
+    if let Ok(dir) = std::env::var("XDG_CONFIG_HOME") {
+        return Some(PathBuf::from(dir));
+    }
+    if cfg!(windows) {
+        if let Ok(dir) = std::env::var("APPDATA") {
+            return Some(PathBuf::from(dir));
+        }
+    }
+    std::env::var("HOME")
+        .ok()
+        .map(|home| PathBuf::from(home).join(".config"))
+}
+
+/// `$XDG_CACHE_HOME`, `%LOCALAPPDATA%` on Windows, or `~/.cache`.
+pub fn cache_dir() -> Option<PathBuf> {
+    if let Ok(dir) = std::env::var("XDG_CACHE_HOME") {
+        return Some(PathBuf::from(dir));
+    }
+    if cfg!(windows) {
+        if let Ok(dir) = std::env::var("LOCALAPPDATA") {
+            return Some(PathBuf::from(dir));
+        }
+    }
+    std::env::var("HOME")
+        .ok()
+        .map(|home| PathBuf::from(home).join(".cache"))
+}
+
+/// Templates embedded in the binary, by file name.
+const EMBEDDED: [(&str, &str); 7] = [
+    ("Cargo.toml", include_str!("../templates/Cargo.toml")),
+    ("part.rs", include_str!("../templates/part.rs")),
+    ("part_lib.rs", include_str!("../templates/part_lib.rs")),
+    ("lib.rs", include_str!("../templates/lib.rs")),
+    ("report.html", include_str!("../templates/report.html")),
+    ("badge.svg", include_str!("../templates/badge.svg")),
+    ("readme.md", include_str!("../templates/readme.md")),
+];
+
+/// Embedded template by file name.
+pub fn embedded(name: &str) -> Option<&'static str> {
+    EMBEDDED
+        .iter()
+        .find(|(embedded, _)| *embedded == name)
+        .map(|(_, template)| *template)
+}
+
+/// Names of the embedded templates and the user's templates, sorted.
+pub fn names() -> Vec<String> {
+    let mut names: Vec<String> = EMBEDDED.iter().map(|(name, _)| (*name).to_owned()).collect();
+    if let Some(Ok(entries)) = template_dir().map(std::fs::read_dir) {
+        names.extend(
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_file())
+                .map(|entry| entry.file_name().to_string_lossy().into_owned()),
+        );
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Template by file name, preferring the user's template over the embedded one.
+pub fn load(name: &str) -> Result<String, AocError> {
+    load_from(template_dir().as_deref(), None, name)
+}
+
+/// Template by file name from the set of templates in the subdirectory `set` of the user's
+/// template directory `dir`, falling back to the user's template and then the embedded one.
+pub fn load_from(dir: Option<&Path>, set: Option<&str>, name: &str) -> Result<String, AocError> {
+    let set_dir = match (dir, set) {
+        (Some(dir), Some(set)) if dir.join(set).is_dir() => Some(dir.join(set)),
+        (_, Some(set)) => return Err(AocError::Config(format!("Unknown template set: {}", set))),
+        (_, None) => None,
+    };
+    for path in [set_dir.map(|dir| dir.join(name)), dir.map(|dir| dir.join(name))].into_iter().flatten() {
+        if path.exists() {
+            return std::fs::read_to_string(&path)
+                .map_err(|e| AocError::io(format!("Failed to read template {}", path.display()), e));
+        }
+    }
+    embedded(name)
+        .map(|template| template.to_owned())
+        .ok_or_else(|| AocError::Config(format!("Unknown template: {}", name)))
+}
+
+/// Where a template comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Embedded,
+    User,
+    /// A user template replacing an embedded one.
+    Overridden,
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Source::Embedded => write!(f, "embedded"),
+            Source::User => write!(f, "user"),
+            Source::Overridden => write!(f, "user, overrides embedded"),
+        }
+    }
+}
+
+/// A set of templates, as its name (`None` for the default set) and its templates' names and sources.
+pub type TemplateSet = (Option<String>, Vec<(String, Source)>);
+
+/// The default templates, embedded or in the user's template directory `dir`, then the sets of
+/// templates in its subdirectories.
+pub fn list(dir: Option<&Path>) -> Vec<TemplateSet> {
+    let files = |dir: &Path| -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut files: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_file())
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+        files
+    };
+    let user = dir.map(files).unwrap_or_default();
+    let mut default: Vec<(String, Source)> = EMBEDDED
+        .iter()
+        .map(|(name, _)| {
+            let source = if user.iter().any(|user| user == name) { Source::Overridden } else { Source::Embedded };
+            ((*name).to_owned(), source)
+        })
+        .collect();
+    default.extend(
+        user.into_iter()
+            .filter(|name| embedded(name).is_none())
+            .map(|name| (name, Source::User)),
+    );
+    default.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut sets = vec![(None, default)];
+    if let Some(Ok(entries)) = dir.map(std::fs::read_dir) {
+        let mut set_dirs: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect();
+        set_dirs.sort();
+        for set_dir in set_dirs {
+            let name = set_dir.file_name().unwrap_or_default().to_string_lossy().into_owned();
+            sets.push((Some(name), files(&set_dir).into_iter().map(|file| (file, Source::User)).collect()));
+        }
+    }
+    sets
+}
+
+/// The sets of templates from `list`, each name in bold followed by its templates and their
+/// sources.
+pub fn render_list(sets: &[TemplateSet]) -> String {
+    let mut rendered = String::new();
+    for (set, templates) in sets {
+        rendered.push_str(&format!("{}\n", set.as_deref().unwrap_or("default").bold()));
+        for (name, source) in templates {
+            rendered.push_str(&format!("  {} ({})\n", name, source));
+        }
+    }
+    rendered
+}
+
+/// Whether the template is rendered with minijinja instead of `render`, like `report.html`,
+/// `badge.svg` and `readme.md`.
+fn is_jinja(name: &str) -> bool {
+    name.ends_with(".html") || name.ends_with(".svg") || name.ends_with(".md")
+}
+
+/// The `{{key}}` placeholders of `template`, as their start and end and their key.
+fn placeholders(template: &str) -> Vec<(usize, usize, &str)> {
+    let mut placeholders = Vec::new();
+    let mut offset = 0;
+    while let Some(start) = template[offset..].find("{{") {
+        let start = offset + start;
+        let Some(end) = template[start + 2..].find("}}") else {
+            break;
+        };
+        let end = start + 2 + end + 2;
+        placeholders.push((start, end, template[start + 2..end - 2].trim()));
+        offset = end;
+    }
+    placeholders
+}
+
+/// The template `name` with its placeholders highlighted: known ones in cyan, unknown ones in red.
+pub fn highlight(name: &str, template: &str) -> String {
+    let mut highlighted = String::with_capacity(template.len());
+    let mut last = 0;
+    for (start, end, key) in placeholders(template) {
+        highlighted.push_str(&template[last..start]);
+        let placeholder = &template[start..end];
+        if is_jinja(name) || PLACEHOLDERS.contains(&key) {
+            highlighted.push_str(&placeholder.cyan().bold().to_string());
+        } else {
+            highlighted.push_str(&placeholder.red().bold().to_string());
+        }
+        last = end;
+    }
+    highlighted.push_str(&template[last..]);
+    highlighted
+}
+
+/// Copy the embedded templates into the user's template directory `dir`, returning each
+/// template's name and whether it was written. Existing templates are kept, unless `force`.
+pub fn init(dir: &Path, force: bool) -> Result<Vec<(&'static str, bool)>, AocError> {
+    std::fs::create_dir_all(dir).map_err(|e| AocError::io(format!("Failed to create {}", dir.display()), e))?;
+    let mut written = Vec::new();
+    for (name, template) in EMBEDDED {
+        let path = dir.join(name);
+        if path.exists() && !force {
+            written.push((name, false));
+            continue;
+        }
+        std::fs::write(&path, template).map_err(|e| AocError::io(format!("Failed to write {}", path.display()), e))?;
+        crate::logging::wrote(&path);
+        written.push((name, true));
+    }
+    Ok(written)
+}
+
+/// Unknown placeholders in the user's templates in `dir` and its sets, as the template's path and
+/// the placeholder.
+pub fn check(dir: &Path) -> Result<Vec<(PathBuf, String)>, AocError> {
+    let mut unknown = Vec::new();
+    for (set, templates) in list(Some(dir)) {
+        let set_dir = set.map_or_else(|| dir.to_owned(), |set| dir.join(set));
+        for (name, source) in templates {
+            if source == Source::Embedded || is_jinja(&name) {
+                continue;
+            }
+            let path = set_dir.join(&name);
+            let template = std::fs::read_to_string(&path)
+                .map_err(|e| AocError::io(format!("Failed to read template {}", path.display()), e))?;
+            for (_, _, key) in placeholders(&template) {
+                if !PLACEHOLDERS.contains(&key) {
+                    unknown.push((path.clone(), format!("{{{{{}}}}}", key)));
+                }
+            }
+        }
+    }
+    Ok(unknown)
+}
+
+/// The unknown placeholders from `check`, a line each, then the known ones.
+pub fn render_unknown(unknown: &[(PathBuf, String)]) -> String {
+    let mut rendered = String::new();
+    for (path, placeholder) in unknown {
+        rendered.push_str(&format!("{}: unknown placeholder {}\n", path.display(), placeholder.red()));
+    }
+    rendered.push_str(&format!("Known placeholders: {}\n", PLACEHOLDERS.join(", ")));
+    rendered
+}
+
+/// Substitute `{{key}}` placeholders. Unknown placeholders are left as they are.
+pub fn render(template: &str, variables: &Variables) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after.find("}}") {
+            Some(end) => {
+                let key = after[..end].trim();
+                match variables.get(key) {
+                    Some(value) => rendered.push_str(&value),
+                    None => rendered.push_str(&rest[start..start + 2 + end + 2]),
+                }
+                rest = &after[end + 2..];
+            }
+            None => {
+                rendered.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let variables = Variables {
+            year: 2022,
+            day: 7,
+            part: 2,
+            name: "day-07".to_owned(),
+            edition: "2021",
+        };
+        assert_eq!(
+            render("Day {{day}} of {{ year }}, part {{part}} in {{name}}", &variables),
+            "Day 7 of 2022, part 2 in day-07"
+        );
+        assert_eq!(render(r#"println!("{}", {{unknown}});"#, &variables), r#"println!("{}", {{unknown}});"#);
+        assert_eq!(render("unclosed {{day", &variables), "unclosed {{day");
+    }
+
+    #[test]
+    fn test_variables_of_day() {
+        let formats = crate::clean::Formats { year: "advent-of-code-", day: "day-" };
+        let day_dir = Path::new("aoc/advent-of-code-2022/day-07");
+        assert_eq!(
+            Variables::of_day(day_dir, &formats, 2, "2021").unwrap(),
+            Variables { year: 2022, day: 7, part: 2, name: "day-07".to_owned(), edition: "2021" }
+        );
+        assert!(Variables::of_day(Path::new("aoc/advent-of-code-2022/notes"), &formats, 2, "2021").is_err());
+    }
+
+    #[test]
+    fn test_list_and_load() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let dir = tmp_dir.path();
+        std::fs::write(dir.join("part.rs"), "// user part\n").unwrap();
+        std::fs::write(dir.join("notes.md"), "# Day {{day}}\n").unwrap();
+        std::fs::create_dir(dir.join("grid")).unwrap();
+        std::fs::write(dir.join("grid/part.rs"), "// grid part\n").unwrap();
+
+        let sets = list(Some(dir));
+        assert_eq!(sets.len(), 2);
+        let (set, default) = &sets[0];
+        assert_eq!(*set, None);
+        assert!(default.contains(&("part.rs".to_owned(), Source::Overridden)));
+        assert!(default.contains(&("notes.md".to_owned(), Source::User)));
+        assert!(default.contains(&("lib.rs".to_owned(), Source::Embedded)));
+        assert_eq!(sets[1], (Some("grid".to_owned()), vec![("part.rs".to_owned(), Source::User)]));
+        let rendered = render_list(&sets);
+        assert!(rendered.contains("\n  part.rs (user, overrides embedded)\n"));
+        assert!(rendered.ends_with(&format!("{}\n  part.rs (user)\n", "grid".bold())));
+
+        assert_eq!(load_from(Some(dir), Some("grid"), "part.rs").unwrap(), "// grid part\n");
+        assert_eq!(load_from(Some(dir), None, "part.rs").unwrap(), "// user part\n");
+        // Falling back from the set to the embedded template
+        assert_eq!(load_from(Some(dir), Some("grid"), "lib.rs").unwrap(), embedded("lib.rs").unwrap());
+        let err = load_from(Some(dir), Some("fast"), "part.rs").unwrap_err();
+        assert_eq!(err.to_string(), "Unknown template set: fast");
+    }
+
+    #[test]
+    fn test_highlight() {
+        let highlighted = highlight("part.rs", "// Day {{day}}, {{ days }}\n");
+        assert_eq!(
+            highlighted,
+            format!("// Day {}, {}\n", "{{day}}".cyan().bold(), "{{ days }}".red().bold())
+        );
+    }
+
+    #[test]
+    fn test_init() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let dir = tmp_dir.path().join("templates");
+        std::fs::create_dir(&dir).unwrap();
+        std::fs::write(dir.join("part.rs"), "// mine\n").unwrap();
+
+        let written = init(&dir, false).unwrap();
+        assert!(written.contains(&("part.rs", false)));
+        assert!(written.contains(&("lib.rs", true)));
+        assert_eq!(std::fs::read_to_string(dir.join("part.rs")).unwrap(), "// mine\n");
+        assert_eq!(std::fs::read_to_string(dir.join("lib.rs")).unwrap(), embedded("lib.rs").unwrap());
+
+        init(&dir, true).unwrap();
+        assert_eq!(std::fs::read_to_string(dir.join("part.rs")).unwrap(), embedded("part.rs").unwrap());
+    }
+
+    #[test]
+    fn test_check() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let dir = tmp_dir.path();
+        init(dir, false).unwrap();
+        assert_eq!(check(dir).unwrap(), []);
+
+        std::fs::create_dir(dir.join("grid")).unwrap();
+        std::fs::write(dir.join("grid/part.rs"), "// Day {{day}} of {{ yaer }}\n").unwrap();
+        let unknown = check(dir).unwrap();
+        assert_eq!(unknown, [(dir.join("grid/part.rs"), "{{yaer}}".to_owned())]);
+        let rendered = render_unknown(&unknown);
+        assert!(rendered.starts_with(&format!("{}: unknown placeholder ", dir.join("grid/part.rs").display())));
+        assert!(rendered.ends_with("Known placeholders: year, day, part, name, crate, edition\n"));
+    }
+}
@@ -0,0 +1,271 @@
+//! Interactive terminal UI over the days of a year, for `aoc tui`.
+use crate::error::AocError;
+use crate::status::DayStatus;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::Frame;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+
+/// How often the statuses are read again from the day directories.
+const REFRESH: Duration = Duration::from_secs(3);
+
+/// How long to wait for a key before redrawing.
+const TICK: Duration = Duration::from_millis(100);
+
+pub struct App {
+    year_dir: PathBuf,
+    year: u16,
+    day_format: String,
+    days: Vec<(PathBuf, DayStatus)>,
+    selected: usize,
+    /// Output of the latest run.
+    output: Vec<String>,
+    scroll: u16,
+    /// Output of the run in progress, when it finishes.
+    running: Option<Receiver<Vec<String>>>,
+    refreshed: Instant,
+}
+
+/// What a key asks the app to do, besides updating itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    None,
+    Run,
+    Quit,
+}
+
+impl App {
+    pub fn new(year_dir: &Path, year: u16, day_format: &str) -> Result<Self, AocError> {
+        let mut app = App {
+            year_dir: year_dir.to_owned(),
+            year,
+            day_format: day_format.to_owned(),
+            days: Vec::new(),
+            selected: 0,
+            output: Vec::new(),
+            scroll: 0,
+            running: None,
+            refreshed: Instant::now(),
+        };
+        app.refresh()?;
+        Ok(app)
+    }
+
+    /// Read the statuses of the days again.
+    pub fn refresh(&mut self) -> Result<(), AocError> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        let mut days = Vec::new();
+        for (day, day_dir) in crate::scaffold::day_dirs(&self.year_dir, &self.day_format)? {
+            let status = crate::status::collect(&day_dir, self.year, day, now)?;
+            days.push((day_dir, status));
+        }
+        self.days = days;
+        self.selected = self.selected.min(self.days.len().saturating_sub(1));
+        self.refreshed = Instant::now();
+        Ok(())
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode) -> Command {
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return Command::Quit,
+            KeyCode::Up | KeyCode::Char('k') => self.select(self.selected.saturating_sub(1)),
+            KeyCode::Down | KeyCode::Char('j') => self.select(self.selected + 1),
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            KeyCode::PageDown => {
+                let last_line = u16::try_from(self.output.len().saturating_sub(1)).unwrap_or(u16::MAX);
+                self.scroll = (self.scroll + 10).min(last_line);
+            }
+            KeyCode::Enter if self.running.is_none() && !self.days.is_empty() => return Command::Run,
+            _ => {}
+        }
+        Command::None
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = index.min(self.days.len().saturating_sub(1));
+    }
+
+    /// Run the latest part of the selected day in the background, in release mode.
+    fn run_selected(&mut self) {
+        let Some((day_dir, _)) = self.days.get(self.selected) else {
+            return;
+        };
+        let day_dir = day_dir.clone();
+        let part = crate::scaffold::detect_latest_part(&day_dir);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(run_part(&day_dir, part));
+        });
+        self.output = vec![format!("Running part_{}...", part)];
+        self.scroll = 0;
+        self.running = Some(receiver);
+    }
+
+    /// Pick up the output of a finished run, and refresh the statuses when they're old.
+    fn update(&mut self) -> Result<(), AocError> {
+        if let Some(receiver) = &self.running {
+            match receiver.try_recv() {
+                Ok(output) => {
+                    self.output = output;
+                    self.running = None;
+                }
+                Err(TryRecvError::Disconnected) => self.running = None,
+                Err(TryRecvError::Empty) => {}
+            }
+        }
+        if self.refreshed.elapsed() >= REFRESH {
+            self.refresh()?;
+        }
+        Ok(())
+    }
+
+    pub fn draw(&self, frame: &mut Frame) {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(32), Constraint::Min(0)])
+            .split(frame.area());
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(10), Constraint::Min(0)])
+            .split(columns[1]);
+
+        let items: Vec<ListItem> = self.days.iter().map(|(_, status)| ListItem::new(day_line(status))).collect();
+        let days = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(format!(" {} ", self.year)))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let mut state = ListState::default().with_selected((!self.days.is_empty()).then_some(self.selected));
+        frame.render_stateful_widget(days, columns[0], &mut state);
+
+        let status = match self.days.get(self.selected) {
+            Some((_, status)) => crate::status::render(status),
+            None => "No days yet, create one with `aoc day`".to_owned(),
+        };
+        let status = Paragraph::new(status)
+            .block(Block::default().borders(Borders::ALL).title(" Status "))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(status, rows[0]);
+
+        let lines: Vec<Line> = self.output.iter().map(|line| Line::raw(line.as_str())).collect();
+        let output = Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Output (Enter: run, PgUp/PgDn: scroll, q: quit) "),
+            )
+            .scroll((self.scroll, 0));
+        frame.render_widget(output, rows[1]);
+    }
+}
+
+/// `Day 1 ** Calorie Counting`, with a star per answered part.
+fn day_line(status: &DayStatus) -> Line<'static> {
+    let stars = "*".repeat(status.answers.len().min(2));
+    let text = format!("Day {:>2} {:<2} {}", status.day, stars, status.title.as_deref().unwrap_or_default());
+    if status.input {
+        Line::raw(text)
+    } else {
+        Line::styled(text, Style::default().fg(Color::DarkGray))
+    }
+}
+
+/// Build and run `part` in `day_dir`, returning cargo's and the part's output as lines.
+fn run_part(day_dir: &Path, part: u8) -> Vec<String> {
+    let start = Instant::now();
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--release", "--quiet", "--bin", &format!("part_{}", part)])
+        .current_dir(day_dir)
+        .stdin(std::process::Stdio::null())
+        .output();
+    let output = match output {
+        Ok(output) => output,
+        Err(e) => return vec![format!("Failed to run cargo: {}", e)],
+    };
+    let mut lines: Vec<String> = String::from_utf8_lossy(&output.stdout).lines().map(str::to_owned).collect();
+    lines.extend(String::from_utf8_lossy(&output.stderr).lines().map(str::to_owned));
+    lines.push(String::new());
+    if output.status.success() {
+        lines.push(format!("part_{} finished in {:.2?}", part, start.elapsed()));
+    } else {
+        lines.push(format!("part_{} failed: {}", part, output.status));
+    }
+    lines
+}
+
+/// Run the app until it's quit, restoring the terminal afterwards.
+pub fn run(mut app: App) -> Result<(), AocError> {
+    let mut terminal = ratatui::try_init().map_err(|e| AocError::io("Failed to start the terminal UI", e))?;
+    let result = (|| loop {
+        terminal
+            .draw(|frame| app.draw(frame))
+            .map_err(|e| AocError::io("Failed to draw", e))?;
+        if event::poll(TICK).map_err(|e| AocError::io("Failed to read events", e))? {
+            if let Event::Key(key) = event::read().map_err(|e| AocError::io("Failed to read events", e))? {
+                if key.kind == KeyEventKind::Press {
+                    match app.handle_key(key.code) {
+                        Command::Quit => return Ok(()),
+                        Command::Run => app.run_selected(),
+                        Command::None => {}
+                    }
+                }
+            }
+        }
+        app.update()?;
+    })();
+    ratatui::restore();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    fn year_dir(parent: &Path) -> PathBuf {
+        let year_dir = parent.join("advent-of-code-2022");
+        for day in ["day-01", "day-02"] {
+            std::fs::create_dir_all(year_dir.join(day).join("src/bin")).unwrap();
+            std::fs::write(year_dir.join(day).join("src/bin/part_1.rs"), "fn main() {}\n").unwrap();
+        }
+        std::fs::write(year_dir.join("day-01/input.txt"), "1\n").unwrap();
+        std::fs::write(year_dir.join("day-01/answers.toml"), "part_1 = \"24000\"\n").unwrap();
+        year_dir
+    }
+
+    #[test]
+    fn test_handle_key() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let mut app = App::new(&year_dir(tmp_dir.path()), 2022, "day-").unwrap();
+        assert_eq!(app.days.len(), 2);
+
+        assert_eq!(app.handle_key(KeyCode::Up), Command::None);
+        assert_eq!(app.selected, 0);
+        app.handle_key(KeyCode::Down);
+        app.handle_key(KeyCode::Down);
+        assert_eq!(app.selected, 1);
+        assert_eq!(app.handle_key(KeyCode::Enter), Command::Run);
+        assert_eq!(app.handle_key(KeyCode::Char('q')), Command::Quit);
+    }
+
+    #[test]
+    fn test_draw() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let app = App::new(&year_dir(tmp_dir.path()), 2022, "day-").unwrap();
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let screen: String = buffer.content().iter().map(|cell| cell.symbol()).collect();
+        assert!(screen.contains("Day  1 *"));
+        assert!(screen.contains("Day  2"));
+        assert!(screen.contains("2022 day 1"));
+        assert!(screen.contains("Part 1: answer 24000"));
+    }
+}
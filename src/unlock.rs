@@ -0,0 +1,134 @@
+//! When puzzles unlock, for `aoc status` and `aoc countdown`.
+//!
+//! Puzzles unlock at midnight in US Eastern time. The event runs from 1 to 25 December, well after
+//! daylight saving time ends in early November, so that's always midnight EST (UTC-5), whatever
+//! the local time zone.
+
+/// Midnight EST, in UTC.
+const UNLOCK_HOUR_UTC: u64 = 5;
+
+/// Days with a puzzle.
+const LAST_DAY: u8 = 25;
+
+/// Seconds since the Unix epoch when the puzzle of `day` in December of `year` unlocks.
+pub fn unlock_time(year: u16, day: u8) -> u64 {
+    // Days from 1970-01-01 to 1 December of `year`, from Howard Hinnant's `days_from_civil`
+    let year = u64::from(year);
+    let (era, year_of_era) = (year / 400, year % 400);
+    let day_of_year = (153 * (12 - 3) + 2) / 5;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = (era * 146_097 + day_of_era).saturating_sub(719_468) + u64::from(day) - 1;
+    days * 86_400 + UNLOCK_HOUR_UTC * 3_600
+}
+
+/// The next puzzle to unlock after `now`, in seconds since the Unix epoch, as its year and day.
+pub fn next_unlock(now: u64) -> (u16, u8) {
+    // Starting a year early, as this doesn't count leap days
+    let year = u16::try_from(1970 + now / (365 * 86_400)).unwrap_or(u16::MAX).saturating_sub(1);
+    (year..=u16::MAX)
+        .flat_map(|year| (1..=LAST_DAY).map(move |day| (year, day)))
+        .find(|(year, day)| unlock_time(*year, *day) > now)
+        .unwrap_or((u16::MAX, LAST_DAY))
+}
+
+/// Format `secs` like `2d 03h 04m 05s`, leaving out leading zero units.
+pub fn format_remaining(secs: u64) -> String {
+    match (secs / 86_400, secs / 3_600 % 24, secs / 60 % 60, secs % 60) {
+        (0, 0, 0, seconds) => format!("{}s", seconds),
+        (0, 0, minutes, seconds) => format!("{}m {:02}s", minutes, seconds),
+        (0, hours, minutes, seconds) => format!("{}h {:02}m {:02}s", hours, minutes, seconds),
+        (days, hours, minutes, seconds) => format!("{}d {:02}h {:02}m {:02}s", days, hours, minutes, seconds),
+    }
+}
+
+/// `Day 5 of 2023 unlocks in 1h 00m 00s`, or when it unlocked if that's before `now`.
+pub fn describe(year: u16, day: u8, now: u64) -> String {
+    let unlock = unlock_time(year, day);
+    match unlock.checked_sub(now) {
+        Some(remaining) if remaining > 0 => {
+            format!("Day {} of {} unlocks in {}", day, year, format_remaining(remaining))
+        }
+        _ => format!("Day {} of {} unlocked {} ago", day, year, format_remaining(now - unlock)),
+    }
+}
+
+/// The puzzle for `aoc countdown`: of `day` in `year`, day 1 of `year`, or else the next to unlock
+/// after `now`.
+pub fn target(year: Option<u16>, day: Option<u8>, now: u64) -> (u16, u8) {
+    match (year, day) {
+        (None, None) => next_unlock(now),
+        (year, day) => (year.unwrap_or_else(|| next_unlock(now).0), day.unwrap_or(1)),
+    }
+}
+
+/// Count down on one line until the puzzle of `day` in `year` unlocks, by the clock of `now`.
+pub fn countdown(year: u16, day: u8, now: impl Fn() -> u64) {
+    use std::io::Write;
+
+    loop {
+        let now = now();
+        print!("\r{}\x1b[K", describe(year, day, now));
+        std::io::stdout().flush().ok();
+        if now >= unlock_time(year, day) {
+            println!();
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_secs(1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unlock_time() {
+        // 2022-12-01T05:00:00Z
+        assert_eq!(unlock_time(2022, 1), 1_669_870_800);
+        // 2015-12-25T05:00:00Z
+        assert_eq!(unlock_time(2015, 25), 1_451_019_600);
+        // 2024-12-01T05:00:00Z, the first Sunday of December in a leap year
+        assert_eq!(unlock_time(2024, 1), 1_733_029_200);
+    }
+
+    #[test]
+    fn test_next_unlock() {
+        // 2023-11-05T06:00:00Z, when EDT ends: still the first of December, at 05:00 UTC
+        assert_eq!(next_unlock(1_699_164_000), (2023, 1));
+        // 2023-12-05T04:59:59Z and 05:00:00Z
+        assert_eq!(next_unlock(unlock_time(2023, 5) - 1), (2023, 5));
+        assert_eq!(next_unlock(unlock_time(2023, 5)), (2023, 6));
+        // After the last day, and early in the next year
+        assert_eq!(next_unlock(unlock_time(2023, 25)), (2024, 1));
+        // 2024-01-01T00:00:00Z
+        assert_eq!(next_unlock(1_704_067_200), (2024, 1));
+        // 2024-03-10T07:00:00Z, when EDT starts
+        assert_eq!(next_unlock(1_710_054_000), (2024, 1));
+    }
+
+    #[test]
+    fn test_describe() {
+        let unlock = unlock_time(2023, 5);
+        assert_eq!(describe(2023, 5, unlock - 3_600), "Day 5 of 2023 unlocks in 1h 00m 00s");
+        assert_eq!(describe(2023, 5, unlock - 2 * 86_400 - 61), "Day 5 of 2023 unlocks in 2d 00h 01m 01s");
+        assert_eq!(describe(2023, 5, unlock), "Day 5 of 2023 unlocked 0s ago");
+        assert_eq!(describe(2023, 5, unlock + 90), "Day 5 of 2023 unlocked 1m 30s ago");
+    }
+
+    #[test]
+    fn test_target() {
+        let now = unlock_time(2023, 5);
+        assert_eq!(target(None, None, now), (2023, 6));
+        assert_eq!(target(None, Some(3), now), (2023, 3));
+        assert_eq!(target(Some(2022), None, now), (2022, 1));
+        assert_eq!(target(Some(2022), Some(3), now), (2022, 3));
+    }
+
+    #[test]
+    fn test_countdown() {
+        let now = std::cell::Cell::new(unlock_time(2023, 5) - 1);
+        // Returns once the clock reaches the unlock
+        countdown(2023, 5, || now.replace(now.get() + 1));
+        assert_eq!(now.get(), unlock_time(2023, 5) + 1);
+    }
+}
@@ -0,0 +1,115 @@
+//! Pages of adventofcode.com.
+use crate::error::AocError;
+
+pub const BASE_URL: &str = "https://adventofcode.com";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Page {
+    Calendar { year: u16 },
+    Puzzle { year: u16, day: u8 },
+    Input { year: u16, day: u8 },
+    Answer { year: u16, day: u8 },
+    /// A private leaderboard, by its id.
+    Leaderboard { year: u16, id: u64 },
+    /// A private leaderboard's JSON API.
+    LeaderboardJson { year: u16, id: u64 },
+    Settings,
+}
+
+impl Page {
+    /// URL of the page on `base_url`, usually `BASE_URL`.
+    pub fn url(&self, base_url: &str) -> String {
+        match self {
+            Page::Calendar { year } => format!("{}/{}", base_url, year),
+            Page::Puzzle { year, day } => format!("{}/{}/day/{}", base_url, year, day),
+            Page::Input { year, day } => format!("{}/{}/day/{}/input", base_url, year, day),
+            Page::Answer { year, day } => format!("{}/{}/day/{}/answer", base_url, year, day),
+            Page::Leaderboard { year, id } => {
+                format!("{}/{}/leaderboard/private/view/{}", base_url, year, id)
+            }
+            Page::LeaderboardJson { year, id } => {
+                format!("{}/{}/leaderboard/private/view/{}.json", base_url, year, id)
+            }
+            Page::Settings => format!("{}/settings", base_url),
+        }
+    }
+}
+
+/// The page `aoc open` opens: the puzzle, its input, or the private leaderboard `leaderboard_id`.
+pub fn open_page(
+    year: Option<u16>,
+    day: Option<u8>,
+    input: bool,
+    leaderboard: bool,
+    leaderboard_id: Option<u64>,
+) -> Result<Page, AocError> {
+    let year =
+        year.ok_or_else(|| AocError::Environment("No year, use --year or run from a year directory".to_owned()))?;
+    if leaderboard {
+        let id = leaderboard_id
+            .ok_or_else(|| AocError::Config("No leaderboard id, set it in the config's [leaderboard] id".to_owned()))?;
+        return Ok(Page::Leaderboard { year, id });
+    }
+    let day = day.ok_or_else(|| AocError::Environment("No day, use --day or run from a day directory".to_owned()))?;
+    if input {
+        Ok(Page::Input { year, day })
+    } else {
+        Ok(Page::Puzzle { year, day })
+    }
+}
+
+/// Open `url` in the default browser.
+#[cfg(feature = "browser")]
+pub fn open_in_browser(url: &str) -> Result<(), AocError> {
+    let mut command = if cfg!(target_os = "windows") {
+        let mut command = std::process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else if cfg!(target_os = "macos") {
+        std::process::Command::new("open")
+    } else {
+        std::process::Command::new("xdg-open")
+    };
+    let status = command
+        .arg(url)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map_err(|e| AocError::io("Failed to open a browser", e))?;
+    if !status.success() {
+        return Err(AocError::Environment(format!("Failed to open a browser: {}", status)));
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "browser"))]
+pub fn open_in_browser(_url: &str) -> Result<(), AocError> {
+    Err("aoc was built without opening a browser".to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_page() {
+        let url = |year, day, input, leaderboard, id| {
+            open_page(year, day, input, leaderboard, id).map(|page| page.url(BASE_URL))
+        };
+        assert_eq!(
+            url(Some(2022), Some(5), false, false, None).unwrap(),
+            "https://adventofcode.com/2022/day/5".to_owned()
+        );
+        assert_eq!(
+            url(Some(2022), Some(5), true, false, None).unwrap(),
+            "https://adventofcode.com/2022/day/5/input".to_owned()
+        );
+        assert_eq!(
+            url(Some(2021), None, false, true, Some(12345)).unwrap(),
+            "https://adventofcode.com/2021/leaderboard/private/view/12345".to_owned()
+        );
+        assert!(url(Some(2021), Some(1), false, true, None).is_err());
+        assert!(url(Some(2022), None, false, false, None).is_err());
+        assert!(url(None, Some(5), false, false, None).is_err());
+    }
+}
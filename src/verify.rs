@@ -0,0 +1,184 @@
+//! Checking solutions still give the accepted answers in `answers.toml`, for `aoc verify`.
+use crate::error::AocError;
+use crate::run_all::PartResult;
+use colored::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Match,
+    Mismatch,
+    /// The part failed to build or run.
+    Error,
+    /// No accepted answer to compare with.
+    Skipped,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Verification {
+    pub day: u8,
+    pub part: u8,
+    pub outcome: Outcome,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+    pub elapsed_ms: Option<f64>,
+    pub error: Option<String>,
+}
+
+/// Accepted answers of the parts of the days in `year_dir`, or only of `day`, by day and part.
+/// Parts without one are `None`.
+pub fn expected(
+    year_dir: &Path,
+    day_format: &str,
+    day: Option<u8>,
+) -> Result<BTreeMap<(u8, u8), Option<String>>, AocError> {
+    let mut expected = BTreeMap::new();
+    for (day_number, day_dir) in crate::scaffold::day_dirs(year_dir, day_format)? {
+        if day.is_some_and(|day| day != day_number) {
+            continue;
+        }
+        let answers = crate::report::read_answers(&day_dir)?;
+        for part in 1..=2 {
+            if day_dir.join(format!("src/bin/part_{}.rs", part)).exists() {
+                expected.insert((day_number, part), answers.get(&format!("part_{}", part)).cloned());
+            }
+        }
+    }
+    Ok(expected)
+}
+
+/// Compare the `results` of running the parts with their `expected` answers.
+pub fn compare(expected: &BTreeMap<(u8, u8), Option<String>>, results: &[PartResult]) -> Vec<Verification> {
+    expected
+        .iter()
+        .map(|(&(day, part), expected)| {
+            let result = results.iter().find(|result| result.day == day && result.part == part);
+            let outcome = match (expected, result) {
+                (None, _) => Outcome::Skipped,
+                (Some(_), Some(PartResult { error: Some(_), .. })) | (Some(_), None) => Outcome::Error,
+                (Some(expected), Some(result)) if result.answer.as_ref() == Some(expected) => Outcome::Match,
+                (Some(_), Some(_)) => Outcome::Mismatch,
+            };
+            Verification {
+                day,
+                part,
+                outcome,
+                expected: expected.clone(),
+                actual: result.and_then(|result| result.answer.clone()),
+                elapsed_ms: result.and_then(|result| result.elapsed_ms),
+                error: match outcome {
+                    Outcome::Error => Some(
+                        result
+                            .and_then(|result| result.error.clone())
+                            .unwrap_or_else(|| "Not run".to_owned()),
+                    ),
+                    _ => None,
+                },
+            }
+        })
+        .collect()
+}
+
+/// Whether every verified part matched, skipping the ones without an accepted answer.
+pub fn passed(verifications: &[Verification]) -> bool {
+    verifications
+        .iter()
+        .all(|verification| matches!(verification.outcome, Outcome::Match | Outcome::Skipped))
+}
+
+/// A line per part, with a ✓ or ✗ and the runtime, and a count of each outcome.
+pub fn render(verifications: &[Verification]) -> String {
+    let mut text = String::new();
+    for verification in verifications {
+        let name = format!("Day {} part {}", verification.day, verification.part);
+        let elapsed = verification
+            .elapsed_ms
+            .map_or_else(String::new, |elapsed_ms| format!(" ({:.3} ms)", elapsed_ms));
+        let expected = verification.expected.as_deref().unwrap_or_default();
+        let line = match verification.outcome {
+            Outcome::Match => format!("{} {}: {}{}", "✓".green(), name, expected, elapsed),
+            Outcome::Mismatch => format!(
+                "{} {}: expected {}, got {}{}",
+                "✗".red(),
+                name,
+                expected,
+                verification.actual.as_deref().unwrap_or_default(),
+                elapsed
+            ),
+            Outcome::Error => format!(
+                "{} {}: {}",
+                "✗".red(),
+                name,
+                verification.error.as_deref().unwrap_or_default()
+            ),
+            Outcome::Skipped => format!("{} {}: skipped, no accepted answer", "-".yellow(), name),
+        };
+        text.push_str(&line);
+        text.push('\n');
+    }
+    let count = |outcomes: &[Outcome]| {
+        verifications
+            .iter()
+            .filter(|verification| outcomes.contains(&verification.outcome))
+            .count()
+    };
+    text.push_str(&format!(
+        "{} passed, {} failed, {} skipped\n",
+        count(&[Outcome::Match]),
+        count(&[Outcome::Mismatch, Outcome::Error]),
+        count(&[Outcome::Skipped])
+    ));
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(day: u8, part: u8, answer: &str) -> PartResult {
+        PartResult {
+            day,
+            part,
+            answer: Some(answer.to_owned()),
+            elapsed_ms: Some(1.5),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_compare() {
+        let expected = BTreeMap::from([
+            ((1, 1), Some("24000".to_owned())),
+            ((1, 2), Some("45000".to_owned())),
+            ((2, 1), None),
+            ((3, 1), Some("15".to_owned())),
+        ]);
+        let results = [
+            result(1, 1, "24000"),
+            result(1, 2, "44999"),
+            PartResult {
+                day: 3,
+                part: 1,
+                answer: None,
+                elapsed_ms: None,
+                error: Some("No input.txt".to_owned()),
+            },
+        ];
+
+        let verifications = compare(&expected, &results);
+        let outcomes: Vec<Outcome> = verifications.iter().map(|verification| verification.outcome).collect();
+        assert_eq!(outcomes, [Outcome::Match, Outcome::Mismatch, Outcome::Skipped, Outcome::Error]);
+        assert!(!passed(&verifications));
+        assert!(passed(&verifications[..1]));
+
+        let text = render(&verifications);
+        assert!(text.contains("Day 1 part 1: 24000 (1.500 ms)"));
+        assert!(text.contains("Day 1 part 2: expected 45000, got 44999"));
+        assert!(text.contains("Day 2 part 1: skipped, no accepted answer"));
+        assert!(text.contains("Day 3 part 1: No input.txt"));
+        assert!(text.ends_with("1 passed, 2 failed, 1 skipped\n"));
+    }
+}
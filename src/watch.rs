@@ -0,0 +1,234 @@
+//! Rerunning a part or its tests whenever the day's sources or inputs change, for `aoc watch`.
+use crate::error::AocError;
+use colored::*;
+use notify::{RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+/// How long the files have to be quiet after a change before rerunning, so a save touching
+/// several files reruns once.
+pub const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// How often to check for Ctrl-C while waiting.
+const POLL: Duration = Duration::from_millis(50);
+
+/// What to rerun on changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mode {
+    /// Run `part`, against `input_path` instead of input.txt if set, killing it after `timeout`.
+    Run { part: u8, input_path: Option<PathBuf>, timeout: Option<Duration> },
+    /// Run the day's tests, or only `part`'s.
+    Test { part: Option<u8> },
+}
+
+/// Whether a change to `path` should rerun the day in `day_dir`: a Rust source in `src/`, or an
+/// input.
+pub fn is_relevant(path: &Path, day_dir: &Path) -> bool {
+    let Ok(relative) = path.strip_prefix(day_dir) else {
+        return false;
+    };
+    relative == Path::new("input.txt")
+        || relative == Path::new("example.txt")
+        || (relative.starts_with("src") && relative.extension().is_some_and(|extension| extension == "rs"))
+}
+
+/// Wait until nothing is received for `quiet`, returning how many messages were received.
+pub fn debounce<T>(receiver: &Receiver<T>, quiet: Duration) -> usize {
+    let mut count = 0;
+    while receiver.recv_timeout(quiet).is_ok() {
+        count += 1;
+    }
+    count
+}
+
+/// How a run ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Finished {
+    Exited(ExitStatus),
+    /// Killed on Ctrl-C.
+    Interrupted,
+    /// Killed for running longer than its timeout.
+    TimedOut,
+}
+
+/// Run `command` until it exits, or kill it once `interrupted` is set or it runs longer than
+/// `timeout`.
+pub fn run_interruptible(
+    command: &mut Command,
+    interrupted: &AtomicBool,
+    timeout: Option<Duration>,
+) -> Result<Finished, AocError> {
+    let start = Instant::now();
+    let mut child = command.spawn().map_err(|e| AocError::io(format!("Failed to run {:?}", command.get_program()), e))?;
+    loop {
+        let finished = if interrupted.load(Ordering::SeqCst) {
+            Some(Finished::Interrupted)
+        } else if timeout.is_some_and(|timeout| start.elapsed() > timeout) {
+            Some(Finished::TimedOut)
+        } else {
+            None
+        };
+        if let Some(finished) = finished {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(finished);
+        }
+        match child.try_wait() {
+            Ok(Some(status)) => return Ok(Finished::Exited(status)),
+            Ok(None) => std::thread::sleep(POLL),
+            Err(e) => return Err(AocError::io(format!("Failed to wait for {:?}", command.get_program()), e)),
+        }
+    }
+}
+
+/// Build and run `mode` once in `day_dir`, printing its output and runtime. Failing is an error,
+/// being interrupted or timing out is not.
+pub fn run_once(
+    day_dir: &Path,
+    mode: &Mode,
+    cargo_args: &[String],
+    interrupted: &AtomicBool,
+) -> Result<Finished, AocError> {
+    let (mut command, timeout) = match mode {
+        Mode::Run { part, input_path, timeout } => {
+            let executable = crate::runner::build_part(day_dir, *part, cargo_args)?;
+            let mut command = Command::new(executable);
+            if let Some(input_path) = input_path {
+                command.env("AOC_INPUT_PATH", input_path);
+            }
+            (command, *timeout)
+        }
+        Mode::Test { part } => (crate::runner::test_command(day_dir, *part, cargo_args)?, None),
+    };
+    command.current_dir(day_dir);
+
+    let start = Instant::now();
+    match run_interruptible(&mut command, interrupted, timeout)? {
+        Finished::Exited(status) if status.success() => {
+            eprintln!("{}", format!("Ran in {:.2?}", start.elapsed()).yellow());
+            Ok(Finished::Exited(status))
+        }
+        Finished::Exited(status) => Err(AocError::Build(format!("Failed after {:.2?}: {}", start.elapsed(), status))),
+        finished => Ok(finished),
+    }
+}
+
+/// Run `mode` once like `run_once`, printing how it failed or timed out. A time out is recorded in
+/// the year's performance log, for the day of `day_dir` named with `day_format`.
+pub fn rerun(day_dir: &Path, day_format: &str, mode: &Mode, cargo_args: &[String], interrupted: &AtomicBool) {
+    match run_once(day_dir, mode, cargo_args, interrupted) {
+        Ok(Finished::TimedOut) => {
+            let Mode::Run { part, timeout: Some(timeout), .. } = *mode else {
+                return;
+            };
+            eprintln!("{}", format!("Timed out after {:?}", timeout).red());
+            let recorded = crate::perf::record_day(day_dir, day_format, |day| {
+                crate::perf::PerfEntry::timed_out(day, part, timeout)
+            });
+            if let Err(err) = recorded {
+                eprintln!("{}", err.to_string().yellow());
+            }
+        }
+        Ok(_) => {}
+        Err(err) => eprintln!("{}", err.to_string().red()),
+    }
+}
+
+/// Call `on_change` now and after every change to the sources and inputs of `day_dir`, until
+/// `interrupted` is set.
+pub fn watch(day_dir: &Path, interrupted: &AtomicBool, mut on_change: impl FnMut()) -> Result<(), AocError> {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let watched_dir = day_dir.to_owned();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let Ok(event) = event else {
+            return;
+        };
+        if !event.kind.is_access() && event.paths.iter().any(|path| is_relevant(path, &watched_dir)) {
+            let _ = sender.send(());
+        }
+    })
+    .map_err(|e| AocError::Environment(format!("Failed to watch {}: {}", day_dir.display(), e)))?;
+    // input.txt and example.txt are watched through the day directory, as editors can replace them
+    watcher
+        .watch(day_dir, RecursiveMode::NonRecursive)
+        .and_then(|()| watcher.watch(&day_dir.join("src"), RecursiveMode::Recursive))
+        .map_err(|e| AocError::Environment(format!("Failed to watch {}: {}", day_dir.display(), e)))?;
+
+    on_change();
+    while !interrupted.load(Ordering::SeqCst) {
+        match receiver.recv_timeout(POLL) {
+            Ok(()) => {
+                debounce(&receiver, DEBOUNCE);
+                on_change();
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_relevant() {
+        let day_dir = Path::new("/aoc/advent-of-code-2022/day-01");
+        assert!(is_relevant(&day_dir.join("src/bin/part_1.rs"), day_dir));
+        assert!(is_relevant(&day_dir.join("src/lib.rs"), day_dir));
+        assert!(is_relevant(&day_dir.join("input.txt"), day_dir));
+        assert!(is_relevant(&day_dir.join("example.txt"), day_dir));
+        assert!(!is_relevant(&day_dir.join("src/bin/.part_1.rs.swp"), day_dir));
+        assert!(!is_relevant(&day_dir.join("target/debug/part_1"), day_dir));
+        assert!(!is_relevant(&day_dir.join("answers.toml"), day_dir));
+        assert!(!is_relevant(Path::new("/aoc/input.txt"), day_dir));
+    }
+
+    #[test]
+    fn test_debounce() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let storm = std::thread::spawn(move || {
+            for _ in 0..5 {
+                sender.send(()).unwrap();
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        });
+        assert_eq!(debounce(&receiver, Duration::from_millis(100)), 5);
+        storm.join().unwrap();
+    }
+
+    #[test]
+    fn test_run_interruptible() {
+        let interrupted = AtomicBool::new(false);
+        let finished = run_interruptible(Command::new("cargo").arg("--version"), &interrupted, None).unwrap();
+        assert!(matches!(finished, Finished::Exited(status) if status.success()));
+
+        // A part which sleeps, as there's no `sleep` everywhere
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let workspace_config = crate::config::WorkspaceConfig::default();
+        let year_dir =
+            crate::scaffold::new_year(tmp_dir.path(), "advent-of-code-", "day-", 2022, false, false, &workspace_config)
+                .unwrap();
+        let day_dir = year_dir.join("day-01");
+        std::fs::write(
+            day_dir.join("src/bin/part_1.rs"),
+            "fn main() { std::thread::sleep(std::time::Duration::from_secs(10)); }\n",
+        )
+        .unwrap();
+        let executable = crate::runner::build_part(&day_dir, 1, &[]).unwrap();
+
+        let timeout = Some(Duration::from_millis(100));
+        let start = Instant::now();
+        let finished = run_interruptible(&mut Command::new(executable), &interrupted, timeout).unwrap();
+        assert_eq!(finished, Finished::TimedOut);
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        interrupted.store(true, Ordering::SeqCst);
+        let finished = run_interruptible(Command::new("cargo").arg("--version"), &interrupted, None).unwrap();
+        assert_eq!(finished, Finished::Interrupted);
+    }
+}
@@ -0,0 +1,8 @@
+//! Advent of Code {{year}}, Day {{day}}: Shared code for all parts
+
+pub mod utils {
+    /// Parse the puzzle input
+    pub fn parse(input: &str) -> Vec<String> {
+        input.lines().map(|s| s.to_owned()).collect()
+    }
+}
@@ -1,3 +1,5 @@
+//! Advent of Code {{year}}, Day {{day}}: Part {{part}}
+
 /// Solve the Puzzle
 fn solve(input: &str) -> i32 {
     let lines: Vec<String> = input.lines().map(|s| s.to_owned()).collect();
@@ -5,7 +7,9 @@ fn solve(input: &str) -> i32 {
 }
 
 fn main() {
-    let input = include_str!("../../input.txt");
+    // `aoc mock` points AOC_INPUT_PATH at a test input
+    let input_path = std::env::var("AOC_INPUT_PATH").unwrap_or_else(|_| "input.txt".to_owned());
+    let input = std::fs::read_to_string(&input_path).expect("Failed to read the input");
     let answer = solve(&input);
     println!("{}", answer);
 }
@@ -25,4 +29,14 @@ mod tests {
             assert_eq!(solve(input), expected);
         }
     }
+
+    /// Answer to example.txt, checked by `aoc example` unless it's empty
+    const EXAMPLE_ANSWER: &str = "";
+
+    #[test]
+    fn test_example() {
+        if let (Ok(example), false) = (std::fs::read_to_string("example.txt"), EXAMPLE_ANSWER.is_empty()) {
+            assert_eq!(solve(&example).to_string(), EXAMPLE_ANSWER);
+        }
+    }
 }
@@ -0,0 +1,43 @@
+//! Advent of Code {{year}}, Day {{day}}: Part {{part}}
+use {{crate}}::utils;
+
+/// Solve the Puzzle
+fn solve(input: &str) -> i32 {
+    let lines = utils::parse(input);
+    todo!()
+}
+
+fn main() {
+    // `aoc mock` points AOC_INPUT_PATH at a test input
+    let input_path = std::env::var("AOC_INPUT_PATH").unwrap_or_else(|_| "input.txt".to_owned());
+    let input = std::fs::read_to_string(&input_path).expect("Failed to read the input");
+    let answer = solve(&input);
+    println!("{}", answer);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[allow(unused_imports)]
+    use pretty_assertions::{assert_eq, assert_ne};
+
+    #[test]
+    fn test_compute() {
+        let tests = vec![
+            ("", 0)
+        ];
+        for (input, expected) in tests {
+            assert_eq!(solve(input), expected);
+        }
+    }
+
+    /// Answer to example.txt, checked by `aoc example` unless it's empty
+    const EXAMPLE_ANSWER: &str = "";
+
+    #[test]
+    fn test_example() {
+        if let (Ok(example), false) = (std::fs::read_to_string("example.txt"), EXAMPLE_ANSWER.is_empty()) {
+            assert_eq!(solve(&example).to_string(), EXAMPLE_ANSWER);
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! `aoc test` against a tiny workspace.
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A year workspace whose only day has a passing test in part 1 and a failing one in part 2.
+fn fixture_year(parent: &Path) -> PathBuf {
+    let year_dir = parent.join("advent-of-code-2022");
+    let day_dir = year_dir.join("day-01");
+    std::fs::create_dir_all(day_dir.join("src/bin")).unwrap();
+    std::fs::write(
+        year_dir.join("Cargo.toml"),
+        "[workspace]\nmembers = [\"day-01\"]\nresolver = \"2\"\n",
+    )
+    .unwrap();
+    std::fs::write(
+        day_dir.join("Cargo.toml"),
+        "[package]\nname = \"day-01\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )
+    .unwrap();
+    for (part, expected) in [(1, 2), (2, 3)] {
+        std::fs::write(
+            day_dir.join(format!("src/bin/part_{}.rs", part)),
+            format!(
+                "fn main() {{}}\n\n#[test]\nfn test_part_{}() {{\n    assert_eq!(1 + 1, {});\n}}\n",
+                part, expected
+            ),
+        )
+        .unwrap();
+    }
+    year_dir
+}
+
+fn aoc(dir: &Path, args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_aoc"))
+        .args(args)
+        .current_dir(dir)
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_test() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let year_dir = fixture_year(tmp_dir.path());
+    let day_dir = year_dir.join("day-01");
+
+    // Only the summary when passing
+    let output = aoc(&day_dir, &["test", "--part", "1"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "1 passed, 0 failed\n");
+
+    // Full output when failing
+    let output = aoc(&day_dir, &["test"]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("test_part_2 ... FAILED"));
+    assert!(stdout.ends_with("1 passed, 1 failed\n"));
+
+    // From the year root, with --verbose and arguments for cargo
+    let output = aoc(&year_dir, &["test", "--day", "1", "--verbose", "--", "test_part_1"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("test test_part_1 ... ok"));
+    assert!(stdout.ends_with("1 passed, 0 failed\n"));
+}
@@ -0,0 +1,113 @@
+//! Command line behavior which doesn't depend on a particular command.
+use std::process::Command;
+
+#[test]
+fn test_no_color() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    // Fails with a colored error, outside of a day directory
+    let stderr = |args: &[&str], no_color: Option<&str>| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_aoc"));
+        command
+            .args(args)
+            .current_dir(tmp_dir.path())
+            .env("CLICOLOR_FORCE", "1")
+            .env_remove("NO_COLOR");
+        if let Some(no_color) = no_color {
+            command.env("NO_COLOR", no_color);
+        }
+        let output = command.output().unwrap();
+        assert!(!output.status.success());
+        String::from_utf8_lossy(&output.stderr).into_owned()
+    };
+
+    assert!(stderr(&["run"], None).contains("\x1b["));
+    assert!(!stderr(&["run", "--no-color"], None).contains("\x1b["));
+    assert!(!stderr(&["--no-color", "run"], None).contains("\x1b["));
+    // Any value counts, even an empty one
+    assert!(!stderr(&["run"], Some("")).contains("\x1b["));
+}
+
+#[test]
+fn test_completions_outside_aoc() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_aoc"))
+        .args(["completions", "bash"])
+        .current_dir(tmp_dir.path())
+        .env("AOC_TEMPLATE_DIR", tmp_dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("_aoc()"));
+}
+
+#[test]
+fn test_invalid_day_dir() {
+    // A day directory whose name doesn't parse used to panic, it's now an error
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let day_dir = tmp_dir.path().join("advent-of-code-2022/day-xx");
+    std::fs::create_dir_all(&day_dir).unwrap();
+    for args in [&["input"][..], &["submit"]] {
+        let output = Command::new(env!("CARGO_BIN_EXE_aoc"))
+            .args(args)
+            .current_dir(&day_dir)
+            .env("NO_COLOR", "1")
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(1), "{:?}", args);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.starts_with("Error: Invalid directory name: day-xx"), "{}", stderr);
+    }
+}
+
+#[test]
+fn test_invalid_workspace_manifest() {
+    // A broken Cargo.toml in the year directory used to panic when creating a day
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let year_dir = tmp_dir.path().join("advent-of-code-2022");
+    std::fs::create_dir_all(&year_dir).unwrap();
+    std::fs::write(year_dir.join("Cargo.toml"), "[workspace\n").unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_aoc"))
+        .args(["day"])
+        .current_dir(&year_dir)
+        .env("NO_COLOR", "1")
+        .env("AOC_TEMPLATE_DIR", tmp_dir.path())
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.starts_with("Error: Invalid "), "{}", stderr);
+    assert!(stderr.contains("Cargo.toml"), "{}", stderr);
+    assert!(!year_dir.join("day-01").exists());
+}
+
+#[test]
+fn test_log_file_writes() {
+    // Creating a day logs a write for each file it creates, manifests included
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let year_dir = tmp_dir.path().join("advent-of-code-2022");
+    std::fs::create_dir_all(&year_dir).unwrap();
+    std::fs::write(year_dir.join("Cargo.toml"), "[workspace]\nmembers = []\n").unwrap();
+    let log_file = tmp_dir.path().join("aoc.jsonl");
+    let output = Command::new(env!("CARGO_BIN_EXE_aoc"))
+        .arg("--log-file")
+        .arg(&log_file)
+        .arg("day")
+        .current_dir(&year_dir)
+        .env("NO_COLOR", "1")
+        .env("XDG_CONFIG_HOME", tmp_dir.path())
+        .env("AOC_TEMPLATE_DIR", tmp_dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let log = std::fs::read_to_string(&log_file).unwrap();
+    let writes: Vec<String> = log
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .filter(|event| event["fields"]["message"] == "write")
+        .map(|event| event["fields"]["path"].as_str().unwrap().replace('\\', "/"))
+        .collect();
+    for file in ["advent-of-code-2022/Cargo.toml", "day-01/Cargo.toml", "day-01/src/bin/part_1.rs"] {
+        assert!(writes.iter().any(|path| path.ends_with(file)), "{}: {:?}", file, writes);
+    }
+}
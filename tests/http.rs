@@ -0,0 +1,460 @@
+//! Requests to Advent of Code through `AocHttp`, against a mock server.
+use aoc::client::{check_session, Answer, AocClient, AocHttp, Backoff, Fetch, Hint, USER_AGENT};
+use aoc::clean::Formats;
+use aoc::error::AocError;
+use aoc::input::{download_missing, Downloads};
+use aoc::urls::Page;
+use httpmock::prelude::*;
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_fetch_input() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/2022/day/1/input")
+            .header("Cookie", "session=secret")
+            .header("User-Agent", USER_AGENT);
+        then.status(200).body("1000\n2000\n\n3000\n");
+    });
+
+    let input = AocClient::new(&server.base_url()).unwrap().with_session("secret").fetch_input(2022, 1).unwrap();
+    mock.assert();
+    assert_eq!(input, "1000\n2000\n\n3000\n");
+}
+
+#[test]
+fn test_fetch_inputs() {
+    let server = MockServer::start();
+    let mocks: Vec<_> = (1..=3)
+        .map(|day| {
+            server.mock(|when, then| {
+                when.method(GET)
+                    .path(format!("/2022/day/{}/input", day))
+                    .header("Cookie", "session=secret")
+                    .header("User-Agent", USER_AGENT);
+                then.status(200).body(format!("{}\n", day)).delay(Duration::from_millis(100));
+            })
+        })
+        .collect();
+    server.mock(|when, then| {
+        when.method(GET).path("/2022/day/25/input");
+        then.status(404);
+    });
+
+    let http = AocClient::new(&server.base_url())
+        .unwrap()
+        .with_session("secret")
+        .with_limits(2, Duration::from_millis(20));
+    let days = [(2022, 1), (2022, 2), (2022, 3), (2022, 25)];
+    let start = Instant::now();
+    let mut started = Vec::new();
+    let mut finished = vec![None; days.len()];
+    http.fetch_inputs(&days, |fetch| match fetch {
+        Fetch::Started(index) => started.push(index),
+        Fetch::Finished(index, input) => finished[index] = Some(input.map_err(|e| e.to_string())),
+    });
+    let elapsed = start.elapsed();
+
+    started.sort();
+    assert_eq!(started, [0, 1, 2, 3]);
+    assert_eq!(
+        finished,
+        [
+            Some(Ok("1\n".to_owned())),
+            Some(Ok("2\n".to_owned())),
+            Some(Ok("3\n".to_owned())),
+            Some(Err("Puzzle has not yet opened, please try again later.".to_owned())),
+        ]
+    );
+    for mock in mocks {
+        mock.assert();
+    }
+    // Two at a time, so the third waits for one of the first two
+    assert!(elapsed >= Duration::from_millis(200), "{:?}", elapsed);
+}
+
+#[test]
+fn test_download_missing() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/2022/day/2/input");
+        then.status(200).body("2\n");
+    });
+    server.mock(|when, then| {
+        when.method(GET).path("/2023/day/1/input");
+        then.status(404);
+    });
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let root = tmp_dir.path();
+    for day_dir in ["advent-of-code-2022/day-01", "advent-of-code-2022/day-02", "advent-of-code-2023/day-01"] {
+        std::fs::create_dir_all(root.join(day_dir)).unwrap();
+    }
+    std::fs::write(root.join("advent-of-code-2022/day-01/input.txt"), "1\n").unwrap();
+
+    let http = AocClient::new(&server.base_url()).unwrap().with_session("secret").with_retries(0, Duration::ZERO);
+    let formats = Formats { year: "advent-of-code-", day: "day-" };
+    // From a day directory, the years next to its year
+    let downloads = download_missing(&http, &root.join("advent-of-code-2022/day-01"), &formats).unwrap();
+    assert_eq!(downloads, Downloads { downloaded: 1, skipped: 1, failed: 1 });
+    mock.assert();
+    assert_eq!(std::fs::read_to_string(root.join("advent-of-code-2022/day-02/input.txt")).unwrap(), "2\n");
+    assert!(!root.join("advent-of-code-2023/day-01/input.txt").exists());
+}
+
+#[test]
+fn test_throttle() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/2022");
+        then.status(200).body("calendar");
+    });
+
+    let http = AocClient::new(&server.base_url())
+        .unwrap()
+        .with_session("secret")
+        .with_limits(4, Duration::from_millis(100));
+    let start = Instant::now();
+    for _ in 0..3 {
+        assert_eq!(http.fetch_page(Page::Calendar { year: 2022 }).unwrap(), "calendar");
+    }
+    // The first request doesn't wait
+    assert!(start.elapsed() >= Duration::from_millis(200), "{:?}", start.elapsed());
+    mock.assert_hits(3);
+}
+
+#[test]
+fn test_fetch_input_not_open() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/2022/day/25/input");
+        then.status(404).body("Please don't repeatedly request this endpoint before it unlocks!");
+    });
+
+    let http = AocClient::new(&server.base_url()).unwrap().with_session("secret").with_retries(3, Duration::ZERO);
+    let err = http.fetch_input(2022, 25).unwrap_err();
+    assert!(matches!(err, AocError::Network { source: None, .. }), "{:?}", err);
+    assert_eq!(err.to_string(), "Puzzle has not yet opened, please try again later.");
+    // The first try, then 3 retries
+    mock.assert_hits(4);
+}
+
+#[test]
+fn test_fetch_input_invalid_session() {
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/2022/day/1/input");
+        then.status(400).body("Puzzle inputs differ by user.  Please log in to get your puzzle input.");
+    });
+
+    let result = AocClient::new(&server.base_url()).unwrap().with_session("expired").fetch_input(2022, 1);
+    assert!(matches!(result, Err(AocError::InvalidSession)), "{:?}", result);
+}
+
+#[test]
+fn test_fetch_puzzle() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/2022/day/3")
+            .header("Cookie", "session=secret")
+            .header("User-Agent", USER_AGENT);
+        then.status(200).body("<article><h2>--- Day 3: Rucksack Reorganization ---</h2></article>");
+    });
+
+    let http = AocClient::new(&server.base_url()).unwrap().with_session("secret");
+    assert!(http.fetch_puzzle(2022, 3).unwrap().contains("Rucksack Reorganization"));
+    mock.assert();
+    // Errors keep the URL
+    let err = http.fetch_page(Page::Calendar { year: 2022 }).unwrap_err();
+    assert!(err.to_string().starts_with(&format!("Failed to download {}/2022", server.base_url())), "{}", err);
+    assert!(matches!(err, AocError::Network { source: Some(_), .. }), "{:?}", err);
+}
+
+#[test]
+fn test_cache() {
+    let server = MockServer::start();
+    let input = server.mock(|when, then| {
+        when.method(GET).path("/2022/day/1/input");
+        then.status(200).body("1000\n");
+    });
+    let puzzle = server.mock(|when, then| {
+        when.method(GET).path("/2022/day/1");
+        then.status(200).body("<article><h2>--- Day 1: Calorie Counting ---</h2></article>");
+    });
+    server.mock(|when, then| {
+        when.method(POST).path("/2022/day/1/answer");
+        then.status(200).body("<article><p>That's the right answer!</p></article>");
+    });
+
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let http = AocClient::new(&server.base_url()).unwrap()
+        .with_session("secret")
+        .with_cache(aoc::cache::Cache::new(tmp_dir.path()));
+    for _ in 0..2 {
+        assert_eq!(http.fetch_input(2022, 1).unwrap(), "1000\n");
+        assert!(http.fetch_puzzle(2022, 1).unwrap().contains("Calorie Counting"));
+    }
+    input.assert_hits(1);
+    puzzle.assert_hits(1);
+
+    // Completing a part invalidates the puzzle page, but not the input
+    assert_eq!(http.post_answer(2022, 1, 1, "24000").unwrap().verdict, Answer::Correct);
+    http.fetch_input(2022, 1).unwrap();
+    http.fetch_puzzle(2022, 1).unwrap();
+    input.assert_hits(1);
+    puzzle.assert_hits(2);
+
+    // Another account doesn't share the cache
+    let other = AocClient::new(&server.base_url()).unwrap()
+        .with_session("other")
+        .with_cache(aoc::cache::Cache::new(tmp_dir.path()));
+    other.fetch_input(2022, 1).unwrap();
+    input.assert_hits(2);
+}
+
+#[test]
+fn test_post_answer_form() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/2022/day/1/answer")
+            .header("Cookie", "session=secret")
+            .header("User-Agent", USER_AGENT)
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .body("level=2&answer=45000");
+        then.status(200).body("<article><p>That's the right answer!</p></article>");
+    });
+
+    let result = AocClient::new(&server.base_url())
+        .unwrap()
+        .with_session("secret")
+        .post_answer(2022, 1, 2, "45000")
+        .unwrap();
+    mock.assert();
+    assert_eq!((result.part, result.answer.as_str()), (2, "45000"));
+    assert_eq!(result.verdict, Answer::Correct);
+    assert_eq!(result.message, "That's the right answer!");
+}
+
+#[test]
+fn test_post_answer_responses() {
+    let server = MockServer::start();
+    let responses = [
+        ("1", "<article><p>That's not the right answer; your answer is too low.</p></article>"),
+        ("2", "<article><p>That's not the right answer.  If you're stuck</p></article>"),
+        ("3", "<article><p>You gave an answer too recently; you have to wait</p></article>"),
+        ("4", "<article><p>You don't seem to be solving the right level.</p></article>"),
+        (
+            "6",
+            "<article><p>You don't seem to be solving the right level.  Did you already complete it? \
+            <a href=\"/2022/day/1\">[Return to Day 1]</a></p></article>",
+        ),
+        ("5", "<article><p>Please log in.</p></article>"),
+    ];
+    for (answer, body) in responses {
+        server.mock(|when, then| {
+            when.method(POST).path("/2022/day/1/answer").body(format!("level=1&answer={}", answer));
+            then.status(200).body(body);
+        });
+    }
+    server.mock(|when, then| {
+        when.method(GET).path("/2022/day/1");
+        then.status(200).body("<article class=\"day-desc\"></article><p>Your puzzle answer was 24000.</p>");
+    });
+
+    let http = AocClient::new(&server.base_url()).unwrap().with_session("secret");
+    let post = |answer| http.post_answer(2022, 1, 1, answer).map(|result| result.verdict);
+    assert_eq!(post("1").unwrap(), Answer::Incorrect(Some(Hint::TooLow)));
+    assert_eq!(post("2").unwrap(), Answer::Incorrect(None));
+    assert_eq!(post("3").unwrap(), Answer::RateLimited(None));
+    assert_eq!(post("4").unwrap(), Answer::AlreadySubmitted(None));
+    assert_eq!(post("6").unwrap(), Answer::AlreadySubmitted(Some(1)));
+    assert!(post("5").unwrap_err().to_string().ends_with("Please log in."));
+}
+
+#[test]
+fn test_post_answer_part_2_locked() {
+    let server = MockServer::start();
+    let answer = server.mock(|when, then| {
+        when.method(POST).path("/2022/day/1/answer").body("level=2&answer=45000");
+        then.status(200).body(
+            "<article><p>You don't seem to be solving the right level.  Did you already complete it? \
+            <a href=\"/2022/day/1\">[Return to Day 1]</a></p></article>",
+        );
+    });
+    // Part 1 isn't solved yet, so part 2 isn't unlocked
+    let puzzle = server.mock(|when, then| {
+        when.method(GET).path("/2022/day/1");
+        then.status(200).body(include_str!("fixtures/puzzle.html"));
+    });
+
+    let result = AocClient::new(&server.base_url())
+        .unwrap()
+        .with_session("secret")
+        .post_answer(2022, 1, 2, "45000")
+        .unwrap();
+    answer.assert();
+    puzzle.assert();
+    assert_eq!(result.verdict, Answer::AlreadySubmitted(None));
+}
+
+#[test]
+fn test_server_error_retries() {
+    let server = MockServer::start();
+    let input = server.mock(|when, then| {
+        when.method(GET).path("/2022/day/1/input");
+        then.status(503).body("Service Unavailable");
+    });
+    let answer = server.mock(|when, then| {
+        when.method(POST).path("/2022/day/1/answer");
+        then.status(502).body("Bad Gateway");
+    });
+    let backoff = Some(Backoff { max_tries: 2, base_ms: 0, cap_ms: 0 });
+    let http = AocClient::new(&server.base_url()).unwrap().with_session("secret").with_backoff(backoff);
+
+    assert!(http.fetch_input(2022, 1).unwrap_err().to_string().contains("503"));
+    // The first try, then 2 retries
+    input.assert_hits(3);
+    assert!(http.post_answer(2022, 1, 1, "1").unwrap_err().to_string().contains("502"));
+    answer.assert_hits(3);
+
+    // Without a backoff, only once
+    assert!(AocClient::new(&server.base_url()).unwrap().with_session("secret").fetch_input(2022, 1).is_err());
+    input.assert_hits(4);
+}
+
+#[test]
+fn test_fetch_username() {
+    let server = MockServer::start();
+    let accepted = server.mock(|when, then| {
+        when.method(GET)
+            .path("/settings")
+            .header("Cookie", "session=abc123")
+            .header("User-Agent", USER_AGENT);
+        then.status(200)
+            .body(r#"<header><div><div class="user">AceofSpades5757 <span class="star-count">50*</span></div></div></header>"#);
+    });
+    let logged_out = server.mock(|when, then| {
+        when.method(GET).path("/settings").header("Cookie", "session=def456");
+        then.status(200)
+            .body(r#"<header><div><p class="wrap">[<a href="/2022/auth/login">Log In</a>]</p></div></header>"#);
+    });
+    server.mock(|when, then| {
+        when.method(GET).path("/settings").header("Cookie", "session=ghi789");
+        then.status(503).body("Service Unavailable");
+    });
+
+    let username = |cookie| AocClient::new(&server.base_url()).unwrap().with_session(cookie).fetch_username();
+    assert_eq!(username("abc123").unwrap(), Some("AceofSpades5757".to_owned()));
+    assert_eq!(username("def456").unwrap(), None);
+    // A server error isn't an invalid session
+    let http = AocClient::new(&server.base_url()).unwrap().with_session("ghi789");
+    let err = check_session(&http).unwrap_err();
+    assert!(matches!(err, AocError::Network { .. }) && err.to_string().contains("503"), "{}", err);
+    accepted.assert();
+    logged_out.assert();
+}
+
+#[test]
+fn test_base_url_override() {
+    // Commands send their requests to the configured server
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/settings").header("Cookie", "session=abc123");
+        then.status(200).body(r#"<input name="username" value="AceofSpades5757">"#);
+    });
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_aoc"))
+        .arg("session")
+        .current_dir(tmp_dir.path())
+        .env("XDG_CONFIG_HOME", tmp_dir.path())
+        .env("session", "abc123")
+        .env("AOC_NETWORK_BASE_URL", server.base_url())
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "Session valid for user: AceofSpades5757\n");
+    mock.assert();
+}
+
+#[test]
+fn test_log_file() {
+    // The log has the command, its requests and writes, but not the session cookie
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/2022/day/1/input").header("Cookie", "session=abc123");
+        then.status(200).body("1000\n2000\n");
+    });
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let day_dir = tmp_dir.path().join("advent-of-code-2022/day-01");
+    std::fs::create_dir_all(&day_dir).unwrap();
+    let log_file = tmp_dir.path().join("logs/aoc.jsonl");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_aoc"))
+        .arg("--log-file")
+        .arg(&log_file)
+        .arg("input")
+        .current_dir(&day_dir)
+        .env("XDG_CONFIG_HOME", tmp_dir.path())
+        .env("XDG_CACHE_HOME", tmp_dir.path().join("cache"))
+        .env("session", "abc123")
+        .env("AOC_NETWORK_BASE_URL", server.base_url())
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(std::fs::read_to_string(day_dir.join("input.txt")).unwrap(), "1000\n2000\n");
+
+    let log = std::fs::read_to_string(&log_file).unwrap();
+    assert!(!log.contains("abc123"), "{}", log);
+    let events: Vec<serde_json::Value> = log.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    let request = events.iter().find(|event| event["fields"]["message"] == "request").unwrap();
+    assert_eq!(request["fields"]["method"], "GET");
+    assert_eq!(request["fields"]["url"], format!("{}/2022/day/1/input", server.base_url()));
+    assert_eq!(request["fields"]["status"], 200);
+    assert!(request["fields"]["headers"].as_str().unwrap().contains("Sensitive"));
+    let command = &request["spans"][0];
+    assert_eq!(command["name"], "command");
+    assert_eq!(command["year"], 2022);
+    assert_eq!(command["day"], 1);
+    let writes: Vec<_> = events
+        .iter()
+        .filter(|event| event["fields"]["message"] == "write")
+        .map(|event| event["fields"]["path"].as_str().unwrap())
+        .collect();
+    assert!(writes.iter().any(|path| path.ends_with("input.txt")), "{:?}", writes);
+    assert!(writes.iter().any(|path| path.ends_with("2022-01.json")), "{:?}", writes);
+}
+
+#[test]
+fn test_diagnose() {
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/settings");
+        then.status(200).body(r#"<input name="username" value="AceofSpades5757">"#);
+    });
+    server.mock(|when, then| {
+        when.method(GET).path("/2022/day/1/input");
+        then.status(400).body("Puzzle inputs differ by user.  Please log in to get your puzzle input.");
+    });
+    let answer = server.mock(|when, then| {
+        when.method(POST).path("/2022/day/1/answer").body("level=1&answer=24000");
+        then.status(200).body("<article><p>You don't seem to be solving the right level.</p></article>");
+    });
+
+    let http = AocClient::new(&server.base_url()).unwrap().with_session("secret");
+    let checks = http.diagnose(2022, Some((1, 1, "24000")));
+    let outcomes: Vec<_> = checks.iter().map(|check| (check.name, check.passed, check.status)).collect();
+    assert_eq!(
+        outcomes,
+        [
+            ("Authentication", true, Some(200)),
+            ("Input access", false, Some(400)),
+            ("Answer submission", true, Some(200)),
+        ]
+    );
+    assert_eq!(checks[0].detail, "logged in as AceofSpades5757");
+    answer.assert();
+    assert_eq!(http.diagnose(2022, None).len(), 2);
+}
@@ -0,0 +1,380 @@
+//! `aoc run` against a tiny day crate.
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A day crate, in a year directory, whose part 1 sums the input's lines.
+fn fixture_day(parent: &Path) -> PathBuf {
+    let day_dir = parent.join("advent-of-code-2022/day-01");
+    std::fs::create_dir_all(day_dir.join("src/bin")).unwrap();
+    std::fs::write(
+        day_dir.join("Cargo.toml"),
+        "[package]\nname = \"day-01\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )
+    .unwrap();
+    std::fs::write(
+        day_dir.join("src/bin/part_1.rs"),
+        r#"fn main() {
+    let path = std::env::var("AOC_INPUT_PATH").unwrap_or_else(|_| "input.txt".to_owned());
+    let input = std::fs::read_to_string(path).unwrap();
+    println!("{}", input.lines().map(|line| line.parse::<i32>().unwrap()).sum::<i32>());
+}
+"#,
+    )
+    .unwrap();
+    std::fs::write(day_dir.join("input.txt"), "1\n2\n3\n").unwrap();
+    day_dir
+}
+
+/// `aoc`, with its config, cache and templates in `tmp_dir` rather than the user's, and without
+/// their `AOC_*` overrides.
+fn aoc_command(tmp_dir: &Path) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_aoc"));
+    for (name, _) in std::env::vars_os() {
+        if name.to_string_lossy().starts_with("AOC_") {
+            command.env_remove(name);
+        }
+    }
+    command
+        .env("XDG_CONFIG_HOME", tmp_dir)
+        .env("XDG_CACHE_HOME", tmp_dir.join("cache"))
+        .env("AOC_TEMPLATE_DIR", tmp_dir.join("templates"));
+    command
+}
+
+fn aoc(tmp_dir: &Path, dir: &Path, args: &[&str]) -> std::process::Output {
+    aoc_command(tmp_dir).args(args).current_dir(dir).output().unwrap()
+}
+
+#[test]
+fn test_run() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let day_dir = fixture_day(tmp_dir.path());
+
+    let output = aoc(tmp_dir.path(), &day_dir, &["run"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "6\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Compiled in") && stderr.contains("ran in"));
+    assert!(day_dir.join("target/release").exists());
+
+    std::fs::write(tmp_dir.path().join("example.txt"), "10\n20\n").unwrap();
+    let output = aoc(tmp_dir.path(), &day_dir, &["run", "--debug", "--part", "1", "--input", "../../example.txt"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "30\n");
+    assert!(day_dir.join("target/debug").exists());
+}
+
+#[test]
+fn test_run_exit_code() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let day_dir = fixture_day(tmp_dir.path());
+    std::fs::write(
+        day_dir.join("src/bin/part_2.rs"),
+        "fn main() {\n    println!(\"partial\");\n    std::process::exit(3);\n}\n",
+    )
+    .unwrap();
+
+    // Part 2 is the latest part
+    let output = aoc(tmp_dir.path(), &day_dir, &["run", "--debug"]);
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "partial\n");
+}
+
+#[test]
+fn test_run_all() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let day_dir = fixture_day(tmp_dir.path());
+    let year_dir = day_dir.parent().unwrap().to_owned();
+    std::fs::write(day_dir.join("src/bin/part_2.rs"), "fn main() {\n    println!(\"two\");\n}\n").unwrap();
+    // Day 2 has no input, day 3 doesn't build, and day 4's part 1 has the same binary name as day 1's
+    for (day, part_1) in [
+        ("day-02", "fn main() {}\n"),
+        ("day-03", "fn main() { broken }\n"),
+        ("day-04", "fn main() {\n    println!(\"four\");\n}\n"),
+    ] {
+        let dir = year_dir.join(day);
+        std::fs::create_dir_all(dir.join("src/bin")).unwrap();
+        std::fs::write(
+            dir.join("Cargo.toml"),
+            format!("[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n", day),
+        )
+        .unwrap();
+        std::fs::write(dir.join("src/bin/part_1.rs"), part_1).unwrap();
+    }
+    std::fs::write(year_dir.join("day-03/input.txt"), "").unwrap();
+    std::fs::write(year_dir.join("day-04/input.txt"), "").unwrap();
+    std::fs::write(
+        year_dir.join("Cargo.toml"),
+        "[workspace]\nmembers = [\"day-01\", \"day-02\", \"day-03\", \"day-04\"]\nresolver = \"2\"\n",
+    )
+    .unwrap();
+
+    let output = aoc(tmp_dir.path(), &year_dir, &["run", "--all", "--debug", "--format", "json", "--parallel", "2"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let results: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let results = results.as_array().unwrap();
+    let days: Vec<(u64, u64)> = results
+        .iter()
+        .map(|result| (result["day"].as_u64().unwrap(), result["part"].as_u64().unwrap()))
+        .collect();
+    assert_eq!(days, vec![(1, 1), (1, 2), (2, 1), (3, 1), (4, 1)]);
+    assert_eq!(results[0]["answer"], "6");
+    assert_eq!(results[1]["answer"], "two");
+    assert_eq!(results[2]["error"], "No input.txt");
+    assert!(results[3]["error"].as_str().unwrap().contains("Failed to build part_1"));
+    assert_eq!(results[4]["answer"], "four");
+
+    let output = aoc(tmp_dir.path(), &day_dir, &["run", "--all", "--debug"]);
+    let table = String::from_utf8_lossy(&output.stdout);
+    assert!(table.starts_with("Day Part Answer"));
+    assert_eq!(table.lines().count(), 6);
+}
+
+#[test]
+fn test_summarize() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let day_dir = fixture_day(tmp_dir.path());
+    let year_dir = day_dir.parent().unwrap().to_owned();
+    std::fs::write(day_dir.join("answers.toml"), "part_1 = 6\n").unwrap();
+    // Day 2's part 1 never finishes
+    let slow_dir = year_dir.join("day-02");
+    std::fs::create_dir_all(slow_dir.join("src/bin")).unwrap();
+    std::fs::write(
+        slow_dir.join("Cargo.toml"),
+        "[package]\nname = \"day-02\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )
+    .unwrap();
+    std::fs::write(slow_dir.join("src/bin/part_1.rs"), "fn main() {\n    loop {}\n}\n").unwrap();
+
+    let output = aoc_command(tmp_dir.path())
+        .args(["summarize", "--timeout", "1"])
+        .current_dir(&year_dir)
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Day 1: Part 1 = 6, Part 2 = —\nDay 2: Part 1 = (timed out after 1s), Part 2 = —\n"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_watch() {
+    use std::io::BufRead;
+    use std::time::Duration;
+
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let day_dir = fixture_day(tmp_dir.path());
+    std::fs::write(day_dir.join("example.txt"), "10\n20\n").unwrap();
+
+    let mut child = aoc_command(tmp_dir.path())
+        .args(["watch", "--example"])
+        .current_dir(&day_dir)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let stdout = std::io::BufReader::new(child.stdout.take().unwrap());
+    std::thread::spawn(move || {
+        for line in stdout.lines() {
+            sender.send(line.unwrap()).unwrap();
+        }
+    });
+
+    let timeout = Duration::from_secs(60);
+    assert_eq!(receiver.recv_timeout(timeout).unwrap(), "30");
+    std::fs::write(day_dir.join("example.txt"), "1\n").unwrap();
+    assert_eq!(receiver.recv_timeout(timeout).unwrap(), "1");
+
+    // Ctrl-C
+    let status = Command::new("kill").args(["-INT", &child.id().to_string()]).status().unwrap();
+    assert!(status.success());
+    assert!(child.wait().unwrap().success());
+}
+
+#[test]
+fn test_verify() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let day_dir = fixture_day(tmp_dir.path());
+    let year_dir = day_dir.parent().unwrap().to_owned();
+    std::fs::write(day_dir.join("answers.toml"), "part_1 = \"6\"\n").unwrap();
+    // Day 2 gives a different answer than the accepted one, day 3 has none
+    for (day, answers) in [("day-02", Some("part_1 = \"7\"\n")), ("day-03", None)] {
+        let other_dir = year_dir.join(day);
+        std::fs::create_dir_all(other_dir.join("src/bin")).unwrap();
+        let cargo_toml = std::fs::read_to_string(day_dir.join("Cargo.toml")).unwrap();
+        std::fs::write(other_dir.join("Cargo.toml"), cargo_toml.replace("day-01", day)).unwrap();
+        std::fs::copy(day_dir.join("src/bin/part_1.rs"), other_dir.join("src/bin/part_1.rs")).unwrap();
+        std::fs::copy(day_dir.join("input.txt"), other_dir.join("input.txt")).unwrap();
+        if let Some(answers) = answers {
+            std::fs::write(other_dir.join("answers.toml"), answers).unwrap();
+        }
+    }
+
+    let output = aoc(tmp_dir.path(), &year_dir, &["--no-color", "verify", "--day", "1"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("✓ Day 1 part 1: 6 ("), "{}", stdout);
+
+    let output = aoc(tmp_dir.path(), &year_dir, &["--no-color", "verify", "--parallel", "2", "--format", "json"]);
+    assert!(!output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let outcomes: Vec<&str> = report
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|verification| verification["outcome"].as_str().unwrap())
+        .collect();
+    assert_eq!(outcomes, ["match", "mismatch", "skipped"]);
+    assert_eq!(report[1]["actual"], "6");
+}
+
+#[test]
+fn test_migrate() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let year_dir = tmp_dir.path().join("advent-of-code-2022");
+    std::fs::create_dir(&year_dir).unwrap();
+    std::fs::write(year_dir.join("Cargo.toml"), "[workspace]\nmembers = [\"day_1\", \"day_2\"]\n").unwrap();
+    for day in ["day_1", "day_2"] {
+        let day_dir = year_dir.join(day);
+        std::fs::create_dir_all(day_dir.join("src/bin")).unwrap();
+        std::fs::write(
+            day_dir.join("Cargo.toml"),
+            format!("[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n", day),
+        )
+        .unwrap();
+        std::fs::write(day_dir.join("src/lib.rs"), "pub fn answer() -> u32 {\n    42\n}\n").unwrap();
+        std::fs::write(
+            day_dir.join("src/bin/part_1.rs"),
+            format!("fn main() {{\n    println!(\"{{}}\", {}::answer());\n}}\n", day),
+        )
+        .unwrap();
+    }
+
+    // Only a plan without --apply
+    let output = aoc(tmp_dir.path(), &year_dir, &["migrate", "--from", "day_"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Would rename"));
+    assert!(year_dir.join("day_1").exists());
+
+    let output = aoc(tmp_dir.path(), &year_dir, &["migrate", "--from", "day_", "--to", "day-", "--apply"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(!year_dir.join("day_1").exists());
+    let cargo_toml = std::fs::read_to_string(year_dir.join("Cargo.toml")).unwrap();
+    assert_eq!(cargo_toml, "[workspace]\nmembers = [\"day-01\", \"day-02\"]\n");
+    let part_1 = std::fs::read_to_string(year_dir.join("day-02/src/bin/part_1.rs")).unwrap();
+    assert!(part_1.contains("day_02::answer()"));
+
+    let build = Command::new("cargo")
+        .args(["build", "--offline", "--quiet"])
+        .current_dir(&year_dir)
+        .output()
+        .unwrap();
+    assert!(build.status.success(), "{}", String::from_utf8_lossy(&build.stderr));
+}
+
+#[test]
+fn test_diff() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let day_dir = fixture_day(tmp_dir.path());
+    let part_1 = std::fs::read_to_string(day_dir.join("src/bin/part_1.rs")).unwrap();
+    std::fs::write(day_dir.join("src/bin/part_1_fast.rs"), &part_1).unwrap();
+    std::fs::write(day_dir.join("src/bin/part_2.rs"), "fn main() {\n    println!(\"7\");\n}\n").unwrap();
+
+    let output = aoc(tmp_dir.path(), &day_dir, &["diff", "--from", "part_1", "--to", "part_1_fast", "--debug"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Answers match"));
+
+    let output = aoc(tmp_dir.path(), &day_dir, &["diff", "--debug"]);
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("--- part_1\n6\n--- part_2\n7\n"), "{}", stdout);
+    assert!(stdout.contains("Answers differ"));
+}
+
+#[test]
+fn test_example() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let day_dir = fixture_day(tmp_dir.path());
+    std::fs::write(day_dir.join("example.txt"), "10\n20\n").unwrap();
+    std::fs::write(
+        day_dir.join("src/bin/part_2.rs"),
+        r#"fn main() {
+    let input = std::fs::read_to_string(std::env::var("AOC_INPUT_PATH").unwrap()).unwrap();
+    println!("{}", input.lines().count());
+}
+
+#[cfg(test)]
+mod tests {
+    const EXAMPLE_ANSWER: &str = "2";
+}
+"#,
+    )
+    .unwrap();
+
+    // Match, recording the expected answer
+    let output = aoc(tmp_dir.path(), &day_dir, &["example", "--part", "1", "--expect", "30"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "30\n");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Part 1: matches the expected answer 30"));
+    let cargo_toml = std::fs::read_to_string(day_dir.join("Cargo.toml")).unwrap();
+    assert!(cargo_toml.contains("[package.metadata.aoc.example]\npart_1 = \"30\"\n"));
+
+    // Mismatch, against the recorded answer of part 1 and the test constant of part 2
+    let output = aoc(tmp_dir.path(), &day_dir, &["example", "--part", "1", "--expect", "31"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Part 1: expected 31, got 30"));
+    let output = aoc(tmp_dir.path(), &day_dir, &["example", "--all-parts"]);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Part 1: expected 31, got 30"));
+    assert!(stderr.contains("Part 2: matches the expected answer 2"));
+
+    // Another example is only checked against --expect
+    std::fs::write(day_dir.join("example_2.txt"), "1\n").unwrap();
+    let output = aoc(tmp_dir.path(), &day_dir, &["example", "--part", "1", "--file", "example_2.txt"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Part 1: no expected answer"));
+
+    // Missing example
+    let output = aoc(tmp_dir.path(), &day_dir, &["example", "--file", "missing.txt"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No example at"));
+}
+
+#[test]
+fn test_new_year() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let day_dir = fixture_day(tmp_dir.path());
+    let year_dir = day_dir.parent().unwrap();
+    std::fs::create_dir_all(year_dir.join("utils/src")).unwrap();
+    std::fs::write(
+        year_dir.join("utils/Cargo.toml"),
+        "[package]\nname = \"utils\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )
+    .unwrap();
+    std::fs::write(year_dir.join("utils/src/lib.rs"), "pub fn parse() {}\n").unwrap();
+    std::fs::write(year_dir.join("rustfmt.toml"), "max_width = 120\n").unwrap();
+
+    // From a day directory, beside its year directory
+    let output = aoc(tmp_dir.path(), &day_dir, &["new-year", "--copy-utils", "--copy-config"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let new_year_dir = tmp_dir.path().join("advent-of-code-2023");
+    assert!(String::from_utf8_lossy(&output.stdout).contains(&format!("cd {}", new_year_dir.display())));
+    let cargo_toml = std::fs::read_to_string(new_year_dir.join("Cargo.toml")).unwrap();
+    assert!(cargo_toml.contains("members = [\"utils\"]"));
+    assert!(cargo_toml.contains("year = 2023"));
+    assert_eq!(std::fs::read_to_string(new_year_dir.join("utils/src/lib.rs")).unwrap(), "pub fn parse() {}\n");
+    assert_eq!(std::fs::read_to_string(new_year_dir.join("rustfmt.toml")).unwrap(), "max_width = 120\n");
+
+    // From the parent, after the latest year
+    let output = aoc(tmp_dir.path(), tmp_dir.path(), &["new-year"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(tmp_dir.path().join("advent-of-code-2024/Cargo.toml").exists());
+    let output = aoc(tmp_dir.path(), tmp_dir.path(), &["new-year", "--year", "2024"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("already exists"));
+    let output = aoc(tmp_dir.path(), tmp_dir.path(), &["new-year", "--year", "9999"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("hasn't been announced"));
+}